@@ -49,6 +49,20 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
     .with_name("Local Testnet")
     .with_id("local_testnet")
     .with_chain_type(ChainType::Local)
-    .with_genesis_config_patch(development_genesis_config())
+    .with_genesis_config_patch(local_testnet_genesis_config())
+    .build())
+}
+
+/// The staging preset uses well-known keys, so it is a local chain. To launch a real network,
+/// export it with `build-spec`, replace the keys, and set its chain type to `Live`.
+pub fn staging_config() -> Result<ChainSpec, String> {
+    Ok(ChainSpec::builder(
+        WASM_BINARY.ok_or_else(|| "Staging wasm not available".to_string())?,
+        None,
+    )
+    .with_name("Staging")
+    .with_id("staging")
+    .with_chain_type(ChainType::Local)
+    .with_genesis_config_patch(staging_genesis_config())
     .build())
 }
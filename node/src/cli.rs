@@ -1,3 +1,4 @@
+use crate::{dev_service::Sealing, pow::PowParams};
 use sc_cli::RunCmd;
 
 #[derive(Debug, clap::Parser)]
@@ -7,6 +8,29 @@ pub struct Cli {
 
     #[clap(flatten)]
     pub run: RunCmd,
+
+    /// The consensus engine that authors and imports blocks when running the node.
+    /// The other subcommands always use Aura's import queue.
+    #[clap(long, value_enum, default_value_t = Consensus::Aura)]
+    pub consensus: Consensus,
+
+    #[clap(flatten)]
+    pub pow: PowParams,
+
+    /// Seal blocks without a consensus engine, either `instant`ly when transactions arrive,
+    /// `manual`ly through the `engine_createBlock` RPC, or every `interval=N` milliseconds.
+    /// Intended for development and tests. Takes the place of `--consensus`.
+    #[clap(long, conflicts_with = "consensus")]
+    pub sealing: Option<Sealing>,
+}
+
+/// The consensus engines that the node can run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Consensus {
+    /// Aura authorship with GRANDPA finality.
+    Aura,
+    /// Proof of work, mined on local threads, without finality.
+    Pow,
 }
 
 #[derive(Debug, clap::Subcommand)]
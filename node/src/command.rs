@@ -1,7 +1,7 @@
 use crate::{
     chain_spec,
-    cli::{Cli, Subcommand},
-    service,
+    cli::{Cli, Consensus, Subcommand},
+    dev_service, pow, service,
 };
 use sc_cli::SubstrateCli;
 use sc_service::PartialComponents;
@@ -36,6 +36,7 @@ impl SubstrateCli for Cli {
         Ok(match id {
             "dev" => Box::new(chain_spec::development_config()?),
             "" | "local" => Box::new(chain_spec::local_testnet_config()?),
+            "staging" => Box::new(chain_spec::staging_config()?),
             path => Box::new(chain_spec::ChainSpec::from_json_file(
                 std::path::PathBuf::from(path),
             )?),
@@ -128,8 +129,16 @@ pub fn run() -> sc_cli::Result<()> {
         }
         None => {
             let runner = cli.create_runner(&cli.run)?;
+            let consensus = cli.consensus;
+            let pow_params = cli.pow.clone();
+            let sealing = cli.sealing;
             runner.run_node_until_exit(|config| async move {
-                service::new_full(config).map_err(sc_cli::Error::Service)
+                match (sealing, consensus) {
+                    (Some(sealing), _) => dev_service::new_dev(config, sealing),
+                    (None, Consensus::Aura) => service::new_full(config),
+                    (None, Consensus::Pow) => pow::new_full(config, pow_params),
+                }
+                .map_err(sc_cli::Error::Service)
             })
         }
     }
@@ -0,0 +1,330 @@
+//! The development service runs the template runtime without a consensus engine. Blocks are
+//! sealed instantly, on demand, or on a fixed interval instead of in Aura slots, which makes it
+//! well suited to integration tests that need blocks quickly.
+//!
+//! Blocks can always be sealed on demand through the `engine_createBlock` RPC, and finalized
+//! through `engine_finalizeBlock`, regardless of the sealing mode.
+
+use crate::{poe_disputes, rpc, service::FullClient};
+use futures::{channel::mpsc, stream::BoxStream, StreamExt};
+use node_template_runtime::{
+    coinbase::CoinbaseInherentDataProvider, timestamp::Timestamp, OuterConstraintChecker,
+    OuterVerifier, RuntimeApi, Transaction,
+};
+use parity_scale_codec::{Decode, Encode};
+use sc_client_api::BlockBackend;
+use sc_consensus_manual_seal::{
+    consensus::aura::AuraConsensusDataProvider, run_manual_seal, EngineCommand, ManualSealParams,
+};
+use sc_service::{error::Error as ServiceError, Configuration, TaskManager};
+use sc_telemetry::{Telemetry, TelemetryWorker};
+use sc_transaction_pool_api::TransactionPool;
+use sp_api::ProvideRuntimeApi;
+use sp_consensus_aura::AuraApi;
+use sp_core::H256;
+use std::{str::FromStr, sync::Arc, time::Duration};
+use tuxedo_core::{
+    genesis::TuxedoGenesisBlockBuilder, types::OpaqueBlock as Block, verifier::Sr25519Signature,
+};
+
+type FullBackend = sc_service::TFullBackend<Block>;
+type FullSelectChain = sc_consensus::LongestChain<FullBackend, Block>;
+
+/// How many sealing commands may queue up before the RPC starts refusing new ones.
+const COMMAND_QUEUE_SIZE: usize = 1024;
+
+/// When the development service seals new blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sealing {
+    /// Seal a block as soon as a transaction enters the pool.
+    Instant,
+    /// Seal blocks only when requested through the `engine_createBlock` RPC.
+    Manual,
+    /// Seal a block every given number of milliseconds, even if it is empty.
+    Interval(u64),
+}
+
+impl FromStr for Sealing {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "instant" => Ok(Self::Instant),
+            "manual" => Ok(Self::Manual),
+            _ => {
+                let millis = s
+                    .strip_prefix("interval=")
+                    .ok_or_else(|| {
+                        format!(
+                            "Unknown sealing mode `{s}`. Expected instant, manual, or interval=N"
+                        )
+                    })?
+                    .parse::<u64>()
+                    .map_err(|e| format!("Invalid sealing interval: {e}"))?;
+                if millis == 0 {
+                    return Err("The sealing interval must be at least one millisecond".into());
+                }
+                Ok(Self::Interval(millis))
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn new_partial(
+    config: &Configuration,
+) -> Result<
+    sc_service::PartialComponents<
+        FullClient,
+        FullBackend,
+        FullSelectChain,
+        sc_consensus::DefaultImportQueue<Block>,
+        sc_transaction_pool::FullPool<Block, FullClient>,
+        Option<Telemetry>,
+    >,
+    ServiceError,
+> {
+    let telemetry = config
+        .telemetry_endpoints
+        .clone()
+        .filter(|x| !x.is_empty())
+        .map(|endpoints| -> Result<_, sc_telemetry::Error> {
+            let worker = TelemetryWorker::new(16)?;
+            let telemetry = worker.handle().new_telemetry(endpoints);
+            Ok((worker, telemetry))
+        })
+        .transpose()?;
+
+    let executor = sc_service::new_native_or_wasm_executor(config);
+
+    let backend = sc_service::new_db_backend(config.db_config())?;
+    let genesis_block_builder = TuxedoGenesisBlockBuilder::new(
+        config.chain_spec.as_storage_builder(),
+        !config.no_genesis(),
+        backend.clone(),
+        executor.clone(),
+    )?;
+
+    let (client, backend, keystore_container, task_manager) =
+        sc_service::new_full_parts_with_genesis_builder::<Block, RuntimeApi, _, _>(
+            config,
+            telemetry.as_ref().map(|(_, telemetry)| telemetry.handle()),
+            executor,
+            backend,
+            genesis_block_builder,
+            false,
+        )?;
+    let client = Arc::new(client);
+
+    let telemetry = telemetry.map(|(worker, telemetry)| {
+        task_manager
+            .spawn_handle()
+            .spawn("telemetry", None, worker.run());
+        telemetry
+    });
+
+    let select_chain = sc_consensus::LongestChain::new(backend.clone());
+
+    let transaction_pool = sc_transaction_pool::BasicPool::new_full(
+        config.transaction_pool.clone(),
+        config.role.is_authority().into(),
+        config.prometheus_registry(),
+        task_manager.spawn_essential_handle(),
+        client.clone(),
+    );
+
+    let import_queue = sc_consensus_manual_seal::import_queue(
+        Box::new(client.clone()),
+        &task_manager.spawn_essential_handle(),
+        config.prometheus_registry(),
+    );
+
+    Ok(sc_service::PartialComponents {
+        client,
+        backend,
+        task_manager,
+        import_queue,
+        keystore_container,
+        select_chain,
+        transaction_pool,
+        other: telemetry,
+    })
+}
+
+/// Builds a new development service that seals blocks according to the given sealing mode.
+pub fn new_dev(config: Configuration, sealing: Sealing) -> Result<TaskManager, ServiceError> {
+    let sc_service::PartialComponents {
+        client,
+        backend,
+        mut task_manager,
+        import_queue,
+        keystore_container,
+        select_chain,
+        transaction_pool,
+        other: mut telemetry,
+    } = new_partial(&config)?;
+
+    let net_config = sc_network::config::FullNetworkConfiguration::new(&config.network);
+
+    let (network, system_rpc_tx, tx_handler_controller, network_starter, sync_service) =
+        sc_service::build_network(sc_service::BuildNetworkParams {
+            config: &config,
+            net_config,
+            client: client.clone(),
+            transaction_pool: transaction_pool.clone(),
+            spawn_handle: task_manager.spawn_handle(),
+            import_queue,
+            block_announce_validator_builder: None,
+            warp_sync_params: None,
+            block_relay: None,
+        })?;
+
+    let role = config.role.clone();
+    let prometheus_registry = config.prometheus_registry().cloned();
+
+    // Sealing commands from the RPC. Only authorities seal blocks, so only they get a sink.
+    let (command_sink, rpc_commands) = mpsc::channel::<EngineCommand<H256>>(COMMAND_QUEUE_SIZE);
+    let command_sink = role.is_authority().then_some(command_sink);
+
+    let rpc_extensions_builder = {
+        let client = client.clone();
+        let pool = transaction_pool.clone();
+
+        Box::new(move |deny_unsafe, _| {
+            let deps = rpc::FullDeps {
+                client: client.clone(),
+                pool: pool.clone(),
+                deny_unsafe,
+                command_sink: command_sink.clone(),
+            };
+            rpc::create_full(deps).map_err(Into::into)
+        })
+    };
+
+    let _rpc_handlers = sc_service::spawn_tasks(sc_service::SpawnTasksParams {
+        network,
+        client: client.clone(),
+        keystore: keystore_container.keystore(),
+        task_manager: &mut task_manager,
+        transaction_pool: transaction_pool.clone(),
+        rpc_builder: rpc_extensions_builder,
+        backend,
+        system_rpc_tx,
+        tx_handler_controller,
+        sync_service,
+        config,
+        telemetry: telemetry.as_mut(),
+    })?;
+
+    // Every full node helps keep the PoE registry free of duplicate claims.
+    task_manager.spawn_handle().spawn(
+        "poe-auto-dispute",
+        None,
+        poe_disputes::run(client.clone(), transaction_pool.clone()),
+    );
+
+    if role.is_authority() {
+        let proposer_factory = sc_basic_authorship::ProposerFactory::new(
+            task_manager.spawn_handle(),
+            client.clone(),
+            transaction_pool.clone(),
+            prometheus_registry.as_ref(),
+            telemetry.as_ref().map(|x| x.handle()),
+        );
+
+        // There is no finality gadget, so automatically sealed blocks are finalized right away.
+        let seal_command = |create_empty| EngineCommand::SealNewBlock {
+            create_empty,
+            finalize: true,
+            parent_hash: None,
+            sender: None,
+        };
+        let automatic_commands: BoxStream<'static, EngineCommand<H256>> = match sealing {
+            Sealing::Instant => transaction_pool
+                .import_notification_stream()
+                .map(move |_| seal_command(false))
+                .boxed(),
+            Sealing::Manual => futures::stream::pending().boxed(),
+            Sealing::Interval(millis) => async_io::Timer::interval(Duration::from_millis(millis))
+                .map(move |_| seal_command(true))
+                .boxed(),
+        };
+        let commands_stream = futures::stream::select(rpc_commands, automatic_commands);
+
+        // Aura requires each block to have a later slot than its parent. When blocks are sealed
+        // faster than the slot duration, each block's time is pushed a slot past the time noted in
+        // its parent, so the chain's time runs ahead of the clock for a while. The parent's time is
+        // read from the chain rather than remembered, so this also holds after a restart.
+        let slot_duration = sc_consensus_aura::slot_duration(&*client)?;
+        let client_for_cidp = client.clone();
+
+        // the manual seal authoring task is considered essential, i.e. if it
+        // fails we take down the service with it.
+        task_manager.spawn_essential_handle().spawn_blocking(
+            "manual-seal",
+            Some("block-authoring"),
+            run_manual_seal(ManualSealParams {
+                block_import: client.clone(),
+                env: proposer_factory,
+                client: client.clone(),
+                pool: transaction_pool,
+                commands_stream,
+                select_chain,
+                consensus_data_provider: Some(Box::new(AuraConsensusDataProvider::new(
+                    client.clone(),
+                ))),
+                create_inherent_data_providers: move |parent_hash, ()| {
+                    let maybe_parent_block = client_for_cidp.clone().block(parent_hash);
+                    let maybe_authorities = client_for_cidp.runtime_api().authorities(parent_hash);
+
+                    async move {
+                        let parent_block = maybe_parent_block?
+                            .ok_or(sp_blockchain::Error::UnknownBlock(parent_hash.to_string()))?
+                            .block;
+
+                        let now = *sp_timestamp::InherentDataProvider::from_system_time();
+                        let earliest = noted_timestamp(&parent_block)
+                            .map_or(0, |parent| parent.saturating_add(slot_duration.as_millis()));
+                        let time = now.as_millis().max(earliest);
+
+                        let parent_idp =
+                            tuxedo_core::inherents::ParentBlockInherentDataProvider(parent_block);
+                        let timestamp = sp_timestamp::InherentDataProvider::new(
+                            sp_timestamp::Timestamp::new(time),
+                        );
+
+                        // Pay the block reward to the authority that owns this slot, just like
+                        // the Aura service does.
+                        let slot = time / slot_duration.as_millis().max(1);
+                        let authorities = maybe_authorities?;
+                        let author = authorities
+                            .get((slot % authorities.len().max(1) as u64) as usize)
+                            .ok_or("No Aura authorities to reward")?;
+                        let coinbase =
+                            CoinbaseInherentDataProvider(OuterVerifier::Sr25519Signature(
+                                Sr25519Signature::new(H256::from_slice(author.as_ref())),
+                            ));
+
+                        Ok((parent_idp, timestamp, coinbase))
+                    }
+                },
+            }),
+        );
+    }
+
+    log::info!("Development Service Ready");
+
+    network_starter.start_network();
+    Ok(task_manager)
+}
+
+/// The time noted by the timestamp inherent in the given block, if it has one.
+fn noted_timestamp(block: &Block) -> Option<u64> {
+    block
+        .extrinsics
+        .iter()
+        .filter_map(|extrinsic| Transaction::decode(&mut &extrinsic.encode()[..]).ok())
+        .filter(|tx| matches!(tx.checker, OuterConstraintChecker::SetTimestamp(_)))
+        .find_map(|tx| tx.outputs.first()?.payload.extract::<Timestamp>().ok())
+        .map(|timestamp| timestamp.time)
+}
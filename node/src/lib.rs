@@ -1,3 +1,6 @@
 pub mod chain_spec;
+pub mod dev_service;
+mod poe_disputes;
+pub mod pow;
 pub mod rpc;
 pub mod service;
@@ -4,6 +4,9 @@
 mod chain_spec;
 mod cli;
 mod command;
+mod dev_service;
+mod poe_disputes;
+mod pow;
 mod rpc;
 mod service;
 
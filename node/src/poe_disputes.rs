@@ -0,0 +1,343 @@
+//! Automatic resolution of duplicate proof of existence claims.
+//!
+//! The PoE piece cannot stop the same hash from being claimed twice, because the claim checker
+//! does not know about claims that already exist in storage. Instead, anyone may later submit a
+//! `PoeDispute` transaction that evicts every claim but the oldest one.
+//!
+//! This module watches newly imported best blocks for duplicate claims and submits those disputes
+//! from the node automatically. It only knows about claims it has seen since the node started, so
+//! it is a convenience rather than a guarantee. A dispute that has become stale, for example
+//! because one of the claims was revoked in the meantime, is simply rejected by the pool.
+//!
+//! When the best chain is reorganized, claims created in the retracted blocks are forgotten, and the
+//! newly enacted blocks are scanned like any other. Claims that were consumed in the retracted blocks
+//! are not restored though, because the index no longer knows about them. So a duplicate of such a
+//! claim is only disputed if the watcher sees the claim again.
+
+use futures::StreamExt;
+use node_template_runtime::{
+    poe::{ClaimData, ExpiringClaimData, PoeDispute},
+    OuterConstraintChecker, Transaction,
+};
+use parity_scale_codec::{Decode, Encode};
+use sc_client_api::{BlockBackend, BlockchainEvents};
+use sc_transaction_pool_api::TransactionPool;
+use sp_core::H256;
+use sp_runtime::{
+    traits::{BlakeTwo256, Hash},
+    transaction_validity::TransactionSource,
+    OpaqueExtrinsic,
+};
+use std::{cmp::Ordering, collections::HashMap, sync::Arc};
+use tuxedo_core::types::{Input, OpaqueBlock as Block, OutputRef, RedemptionStrategy};
+
+/// The oldest known claim on each hash, and where it is stored.
+#[derive(Default)]
+struct ClaimIndex {
+    /// claimed hash => (output_ref, effective_height)
+    by_hash: HashMap<H256, (OutputRef, u32)>,
+    /// output_ref => claimed hash
+    by_ref: HashMap<OutputRef, H256>,
+}
+
+impl ClaimIndex {
+    /// Forget about a claim that has been consumed, if it is known.
+    fn remove(&mut self, output_ref: &OutputRef) {
+        if let Some(hash) = self.by_ref.remove(output_ref) {
+            self.by_hash.remove(&hash);
+        }
+    }
+
+    /// Record a newly created claim. If another claim on the same hash is already known,
+    /// return the dispute transaction that evicts whichever of the two came later.
+    fn insert(&mut self, claim: ClaimData, output_ref: OutputRef) -> Option<Transaction> {
+        let Some((known_ref, known_height)) = self.by_hash.get(&claim.claim).cloned() else {
+            self.track(claim, output_ref);
+            return None;
+        };
+
+        let (winner, loser) = match claim.effective_height.cmp(&known_height) {
+            Ordering::Less => {
+                self.by_ref.remove(&known_ref);
+                self.track(claim, output_ref.clone());
+                (output_ref, known_ref)
+            }
+            Ordering::Greater => (known_ref, output_ref),
+            // Neither claim came first, so the dispute checker cannot pick a winner.
+            Ordering::Equal => return None,
+        };
+
+        Some(Transaction {
+            inputs: vec![Input {
+                output_ref: loser,
+                redeemer: RedemptionStrategy::Eviction,
+            }],
+            peeks: vec![winner],
+            outputs: vec![],
+            checker: OuterConstraintChecker::PoeDispute(PoeDispute),
+        })
+    }
+
+    /// Record the claims created and consumed by a newly enacted block's transactions,
+    /// and return the disputes for any duplicates among them.
+    fn enact(&mut self, transactions: &[Transaction]) -> Vec<Transaction> {
+        let mut disputes = Vec::new();
+        for tx in transactions {
+            for input in &tx.inputs {
+                self.remove(&input.output_ref);
+            }
+
+            for (output_ref, claim) in created_claims(tx) {
+                disputes.extend(self.insert(claim, output_ref));
+            }
+        }
+        disputes
+    }
+
+    /// Forget the claims created by a retracted block's transactions.
+    fn retract(&mut self, transactions: &[Transaction]) {
+        for tx in transactions {
+            for (output_ref, _) in created_claims(tx) {
+                self.remove(&output_ref);
+            }
+        }
+    }
+
+    fn track(&mut self, claim: ClaimData, output_ref: OutputRef) {
+        self.by_ref.insert(output_ref.clone(), claim.claim);
+        self.by_hash
+            .insert(claim.claim, (output_ref, claim.effective_height));
+    }
+}
+
+/// The claims created by the given transaction, along with where they are stored.
+fn created_claims(tx: &Transaction) -> impl Iterator<Item = (OutputRef, ClaimData)> + '_ {
+    let tx_hash = BlakeTwo256::hash_of(&tx.encode());
+    tx.outputs
+        .iter()
+        .enumerate()
+        .filter_map(move |(i, output)| {
+            let claim = output
+                .payload
+                .extract::<ClaimData>()
+                .or_else(|_| {
+                    output
+                        .payload
+                        .extract::<ExpiringClaimData>()
+                        .map(|expiring| expiring.claim)
+                })
+                .ok()?;
+            let output_ref = OutputRef {
+                tx_hash,
+                index: i as u32,
+            };
+            Some((output_ref, claim))
+        })
+}
+
+/// The transactions in the block with the given hash, or nothing if its body is unavailable.
+fn block_transactions<C: BlockBackend<Block>>(client: &C, hash: H256) -> Vec<Transaction> {
+    let extrinsics = match client.block_body(hash) {
+        Ok(Some(extrinsics)) => extrinsics,
+        Ok(None) => return Vec::new(),
+        Err(e) => {
+            log::warn!("PoE dispute watcher could not read block body: {e:?}");
+            return Vec::new();
+        }
+    };
+
+    extrinsics
+        .iter()
+        .filter_map(|extrinsic| Transaction::decode(&mut &extrinsic.encode()[..]).ok())
+        .collect()
+}
+
+/// Watch the chain for duplicate PoE claims and submit disputes for them to the local pool.
+///
+/// This future runs for as long as the client keeps producing import notifications.
+pub async fn run<C, P>(client: Arc<C>, pool: Arc<P>)
+where
+    C: BlockchainEvents<Block> + BlockBackend<Block>,
+    P: TransactionPool<Block = Block>,
+{
+    let mut index = ClaimIndex::default();
+    let mut imports = client.import_notification_stream();
+
+    while let Some(notification) = imports.next().await {
+        if !notification.is_new_best {
+            continue;
+        }
+
+        // If the best chain was reorganized, undo the retracted blocks before scanning the enacted ones.
+        // The tree route ends at the new block's parent, so the new block itself is scanned last.
+        let mut disputes = Vec::new();
+        if let Some(route) = &notification.tree_route {
+            for retracted in route.retracted() {
+                index.retract(&block_transactions(&*client, retracted.hash));
+            }
+            for enacted in route.enacted() {
+                disputes.extend(index.enact(&block_transactions(&*client, enacted.hash)));
+            }
+        }
+        disputes.extend(index.enact(&block_transactions(&*client, notification.hash)));
+
+        for dispute in disputes {
+            let Ok(extrinsic) = OpaqueExtrinsic::decode(&mut &dispute.encode()[..]) else {
+                continue;
+            };
+            match pool
+                .submit_one(notification.hash, TransactionSource::Local, extrinsic)
+                .await
+            {
+                Ok(hash) => log::info!("Submitted PoE dispute {hash:?}"),
+                Err(e) => log::debug!("PoE dispute was not accepted: {e:?}"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use node_template_runtime::{poe::PoeClaim, Output};
+    use tuxedo_core::verifier::UpForGrabs;
+
+    fn claim(effective_height: u32) -> ClaimData {
+        ClaimData {
+            claim: H256::repeat_byte(1),
+            effective_height,
+        }
+    }
+
+    fn output_ref(index: u32) -> OutputRef {
+        OutputRef {
+            tx_hash: H256::repeat_byte(9),
+            index,
+        }
+    }
+
+    fn dispute(winner: OutputRef, loser: OutputRef) -> Transaction {
+        Transaction {
+            inputs: vec![Input {
+                output_ref: loser,
+                redeemer: RedemptionStrategy::Eviction,
+            }],
+            peeks: vec![winner],
+            outputs: vec![],
+            checker: OuterConstraintChecker::PoeDispute(PoeDispute),
+        }
+    }
+
+    fn claim_transaction(claims: Vec<ClaimData>) -> Transaction {
+        Transaction {
+            inputs: vec![],
+            peeks: vec![],
+            outputs: claims
+                .into_iter()
+                .map(|claim| Output {
+                    payload: claim.into(),
+                    verifier: UpForGrabs.into(),
+                })
+                .collect(),
+            checker: OuterConstraintChecker::PoeClaim(PoeClaim::default()),
+        }
+    }
+
+    #[test]
+    fn insert_first_claim_needs_no_dispute() {
+        let mut index = ClaimIndex::default();
+
+        assert_eq!(index.insert(claim(5), output_ref(0)), None);
+    }
+
+    #[test]
+    fn insert_newer_duplicate_disputes_it() {
+        let mut index = ClaimIndex::default();
+        index.insert(claim(5), output_ref(0));
+
+        assert_eq!(
+            index.insert(claim(6), output_ref(1)),
+            Some(dispute(output_ref(0), output_ref(1)))
+        );
+        // The older claim is still the one that is known.
+        assert_eq!(
+            index.insert(claim(7), output_ref(2)),
+            Some(dispute(output_ref(0), output_ref(2)))
+        );
+    }
+
+    #[test]
+    fn insert_older_duplicate_disputes_known_claim() {
+        let mut index = ClaimIndex::default();
+        index.insert(claim(5), output_ref(0));
+
+        assert_eq!(
+            index.insert(claim(4), output_ref(1)),
+            Some(dispute(output_ref(1), output_ref(0)))
+        );
+        // The older claim replaced the known one.
+        assert_eq!(
+            index.insert(claim(6), output_ref(2)),
+            Some(dispute(output_ref(1), output_ref(2)))
+        );
+        assert!(!index.by_ref.contains_key(&output_ref(0)));
+    }
+
+    #[test]
+    fn insert_duplicate_at_same_height_needs_no_dispute() {
+        let mut index = ClaimIndex::default();
+        index.insert(claim(5), output_ref(0));
+
+        assert_eq!(index.insert(claim(5), output_ref(1)), None);
+        // The first claim is still the one that is known.
+        assert_eq!(
+            index.insert(claim(6), output_ref(2)),
+            Some(dispute(output_ref(0), output_ref(2)))
+        );
+    }
+
+    #[test]
+    fn remove_consumed_claim_forgets_it() {
+        let mut index = ClaimIndex::default();
+        index.insert(claim(5), output_ref(0));
+
+        index.remove(&output_ref(0));
+
+        assert_eq!(index.insert(claim(6), output_ref(1)), None);
+    }
+
+    #[test]
+    fn remove_unknown_claim_does_nothing() {
+        let mut index = ClaimIndex::default();
+        index.insert(claim(5), output_ref(0));
+
+        index.remove(&output_ref(1));
+
+        assert_eq!(
+            index.insert(claim(6), output_ref(2)),
+            Some(dispute(output_ref(0), output_ref(2)))
+        );
+    }
+
+    #[test]
+    fn enact_disputes_duplicates_in_the_same_block() {
+        let mut index = ClaimIndex::default();
+        let tx = claim_transaction(vec![claim(5), claim(6)]);
+        let tx_hash = BlakeTwo256::hash_of(&tx.encode());
+        let first = OutputRef { tx_hash, index: 0 };
+        let second = OutputRef { tx_hash, index: 1 };
+
+        assert_eq!(index.enact(&[tx]), vec![dispute(first, second)]);
+    }
+
+    #[test]
+    fn retract_forgets_created_claims() {
+        let mut index = ClaimIndex::default();
+        let tx = claim_transaction(vec![claim(5)]);
+        index.enact(&[tx.clone()]);
+
+        index.retract(&[tx]);
+
+        assert_eq!(index.insert(claim(6), output_ref(0)), None);
+    }
+}
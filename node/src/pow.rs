@@ -0,0 +1,432 @@
+//! Proof-of-work consensus for the template node, as an alternative to Aura and GRANDPA.
+//!
+//! Blocks are sealed by finding a nonce whose hash, together with the block's pre-hash and the
+//! difficulty, meets the difficulty. The difficulty itself is adjusted on chain by the difficulty
+//! piece, and read from the parent block through its runtime API. There is no finality gadget;
+//! the chain with the most accumulated difficulty wins.
+//!
+//! Authorities mine on local threads and are rewarded through the coinbase piece.
+
+use crate::{poe_disputes, rpc, service::FullClient};
+use node_template_runtime::{
+    coinbase::CoinbaseInherentDataProvider, difficulty::DifficultyApi, OuterVerifier, RuntimeApi,
+};
+use parity_scale_codec::{Decode, Encode};
+use sc_client_api::BlockBackend;
+use sc_consensus_pow::{Error as PowError, PowAlgorithm, PowBlockImport};
+use sc_service::{error::Error as ServiceError, Configuration, TaskManager};
+use sc_telemetry::{Telemetry, TelemetryWorker};
+use sha3::{Digest, Sha3_256};
+use sp_api::ProvideRuntimeApi;
+use sp_consensus_pow::Seal as RawSeal;
+use sp_core::{H256, U256};
+use sp_runtime::generic::BlockId;
+use std::{sync::Arc, time::Duration};
+use tuxedo_core::{
+    genesis::TuxedoGenesisBlockBuilder, types::OpaqueBlock as Block, verifier::Sr25519Signature,
+    Verifier,
+};
+
+type FullBackend = sc_service::TFullBackend<Block>;
+type FullSelectChain = sc_consensus::LongestChain<FullBackend, Block>;
+
+/// How many nonces a mining thread tries before checking whether there is a new block to mine on.
+const NONCES_PER_ROUND: u64 = 10_000;
+
+/// The hash function that seals blocks. Every node on the chain must use the same one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum PowHasher {
+    /// Blake2b with a 256 bit output, as used throughout Substrate.
+    Blake2,
+    /// SHA3-256.
+    Sha3,
+}
+
+impl PowHasher {
+    fn hash(&self, data: &[u8]) -> H256 {
+        match self {
+            Self::Blake2 => H256(sp_core::hashing::blake2_256(data)),
+            Self::Sha3 => H256(Sha3_256::digest(data).into()),
+        }
+    }
+}
+
+/// Options for running the node with proof-of-work consensus.
+#[derive(Debug, Clone, clap::Args)]
+pub struct PowParams {
+    /// The hash function that seals blocks.
+    #[clap(long, value_enum, default_value_t = PowHasher::Sha3)]
+    pub pow_hasher: PowHasher,
+
+    /// The number of threads that mine blocks. Only authorities mine.
+    #[clap(long, default_value_t = 1)]
+    pub mining_threads: usize,
+
+    /// The sr25519 public key that block rewards are paid to. If none is given, rewards are burned.
+    #[clap(long)]
+    pub reward_key: Option<H256>,
+}
+
+/// The seal that proves a block was mined. It goes in the block's seal digest.
+#[derive(Clone, PartialEq, Eq, Encode, Decode, Debug)]
+pub struct Seal {
+    /// The difficulty that the block was mined at.
+    pub difficulty: u128,
+    /// The hash of the block's pre-hash, difficulty, and nonce.
+    pub work: H256,
+    /// The nonce that the miner found.
+    pub nonce: H256,
+}
+
+/// Everything that is hashed to compute a seal's work.
+#[derive(Clone, PartialEq, Eq, Encode, Decode, Debug)]
+struct Compute {
+    difficulty: u128,
+    pre_hash: H256,
+    nonce: H256,
+}
+
+impl Compute {
+    fn seal(self, hasher: PowHasher) -> Seal {
+        Seal {
+            work: hasher.hash(&self.encode()),
+            difficulty: self.difficulty,
+            nonce: self.nonce,
+        }
+    }
+}
+
+/// Whether a hash meets a difficulty. That is the case when the hash, interpreted as a number,
+/// can be multiplied by the difficulty without overflowing 256 bits.
+fn hash_meets_difficulty(hash: &H256, difficulty: u128) -> bool {
+    let (_, overflowed) = U256::from_big_endian(hash.as_bytes()).overflowing_mul(difficulty.into());
+    !overflowed
+}
+
+/// The proof-of-work algorithm, which takes its difficulty from the chain.
+pub struct TuxedoPow<C> {
+    client: Arc<C>,
+    hasher: PowHasher,
+}
+
+impl<C> TuxedoPow<C> {
+    /// Create the algorithm, reading difficulties through the given client.
+    pub fn new(client: Arc<C>, hasher: PowHasher) -> Self {
+        Self { client, hasher }
+    }
+}
+
+// Derive would require `C: Clone`, but the client is behind an `Arc`.
+impl<C> Clone for TuxedoPow<C> {
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client.clone(),
+            hasher: self.hasher,
+        }
+    }
+}
+
+impl<C> PowAlgorithm<Block> for TuxedoPow<C>
+where
+    C: ProvideRuntimeApi<Block>,
+    C::Api: DifficultyApi<Block>,
+{
+    type Difficulty = u128;
+
+    fn difficulty(&self, parent: H256) -> Result<u128, PowError<Block>> {
+        self.client
+            .runtime_api()
+            .difficulty(parent)
+            .map_err(|e| PowError::Environment(format!("Fetching difficulty failed: {e}")))
+    }
+
+    fn verify(
+        &self,
+        _parent: &BlockId<Block>,
+        pre_hash: &H256,
+        _pre_digest: Option<&[u8]>,
+        seal: &RawSeal,
+        difficulty: u128,
+    ) -> Result<bool, PowError<Block>> {
+        let Ok(seal) = Seal::decode(&mut &seal[..]) else {
+            return Ok(false);
+        };
+
+        let compute = Compute {
+            difficulty,
+            pre_hash: *pre_hash,
+            nonce: seal.nonce,
+        };
+
+        Ok(hash_meets_difficulty(&seal.work, difficulty) && compute.seal(self.hasher) == seal)
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn new_partial(
+    config: &Configuration,
+    hasher: PowHasher,
+) -> Result<
+    sc_service::PartialComponents<
+        FullClient,
+        FullBackend,
+        FullSelectChain,
+        sc_consensus::DefaultImportQueue<Block>,
+        sc_transaction_pool::FullPool<Block, FullClient>,
+        Option<Telemetry>,
+    >,
+    ServiceError,
+> {
+    let telemetry = config
+        .telemetry_endpoints
+        .clone()
+        .filter(|x| !x.is_empty())
+        .map(|endpoints| -> Result<_, sc_telemetry::Error> {
+            let worker = TelemetryWorker::new(16)?;
+            let telemetry = worker.handle().new_telemetry(endpoints);
+            Ok((worker, telemetry))
+        })
+        .transpose()?;
+
+    let executor = sc_service::new_native_or_wasm_executor(config);
+
+    let backend = sc_service::new_db_backend(config.db_config())?;
+    let genesis_block_builder = TuxedoGenesisBlockBuilder::new(
+        config.chain_spec.as_storage_builder(),
+        !config.no_genesis(),
+        backend.clone(),
+        executor.clone(),
+    )?;
+
+    let (client, backend, keystore_container, task_manager) =
+        sc_service::new_full_parts_with_genesis_builder::<Block, RuntimeApi, _, _>(
+            config,
+            telemetry.as_ref().map(|(_, telemetry)| telemetry.handle()),
+            executor,
+            backend,
+            genesis_block_builder,
+            false,
+        )?;
+    let client = Arc::new(client);
+
+    let telemetry = telemetry.map(|(worker, telemetry)| {
+        task_manager
+            .spawn_handle()
+            .spawn("telemetry", None, worker.run());
+        telemetry
+    });
+
+    let select_chain = sc_consensus::LongestChain::new(backend.clone());
+
+    let transaction_pool = sc_transaction_pool::BasicPool::new_full(
+        config.transaction_pool.clone(),
+        config.role.is_authority().into(),
+        config.prometheus_registry(),
+        task_manager.spawn_essential_handle(),
+        client.clone(),
+    );
+
+    let pow_block_import = PowBlockImport::new(
+        client.clone(),
+        client.clone(),
+        TuxedoPow::new(client.clone(), hasher),
+        0,
+        select_chain.clone(),
+        |_, ()| async move { Ok(sp_timestamp::InherentDataProvider::from_system_time()) },
+    );
+
+    let import_queue = sc_consensus_pow::import_queue(
+        Box::new(pow_block_import),
+        None,
+        TuxedoPow::new(client.clone(), hasher),
+        &task_manager.spawn_essential_handle(),
+        config.prometheus_registry(),
+    )?;
+
+    Ok(sc_service::PartialComponents {
+        client,
+        backend,
+        task_manager,
+        import_queue,
+        keystore_container,
+        select_chain,
+        transaction_pool,
+        other: telemetry,
+    })
+}
+
+/// Builds a new service for a full client that uses proof-of-work consensus.
+pub fn new_full(config: Configuration, params: PowParams) -> Result<TaskManager, ServiceError> {
+    let sc_service::PartialComponents {
+        client,
+        backend,
+        mut task_manager,
+        import_queue,
+        keystore_container,
+        select_chain,
+        transaction_pool,
+        other: mut telemetry,
+    } = new_partial(&config, params.pow_hasher)?;
+
+    let net_config = sc_network::config::FullNetworkConfiguration::new(&config.network);
+
+    let (network, system_rpc_tx, tx_handler_controller, network_starter, sync_service) =
+        sc_service::build_network(sc_service::BuildNetworkParams {
+            config: &config,
+            net_config,
+            client: client.clone(),
+            transaction_pool: transaction_pool.clone(),
+            spawn_handle: task_manager.spawn_handle(),
+            import_queue,
+            block_announce_validator_builder: None,
+            warp_sync_params: None,
+            block_relay: None,
+        })?;
+
+    let role = config.role.clone();
+    let prometheus_registry = config.prometheus_registry().cloned();
+
+    let rpc_extensions_builder = {
+        let client = client.clone();
+        let pool = transaction_pool.clone();
+
+        Box::new(move |deny_unsafe, _| {
+            let deps = rpc::FullDeps {
+                client: client.clone(),
+                pool: pool.clone(),
+                deny_unsafe,
+                command_sink: None,
+            };
+            rpc::create_full(deps).map_err(Into::into)
+        })
+    };
+
+    let _rpc_handlers = sc_service::spawn_tasks(sc_service::SpawnTasksParams {
+        network: network.clone(),
+        client: client.clone(),
+        keystore: keystore_container.keystore(),
+        task_manager: &mut task_manager,
+        transaction_pool: transaction_pool.clone(),
+        rpc_builder: rpc_extensions_builder,
+        backend,
+        system_rpc_tx,
+        tx_handler_controller,
+        sync_service: sync_service.clone(),
+        config,
+        telemetry: telemetry.as_mut(),
+    })?;
+
+    // Every full node helps keep the PoE registry free of duplicate claims.
+    task_manager.spawn_handle().spawn(
+        "poe-auto-dispute",
+        None,
+        poe_disputes::run(client.clone(), transaction_pool.clone()),
+    );
+
+    if role.is_authority() {
+        let proposer_factory = sc_basic_authorship::ProposerFactory::new(
+            task_manager.spawn_handle(),
+            client.clone(),
+            transaction_pool.clone(),
+            prometheus_registry.as_ref(),
+            telemetry.as_ref().map(|x| x.handle()),
+        );
+
+        let algorithm = TuxedoPow::new(client.clone(), params.pow_hasher);
+        let pow_block_import = PowBlockImport::new(
+            client.clone(),
+            client.clone(),
+            algorithm.clone(),
+            0,
+            select_chain.clone(),
+            |_, ()| async move { Ok(sp_timestamp::InherentDataProvider::from_system_time()) },
+        );
+
+        // The reward goes to the configured key, or is burned if there is none.
+        let reward_verifier = match params.reward_key {
+            Some(key) => OuterVerifier::Sr25519Signature(Sr25519Signature::new(key)),
+            None => OuterVerifier::new_unspendable()
+                .expect("The template's verifier can be made unspendable."),
+        };
+        let client_for_cidp = client.clone();
+
+        let (worker, worker_task) = sc_consensus_pow::start_mining_worker(
+            Box::new(pow_block_import),
+            client.clone(),
+            select_chain,
+            algorithm,
+            proposer_factory,
+            sync_service.clone(),
+            sync_service,
+            None,
+            move |parent_hash, ()| {
+                let maybe_parent_block = client_for_cidp.clone().block(parent_hash);
+                let reward_verifier = reward_verifier.clone();
+
+                async move {
+                    let parent_block = maybe_parent_block?
+                        .ok_or(sp_blockchain::Error::UnknownBlock(parent_hash.to_string()))?
+                        .block;
+
+                    let parent_idp =
+                        tuxedo_core::inherents::ParentBlockInherentDataProvider(parent_block);
+                    let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
+                    let coinbase = CoinbaseInherentDataProvider(reward_verifier);
+
+                    Ok((parent_idp, timestamp, coinbase))
+                }
+            },
+            // Rebuild at least as often as the timestamp piece allows blocks, so that a proposal
+            // that was built too soon after its parent is soon replaced.
+            Duration::from_secs(2),
+            Duration::from_secs(10),
+        );
+
+        // The mining worker task is considered essential, i.e. if it
+        // fails we take down the service with it.
+        task_manager.spawn_essential_handle().spawn_blocking(
+            "pow-mining-worker",
+            Some("block-authoring"),
+            worker_task,
+        );
+
+        // Each thread tries a different residue class of nonces, so that they never repeat each other's work.
+        let threads = params.mining_threads as u64;
+        for thread_index in 0..threads {
+            let worker = worker.clone();
+            let hasher = params.pow_hasher;
+
+            task_manager.spawn_handle().spawn_blocking(
+                "pow-miner",
+                Some("block-authoring"),
+                async move {
+                    let mut nonce = thread_index;
+                    loop {
+                        let Some(metadata) = worker.metadata() else {
+                            std::thread::sleep(Duration::from_millis(100));
+                            continue;
+                        };
+
+                        for _ in 0..NONCES_PER_ROUND {
+                            let seal = Compute {
+                                difficulty: metadata.difficulty,
+                                pre_hash: metadata.pre_hash,
+                                nonce: H256::from_low_u64_be(nonce),
+                            }
+                            .seal(hasher);
+                            nonce = nonce.wrapping_add(threads);
+
+                            if hash_meets_difficulty(&seal.work, metadata.difficulty) {
+                                worker.submit(seal.encode()).await;
+                                break;
+                            }
+                        }
+                    }
+                },
+            );
+        }
+    }
+
+    network_starter.start_network();
+    Ok(task_manager)
+}
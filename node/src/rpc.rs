@@ -7,11 +7,17 @@
 
 use std::sync::Arc;
 
+use futures::channel::mpsc::Sender;
 use jsonrpsee::RpcModule;
+use sc_consensus_manual_seal::{
+    rpc::{ManualSeal, ManualSealApiServer},
+    EngineCommand,
+};
 use sc_transaction_pool_api::TransactionPool;
 use sp_api::ProvideRuntimeApi;
 use sp_block_builder::BlockBuilder;
 use sp_blockchain::{Error as BlockChainError, HeaderBackend, HeaderMetadata};
+use sp_core::H256;
 use tuxedo_core::types::OpaqueBlock as Block;
 
 pub use sc_rpc_api::DenyUnsafe;
@@ -24,11 +30,13 @@ pub struct FullDeps<C, P> {
     pub pool: Arc<P>,
     /// Whether to deny unsafe calls
     pub deny_unsafe: DenyUnsafe,
+    /// A channel to the manual seal authoring task, when the node seals blocks on demand.
+    pub command_sink: Option<Sender<EngineCommand<H256>>>,
 }
 
 /// Instantiate all full RPC extensions.
 pub fn create_full<C, P>(
-    _deps: FullDeps<C, P>,
+    deps: FullDeps<C, P>,
 ) -> Result<RpcModule<()>, Box<dyn std::error::Error + Send + Sync>>
 where
     C: ProvideRuntimeApi<Block>
@@ -40,7 +48,14 @@ where
     C::Api: BlockBuilder<Block>,
     P: TransactionPool + 'static,
 {
-    let module = RpcModule::new(());
+    let mut module = RpcModule::new(());
+    let FullDeps { command_sink, .. } = deps;
+
+    if let Some(command_sink) = command_sink {
+        // Exposes `engine_createBlock` and `engine_finalizeBlock`.
+        module.merge(ManualSeal::new(command_sink).into_rpc())?;
+    }
+
     // Extend this RPC with a custom API by using the following syntax.
     // `YourRpcStruct` should have a reference to a client, which is needed
     // to call into the runtime.
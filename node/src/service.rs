@@ -1,7 +1,9 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
-use crate::rpc;
-use node_template_runtime::{self, RuntimeApi};
+use crate::{poe_disputes, rpc};
+use node_template_runtime::{
+    self, coinbase::CoinbaseInherentDataProvider, OuterVerifier, RuntimeApi,
+};
 use sc_client_api::BlockBackend;
 use sc_consensus_aura::{ImportQueueParams, SlotProportion, StartAuraParams};
 use sc_consensus_grandpa::SharedVoterState;
@@ -9,9 +11,13 @@ pub use sc_executor::NativeElseWasmExecutor;
 use sc_service::{error::Error as ServiceError, Configuration, TaskManager, WarpSyncParams};
 use sc_telemetry::{Telemetry, TelemetryWorker};
 use sc_transaction_pool_api::OffchainTransactionPoolFactory;
-use sp_consensus_aura::sr25519::AuthorityPair as AuraPair;
+use sp_api::ProvideRuntimeApi;
+use sp_consensus_aura::{sr25519::AuthorityPair as AuraPair, AuraApi};
+use sp_core::H256;
 use std::{sync::Arc, time::Duration};
-use tuxedo_core::{genesis::TuxedoGenesisBlockBuilder, types::OpaqueBlock as Block};
+use tuxedo_core::{
+    genesis::TuxedoGenesisBlockBuilder, types::OpaqueBlock as Block, verifier::Sr25519Signature,
+};
 
 // Our native executor instance.
 pub struct ExecutorDispatch;
@@ -218,6 +224,7 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
                 client: client.clone(),
                 pool: pool.clone(),
                 deny_unsafe,
+                command_sink: None,
             };
             rpc::create_full(deps).map_err(Into::into)
         })
@@ -238,6 +245,13 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
         telemetry: telemetry.as_mut(),
     })?;
 
+    // Every full node helps keep the PoE registry free of duplicate claims.
+    task_manager.spawn_handle().spawn(
+        "poe-auto-dispute",
+        None,
+        poe_disputes::run(client.clone(), transaction_pool.clone()),
+    );
+
     if role.is_authority() {
         let proposer_factory = sc_basic_authorship::ProposerFactory::new(
             task_manager.spawn_handle(),
@@ -259,6 +273,7 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
                 proposer_factory,
                 create_inherent_data_providers: move |parent_hash, ()| {
                     let maybe_parent_block = client_for_cidp.clone().block(parent_hash);
+                    let maybe_authorities = client_for_cidp.runtime_api().authorities(parent_hash);
 
                     async move {
                         let parent_block = maybe_parent_block?
@@ -275,7 +290,18 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
                                 slot_duration,
                             );
 
-                        Ok((slot, parent_idp, timestamp))
+                        // Pay the block reward to the authority that owns this slot, the same way
+                        // Aura decides who may author it.
+                        let authorities = maybe_authorities?;
+                        let author = authorities
+                            .get((u64::from(*slot) % authorities.len().max(1) as u64) as usize)
+                            .ok_or("No Aura authorities to reward")?;
+                        let coinbase =
+                            CoinbaseInherentDataProvider(OuterVerifier::Sr25519Signature(
+                                Sr25519Signature::new(H256::from_slice(author.as_ref())),
+                            ));
+
+                        Ok((slot, parent_idp, timestamp, coinbase))
                     }
                 },
                 force_authoring,
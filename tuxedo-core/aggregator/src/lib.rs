@@ -1,14 +1,63 @@
 use convert_case::{Case, Casing};
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, Ident, ItemEnum};
+use syn::{parse_macro_input, DeriveInput, GenericParam, Ident, ItemEnum, LitStr};
 
-/// Automatically implements `From` for each type in an aggregate type enum.
+/// Derives the `UtxoData` trait with a type id generated deterministically from a stable name.
+///
+/// The name is the name of the type, unless it is given explicitly with
+/// `#[utxo_data(name = "...")]`. Neither the module nor the crate the type is declared in is part
+/// of the name, so moving the type or renaming its crate does not change its id. A type that is
+/// renamed after its data is stored on chain should pin its old name with the attribute.
+///
+/// Const generic parameters are mixed in to the type id so that each instantiation
+/// of a type like `MintAuthority<const ID: u8>` gets its own id. Ordinary type parameters are not.
+#[proc_macro_derive(UtxoData, attributes(utxo_data))]
+pub fn derive_utxo_data(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+
+    let type_name = ast.ident;
+    let mut stable_name = LitStr::new(&type_name.to_string(), type_name.span());
+    for attr in ast.attrs.iter().filter(|a| a.path().is_ident("utxo_data")) {
+        let parsed = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                stable_name = meta.value()?.parse()?;
+                Ok(())
+            } else {
+                Err(meta.error("expected `name = \"...\"`"))
+            }
+        });
+        if let Err(e) = parsed {
+            return e.to_compile_error().into();
+        }
+    }
+
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let const_params = ast.generics.params.iter().filter_map(|param| match param {
+        GenericParam::Const(c) => Some(c.ident.clone()),
+        _ => None,
+    });
+
+    let output = quote! {
+        impl #impl_generics tuxedo_core::dynamic_typing::UtxoData for #type_name #ty_generics #where_clause {
+            const TYPE_ID: [u8; 4] = tuxedo_core::dynamic_typing::derive_type_id(
+                #stable_name,
+                &[#( #const_params as u128 ),*],
+            );
+        }
+    };
+
+    output.into()
+}
+
+/// Automatically implements `From` for each type in an aggregate type enum,
+/// and `TryFrom` to go back the other way.
 ///
 /// The supplied enum should have a single unnamed type parameter for each variant.
 /// And the type for each variant should be unique in the enum.
 ///
-/// The macro generates all the `From` implementations automatically.
+/// The macro generates all the conversion implementations automatically. Unwrapping an
+/// aggregate that holds a different variant fails with `tuxedo_core::types::WrongVariant`.
 #[proc_macro_attribute]
 pub fn aggregate(_: TokenStream, body: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(body as ItemEnum);
@@ -34,9 +83,21 @@ pub fn aggregate(_: TokenStream, body: TokenStream) -> TokenStream {
     });
     let variants = variant_type_pairs.clone().map(|(v, _t)| v);
     let variants2 = variants.clone();
+    let variants3 = variants.clone();
     let inner_types = variant_type_pairs.map(|(_v, t)| t);
     let inner_types2 = inner_types.clone();
 
+    // A match expression that names whichever variant the aggregate `a` actually holds.
+    // It is used in every one of the un-wrapping impls to report what was found instead.
+    let found_variant = quote! {
+        match &a {
+            #(
+                #outer_type::#variants3(_) => stringify!(#variants3),
+            )*
+        }
+    };
+    let found_variants = variants.clone().map(|_| found_variant.clone());
+
     let output = quote! {
         // First keep the original code in tact
         #original_code
@@ -50,14 +111,20 @@ pub fn aggregate(_: TokenStream, body: TokenStream) -> TokenStream {
             }
         )*
 
-        // Finally write all the un-wrapping From impls
+        // Finally write all the un-wrapping TryFrom impls
         #(
-            impl From<#outer_type> for #inner_types2 {
-                fn from(a: #outer_type) -> Self {
+            impl TryFrom<#outer_type> for #inner_types2 {
+                type Error = tuxedo_core::types::WrongVariant;
+
+                fn try_from(a: #outer_type) -> Result<Self, Self::Error> {
+                    let found = #found_variants;
                     if let #outer_type::#variants2(b) = a {
-                        b
+                        Ok(b)
                     } else {
-                        panic!("wrong type or something...")
+                        Err(tuxedo_core::types::WrongVariant {
+                            expected: stringify!(#variants2),
+                            found,
+                        })
                     }
                 }
             }
@@ -188,6 +255,9 @@ pub fn tuxedo_verifier(_: TokenStream, body: TokenStream) -> TokenStream {
 /// It also declares an associated error type. The error type has a variant for each inner constraint checker,
 /// just like this original enum. however, the contained values in the error enum are of the corresponding types
 /// for the inner constraint checker.
+///
+/// Finally it collects the UTXO types reported by each inner constraint checker so that type id
+/// collisions between pieces are detected when the genesis state is built.
 #[proc_macro_attribute]
 pub fn tuxedo_constraint_checker(_attrs: TokenStream, body: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(body as ItemEnum);
@@ -225,10 +295,10 @@ pub fn tuxedo_constraint_checker(_attrs: TokenStream, body: TokenStream) -> Toke
     let inner_types2 = inner_types.clone();
     let inner_types3 = inner_types.clone();
     let inner_types4 = inner_types.clone();
+    let inner_types5 = inner_types.clone();
     let variants2 = variants.clone();
-    let variants3 = variants.clone();
-    let variants4 = variants.clone();
     let variants5 = variants.clone();
+    let variants6 = variants.clone();
 
     let output = quote! {
         // Preserve the original enum, and write the From impls
@@ -250,16 +320,16 @@ pub fn tuxedo_constraint_checker(_attrs: TokenStream, body: TokenStream) -> Toke
         impl tuxedo_core::ConstraintChecker for #outer_type {
             type Error = #error_type;
 
-            fn check (
+            fn check<V: tuxedo_core::Verifier>(
                 &self,
-                inputs: &[tuxedo_core::dynamic_typing::DynamicallyTypedData],
-                evicted_inputs: &[tuxedo_core::dynamic_typing::DynamicallyTypedData],
-                peeks: &[tuxedo_core::dynamic_typing::DynamicallyTypedData],
-                outputs: &[tuxedo_core::dynamic_typing::DynamicallyTypedData],
+                inputs: &[tuxedo_core::types::Output<V>],
+                evicted_inputs: &[tuxedo_core::types::Output<V>],
+                peeks: &[tuxedo_core::types::Output<V>],
+                outputs: &[tuxedo_core::types::Output<V>],
             ) -> Result<TransactionPriority, Self::Error> {
                 match self {
                     #(
-                        Self::#variants5(inner) => inner.check(inputs, evicted_inputs, peeks, outputs).map_err(|e| Self::Error::#variants5(e)),
+                        Self::#variants5(inner) => tuxedo_core::ConstraintChecker::check(inner, inputs, evicted_inputs, peeks, outputs).map_err(|e| Self::Error::#variants5(e)),
                     )*
                 }
             }
@@ -273,10 +343,18 @@ pub fn tuxedo_constraint_checker(_attrs: TokenStream, body: TokenStream) -> Toke
 
             }
 
+            fn is_compoundable(&self) -> bool {
+                match self {
+                    #(
+                        Self::#variants6(inner) => inner.is_compoundable(),
+                    )*
+                }
+            }
+
             fn create_inherents<V: tuxedo_core::Verifier>(
                 authoring_inherent_data: &InherentData,
                 previous_inherents: Vec<(tuxedo_core::types::Transaction<V, #outer_type>, sp_core::H256)>,
-            ) -> Vec<tuxedo_core::types::Transaction<V, #outer_type>>  {
+            ) -> Result<Vec<tuxedo_core::types::Transaction<V, #outer_type>>, tuxedo_core::inherents::InherentError>  {
 
                 let mut all_inherents = Vec::new();
 
@@ -286,14 +364,13 @@ pub fn tuxedo_constraint_checker(_attrs: TokenStream, body: TokenStream) -> Toke
                         let previous_inherents = previous_inherents
                             .iter()
                             .filter_map(|(tx, hash)| {
-                                match tx.checker {
-                                    #outer_type::#variants3(ref inner_checker) => Some((tx.transform::<#inner_types3>(), *hash )),
-                                    _ => None,
-                                }
+                                tx.try_transform::<#inner_types3>()
+                                    .ok()
+                                    .map(|inner_tx| (inner_tx, *hash))
                             })
                             .collect();
 
-                        let inherents = <#inner_types3 as tuxedo_core::ConstraintChecker>::create_inherents(authoring_inherent_data, previous_inherents)
+                        let inherents = <#inner_types3 as tuxedo_core::ConstraintChecker>::create_inherents(authoring_inherent_data, previous_inherents)?
                             .iter()
                             .map(|tx| tx.transform::<#outer_type>())
                             .collect::<Vec<_>>();
@@ -302,7 +379,7 @@ pub fn tuxedo_constraint_checker(_attrs: TokenStream, body: TokenStream) -> Toke
                 )*
 
                 // Return the aggregate of all inherent extrinsics from all constituent constraint checkers.
-                all_inherents
+                Ok(all_inherents)
             }
 
             fn check_inherents<V: tuxedo_core::Verifier>(
@@ -311,14 +388,10 @@ pub fn tuxedo_constraint_checker(_attrs: TokenStream, body: TokenStream) -> Toke
                 result: &mut sp_inherents::CheckInherentsResult,
             ) {
                 #(
+                    // Inherents belonging to other pieces fail to unwrap and are simply skipped.
                     let relevant_inherents: Vec<tuxedo_core::types::Transaction<V, #inner_types4>> = inherents
                         .iter()
-                        .filter_map(|tx| {
-                            match tx.checker {
-                                #outer_type::#variants4(ref inner_checker) => Some(tx.transform::<#inner_types4>()),
-                                _ => None,
-                            }
-                        })
+                        .filter_map(|tx| tx.try_transform::<#inner_types4>().ok())
                         .collect();
 
                     <#inner_types4 as tuxedo_core::ConstraintChecker>::check_inherents(importing_inherent_data, relevant_inherents, result);
@@ -348,6 +421,95 @@ pub fn tuxedo_constraint_checker(_attrs: TokenStream, body: TokenStream) -> Toke
                 all_transactions
             }
 
+            fn utxo_types() -> Vec<tuxedo_core::dynamic_typing::UtxoType> {
+                let mut all_types = Vec::new();
+
+                #(
+                    all_types.extend(<#inner_types5 as tuxedo_core::ConstraintChecker>::utxo_types());
+                )*
+
+                all_types
+            }
+
+        }
+    };
+
+    output.into()
+}
+
+/// This macro treats the supplied enum as an aggregate UTXO payload. As such, it implements the `From`
+/// trait for each of the inner types. Then it implements the `UtxoPayload` trait for this enum so that
+/// dynamically typed data can be decoded directly into whichever variant it belongs to.
+///
+/// Each inner type must itself implement `UtxoPayload`, which is the case for every `UtxoData` type.
+/// Decoding tries the variants in order and picks the first one whose type id matches.
+#[proc_macro_attribute]
+pub fn tuxedo_payload(_: TokenStream, body: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(body as ItemEnum);
+    let original_code = ast.clone();
+
+    let outer_type = ast.ident;
+    let variant_type_pairs = ast.variants.iter().map(|variant| {
+        // Make sure there is only a single field, and if not, give a helpful error
+        assert!(
+            variant.fields.len() == 1,
+            "Each variant must have a single unnamed field"
+        );
+        (
+            variant.ident.clone(),
+            variant
+                .fields
+                .iter()
+                .next()
+                .expect("exactly one field per variant")
+                .ty
+                .clone(),
+        )
+    });
+    let variants = variant_type_pairs.clone().map(|(v, _t)| v);
+    let inner_types = variant_type_pairs.map(|(_v, t)| t);
+
+    let inner_types2 = inner_types.clone();
+    let variants2 = variants.clone();
+
+    let output = quote! {
+        // Preserve the original enum, and write the From impls
+        #[tuxedo_core::aggregate]
+        #original_code
+
+        impl tuxedo_core::dynamic_typing::UtxoPayload for #outer_type {
+            fn from_dynamic(
+                data: &tuxedo_core::dynamic_typing::DynamicallyTypedData,
+            ) -> Result<Self, tuxedo_core::dynamic_typing::DynamicTypingError> {
+                #(
+                    match <#inner_types as tuxedo_core::dynamic_typing::UtxoPayload>::from_dynamic(data) {
+                        Ok(inner) => return Ok(Self::#variants(inner)),
+                        // This data belongs to some other variant, so keep looking.
+                        Err(tuxedo_core::dynamic_typing::DynamicTypingError::WrongType) => (),
+                        Err(e) => return Err(e),
+                    }
+                )*
+
+                Err(tuxedo_core::dynamic_typing::DynamicTypingError::WrongType)
+            }
+
+            fn to_dynamic(&self) -> tuxedo_core::dynamic_typing::DynamicallyTypedData {
+                match self {
+                    #(
+                        Self::#variants2(inner) => tuxedo_core::dynamic_typing::UtxoPayload::to_dynamic(inner),
+                    )*
+                }
+            }
+
+            fn utxo_types() -> Vec<tuxedo_core::dynamic_typing::UtxoType> {
+                let mut all_types = Vec::new();
+
+                #(
+                    all_types.extend(<#inner_types2 as tuxedo_core::dynamic_typing::UtxoPayload>::utxo_types());
+                )*
+
+                all_types
+            }
         }
     };
 
@@ -0,0 +1,334 @@
+//! Compound transactions that satisfy several constraint checkers at once.
+//!
+//! Each Tuxedo transaction names exactly one constraint checker. Sometimes it is useful to
+//! combine the logic of independently written pieces into a single atomic transaction. For
+//! example, paying a fee in coins while breeding kitties, or swapping a kitty for some money.
+//! Rather than writing a bespoke piece for every such combination, a transaction may use the
+//! `CompoundConstraintChecker`, which divides the transaction into parts. Each part names an
+//! inner constraint checker along with how many inputs, evicted inputs, peeks, and outputs it
+//! claims. The parts claim consecutive runs of each kind of data in order, and together they
+//! must claim all of it. Each part is then checked independently by its own checker, and the
+//! transaction is valid only if every part is.
+//!
+//! Note that normal inputs and evicted inputs are counted separately. A part's inputs are taken
+//! from the transaction's redeemed inputs in the order they appear, skipping any evictions,
+//! and vice versa.
+//!
+//! Runtimes opt in by including a variant such as
+//! `Compound(CompoundConstraintChecker<OuterConstraintChecker>)` in their aggregate constraint checker.
+//! Pieces whose rules could be dodged by splitting a transaction into parts opt out by setting
+//! `COMPOUNDABLE` to false on their constraint checkers. So do pieces that write to storage while
+//! checking, or that are inherents.
+//!
+//! A part may not itself be a compound transaction. Nesting would add nothing, and checking deeply
+//! nested parts would recurse once per level. The parts are stored in a `Vec`, so each level of
+//! nesting also counts towards the depth limit that the runtime API applies when decoding.
+
+use crate::{ensure, types::Output, ConstraintChecker, Verifier, VerifierAwareConstraintChecker};
+use parity_scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use serde::{Deserialize, Serialize};
+use sp_runtime::transaction_validity::TransactionPriority;
+use sp_std::{boxed::Box, vec::Vec};
+
+/// One part of a compound transaction, along with the amount of data it claims.
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, TypeInfo)]
+pub struct CompoundPart<C> {
+    /// The constraint checker that checks this part of the transaction.
+    pub checker: C,
+    /// How many of the transaction's redeemed inputs belong to this part.
+    pub inputs: u32,
+    /// How many of the transaction's evicted inputs belong to this part.
+    pub evicted_inputs: u32,
+    /// How many of the transaction's peeks belong to this part.
+    pub peeks: u32,
+    /// How many of the transaction's outputs belong to this part.
+    pub outputs: u32,
+}
+
+/// A constraint checker that partitions a transaction among several inner constraint checkers.
+///
+/// The inner checker type is usually the runtime's own aggregate constraint checker.
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, TypeInfo)]
+pub struct CompoundConstraintChecker<C>(pub Vec<CompoundPart<C>>);
+
+/// Errors that can occur when checking a compound transaction.
+#[derive(Debug, PartialEq, Eq)]
+pub enum CompoundError<E> {
+    /// A compound transaction must have at least two parts. A single part
+    /// should just use its inner checker directly.
+    TooFewParts,
+    /// The parts do not claim exactly the inputs, evictions, peeks, and outputs
+    /// that are present in the transaction.
+    PartitionMismatch,
+    /// Inherents may not be part of a compound transaction.
+    InherentPart,
+    /// One of the parts uses a constraint checker that does not allow being part of a
+    /// compound transaction.
+    NotCompoundable,
+    /// One of the parts failed its own constraint checker.
+    PartFailed {
+        /// The position of the failing part in the compound transaction.
+        index: u32,
+        /// The error returned by that part's constraint checker.
+        error: Box<E>,
+    },
+}
+
+/// Take the next `count` items from the front of `remaining`, if there are that many.
+fn take<'a, T>(remaining: &mut &'a [T], count: u32) -> Option<&'a [T]> {
+    let count = count as usize;
+    if count > remaining.len() {
+        return None;
+    }
+    let (claimed, rest) = remaining.split_at(count);
+    *remaining = rest;
+    Some(claimed)
+}
+
+impl<C: ConstraintChecker> VerifierAwareConstraintChecker for CompoundConstraintChecker<C> {
+    type Error = CompoundError<C::Error>;
+
+    fn check<V: Verifier>(
+        &self,
+        inputs: &[Output<V>],
+        evicted_inputs: &[Output<V>],
+        peeks: &[Output<V>],
+        outputs: &[Output<V>],
+    ) -> Result<TransactionPriority, Self::Error> {
+        ensure!(self.0.len() >= 2, CompoundError::TooFewParts);
+
+        // First make sure the partition is valid so that parts are only checked
+        // when the transaction is well formed.
+        let mut remaining = (inputs, evicted_inputs, peeks, outputs);
+        let mut claims = Vec::with_capacity(self.0.len());
+        for part in self.0.iter() {
+            ensure!(!part.checker.is_inherent(), CompoundError::InherentPart);
+            ensure!(
+                part.checker.is_compoundable(),
+                CompoundError::NotCompoundable
+            );
+            claims.push((
+                take(&mut remaining.0, part.inputs).ok_or(CompoundError::PartitionMismatch)?,
+                take(&mut remaining.1, part.evicted_inputs)
+                    .ok_or(CompoundError::PartitionMismatch)?,
+                take(&mut remaining.2, part.peeks).ok_or(CompoundError::PartitionMismatch)?,
+                take(&mut remaining.3, part.outputs).ok_or(CompoundError::PartitionMismatch)?,
+            ));
+        }
+        ensure!(
+            remaining.0.is_empty()
+                && remaining.1.is_empty()
+                && remaining.2.is_empty()
+                && remaining.3.is_empty(),
+            CompoundError::PartitionMismatch
+        );
+
+        // Now check each part independently. The priority is the total of all the parts.
+        let mut priority: TransactionPriority = 0;
+        for (index, (part, (inputs, evicted_inputs, peeks, outputs))) in
+            self.0.iter().zip(claims).enumerate()
+        {
+            let part_priority =
+                ConstraintChecker::check(&part.checker, inputs, evicted_inputs, peeks, outputs)
+                    .map_err(|error| CompoundError::PartFailed {
+                        index: index as u32,
+                        error: Box::new(error),
+                    })?;
+            priority = priority.saturating_add(part_priority);
+        }
+
+        Ok(priority)
+    }
+
+    // Nested compound transactions are rejected before any of their parts are checked.
+    const COMPOUNDABLE: bool = false;
+
+    // The utxo types are deliberately not reported here. The inner checker is usually the
+    // runtime's aggregate checker, which already reports them, and asking it again would recurse forever.
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        dynamic_typing::{testing::Bogus, DynamicallyTypedData},
+        verifier::TestVerifier,
+        SimpleConstraintChecker,
+    };
+
+    /// A checker that expects an exact number of inputs and outputs and returns
+    /// the number of outputs as its priority.
+    #[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, TypeInfo)]
+    struct Counting {
+        inputs: u32,
+        outputs: u32,
+    }
+
+    impl SimpleConstraintChecker for Counting {
+        type Error = ();
+
+        fn check(
+            &self,
+            input_data: &[DynamicallyTypedData],
+            _evicted_input_data: &[DynamicallyTypedData],
+            _peek_data: &[DynamicallyTypedData],
+            output_data: &[DynamicallyTypedData],
+        ) -> Result<TransactionPriority, ()> {
+            ensure!(input_data.len() == self.inputs as usize, ());
+            ensure!(output_data.len() == self.outputs as usize, ());
+            Ok(self.outputs as u64)
+        }
+    }
+
+    fn part(inputs: u32, outputs: u32) -> CompoundPart<Counting> {
+        CompoundPart {
+            checker: Counting { inputs, outputs },
+            inputs,
+            evicted_inputs: 0,
+            peeks: 0,
+            outputs,
+        }
+    }
+
+    fn utxos(n: usize) -> Vec<Output<TestVerifier>> {
+        (0..n)
+            .map(|_| Output {
+                payload: Bogus.into(),
+                verifier: TestVerifier { verifies: true },
+            })
+            .collect()
+    }
+
+    #[test]
+    fn compound_valid_partition_works() {
+        let checker = CompoundConstraintChecker(vec![part(1, 2), part(2, 1)]);
+
+        assert_eq!(
+            VerifierAwareConstraintChecker::check(&checker, &utxos(3), &[], &[], &utxos(3)),
+            Ok(3)
+        );
+    }
+
+    #[test]
+    fn compound_with_single_part_fails() {
+        let checker = CompoundConstraintChecker(vec![part(1, 1)]);
+
+        assert_eq!(
+            VerifierAwareConstraintChecker::check(&checker, &utxos(1), &[], &[], &utxos(1)),
+            Err(CompoundError::TooFewParts)
+        );
+    }
+
+    #[test]
+    fn compound_with_unclaimed_data_fails() {
+        let checker = CompoundConstraintChecker(vec![part(1, 1), part(1, 1)]);
+
+        assert_eq!(
+            VerifierAwareConstraintChecker::check(&checker, &utxos(3), &[], &[], &utxos(2)),
+            Err(CompoundError::PartitionMismatch)
+        );
+    }
+
+    #[test]
+    fn compound_with_overclaimed_data_fails() {
+        let checker = CompoundConstraintChecker(vec![part(1, 1), part(1, 2)]);
+
+        assert_eq!(
+            VerifierAwareConstraintChecker::check(&checker, &utxos(2), &[], &[], &utxos(2)),
+            Err(CompoundError::PartitionMismatch)
+        );
+    }
+
+    #[test]
+    fn compound_reports_failing_part() {
+        // The second part claims one input, but its checker expects two.
+        let mut second = part(2, 0);
+        second.inputs = 1;
+        let checker = CompoundConstraintChecker(vec![part(1, 0), second]);
+
+        assert_eq!(
+            VerifierAwareConstraintChecker::check(&checker, &utxos(2), &[], &[], &[]),
+            Err(CompoundError::PartFailed {
+                index: 1,
+                error: Box::new(()),
+            })
+        );
+    }
+
+    #[test]
+    fn compound_rejects_inherent_parts() {
+        use crate::constraint_checker::testing::TestConstraintChecker;
+
+        let inherent_part = CompoundPart {
+            checker: TestConstraintChecker {
+                checks: true,
+                inherent: true,
+            },
+            inputs: 0,
+            evicted_inputs: 0,
+            peeks: 0,
+            outputs: 0,
+        };
+        let checker = CompoundConstraintChecker(vec![inherent_part.clone(), inherent_part]);
+
+        assert_eq!(
+            VerifierAwareConstraintChecker::check::<TestVerifier>(&checker, &[], &[], &[], &[]),
+            Err(CompoundError::InherentPart)
+        );
+    }
+
+    /// A checker that accepts anything, but does not allow being part of a compound transaction.
+    #[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, TypeInfo)]
+    struct Standalone;
+
+    impl SimpleConstraintChecker for Standalone {
+        type Error = ();
+
+        fn check(
+            &self,
+            _input_data: &[DynamicallyTypedData],
+            _evicted_input_data: &[DynamicallyTypedData],
+            _peek_data: &[DynamicallyTypedData],
+            _output_data: &[DynamicallyTypedData],
+        ) -> Result<TransactionPriority, ()> {
+            Ok(0)
+        }
+
+        const COMPOUNDABLE: bool = false;
+    }
+
+    #[test]
+    fn compound_rejects_non_compoundable_parts() {
+        let standalone_part = CompoundPart {
+            checker: Standalone,
+            inputs: 1,
+            evicted_inputs: 0,
+            peeks: 0,
+            outputs: 1,
+        };
+        let checker = CompoundConstraintChecker(vec![standalone_part.clone(), standalone_part]);
+
+        assert_eq!(
+            VerifierAwareConstraintChecker::check(&checker, &utxos(2), &[], &[], &utxos(2)),
+            Err(CompoundError::NotCompoundable)
+        );
+    }
+
+    #[test]
+    fn compound_rejects_nested_compound_parts() {
+        let nested_part = CompoundPart {
+            checker: CompoundConstraintChecker(vec![part(1, 1), part(1, 1)]),
+            inputs: 2,
+            evicted_inputs: 0,
+            peeks: 0,
+            outputs: 2,
+        };
+        let checker = CompoundConstraintChecker(vec![nested_part.clone(), nested_part]);
+
+        assert_eq!(
+            VerifierAwareConstraintChecker::check(&checker, &utxos(4), &[], &[], &utxos(4)),
+            Err(CompoundError::NotCompoundable)
+        );
+    }
+}
@@ -11,6 +11,16 @@
 //! and outputs (and peeks) is valid. For example making sure no extra money was created, or making sure the chemical
 //! reaction balances.
 //!
+//! Pieces that would rather not deal with dynamic typing can implement `TypedConstraintChecker`
+//! instead. It receives the data already decoded into a payload type of the piece's choosing, and
+//! it becomes a `SimpleConstraintChecker` through a blanket implementation. Typed checkers are
+//! opt-in. So far only the amoeba piece uses one. The other pieces in the wardrobe work with
+//! several types at once, often ones that are generic or belong to other pieces, and they report
+//! a specific error for each misplaced type, so they still call `extract` themselves.
+//!
+//! Pieces whose rules depend on who owns the UTXOs involved can implement
+//! `VerifierAwareConstraintChecker`, which receives the full outputs, verifiers included.
+//!
 //! ## Inherents
 //!
 //! If you need to tap in to [Substrate's inherent system](https://docs.substrate.io/learn/transaction-types/#inherent-transactions)
@@ -30,7 +40,12 @@ use sp_core::H256;
 use sp_inherents::{CheckInherentsResult, InherentData};
 use sp_std::{fmt::Debug, vec::Vec};
 
-use crate::{dynamic_typing::DynamicallyTypedData, types::Transaction, Verifier};
+use crate::{
+    dynamic_typing::{DynamicallyTypedData, UtxoPayload, UtxoType},
+    inherents::InherentError,
+    types::{Output, Transaction},
+    Verifier,
+};
 use parity_scale_codec::{Decode, Encode};
 use sp_runtime::transaction_validity::TransactionPriority;
 
@@ -52,6 +67,165 @@ pub trait SimpleConstraintChecker: Debug + Encode + Decode + Clone {
         peek_data: &[DynamicallyTypedData],
         output_data: &[DynamicallyTypedData],
     ) -> Result<TransactionPriority, Self::Error>;
+
+    /// The types of data that this constraint checker reads from or writes to UTXOs.
+    ///
+    /// Reporting types here is optional, but it allows the runtime to detect, at genesis,
+    /// when two pieces accidentally use the same type id for different types.
+    fn utxo_types() -> Vec<UtxoType> {
+        Vec::new()
+    }
+
+    /// Whether this constraint checker may check one part of a compound transaction.
+    ///
+    /// Checkers whose rules could be dodged by splitting a transaction into parts, such as
+    /// rules about what must be paid for something, should set this to false.
+    const COMPOUNDABLE: bool = true;
+}
+
+/// Identifies which part of a transaction contained data that did not match
+/// the payload type of a `TypedConstraintChecker`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum BadlyTypedData {
+    /// A normal input had the wrong type or failed to decode.
+    Input,
+    /// An evicted input had the wrong type or failed to decode.
+    EvictedInput,
+    /// An output had the wrong type or failed to decode.
+    Output,
+}
+
+/// A constraint checker that receives strongly typed data instead of dynamically typed data.
+///
+/// The payload is usually a single `UtxoData` type, or an enum declared with `#[tuxedo_payload]`
+/// when the checker works with several types. The inputs, evicted inputs, and outputs are decoded
+/// before `check` is called, and any data that does not decode is reported through the checker's
+/// own error type.
+///
+/// Peeks are passed through untyped. Pieces commonly peek at data that belongs to other pieces,
+/// such as a timestamp or the result of a vote, and that data should not have to be part of the
+/// checker's payload.
+///
+/// Every `TypedConstraintChecker` is also a `SimpleConstraintChecker`, and it reports the types
+/// in its payload for collision detection automatically.
+pub trait TypedConstraintChecker: Debug + Encode + Decode + Clone {
+    /// The strongly typed data that this constraint checker operates on
+    type Payload: UtxoPayload;
+
+    /// The error type that this constraint checker may return
+    type Error: Debug + From<BadlyTypedData>;
+
+    /// The on chain logic that makes the final check for whether a transaction is valid.
+    fn check(
+        &self,
+        input_data: &[Self::Payload],
+        evicted_input_data: &[Self::Payload],
+        peek_data: &[DynamicallyTypedData],
+        output_data: &[Self::Payload],
+    ) -> Result<TransactionPriority, Self::Error>;
+}
+
+/// Decode a slice of dynamically typed data into a typed payload, reporting `which`
+/// part of the transaction was at fault if any of it fails.
+fn decode_payloads<P: UtxoPayload>(
+    data: &[DynamicallyTypedData],
+    which: BadlyTypedData,
+) -> Result<Vec<P>, BadlyTypedData> {
+    data.iter()
+        .map(|d| P::from_dynamic(d).map_err(|_| which))
+        .collect()
+}
+
+impl<T: TypedConstraintChecker> SimpleConstraintChecker for T {
+    type Error = <T as TypedConstraintChecker>::Error;
+
+    fn check(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        evicted_input_data: &[DynamicallyTypedData],
+        peek_data: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> Result<TransactionPriority, Self::Error> {
+        let inputs = decode_payloads(input_data, BadlyTypedData::Input)?;
+        let evicted_inputs = decode_payloads(evicted_input_data, BadlyTypedData::EvictedInput)?;
+        let outputs = decode_payloads(output_data, BadlyTypedData::Output)?;
+
+        TypedConstraintChecker::check(self, &inputs, &evicted_inputs, peek_data, &outputs)
+    }
+
+    fn utxo_types() -> Vec<UtxoType> {
+        T::Payload::utxo_types()
+    }
+}
+
+/// A constraint checker that sees the verifiers of the UTXOs involved, not just their data.
+///
+/// This is useful for rules about ownership, such as requiring a newly bred kitty to be owned
+/// by the same key as its parents, or requiring a fee output to be locked to a particular
+/// verifier. The checker is generic over the runtime's verifier type, so it may either work with
+/// the typed verifiers directly, or compare their SCALE encodings without knowing what they are.
+///
+/// Every `SimpleConstraintChecker` is also a `VerifierAwareConstraintChecker` through a blanket
+/// implementation that simply ignores the verifiers.
+pub trait VerifierAwareConstraintChecker: Debug + Encode + Decode + Clone {
+    /// The error type that this constraint checker may return
+    type Error: Debug;
+
+    /// The on chain logic that makes the final check for whether a transaction is valid.
+    ///
+    /// Inputs and peeks are the full outputs being consumed or read from storage.
+    fn check<V: Verifier>(
+        &self,
+        inputs: &[Output<V>],
+        evicted_inputs: &[Output<V>],
+        peeks: &[Output<V>],
+        outputs: &[Output<V>],
+    ) -> Result<TransactionPriority, Self::Error>;
+
+    /// The types of data that this constraint checker reads from or writes to UTXOs.
+    ///
+    /// Reporting types here is optional, but it allows the runtime to detect, at genesis,
+    /// when two pieces accidentally use the same type id for different types.
+    fn utxo_types() -> Vec<UtxoType> {
+        Vec::new()
+    }
+
+    /// Whether this constraint checker may check one part of a compound transaction.
+    ///
+    /// Checkers whose rules could be dodged by splitting a transaction into parts, such as
+    /// rules about what must be paid for something, should set this to false.
+    const COMPOUNDABLE: bool = true;
+}
+
+/// Strip the verifiers from a slice of outputs, leaving only the payloads.
+fn payloads<V>(utxos: &[Output<V>]) -> Vec<DynamicallyTypedData> {
+    utxos.iter().map(|o| o.payload.clone()).collect()
+}
+
+impl<T: SimpleConstraintChecker> VerifierAwareConstraintChecker for T {
+    type Error = <T as SimpleConstraintChecker>::Error;
+
+    fn check<V: Verifier>(
+        &self,
+        inputs: &[Output<V>],
+        evicted_inputs: &[Output<V>],
+        peeks: &[Output<V>],
+        outputs: &[Output<V>],
+    ) -> Result<TransactionPriority, Self::Error> {
+        SimpleConstraintChecker::check(
+            self,
+            &payloads(inputs),
+            &payloads(evicted_inputs),
+            &payloads(peeks),
+            &payloads(outputs),
+        )
+    }
+
+    fn utxo_types() -> Vec<UtxoType> {
+        <T as SimpleConstraintChecker>::utxo_types()
+    }
+
+    const COMPOUNDABLE: bool = <T as SimpleConstraintChecker>::COMPOUNDABLE;
 }
 
 /// The raw and fully powerful `ConstraintChecker` interface used by the
@@ -61,6 +235,8 @@ pub trait SimpleConstraintChecker: Debug + Encode + Decode + Clone {
 /// If you are:
 /// * Working on a simple non-inherent constraint checker -> Use the `SimpleConstraintChecker` trait instead
 ///   and rely on its blanket implementation.
+/// * Working on a non-inherent constraint checker that cares about verifiers -> Use the
+///   `VerifierAwareConstraintChecker` trait instead and rely on its blanket implementation.
 /// * Working on an inherent constraint checker -> Implement `SimpleConstraintChecker` and `InherentHooks` and use the
 /// `InherentAdapter` wrapper type.
 /// * Considering an aggregate constraint checker that is part inherent, part not -> let the macro handle it for you.
@@ -74,24 +250,29 @@ pub trait ConstraintChecker: Debug + Encode + Decode + Clone {
     type Error: Debug;
 
     /// The on chain logic that makes the final check for whether a transaction is valid.
-    fn check(
+    fn check<V: Verifier>(
         &self,
-        input_data: &[DynamicallyTypedData],
-        evicted_input_data: &[DynamicallyTypedData],
-        peek_data: &[DynamicallyTypedData],
-        output_data: &[DynamicallyTypedData],
+        inputs: &[Output<V>],
+        evicted_inputs: &[Output<V>],
+        peeks: &[Output<V>],
+        outputs: &[Output<V>],
     ) -> Result<TransactionPriority, Self::Error>;
 
     /// Tells whether this extrinsic is an inherent or not.
     /// If you return true here, you must provide the correct inherent hooks above.
     fn is_inherent(&self) -> bool;
 
+    /// Tells whether this checker may check one part of a compound transaction.
+    fn is_compoundable(&self) -> bool {
+        true
+    }
+
     /// Create the inherent extrinsics to insert into a block that is being authored locally.
     /// The inherent data is supplied by the authoring node.
     fn create_inherents<V: Verifier>(
         authoring_inherent_data: &InherentData,
         previous_inherents: Vec<(Transaction<V, Self>, H256)>,
-    ) -> Vec<Transaction<V, Self>>;
+    ) -> Result<Vec<Transaction<V, Self>>, InherentError>;
 
     /// Perform off-chain pre-execution checks on the inherents.
     /// The inherent data is supplied by the importing node.
@@ -105,34 +286,45 @@ pub trait ConstraintChecker: Debug + Encode + Decode + Clone {
 
     /// Return the genesis transactions that are required for the inherents.
     fn genesis_transactions<V: Verifier>() -> Vec<Transaction<V, Self>>;
+
+    /// The types of data that this constraint checker reads from or writes to UTXOs.
+    /// Used to detect type id collisions between the pieces in a runtime.
+    fn utxo_types() -> Vec<UtxoType> {
+        Vec::new()
+    }
 }
 
-// We automatically supply every single simple constraint checker with a dummy set
-// of inherent hooks. This allows "normal" non-inherent constraint checkers to satisfy the
-// executive's expected interfaces without the piece author worrying about inherents.
-impl<T: SimpleConstraintChecker> ConstraintChecker for T {
-    // Use the same error type used in the simple implementation.
-    type Error = <T as SimpleConstraintChecker>::Error;
+// We automatically supply every single verifier-aware constraint checker (and therefore every
+// simple constraint checker) with a dummy set of inherent hooks. This allows "normal" non-inherent
+// constraint checkers to satisfy the executive's expected interfaces without the piece author
+// worrying about inherents.
+impl<T: VerifierAwareConstraintChecker> ConstraintChecker for T {
+    // Use the same error type used in the underlying implementation.
+    type Error = <T as VerifierAwareConstraintChecker>::Error;
 
-    fn check(
+    fn check<V: Verifier>(
         &self,
-        input_data: &[DynamicallyTypedData],
-        evicted_input_data: &[DynamicallyTypedData],
-        peek_data: &[DynamicallyTypedData],
-        output_data: &[DynamicallyTypedData],
+        inputs: &[Output<V>],
+        evicted_inputs: &[Output<V>],
+        peeks: &[Output<V>],
+        outputs: &[Output<V>],
     ) -> Result<TransactionPriority, Self::Error> {
-        SimpleConstraintChecker::check(self, input_data, evicted_input_data, peek_data, output_data)
+        VerifierAwareConstraintChecker::check(self, inputs, evicted_inputs, peeks, outputs)
     }
 
     fn is_inherent(&self) -> bool {
         false
     }
 
+    fn is_compoundable(&self) -> bool {
+        <T as VerifierAwareConstraintChecker>::COMPOUNDABLE
+    }
+
     fn create_inherents<V>(
         _authoring_inherent_data: &InherentData,
         _previous_inherents: Vec<(Transaction<V, Self>, H256)>,
-    ) -> Vec<Transaction<V, Self>> {
-        Vec::new()
+    ) -> Result<Vec<Transaction<V, Self>>, InherentError> {
+        Ok(Vec::new())
     }
 
     fn check_inherents<V>(
@@ -151,6 +343,10 @@ impl<T: SimpleConstraintChecker> ConstraintChecker for T {
     fn genesis_transactions<V>() -> Vec<Transaction<V, Self>> {
         Vec::new()
     }
+
+    fn utxo_types() -> Vec<UtxoType> {
+        <T as VerifierAwareConstraintChecker>::utxo_types()
+    }
 }
 
 /// Utilities for writing constraint-checker-related unit tests
@@ -160,7 +356,15 @@ pub mod testing {
     use scale_info::TypeInfo;
     use serde::{Deserialize, Serialize};
 
-    use super::{ConstraintChecker, DynamicallyTypedData, TransactionPriority};
+    use super::{
+        BadlyTypedData, ConstraintChecker, DynamicallyTypedData, SimpleConstraintChecker,
+        TransactionPriority, TypedConstraintChecker, VerifierAwareConstraintChecker,
+    };
+    use crate::{
+        dynamic_typing::{testing::Bogus, UtxoType},
+        types::Output,
+        verifier::TestVerifier,
+    };
 
     /// A testing checker that passes (with zero priority) or not depending on
     /// the boolean value enclosed.
@@ -175,12 +379,12 @@ pub mod testing {
     impl ConstraintChecker for TestConstraintChecker {
         type Error = ();
 
-        fn check(
+        fn check<V: crate::Verifier>(
             &self,
-            _input_data: &[DynamicallyTypedData],
-            _evicted_input_data: &[DynamicallyTypedData],
-            _peek_data: &[DynamicallyTypedData],
-            _output_data: &[DynamicallyTypedData],
+            _inputs: &[Output<V>],
+            _evicted_inputs: &[Output<V>],
+            _peeks: &[Output<V>],
+            _outputs: &[Output<V>],
         ) -> Result<TransactionPriority, ()> {
             if self.checks {
                 Ok(0)
@@ -196,7 +400,8 @@ pub mod testing {
         fn create_inherents<V: crate::Verifier>(
             _: &sp_inherents::InherentData,
             _: Vec<(crate::types::Transaction<V, Self>, sp_core::H256)>,
-        ) -> Vec<crate::types::Transaction<V, Self>> {
+        ) -> Result<Vec<crate::types::Transaction<V, Self>>, crate::inherents::InherentError>
+        {
             unimplemented!()
         }
 
@@ -219,7 +424,7 @@ pub mod testing {
             checks: true,
             inherent: false,
         }
-        .check(&[], &[], &[], &[]);
+        .check::<TestVerifier>(&[], &[], &[], &[]);
         assert_eq!(result, Ok(0));
     }
 
@@ -229,7 +434,187 @@ pub mod testing {
             checks: false,
             inherent: false,
         }
-        .check(&[], &[], &[], &[]);
+        .check::<TestVerifier>(&[], &[], &[], &[]);
+        assert_eq!(result, Err(()));
+    }
+
+    /// A typed checker that passes when it has at least as many inputs as outputs.
+    #[derive(Serialize, Deserialize, Encode, Decode, Debug, Clone, PartialEq, Eq, TypeInfo)]
+    pub struct TestTypedChecker;
+
+    #[derive(Debug, PartialEq, Eq)]
+    pub enum TestTypedError {
+        BadlyTyped(BadlyTypedData),
+        TooManyOutputs,
+    }
+
+    impl From<BadlyTypedData> for TestTypedError {
+        fn from(e: BadlyTypedData) -> Self {
+            Self::BadlyTyped(e)
+        }
+    }
+
+    impl TypedConstraintChecker for TestTypedChecker {
+        type Payload = Bogus;
+        type Error = TestTypedError;
+
+        fn check(
+            &self,
+            input_data: &[Bogus],
+            _evicted_input_data: &[Bogus],
+            _peek_data: &[DynamicallyTypedData],
+            output_data: &[Bogus],
+        ) -> Result<TransactionPriority, TestTypedError> {
+            if input_data.len() >= output_data.len() {
+                Ok(0)
+            } else {
+                Err(TestTypedError::TooManyOutputs)
+            }
+        }
+    }
+
+    fn not_bogus() -> DynamicallyTypedData {
+        DynamicallyTypedData {
+            data: Vec::new(),
+            type_id: *b"nope",
+        }
+    }
+
+    #[test]
+    fn typed_checker_passes() {
+        let result = SimpleConstraintChecker::check(
+            &TestTypedChecker,
+            &[Bogus.into(), Bogus.into()],
+            &[],
+            &[],
+            &[Bogus.into()],
+        );
+        assert_eq!(result, Ok(0));
+    }
+
+    #[test]
+    fn typed_checker_fails() {
+        let result =
+            SimpleConstraintChecker::check(&TestTypedChecker, &[], &[], &[], &[Bogus.into()]);
+        assert_eq!(result, Err(TestTypedError::TooManyOutputs));
+    }
+
+    #[test]
+    fn typed_checker_reports_badly_typed_data() {
+        let bogus: DynamicallyTypedData = Bogus.into();
+        let cases = [
+            (
+                [not_bogus()],
+                [bogus.clone()],
+                [bogus.clone()],
+                [bogus.clone()],
+                BadlyTypedData::Input,
+            ),
+            (
+                [bogus.clone()],
+                [not_bogus()],
+                [bogus.clone()],
+                [bogus.clone()],
+                BadlyTypedData::EvictedInput,
+            ),
+            (
+                [bogus.clone()],
+                [bogus.clone()],
+                [bogus.clone()],
+                [not_bogus()],
+                BadlyTypedData::Output,
+            ),
+        ];
+
+        for (inputs, evictions, peeks, outputs, expected) in cases {
+            let result = SimpleConstraintChecker::check(
+                &TestTypedChecker,
+                &inputs,
+                &evictions,
+                &peeks,
+                &outputs,
+            );
+            assert_eq!(result, Err(TestTypedError::BadlyTyped(expected)));
+        }
+    }
+
+    #[test]
+    fn typed_checker_accepts_peeks_of_any_type() {
+        let result = SimpleConstraintChecker::check(
+            &TestTypedChecker,
+            &[Bogus.into()],
+            &[],
+            &[not_bogus()],
+            &[Bogus.into()],
+        );
+        assert_eq!(result, Ok(0));
+    }
+
+    #[test]
+    fn typed_checker_reports_payload_types() {
+        let types = <TestTypedChecker as SimpleConstraintChecker>::utxo_types();
+        assert_eq!(types, vec![UtxoType::of::<Bogus>()]);
+    }
+
+    /// A verifier aware checker that requires every output to be locked
+    /// the same way as the first input.
+    #[derive(Serialize, Deserialize, Encode, Decode, Debug, Clone, PartialEq, Eq, TypeInfo)]
+    pub struct TestSameOwnerChecker;
+
+    impl VerifierAwareConstraintChecker for TestSameOwnerChecker {
+        type Error = ();
+
+        fn check<V: crate::Verifier>(
+            &self,
+            inputs: &[Output<V>],
+            _evicted_inputs: &[Output<V>],
+            _peeks: &[Output<V>],
+            outputs: &[Output<V>],
+        ) -> Result<TransactionPriority, ()> {
+            let owner = inputs.first().ok_or(())?.verifier.encode();
+            if outputs.iter().all(|o| o.verifier.encode() == owner) {
+                Ok(0)
+            } else {
+                Err(())
+            }
+        }
+    }
+
+    fn bogus_owned_by(verifies: bool) -> Output<TestVerifier> {
+        Output {
+            payload: Bogus.into(),
+            verifier: TestVerifier { verifies },
+        }
+    }
+
+    #[test]
+    fn verifier_aware_checker_sees_verifiers() {
+        let result = ConstraintChecker::check(
+            &TestSameOwnerChecker,
+            &[bogus_owned_by(true)],
+            &[],
+            &[],
+            &[bogus_owned_by(true), bogus_owned_by(true)],
+        );
+        assert_eq!(result, Ok(0));
+    }
+
+    #[test]
+    fn verifier_aware_checker_rejects_different_verifier() {
+        let result = ConstraintChecker::check(
+            &TestSameOwnerChecker,
+            &[bogus_owned_by(true)],
+            &[],
+            &[],
+            &[bogus_owned_by(true), bogus_owned_by(false)],
+        );
         assert_eq!(result, Err(()));
     }
+
+    #[test]
+    fn simple_checker_adapts_to_full_outputs() {
+        let result =
+            ConstraintChecker::check(&TestTypedChecker, &[], &[], &[], &[bogus_owned_by(true)]);
+        assert_eq!(result, Err(TestTypedError::TooManyOutputs));
+    }
 }
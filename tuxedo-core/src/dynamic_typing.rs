@@ -25,8 +25,9 @@
 //! To solve this problem we associate a four-byte type identifier with each data type that can
 //! be stored in a UTXO. When a UTXO is stored, the type identifier is stored along with the
 //! serialized data. When the UTXO is later read from storage, the type identifier is checked
-//! against the type into which the data is being decoded. Currently this read-time checking
-//! is the job of the piece developer, although that may be able to improve in the future.
+//! against the type into which the data is being decoded. Pieces that implement
+//! `TypedConstraintChecker` have this read-time checking done for them. Pieces that implement
+//! `SimpleConstraintChecker` directly must call `extract` themselves.
 //!
 //! # Comparison with `sp_std::any`
 //!
@@ -49,7 +50,7 @@
 use parity_scale_codec::{Decode, Encode};
 use scale_info::TypeInfo;
 use serde::{Deserialize, Serialize};
-use sp_std::vec::Vec;
+use sp_std::{vec, vec::Vec};
 
 /// A piece of encoded data with a type id associated
 /// Strongly typed data can be extracted
@@ -64,14 +65,96 @@ pub struct DynamicallyTypedData {
 /// use the newtype pattern: https://doc.rust-lang.org/book/ch19-04-advanced-types.html.
 /// Using a new type allows strong type disambiguation between bespoke use-cases in which
 /// the same primitive may be stored.
+///
+/// The easiest way to implement this trait for new types is with `#[derive(UtxoData)]`, which
+/// derives the type id from the type's name. Types that already have data stored on a live chain
+/// must keep their hand-picked ids, because changing a type id makes existing UTXOs unreadable.
+/// The pieces in this repository do so.
 pub trait UtxoData: Encode + Decode {
-    //TODO Not great that it is up to the runtime dev to enforce uniqueness
-    // Maybe macros can help... Doesn't frame somehow pass info about the string in construct runtime to the pallet-level storage items?
-    /// A unique identifier for this type. For now choosing this value and making sure it
-    /// really is unique is the problem of the developer. Ideally this would be better.
+    /// A unique identifier for this type.
+    ///
+    /// When this is chosen by hand, making sure it really is unique is the problem of the developer.
+    /// Collisions between the pieces in a runtime are detected when building the genesis state
+    /// as long as each piece reports its types in `SimpleConstraintChecker::utxo_types`.
     const TYPE_ID: [u8; 4];
 }
 
+/// Derives `UtxoData` with a type id generated deterministically from the type name, or from
+/// the name given with `#[utxo_data(name = "...")]`. Const generic parameters are included in the
+/// id so that, for example, `MintAuthority<0>` and `MintAuthority<1>` receive different ids.
+pub use aggregator::UtxoData;
+
+/// Deterministically compute a type id from a type's name and const generic parameters.
+///
+/// This is the function used by `#[derive(UtxoData)]`. It is a 32 bit FNV-1a hash, which is
+/// not cryptographically secure, but collisions are detected at genesis anyway.
+pub const fn derive_type_id(name: &str, const_params: &[u128]) -> [u8; 4] {
+    const OFFSET_BASIS: u32 = 0x811c9dc5;
+    const PRIME: u32 = 0x01000193;
+
+    let mut hash = OFFSET_BASIS;
+
+    let name = name.as_bytes();
+    let mut i = 0;
+    while i < name.len() {
+        hash ^= name[i] as u32;
+        hash = hash.wrapping_mul(PRIME);
+        i += 1;
+    }
+
+    let mut i = 0;
+    while i < const_params.len() {
+        let param = const_params[i].to_le_bytes();
+        let mut j = 0;
+        while j < param.len() {
+            hash ^= param[j] as u32;
+            hash = hash.wrapping_mul(PRIME);
+            j += 1;
+        }
+        i += 1;
+    }
+
+    hash.to_le_bytes()
+}
+
+/// Describes a type of data that a constraint checker reads from or writes to UTXOs.
+///
+/// Aggregate constraint checkers collect these from all of their inner checkers so that
+/// type id collisions between pieces can be detected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UtxoType {
+    /// The dynamic type id that is stored alongside the data.
+    pub type_id: [u8; 4],
+    /// The rust type name. Only used for diagnostics.
+    pub type_name: &'static str,
+    /// The compiler's own unique identifier for the type.
+    rust_type_id: core::any::TypeId,
+}
+
+impl UtxoType {
+    /// Describe the given type.
+    pub fn of<T: UtxoData + 'static>() -> Self {
+        Self {
+            type_id: T::TYPE_ID,
+            type_name: core::any::type_name::<T>(),
+            rust_type_id: core::any::TypeId::of::<T>(),
+        }
+    }
+}
+
+/// Search the given types for two distinct types that share a type id.
+///
+/// The same type may appear many times, as is common when several constraint checkers
+/// from one piece are installed in the same runtime. That is not a collision.
+pub fn find_type_id_collision(types: &[UtxoType]) -> Option<(UtxoType, UtxoType)> {
+    types.iter().enumerate().find_map(|(i, a)| {
+        types[i + 1..]
+            .iter()
+            .find(|b| a.type_id == b.type_id && a.rust_type_id != b.rust_type_id)
+            .map(|b| (*a, *b))
+    })
+}
+
 impl DynamicallyTypedData {
     /// Extracts strongly typed data from an Output, iff the output contains the type of data
     /// specified. If the contained data is not the specified type, or decoding fails, this errors.
@@ -110,6 +193,56 @@ impl sp_std::fmt::Display for DynamicTypingError {
 #[cfg(feature = "std")]
 impl std::error::Error for DynamicTypingError {}
 
+/// Strongly typed data that can be stored in a UTXO.
+///
+/// Every `UtxoData` type is a payload. Runtimes that want to work with several types at once
+/// can declare an outer payload enum with the `#[tuxedo_payload]` macro, just like they declare
+/// an outer verifier. Storage still holds `DynamicallyTypedData`, so this trait is the bridge
+/// between the two representations.
+pub trait UtxoPayload: Sized {
+    /// Decode strongly typed data from its dynamically typed form.
+    fn from_dynamic(data: &DynamicallyTypedData) -> Result<Self, DynamicTypingError>;
+
+    /// Package this data with its dynamic typing tag.
+    fn to_dynamic(&self) -> DynamicallyTypedData;
+
+    /// The UTXO types that this payload may hold.
+    fn utxo_types() -> Vec<UtxoType>;
+}
+
+impl<T: UtxoData + 'static> UtxoPayload for T {
+    fn from_dynamic(data: &DynamicallyTypedData) -> Result<Self, DynamicTypingError> {
+        data.extract()
+    }
+
+    fn to_dynamic(&self) -> DynamicallyTypedData {
+        DynamicallyTypedData {
+            data: self.encode(),
+            type_id: T::TYPE_ID,
+        }
+    }
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![UtxoType::of::<T>()]
+    }
+}
+
+// Dynamically typed data is trivially a payload. This is what keeps the untyped
+// interfaces working while pieces migrate to typed ones.
+impl UtxoPayload for DynamicallyTypedData {
+    fn from_dynamic(data: &DynamicallyTypedData) -> Result<Self, DynamicTypingError> {
+        Ok(data.clone())
+    }
+
+    fn to_dynamic(&self) -> DynamicallyTypedData {
+        self.clone()
+    }
+
+    fn utxo_types() -> Vec<UtxoType> {
+        Vec::new()
+    }
+}
+
 //TODO, I tried replacing the extract method above with this impl,
 // but it conflicts with something in core, that I don't understand.
 // Extracts strongly typed data from dynamically typed data.
@@ -141,12 +274,35 @@ pub mod testing {
     /// need to make sure that the piece properly sanitizes the dynamically
     /// typed data that is passed into its verifiers.
     /// This type is used to represent incorrectly typed data.
-    #[derive(Encode, Decode, PartialEq, Eq, Debug)]
+    #[derive(Encode, Decode, PartialEq, Eq, Debug, Clone)]
     pub struct Bogus;
 
     impl UtxoData for Bogus {
         const TYPE_ID: [u8; 4] = *b"bogs";
     }
+
+    /// A coin for use in tests.
+    ///
+    /// Pieces that are generic over the `Cash` they accept, like the dex or voting,
+    /// can use this type to test their constraint checkers without depending on money.
+    /// It is only available with the `std` feature, so it never ends up in a runtime's wasm.
+    #[cfg(feature = "std")]
+    #[derive(Encode, Decode, PartialEq, Eq, Debug, Clone)]
+    pub struct Gold(pub u128);
+
+    #[cfg(feature = "std")]
+    impl UtxoData for Gold {
+        const TYPE_ID: [u8; 4] = *b"gold";
+    }
+
+    #[cfg(feature = "std")]
+    impl crate::traits::Cash for Gold {
+        fn value(&self) -> u128 {
+            self.0
+        }
+
+        const ID: u8 = 1;
+    }
 }
 
 #[cfg(test)]
@@ -163,6 +319,83 @@ mod tests {
         const TYPE_ID: [u8; 4] = *b"byte";
     }
 
+    /// A type that accidentally uses the same type id as `Byte`.
+    #[derive(Encode, Decode, Debug, PartialEq, Eq, Clone)]
+    struct AlsoByte(u8);
+
+    impl UtxoData for AlsoByte {
+        const TYPE_ID: [u8; 4] = *b"byte";
+    }
+
+    #[derive(Encode, Decode, UtxoData)]
+    struct Derived;
+
+    #[derive(Encode, Decode, UtxoData)]
+    struct OtherDerived;
+
+    #[derive(Encode, Decode, UtxoData)]
+    struct DerivedWithConst<const ID: u8>;
+
+    /// A type that was renamed after its data was stored, so it pins its old name.
+    #[derive(Encode, Decode, UtxoData)]
+    #[utxo_data(name = "Derived")]
+    struct Renamed<const ID: u8>;
+
+    #[test]
+    fn derived_type_id_comes_from_type_name() {
+        assert_eq!(Derived::TYPE_ID, derive_type_id("Derived", &[]));
+    }
+
+    #[test]
+    fn derived_type_id_comes_from_explicit_name() {
+        assert_eq!(Renamed::<1>::TYPE_ID, derive_type_id("Derived", &[1]));
+    }
+
+    #[test]
+    fn derived_type_ids_differ() {
+        assert_ne!(Derived::TYPE_ID, OtherDerived::TYPE_ID);
+    }
+
+    #[test]
+    fn derived_type_ids_include_const_generics() {
+        assert_ne!(
+            DerivedWithConst::<0>::TYPE_ID,
+            DerivedWithConst::<1>::TYPE_ID
+        );
+    }
+
+    #[test]
+    fn no_collision_among_distinct_ids() {
+        let types = [
+            UtxoType::of::<Byte>(),
+            UtxoType::of::<Derived>(),
+            UtxoType::of::<OtherDerived>(),
+        ];
+
+        assert_eq!(find_type_id_collision(&types), None);
+    }
+
+    #[test]
+    fn repeated_type_is_not_a_collision() {
+        let types = [UtxoType::of::<Byte>(), UtxoType::of::<Byte>()];
+
+        assert_eq!(find_type_id_collision(&types), None);
+    }
+
+    #[test]
+    fn collision_detected() {
+        let types = [
+            UtxoType::of::<Byte>(),
+            UtxoType::of::<Derived>(),
+            UtxoType::of::<AlsoByte>(),
+        ];
+
+        assert_eq!(
+            find_type_id_collision(&types),
+            Some((UtxoType::of::<Byte>(), UtxoType::of::<AlsoByte>()))
+        );
+    }
+
     #[test]
     fn extract_works() {
         let original_b = Byte(4);
@@ -211,4 +444,88 @@ mod tests {
 
         assert_eq!(actual, expected);
     }
+
+    /// A payload enum aggregating two data types, as a runtime would declare it.
+    #[crate::tuxedo_payload]
+    #[derive(Debug, PartialEq, Eq, Clone)]
+    enum TestPayload {
+        Byte(Byte),
+        Bogus(Bogus),
+    }
+
+    #[test]
+    fn payload_enum_from_dynamic_works() {
+        let byte: DynamicallyTypedData = Byte(4).into();
+        let bogus: DynamicallyTypedData = Bogus.into();
+
+        assert_eq!(
+            TestPayload::from_dynamic(&byte),
+            Ok(TestPayload::Byte(Byte(4)))
+        );
+        assert_eq!(
+            TestPayload::from_dynamic(&bogus),
+            Ok(TestPayload::Bogus(Bogus))
+        );
+    }
+
+    #[test]
+    fn payload_enum_from_dynamic_wrong_type() {
+        let other = DynamicallyTypedData {
+            data: 4u8.encode(),
+            type_id: *b"othr",
+        };
+
+        assert_eq!(
+            TestPayload::from_dynamic(&other),
+            Err(DynamicTypingError::WrongType)
+        );
+    }
+
+    #[test]
+    fn payload_enum_from_dynamic_decode_fails() {
+        let malformed = DynamicallyTypedData {
+            data: Vec::new(),
+            type_id: Byte::TYPE_ID,
+        };
+
+        assert_eq!(
+            TestPayload::from_dynamic(&malformed),
+            Err(DynamicTypingError::DecodingFailed)
+        );
+    }
+
+    #[test]
+    fn payload_enum_to_dynamic_round_trip() {
+        let payload = TestPayload::Byte(Byte(4));
+
+        assert_eq!(payload.to_dynamic(), DynamicallyTypedData::from(Byte(4)));
+        assert_eq!(
+            TestPayload::from_dynamic(&payload.to_dynamic()),
+            Ok(payload)
+        );
+    }
+
+    #[test]
+    fn payload_enum_unwraps_to_inner_type() {
+        assert_eq!(Byte::try_from(TestPayload::Byte(Byte(4))), Ok(Byte(4)));
+    }
+
+    #[test]
+    fn payload_enum_unwrap_wrong_variant() {
+        assert_eq!(
+            Byte::try_from(TestPayload::Bogus(Bogus)),
+            Err(crate::types::WrongVariant {
+                expected: "Byte",
+                found: "Bogus",
+            })
+        );
+    }
+
+    #[test]
+    fn payload_enum_reports_inner_types() {
+        assert_eq!(
+            TestPayload::utxo_types(),
+            vec![UtxoType::of::<Byte>(), UtxoType::of::<Bogus>()]
+        );
+    }
 }
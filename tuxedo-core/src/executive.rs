@@ -8,12 +8,13 @@
 
 use crate::{
     constraint_checker::ConstraintChecker,
-    dynamic_typing::DynamicallyTypedData,
     ensure,
-    inherents::PARENT_INHERENT_IDENTIFIER,
+    fees::{self, FeePolicy},
+    inherents::{InherentError, PARENT_INHERENT_IDENTIFIER},
+    rent::RentPolicy,
     types::{
-        Block, BlockNumber, DispatchResult, Header, OutputRef, RedemptionStrategy, Transaction,
-        UtxoError,
+        Block, BlockNumber, DispatchResult, Header, Output, OutputRef, RedemptionStrategy,
+        Transaction, UtxoError,
     },
     utxo_set::TransparentUtxoSet,
     verifier::Verifier,
@@ -29,19 +30,27 @@ use sp_runtime::{
         InvalidTransaction, TransactionLongevity, TransactionSource, TransactionValidity,
         TransactionValidityError, ValidTransaction,
     },
-    ApplyExtrinsicResult, ExtrinsicInclusionMode, StateVersion,
+    ApplyExtrinsicResult, DigestItem, ExtrinsicInclusionMode, StateVersion,
 };
 use sp_std::marker::PhantomData;
 use sp_std::{collections::btree_set::BTreeSet, vec::Vec};
 
 /// The executive. Each runtime is encouraged to make a type alias called `Executive` that fills
 /// in the proper generic types.
-pub struct Executive<V, C>(PhantomData<(V, C)>);
-
-impl<V, C> Executive<V, C>
+///
+/// The third generic type is the runtime's rent policy. It is optional, and by default
+/// no UTXOs are ever considered stale. See the `rent` module for details.
+///
+/// The final generic type is the runtime's fee policy. It is also optional, and by default
+/// no fees are collected. See the `fees` module for details.
+pub struct Executive<V, C, R = (), F = ()>(PhantomData<(V, C, R, F)>);
+
+impl<V, C, R, F> Executive<V, C, R, F>
 where
     V: Verifier,
     C: ConstraintChecker,
+    R: RentPolicy<V>,
+    F: FeePolicy<C>,
     Block<V, C>: BlockT<Extrinsic = Transaction<V, C>, Hash = sp_core::H256>,
     Transaction<V, C>: Extrinsic,
 {
@@ -76,10 +85,12 @@ where
         let stripped_encoded = stripped.encode();
 
         // Check that the verifiers of all inputs are satisfied
-        // Keep a Vec of the input data for passing to the constraint checker
+        // Keep a Vec of the input utxos for passing to the constraint checker
         // Keep track of any missing inputs for use in the tagged transaction pool
-        let mut input_data = Vec::new();
-        let mut evicted_input_data = Vec::new();
+        // Keep stale evictions separately. They are handled by the rent policy, not the constraint checker.
+        let mut inputs = Vec::new();
+        let mut evicted_inputs = Vec::new();
+        let mut stale_inputs = Vec::new();
         let mut missing_inputs = Vec::new();
         for input in transaction.inputs.iter() {
             if let Some(input_utxo) = TransparentUtxoSet::<V>::peek_utxo(&input.output_ref) {
@@ -95,22 +106,28 @@ where
                             ),
                             UtxoError::VerifierError
                         );
-                        input_data.push(input_utxo.payload);
+                        inputs.push(input_utxo);
+                    }
+                    RedemptionStrategy::Eviction => {
+                        if Self::is_stale(&input.output_ref, &input_utxo) {
+                            stale_inputs.push(input_utxo);
+                        } else {
+                            evicted_inputs.push(input_utxo);
+                        }
                     }
-                    RedemptionStrategy::Eviction => evicted_input_data.push(input_utxo.payload),
                 }
             } else {
                 missing_inputs.push(input.output_ref.clone().encode());
             }
         }
 
-        // Make a Vec of the peek data for passing to the constraint checker
+        // Make a Vec of the peeked utxos for passing to the constraint checker
         // Keep track of any missing peeks for use in the tagged transaction pool
         // Use the same vec as previously to keep track of missing peeks
-        let mut peek_data = Vec::new();
+        let mut peeks = Vec::new();
         for output_ref in transaction.peeks.iter() {
             if let Some(peek_utxo) = TransparentUtxoSet::<V>::peek_utxo(output_ref) {
-                peek_data.push(peek_utxo.payload);
+                peeks.push(peek_utxo);
             } else {
                 missing_inputs.push(output_ref.encode());
             }
@@ -163,17 +180,26 @@ where
             });
         }
 
-        // Extract the payload data from each output
-        let output_data: Vec<DynamicallyTypedData> = transaction
-            .outputs
-            .iter()
-            .map(|o| o.payload.clone())
-            .collect();
+        // If any stale UTXOs were evicted, the rent policy may require their value to be
+        // routed to a treasury. That output must come last, and it is not shown to the constraint checker.
+        let mut checked_outputs = &transaction.outputs[..];
+        if !stale_inputs.is_empty() {
+            if let Some(treasury_output) = R::treasury_output(&stale_inputs) {
+                let (last, rest) = checked_outputs
+                    .split_last()
+                    .ok_or(UtxoError::MissingTreasuryOutput)?;
+                ensure!(
+                    last.encode() == treasury_output.encode(),
+                    UtxoError::MissingTreasuryOutput
+                );
+                checked_outputs = rest;
+            }
+        }
 
         // Call the constraint checker
         transaction
             .checker
-            .check(&input_data, &evicted_input_data, &peek_data, &output_data)
+            .check(&inputs, &evicted_inputs, &peeks, checked_outputs)
             .map_err(UtxoError::ConstraintCheckerError)?;
 
         // Return the valid transaction
@@ -206,9 +232,35 @@ where
             UtxoError::MissingInput
         );
 
+        // Work out this transaction's fee, if the runtime's fee policy charges one.
+        // This must happen before the inputs are consumed.
+        let mut fee = 0;
+        if F::COLLECT_FEES {
+            let input_data: Vec<_> = transaction
+                .inputs
+                .iter()
+                .filter(|input| matches!(input.redeemer, RedemptionStrategy::Redemption(_)))
+                .filter_map(|input| TransparentUtxoSet::<V>::peek_utxo(&input.output_ref))
+                .map(|utxo| utxo.payload)
+                .collect();
+            let output_data: Vec<_> = transaction
+                .outputs
+                .iter()
+                .map(|output| output.payload.clone())
+                .collect();
+            fee = F::fee(&transaction.checker, &input_data, &output_data);
+        }
+        let pays_out_fees = F::COLLECT_FEES && F::pays_out_fees(&transaction.checker);
+
         // At this point, all validation is complete, so we can commit the storage changes.
         Self::update_storage(transaction);
 
+        // Fees that this transaction paid out are no longer owed to anyone.
+        if pays_out_fees {
+            fees::clear_collected_fees();
+        }
+        fees::collect_fee(fee);
+
         Ok(())
     }
 
@@ -220,6 +272,9 @@ where
         // Remove verified UTXOs
         for input in &transaction.inputs {
             TransparentUtxoSet::<V>::consume_utxo(&input.output_ref);
+            if R::TRACK_AGE {
+                TransparentUtxoSet::<V>::clear_creation_height(&input.output_ref);
+            }
         }
 
         debug!(
@@ -232,10 +287,23 @@ where
                 tx_hash: BlakeTwo256::hash_of(&transaction.encode()),
                 index: index as u32,
             };
+            if R::TRACK_AGE && R::track_age_of(output) {
+                TransparentUtxoSet::<V>::store_creation_height(&output_ref, Self::block_height());
+            }
             TransparentUtxoSet::<V>::store_utxo(output_ref, output);
         }
     }
 
+    /// Helper function to determine whether the given utxo is stale according to the runtime's rent policy.
+    fn is_stale(output_ref: &OutputRef, output: &Output<V>) -> bool {
+        let age = (R::TRACK_AGE && R::track_age_of(output)).then(|| {
+            let created = TransparentUtxoSet::<V>::creation_height(output_ref).unwrap_or_default();
+            Self::block_height().saturating_sub(created)
+        });
+
+        R::is_stale(output, age)
+    }
+
     /// A helper function that allows tuxedo runtimes to read the current block height
     pub fn block_height() -> BlockNumber {
         sp_io::storage::get(HEIGHT_KEY)
@@ -243,6 +311,21 @@ where
             .expect("A height is stored at the beginning of block one and never cleared.")
     }
 
+    /// A helper function that allows tuxedo runtimes to add a log to the digest of the block
+    /// that is currently being built or executed. Pieces use this to signal things like authority
+    /// set changes to the client.
+    ///
+    /// Outside of a block, such as when validating transactions for the pool, this does nothing.
+    pub fn deposit_log(log: DigestItem) {
+        let Some(mut header) =
+            sp_io::storage::get(HEADER_KEY).and_then(|d| Header::decode(&mut &*d).ok())
+        else {
+            return;
+        };
+        header.digest_mut().push(log);
+        sp_io::storage::set(HEADER_KEY, &header.encode());
+    }
+
     // These next three methods are for the block authoring workflow.
     // Open the block, apply zero or more extrinsics, close the block
 
@@ -316,9 +399,29 @@ where
         header
     }
 
-    // This one is for the Core api. It is used to import blocks authored by foreign nodes.
+    // These are for the Core api. They are used to import blocks authored by foreign nodes.
 
+    /// Execute a block that was authored by another node.
+    ///
+    /// The block's digest is not checked. Logs that the pieces deposit while executing the block
+    /// are ignored, so a malicious author could leave them out or add others. Runtimes whose pieces
+    /// deposit logs that the client relies on should use `execute_block_checking_digest` instead.
     pub fn execute_block(block: Block<V, C>) {
+        Self::execute_block_inner(block, false)
+    }
+
+    /// Execute a block that was authored by another node, and check that its digest holds exactly
+    /// its pre-runtime logs, the logs that the pieces deposited while executing it, and its seals.
+    ///
+    /// This is needed when pieces deposit logs that the client relies on, such as authority set
+    /// changes. Blocks whose digests hold any other logs are rejected, so the client must not add
+    /// any, except as pre-runtime logs or seals. The template node meets this requirement with
+    /// Aura, GRANDPA, proof of work, and manual sealing.
+    pub fn execute_block_checking_digest(block: Block<V, C>) {
+        Self::execute_block_inner(block, true)
+    }
+
+    fn execute_block_inner(block: Block<V, C>, check_digest: bool) {
         debug!(
             target: LOG_TARGET,
             "Entering execute_block. block: {:?}", block
@@ -327,7 +430,18 @@ where
         // Store the header. Although we don't need to mutate it, we do need to make
         // info, such as the block height, available to individual pieces. This will
         // be cleared before the end of the block
-        sp_io::storage::set(HEADER_KEY, &block.header().encode());
+        //
+        // When the digest is checked, only the pre-runtime logs are kept. Any other logs must be
+        // deposited again by the pieces while executing the block, and are compared with the
+        // block's digest below.
+        let mut pre_header = block.header().clone();
+        if check_digest {
+            pre_header
+                .digest_mut()
+                .logs
+                .retain(|log| matches!(log, DigestItem::PreRuntime(..)));
+        }
+        sp_io::storage::set(HEADER_KEY, &pre_header.encode());
 
         // Also store the height persistently so it is available when
         // performing pool validations and other off-chain runtime calls.
@@ -358,6 +472,26 @@ where
             }
         }
 
+        // Check that the digest holds exactly the logs that were deposited while executing.
+        // Seals are added by the author after the block is built, so they are not compared.
+        if check_digest {
+            let executed_header = sp_io::storage::get(HEADER_KEY)
+                .and_then(|d| Header::decode(&mut &*d).ok())
+                .expect("We stored the header at the beginning of the block, qed");
+            let expected_logs = block
+                .header()
+                .digest()
+                .logs()
+                .iter()
+                .filter(|log| !matches!(log, DigestItem::Seal(..)))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                executed_header.digest().logs().iter().collect::<Vec<_>>(),
+                expected_logs,
+                "digest mismatch"
+            );
+        }
+
         // Clear the transient header out of storage
         sp_io::storage::clear(HEADER_KEY);
 
@@ -423,17 +557,32 @@ where
     }
 
     // The next two are for the standard beginning-of-block inherent extrinsics.
+    //
+    // If the inherents cannot be created, the error is logged and no inherents are returned.
+    // The resulting block is then rejected by `check_inherents` instead of aborting the runtime.
     pub fn inherent_extrinsics(data: sp_inherents::InherentData) -> Vec<Transaction<V, C>> {
         debug!(
             target: LOG_TARGET,
             "Entering `inherent_extrinsics`."
         );
 
+        Self::create_inherents(&data).unwrap_or_else(|e| {
+            log::error!(
+                target: LOG_TARGET,
+                "Could not create inherents: {:?}", e
+            );
+            Vec::new()
+        })
+    }
+
+    /// Create the inherents for a block that is being authored locally.
+    fn create_inherents(data: &InherentData) -> Result<Vec<Transaction<V, C>>, InherentError> {
         // Extract the complete parent block from the inherent data
         let parent: Block<V, C> = data
             .get_data(&PARENT_INHERENT_IDENTIFIER)
-            .expect("Parent block inherent data should be able to decode.")
-            .expect("Parent block should be present among authoring inherent data.");
+            .ok()
+            .flatten()
+            .ok_or(InherentError::MissingInherentData)?;
 
         // Extract the inherents from the previous block, which can be found at the beginning of the extrinsics list.
         // The parent is already imported, so we know it is valid and we know its inherents came first.
@@ -457,7 +606,7 @@ where
         );
 
         // Call into constraint checker's own inherent hooks to create the actual transactions
-        C::create_inherents(&data, previous_blocks_inherents)
+        C::create_inherents(data, previous_blocks_inherents)
     }
 
     pub fn check_inherents(
@@ -497,7 +646,7 @@ mod tests {
 
     use crate::{
         constraint_checker::testing::TestConstraintChecker,
-        dynamic_typing::{testing::Bogus, UtxoData},
+        dynamic_typing::{testing::Bogus, DynamicallyTypedData, UtxoData},
         types::{Input, Output},
         verifier::TestVerifier,
     };
@@ -508,6 +657,77 @@ mod tests {
     pub type TestHeader = sp_runtime::generic::Header<u32, BlakeTwo256>;
     pub type TestBlock = sp_runtime::generic::Block<TestHeader, TestTransaction>;
     pub type TestExecutive = Executive<TestVerifier, TestConstraintChecker>;
+    pub type RentTestExecutive = Executive<TestVerifier, TestConstraintChecker, TestRentPolicy>;
+    pub type SelectiveRentTestExecutive =
+        Executive<TestVerifier, TestConstraintChecker, SelectiveRentPolicy>;
+    pub type FeeTestExecutive = Executive<TestVerifier, TestConstraintChecker, (), TestFeePolicy>;
+
+    /// A rent policy for use in tests.
+    ///
+    /// Any UTXO that is at least ten blocks old is stale, and the value of stale
+    /// UTXOs is always routed to the same treasury output.
+    pub struct TestRentPolicy;
+
+    impl RentPolicy<TestVerifier> for TestRentPolicy {
+        const TRACK_AGE: bool = true;
+
+        fn is_stale(_: &Output<TestVerifier>, age: Option<BlockNumber>) -> bool {
+            age.is_some_and(|age| age >= 10)
+        }
+
+        fn treasury_output(_: &[Output<TestVerifier>]) -> Option<Output<TestVerifier>> {
+            Some(treasury_output())
+        }
+    }
+
+    /// A rent policy for use in tests that only tracks the ages of UTXOs whose verifier verifies.
+    /// Otherwise it is the same as `TestRentPolicy`.
+    pub struct SelectiveRentPolicy;
+
+    impl RentPolicy<TestVerifier> for SelectiveRentPolicy {
+        const TRACK_AGE: bool = true;
+
+        fn track_age_of(output: &Output<TestVerifier>) -> bool {
+            output.verifier.verifies
+        }
+
+        fn is_stale(output: &Output<TestVerifier>, age: Option<BlockNumber>) -> bool {
+            TestRentPolicy::is_stale(output, age)
+        }
+
+        fn treasury_output(stale: &[Output<TestVerifier>]) -> Option<Output<TestVerifier>> {
+            TestRentPolicy::treasury_output(stale)
+        }
+    }
+
+    /// A fee policy for use in tests.
+    ///
+    /// Every redeemed input pays a fee of ten, regardless of the constraint checker.
+    /// Inherents pay out the fees collected so far, like a coinbase would.
+    pub struct TestFeePolicy;
+
+    impl FeePolicy<TestConstraintChecker> for TestFeePolicy {
+        const COLLECT_FEES: bool = true;
+
+        fn fee(
+            _: &TestConstraintChecker,
+            input_data: &[DynamicallyTypedData],
+            _: &[DynamicallyTypedData],
+        ) -> u128 {
+            10 * input_data.len() as u128
+        }
+
+        fn pays_out_fees(checker: &TestConstraintChecker) -> bool {
+            checker.inherent
+        }
+    }
+
+    fn treasury_output() -> Output<TestVerifier> {
+        Output {
+            payload: Bogus.into(),
+            verifier: TestVerifier { verifies: true },
+        }
+    }
 
     /// Construct a mock OutputRef from a transaction number and index in that transaction.
     ///
@@ -936,6 +1156,113 @@ mod tests {
         });
     }
 
+    #[test]
+    fn apply_without_fee_policy_collects_no_fees() {
+        let output_ref = mock_output_ref(0, 0);
+        ExternalityBuilder::default()
+            .with_utxo(output_ref.clone(), Bogus, true)
+            .build()
+            .execute_with(|| {
+                let tx = TestTransactionBuilder::default()
+                    .with_input(Input {
+                        output_ref,
+                        redeemer: Default::default(),
+                    })
+                    .build(true, false);
+
+                assert_eq!(TestExecutive::apply_tuxedo_transaction(tx), Ok(()));
+                assert_eq!(fees::collected_fees(), 0);
+            });
+    }
+
+    #[test]
+    fn apply_with_fee_policy_collects_fees_for_redeemed_inputs() {
+        let redeemed = mock_output_ref(0, 0);
+        let evicted = mock_output_ref(0, 1);
+        ExternalityBuilder::default()
+            .with_utxo(redeemed.clone(), Bogus, true)
+            .with_utxo(evicted.clone(), Bogus, true)
+            .build()
+            .execute_with(|| {
+                let tx = TestTransactionBuilder::default()
+                    .with_input(Input {
+                        output_ref: redeemed,
+                        redeemer: Default::default(),
+                    })
+                    .with_input(Input {
+                        output_ref: evicted,
+                        redeemer: RedemptionStrategy::Eviction,
+                    })
+                    .build(true, false);
+
+                assert_eq!(FeeTestExecutive::apply_tuxedo_transaction(tx), Ok(()));
+                assert_eq!(fees::collected_fees(), 10);
+            });
+    }
+
+    #[test]
+    fn apply_fee_payout_clears_collected_fees() {
+        ExternalityBuilder::default().build().execute_with(|| {
+            fees::collect_fee(25);
+            let tx = TestTransactionBuilder::default().build(true, true);
+
+            assert_eq!(FeeTestExecutive::apply_tuxedo_transaction(tx), Ok(()));
+            assert_eq!(fees::collected_fees(), 0);
+        });
+    }
+
+    #[test]
+    fn apply_without_fee_payout_keeps_collected_fees() {
+        ExternalityBuilder::default().build().execute_with(|| {
+            fees::collect_fee(25);
+            let tx = TestTransactionBuilder::default().build(true, false);
+
+            assert_eq!(FeeTestExecutive::apply_tuxedo_transaction(tx), Ok(()));
+            assert_eq!(fees::collected_fees(), 25);
+        });
+    }
+
+    #[test]
+    fn validate_with_fee_policy_collects_no_fees() {
+        let output_ref = mock_output_ref(0, 0);
+        ExternalityBuilder::default()
+            .with_utxo(output_ref.clone(), Bogus, true)
+            .build()
+            .execute_with(|| {
+                let tx = TestTransactionBuilder::default()
+                    .with_input(Input {
+                        output_ref,
+                        redeemer: Default::default(),
+                    })
+                    .build(true, false);
+
+                assert!(FeeTestExecutive::validate_tuxedo_transaction(&tx).is_ok());
+                assert_eq!(fees::collected_fees(), 0);
+            });
+    }
+
+    #[test]
+    fn apply_invalid_transaction_with_fee_policy_collects_no_fees() {
+        let output_ref = mock_output_ref(0, 0);
+        ExternalityBuilder::default()
+            .with_utxo(output_ref.clone(), Bogus, true)
+            .build()
+            .execute_with(|| {
+                let tx = TestTransactionBuilder::default()
+                    .with_input(Input {
+                        output_ref,
+                        redeemer: Default::default(),
+                    })
+                    .build(false, false);
+
+                assert_eq!(
+                    FeeTestExecutive::apply_tuxedo_transaction(tx),
+                    Err(UtxoError::ConstraintCheckerError(()))
+                );
+                assert_eq!(fees::collected_fees(), 0);
+            });
+    }
+
     #[test]
     fn update_storage_consumes_input() {
         let output_ref = mock_output_ref(0, 0);
@@ -986,6 +1313,224 @@ mod tests {
         });
     }
 
+    #[test]
+    fn update_storage_without_rent_policy_does_not_track_age() {
+        ExternalityBuilder::default().build().execute_with(|| {
+            let tx = TestTransactionBuilder::default()
+                .with_output(treasury_output())
+                .build(true, false);
+
+            let tx_hash = BlakeTwo256::hash_of(&tx.encode());
+            let output_ref = OutputRef { tx_hash, index: 0 };
+
+            TestExecutive::update_storage(tx);
+
+            assert_eq!(
+                TransparentUtxoSet::<TestVerifier>::creation_height(&output_ref),
+                None
+            );
+        });
+    }
+
+    #[test]
+    fn update_storage_with_rent_policy_tracks_age() {
+        ExternalityBuilder::default()
+            .with_pre_header(H256::zero(), 7)
+            .build()
+            .execute_with(|| {
+                let tx = TestTransactionBuilder::default()
+                    .with_output(treasury_output())
+                    .build(true, false);
+
+                let tx_hash = BlakeTwo256::hash_of(&tx.encode());
+                let output_ref = OutputRef { tx_hash, index: 0 };
+
+                RentTestExecutive::update_storage(tx);
+                assert_eq!(
+                    TransparentUtxoSet::<TestVerifier>::creation_height(&output_ref),
+                    Some(7)
+                );
+
+                // Now consume it and make sure the height is cleaned up as well.
+                let tx = TestTransactionBuilder::default()
+                    .with_input(Input {
+                        output_ref: output_ref.clone(),
+                        redeemer: Default::default(),
+                    })
+                    .build(true, false);
+
+                RentTestExecutive::update_storage(tx);
+                assert_eq!(
+                    TransparentUtxoSet::<TestVerifier>::creation_height(&output_ref),
+                    None
+                );
+            });
+    }
+
+    #[test]
+    fn update_storage_with_selective_rent_policy_only_tracks_selected_ages() {
+        ExternalityBuilder::default()
+            .with_pre_header(H256::zero(), 7)
+            .build()
+            .execute_with(|| {
+                let untracked_output = Output {
+                    payload: Bogus.into(),
+                    verifier: TestVerifier { verifies: false },
+                };
+                let tx = TestTransactionBuilder::default()
+                    .with_output(treasury_output())
+                    .with_output(untracked_output)
+                    .build(true, false);
+
+                let tx_hash = BlakeTwo256::hash_of(&tx.encode());
+                let tracked_ref = OutputRef { tx_hash, index: 0 };
+                let untracked_ref = OutputRef { tx_hash, index: 1 };
+
+                SelectiveRentTestExecutive::update_storage(tx);
+
+                assert_eq!(
+                    TransparentUtxoSet::<TestVerifier>::creation_height(&tracked_ref),
+                    Some(7)
+                );
+                assert_eq!(
+                    TransparentUtxoSet::<TestVerifier>::creation_height(&untracked_ref),
+                    None
+                );
+            });
+    }
+
+    #[test]
+    fn validate_evicting_untracked_utxo_needs_no_treasury() {
+        let output_ref = mock_output_ref(0, 0);
+
+        ExternalityBuilder::default()
+            .with_utxo(output_ref.clone(), Bogus, false)
+            .with_pre_header(H256::zero(), 10)
+            .build()
+            .execute_with(|| {
+                let input = Input {
+                    output_ref,
+                    redeemer: RedemptionStrategy::Eviction,
+                };
+
+                let tx = TestTransactionBuilder::default()
+                    .with_input(input)
+                    .build(true, false);
+
+                let vt = SelectiveRentTestExecutive::validate_tuxedo_transaction(&tx).unwrap();
+
+                assert_eq!(vt, ValidTransactionBuilder::default().into());
+            });
+    }
+
+    #[test]
+    fn validate_evicting_young_utxo_needs_no_treasury() {
+        let output_ref = mock_output_ref(0, 0);
+
+        ExternalityBuilder::default()
+            .with_utxo(output_ref.clone(), Bogus, false)
+            .with_pre_header(H256::zero(), 9)
+            .build()
+            .execute_with(|| {
+                let input = Input {
+                    output_ref,
+                    redeemer: RedemptionStrategy::Eviction,
+                };
+
+                let tx = TestTransactionBuilder::default()
+                    .with_input(input)
+                    .build(true, false);
+
+                let vt = RentTestExecutive::validate_tuxedo_transaction(&tx).unwrap();
+
+                assert_eq!(vt, ValidTransactionBuilder::default().into());
+            });
+    }
+
+    #[test]
+    fn validate_evicting_stale_utxo_without_treasury_output_fails() {
+        let output_ref = mock_output_ref(0, 0);
+
+        ExternalityBuilder::default()
+            .with_utxo(output_ref.clone(), Bogus, false)
+            .with_pre_header(H256::zero(), 10)
+            .build()
+            .execute_with(|| {
+                let input = Input {
+                    output_ref,
+                    redeemer: RedemptionStrategy::Eviction,
+                };
+
+                let tx = TestTransactionBuilder::default()
+                    .with_input(input)
+                    .build(true, false);
+
+                let result = RentTestExecutive::validate_tuxedo_transaction(&tx);
+
+                assert_eq!(result, Err(UtxoError::MissingTreasuryOutput));
+            });
+    }
+
+    #[test]
+    fn validate_evicting_stale_utxo_with_wrong_treasury_output_fails() {
+        let output_ref = mock_output_ref(0, 0);
+
+        ExternalityBuilder::default()
+            .with_utxo(output_ref.clone(), Bogus, false)
+            .with_pre_header(H256::zero(), 10)
+            .build()
+            .execute_with(|| {
+                let input = Input {
+                    output_ref,
+                    redeemer: RedemptionStrategy::Eviction,
+                };
+                let wrong_output = Output {
+                    payload: Bogus.into(),
+                    verifier: TestVerifier { verifies: false },
+                };
+
+                let tx = TestTransactionBuilder::default()
+                    .with_input(input)
+                    .with_output(wrong_output)
+                    .build(true, false);
+
+                let result = RentTestExecutive::validate_tuxedo_transaction(&tx);
+
+                assert_eq!(result, Err(UtxoError::MissingTreasuryOutput));
+            });
+    }
+
+    #[test]
+    fn apply_evicting_stale_utxo_with_treasury_output_works() {
+        let output_ref = mock_output_ref(0, 0);
+
+        ExternalityBuilder::default()
+            .with_utxo(output_ref.clone(), Bogus, false)
+            .with_pre_header(H256::zero(), 10)
+            .build()
+            .execute_with(|| {
+                let input = Input {
+                    output_ref: output_ref.clone(),
+                    redeemer: RedemptionStrategy::Eviction,
+                };
+
+                let tx = TestTransactionBuilder::default()
+                    .with_input(input)
+                    .with_output(treasury_output())
+                    .build(true, false);
+                let treasury_ref = OutputRef {
+                    tx_hash: BlakeTwo256::hash_of(&tx.encode()),
+                    index: 0,
+                };
+
+                assert_eq!(RentTestExecutive::apply_tuxedo_transaction(tx), Ok(()));
+
+                // The stale utxo is gone and the treasury has been paid
+                assert!(!sp_io::storage::exists(&output_ref.encode()));
+                assert!(sp_io::storage::exists(&treasury_ref.encode()));
+            });
+    }
+
     #[test]
     fn open_block_works() {
         let header = TestHeader {
@@ -1083,6 +1628,31 @@ mod tests {
             });
     }
 
+    #[test]
+    fn deposit_log_adds_to_closed_header() {
+        let log = DigestItem::Consensus(*b"test", vec![1, 2, 3]);
+        ExternalityBuilder::default()
+            .with_pre_header(H256::repeat_byte(5), 6)
+            .build()
+            .execute_with(|| {
+                TestExecutive::deposit_log(log.clone());
+                let returned_header = TestExecutive::close_block();
+
+                assert_eq!(returned_header.digest.logs, vec![log]);
+            });
+    }
+
+    #[test]
+    fn deposit_log_outside_block_does_nothing() {
+        ExternalityBuilder::default().build().execute_with(|| {
+            sp_io::storage::clear(HEADER_KEY);
+
+            TestExecutive::deposit_log(DigestItem::Consensus(*b"test", vec![1, 2, 3]));
+
+            assert!(!sp_io::storage::exists(HEADER_KEY));
+        });
+    }
+
     #[test]
     fn execute_empty_block_works() {
         ExternalityBuilder::default().build().execute_with(|| {
@@ -1171,6 +1741,80 @@ mod tests {
         });
     }
 
+    #[test]
+    fn execute_block_with_pre_runtime_and_seal_logs_works() {
+        ExternalityBuilder::default().build().execute_with(|| {
+            let mut digest = sp_runtime::Digest::default();
+            digest.push(DigestItem::PreRuntime(*b"test", vec![1]));
+            digest.push(DigestItem::Seal(*b"test", vec![2]));
+            let b = TestBlock {
+                header: TestHeader {
+                    parent_hash: H256::zero(),
+                    number: 6,
+                    state_root: array_bytes::hex_n_into_unchecked(
+                        "cc2d78f5977b6e9e16f4417f60cbd7edaad0c39a6a7cd21281e847da7dd210b9",
+                    ),
+                    extrinsics_root: array_bytes::hex_n_into_unchecked(
+                        "03170a2e7597b7b7e3d84c05391d139a62b157e78786d8c082f29dcf4c111314",
+                    ),
+                    digest,
+                },
+                extrinsics: Vec::new(),
+            };
+
+            TestExecutive::execute_block_checking_digest(b);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "digest mismatch")]
+    fn execute_block_with_undeposited_log_fails() {
+        ExternalityBuilder::default().build().execute_with(|| {
+            let mut digest = sp_runtime::Digest::default();
+            digest.push(DigestItem::Consensus(*b"test", vec![1, 2, 3]));
+            let b = TestBlock {
+                header: TestHeader {
+                    parent_hash: H256::zero(),
+                    number: 6,
+                    state_root: array_bytes::hex_n_into_unchecked(
+                        "cc2d78f5977b6e9e16f4417f60cbd7edaad0c39a6a7cd21281e847da7dd210b9",
+                    ),
+                    extrinsics_root: array_bytes::hex_n_into_unchecked(
+                        "03170a2e7597b7b7e3d84c05391d139a62b157e78786d8c082f29dcf4c111314",
+                    ),
+                    digest,
+                },
+                extrinsics: Vec::new(),
+            };
+
+            TestExecutive::execute_block_checking_digest(b);
+        });
+    }
+
+    #[test]
+    fn execute_block_without_digest_check_ignores_undeposited_log() {
+        ExternalityBuilder::default().build().execute_with(|| {
+            let mut digest = sp_runtime::Digest::default();
+            digest.push(DigestItem::Consensus(*b"test", vec![1, 2, 3]));
+            let b = TestBlock {
+                header: TestHeader {
+                    parent_hash: H256::zero(),
+                    number: 6,
+                    state_root: array_bytes::hex_n_into_unchecked(
+                        "cc2d78f5977b6e9e16f4417f60cbd7edaad0c39a6a7cd21281e847da7dd210b9",
+                    ),
+                    extrinsics_root: array_bytes::hex_n_into_unchecked(
+                        "03170a2e7597b7b7e3d84c05391d139a62b157e78786d8c082f29dcf4c111314",
+                    ),
+                    digest,
+                },
+                extrinsics: Vec::new(),
+            };
+
+            TestExecutive::execute_block(b);
+        });
+    }
+
     #[test]
     #[should_panic(expected = "extrinsics root mismatch")]
     fn execute_block_extrinsic_root_mismatch() {
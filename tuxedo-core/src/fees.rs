@@ -0,0 +1,111 @@
+//! Transaction fees that are collected for block authors.
+//!
+//! Tuxedo has no built-in notion of fees. Pieces like money allow a transaction's outputs to be
+//! worth less than its inputs, and the difference is simply destroyed. Constraint checkers are
+//! pure, so they cannot keep track of this value themselves.
+//!
+//! This module allows a runtime to opt in to an executive-level policy that decides how much of
+//! each applied transaction counts as a fee. The executive adds up the fees of all the transactions
+//! it applies, and a piece such as the coinbase may pay them out. The policy names the transactions
+//! that do so, and the executive clears the collected fees once such a transaction has been applied.
+//! Fees are only ever collected or cleared when transactions are applied, never when they are
+//! validated in the pool.
+
+use crate::{dynamic_typing::DynamicallyTypedData, FEES_KEY};
+use parity_scale_codec::{Decode, Encode};
+
+/// A policy that decides how much of each transaction counts as a fee.
+///
+/// The unit type implements this trait with no fees at all, and is the default
+/// policy used by the executive.
+pub trait FeePolicy<C> {
+    /// Whether the executive should collect fees at all.
+    ///
+    /// Collecting fees requires reading each transaction's inputs a second time, so it is
+    /// disabled by default.
+    const COLLECT_FEES: bool = false;
+
+    /// The fee paid by a transaction that is being applied.
+    ///
+    /// The input data only includes the inputs that were redeemed, not those that were evicted.
+    /// The output data includes all of the transaction's outputs.
+    fn fee(
+        _checker: &C,
+        _input_data: &[DynamicallyTypedData],
+        _output_data: &[DynamicallyTypedData],
+    ) -> u128 {
+        0
+    }
+
+    /// Whether a transaction that is being applied pays out the fees collected so far.
+    /// If so, the executive clears them once the transaction has been applied.
+    ///
+    /// Constraint checkers are pure, so a piece that pays out fees cannot clear them itself.
+    /// Like the fee, this is only consulted when the policy collects fees.
+    fn pays_out_fees(_checker: &C) -> bool {
+        false
+    }
+}
+
+impl<C> FeePolicy<C> for () {}
+
+/// The fees collected since they were last cleared.
+pub fn collected_fees() -> u128 {
+    sp_io::storage::get(FEES_KEY)
+        .and_then(|d| u128::decode(&mut &*d).ok())
+        .unwrap_or_default()
+}
+
+/// Clear the collected fees, typically because they have just been paid out.
+pub fn clear_collected_fees() {
+    sp_io::storage::clear(FEES_KEY);
+}
+
+/// Add the given fee to those collected so far.
+pub(crate) fn collect_fee(fee: u128) {
+    if fee > 0 {
+        let total = collected_fees().saturating_add(fee);
+        sp_io::storage::set(FEES_KEY, &total.encode());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sp_io::TestExternalities;
+
+    #[test]
+    fn no_fees_collected_initially() {
+        TestExternalities::default().execute_with(|| {
+            assert_eq!(collected_fees(), 0);
+        });
+    }
+
+    #[test]
+    fn collected_fees_accumulate_and_clear() {
+        TestExternalities::default().execute_with(|| {
+            collect_fee(3);
+            collect_fee(4);
+            assert_eq!(collected_fees(), 7);
+
+            clear_collected_fees();
+            assert_eq!(collected_fees(), 0);
+        });
+    }
+
+    #[test]
+    fn collected_fees_saturate() {
+        TestExternalities::default().execute_with(|| {
+            collect_fee(u128::MAX);
+            collect_fee(1);
+            assert_eq!(collected_fees(), u128::MAX);
+        });
+    }
+
+    #[test]
+    fn unit_policy_charges_no_fee() {
+        assert!(!<() as FeePolicy<()>>::COLLECT_FEES);
+        assert_eq!(<() as FeePolicy<()>>::fee(&(), &[], &[]), 0);
+        assert!(!<() as FeePolicy<()>>::pays_out_fees(&()));
+    }
+}
@@ -1,9 +1,10 @@
 //! Custom GenesisConfigBuilder for Tuxedo, to allow extrinsics to be added to the genesis block.
 
 use crate::{
+    dynamic_typing::find_type_id_collision,
     ensure,
     types::{OutputRef, Transaction},
-    ConstraintChecker, Verifier, EXTRINSIC_KEY, HEIGHT_KEY,
+    ConstraintChecker, Verifier, EXTRINSIC_KEY, HEIGHT_KEY, LOG_TARGET,
 };
 use parity_scale_codec::Encode;
 use sp_runtime::traits::Hash as HashT;
@@ -22,6 +23,18 @@ where
     /// The input transactions must be ordered: inherents first, then extrinsics.
     /// The genesis transactions will not be validated by the corresponding ConstraintChecker or Verifier.
     pub fn build(genesis_transactions: Vec<Transaction<V, C>>) -> sp_genesis_builder::Result {
+        // Make sure no two pieces in this runtime store different types under the same type id.
+        if let Some((a, b)) = find_type_id_collision(&C::utxo_types()) {
+            log::error!(
+                target: LOG_TARGET,
+                "Types {} and {} share the type id {:?}",
+                a.type_name,
+                b.type_name,
+                a.type_id
+            );
+            return Err("Two pieces in this runtime use the same UTXO type id.".into());
+        }
+
         // The transactions are stored under a special key.
         sp_io::storage::set(EXTRINSIC_KEY, &genesis_transactions.encode());
 
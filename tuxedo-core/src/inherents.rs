@@ -39,7 +39,10 @@ use sp_inherents::{
 };
 use sp_std::{vec, vec::Vec};
 
-use crate::{types::Transaction, ConstraintChecker, SimpleConstraintChecker, Verifier};
+use crate::{
+    constraint_checker::VerifierAwareConstraintChecker, ensure, types::Transaction,
+    ConstraintChecker, SimpleConstraintChecker, Verifier,
+};
 
 /// An inherent identifier for the Tuxedo parent block inherent
 pub const PARENT_INHERENT_IDENTIFIER: InherentIdentifier = *b"prnt_blk";
@@ -85,6 +88,29 @@ impl<B: sp_runtime::traits::Block> sp_inherents::InherentDataProvider
     }
 }
 
+/// Reasons that Tuxedo could not create an inherent, or found a malformed one while checking.
+///
+/// These are always fatal. A block whose inherents cannot be checked must not be imported.
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, Copy, TypeInfo)]
+pub enum InherentError {
+    /// The inherent data that a piece needs is missing or could not be decoded.
+    MissingInherentData,
+    /// A piece expected exactly one inherent in the previous block but found none.
+    MissingPreviousInherent,
+    /// A piece expected exactly one inherent in the previous block but found several.
+    MultiplePreviousInherents,
+    /// An inherent does not have the inputs or outputs that its piece always creates.
+    MalformedInherent,
+    /// The runtime's verifier cannot create an unspendable verifier for an inherent's outputs.
+    NoUnspendableVerifier,
+}
+
+impl IsFatalError for InherentError {
+    fn is_fatal_error(&self) -> bool {
+        true
+    }
+}
+
 /// Tuxedo's controlled interface around Substrate's concept of inherents.
 ///
 /// This interface assumes that each inherent will appear exactly once in each block.
@@ -105,13 +131,16 @@ pub trait InherentHooks: SimpleConstraintChecker + Sized {
     fn create_inherent<V: Verifier>(
         authoring_inherent_data: &InherentData,
         previous_inherent: (Transaction<V, Self>, H256),
-    ) -> Transaction<V, Self>;
+    ) -> Result<Transaction<V, Self>, InherentError>;
 
     /// Perform off-chain pre-execution checks on the inherent.
     /// The inherent data is supplied by the importing node.
     /// The inherent data available here is not guaranteed to be the
     /// same as what is available at authoring time.
-    fn check_inherent<V>(
+    ///
+    /// The inherent comes from a block that has not been executed yet, so it may be malformed.
+    /// Rather than panicking, report that with an [`InherentError`] in the results.
+    fn check_inherent<V: Verifier>(
         importing_inherent_data: &InherentData,
         inherent: Transaction<V, Self>,
         results: &mut CheckInherentsResult,
@@ -161,47 +190,46 @@ impl<C: SimpleConstraintChecker + InherentHooks + 'static> ConstraintChecker
 {
     type Error = <C as SimpleConstraintChecker>::Error;
 
-    fn check(
+    fn check<V: Verifier>(
         &self,
-        input_data: &[crate::dynamic_typing::DynamicallyTypedData],
-        evicted_input_data: &[crate::dynamic_typing::DynamicallyTypedData],
-        peek_data: &[crate::dynamic_typing::DynamicallyTypedData],
-        output_data: &[crate::dynamic_typing::DynamicallyTypedData],
+        inputs: &[crate::types::Output<V>],
+        evicted_inputs: &[crate::types::Output<V>],
+        peeks: &[crate::types::Output<V>],
+        outputs: &[crate::types::Output<V>],
     ) -> Result<sp_runtime::transaction_validity::TransactionPriority, Self::Error> {
-        SimpleConstraintChecker::check(
-            &self.0,
-            input_data,
-            evicted_input_data,
-            peek_data,
-            output_data,
-        )
+        VerifierAwareConstraintChecker::check(&self.0, inputs, evicted_inputs, peeks, outputs)
     }
 
     fn is_inherent(&self) -> bool {
         true
     }
 
+    fn is_compoundable(&self) -> bool {
+        <C as SimpleConstraintChecker>::COMPOUNDABLE
+    }
+
     fn create_inherents<V: Verifier>(
         authoring_inherent_data: &InherentData,
         previous_inherents: Vec<(Transaction<V, Self>, H256)>,
-    ) -> Vec<Transaction<V, Self>> {
-        if previous_inherents.len() > 1 {
-            panic!("Authoring a leaf inherent constraint checker, but multiple previous inherents were supplied.")
-        }
+    ) -> Result<Vec<Transaction<V, Self>>, InherentError> {
+        ensure!(
+            previous_inherents.len() <= 1,
+            InherentError::MultiplePreviousInherents
+        );
 
         let (previous_inherent, hash) = previous_inherents
-            .first()
-            .cloned()
-            .expect("Previous inherent exists.");
+            .into_iter()
+            .next()
+            .ok_or(InherentError::MissingPreviousInherent)?;
         let current_inherent = wrap_transaction(<C as InherentHooks>::create_inherent(
             authoring_inherent_data,
             (unwrap_transaction(previous_inherent), hash),
-        ));
+        )?);
 
-        vec![current_inherent]
+        Ok(vec![current_inherent])
     }
 
-    fn check_inherents<V: Clone>(
+    fn check_inherents<V: Verifier>(
         importing_inherent_data: &InherentData,
         inherents: Vec<Transaction<V, Self>>,
         results: &mut CheckInherentsResult,
@@ -239,4 +267,8 @@ impl<C: SimpleConstraintChecker + InherentHooks + 'static> ConstraintChecker
             .map(|gtx| wrap_transaction(gtx))
             .collect()
     }
+
+    fn utxo_types() -> Vec<crate::dynamic_typing::UtxoType> {
+        <C as SimpleConstraintChecker>::utxo_types()
+    }
 }
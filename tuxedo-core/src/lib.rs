@@ -5,21 +5,30 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+// Allow the macros, which refer to `tuxedo_core`, to be used within this crate as well.
+extern crate self as tuxedo_core;
+
 pub mod dynamic_typing;
 mod executive;
 
+pub mod compound;
 pub mod constraint_checker;
+pub mod fees;
 pub mod genesis;
 pub mod inherents;
 pub mod metadata;
+pub mod rent;
 pub mod support_macros;
 pub mod traits;
 pub mod types;
 pub mod utxo_set;
 pub mod verifier;
 
-pub use aggregator::{aggregate, tuxedo_constraint_checker, tuxedo_verifier};
-pub use constraint_checker::{ConstraintChecker, SimpleConstraintChecker};
+pub use aggregator::{aggregate, tuxedo_constraint_checker, tuxedo_payload, tuxedo_verifier};
+pub use constraint_checker::{
+    ConstraintChecker, SimpleConstraintChecker, TypedConstraintChecker,
+    VerifierAwareConstraintChecker,
+};
 pub use executive::Executive;
 pub use inherents::{InherentAdapter, InherentHooks};
 pub use metadata::TuxedoMetadata;
@@ -39,3 +48,11 @@ const HEIGHT_KEY: &[u8] = b"height";
 /// A transient storage key that will hold the list of extrinsics that have been applied so far.
 /// This key is cleared before the end of the block.
 const EXTRINSIC_KEY: &[u8] = b"extrinsics";
+
+/// A storage key prefix under which the creation height of each UTXO is recorded.
+/// This is only used by runtimes whose rent policy tracks the age of UTXOs.
+const CREATION_HEIGHT_PREFIX: &[u8] = b"created";
+
+/// A storage key that will hold the fees collected since they were last paid out.
+/// This is only used by runtimes whose fee policy collects fees.
+const FEES_KEY: &[u8] = b"collected_fees";
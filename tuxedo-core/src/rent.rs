@@ -0,0 +1,149 @@
+//! Storage rent and automatic expiry of stale UTXOs.
+//!
+//! In an account based system, accounts that fall below an existential deposit are reaped.
+//! UTXO chains have no such mechanism by default. Each piece that wants its UTXOs cleaned up
+//! must invent its own eviction rules (see the timestamp and PoE pieces for examples), and
+//! dust or abandoned UTXOs otherwise live in state forever.
+//!
+//! This module allows a runtime to opt in to an executive-level policy that decides which
+//! UTXOs are considered stale. Stale UTXOs may be evicted by anyone in any transaction. The
+//! executive handles these evictions itself, and they are never shown to the transaction's
+//! constraint checker. If the policy requires it, the value of the evicted UTXOs is routed to
+//! a treasury output, which must be the final output of the transaction, and which is also
+//! hidden from the constraint checker.
+//!
+//! A transaction that does nothing but collect rent may use the `CollectRent` constraint checker
+//! which is provided here. Runtimes that opt in to a rent policy should include it in their
+//! aggregate constraint checker.
+
+use crate::{
+    dynamic_typing::DynamicallyTypedData,
+    ensure,
+    types::{BlockNumber, Output},
+    SimpleConstraintChecker,
+};
+use parity_scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use serde::{Deserialize, Serialize};
+use sp_runtime::transaction_validity::TransactionPriority;
+
+/// A policy that decides which UTXOs have become stale and may be evicted by anyone.
+///
+/// The unit type implements this trait with no stale UTXOs at all, and is the default
+/// policy used by the executive.
+pub trait RentPolicy<V> {
+    /// Whether the executive should record the block height at which each UTXO is created.
+    ///
+    /// This is required for age-based expiry, but costs an additional storage item per tracked
+    /// UTXO, so it is disabled by default. UTXOs that were created before tracking began, including
+    /// those created at genesis, are treated as if they were created at block zero.
+    const TRACK_AGE: bool = false;
+
+    /// Whether the executive should record the block height at which this particular UTXO is created.
+    ///
+    /// This is only consulted when `TRACK_AGE` is enabled. By default the age of every UTXO is tracked.
+    /// A policy that only expires some types of UTXOs by age should restrict this to those types, so
+    /// that the rest of the state does not pay for the extra storage. The ages of untracked UTXOs are
+    /// passed to `is_stale` as `None`.
+    fn track_age_of(_output: &Output<V>) -> bool {
+        true
+    }
+
+    /// Decide whether the given UTXO is stale.
+    ///
+    /// The age is the number of blocks since the UTXO was created, or `None` when the policy
+    /// does not track the age of this UTXO. A typical policy considers a UTXO stale if its value or size is
+    /// below some threshold, or if it is older than some configured age.
+    fn is_stale(_output: &Output<V>, _age: Option<BlockNumber>) -> bool {
+        false
+    }
+
+    /// Construct the output that receives the value of the stale UTXOs evicted by a single transaction.
+    ///
+    /// When this returns `Some`, the transaction evicting the stale UTXOs must include exactly this
+    /// output as its final output. When it returns `None`, the value is simply destroyed.
+    fn treasury_output(_stale: &[Output<V>]) -> Option<Output<V>> {
+        None
+    }
+}
+
+impl<V> RentPolicy<V> for () {}
+
+/// Errors that can occur when collecting rent.
+#[derive(Debug, Eq, PartialEq)]
+pub enum RentError {
+    /// A rent collection transaction may only evict stale UTXOs and route their
+    /// value to the treasury. All other inputs, peeks, and outputs are forbidden.
+    UnexpectedData,
+}
+
+/// A constraint checker for transactions that do nothing but evict stale UTXOs.
+///
+/// By the time this checker is called, the executive has already removed the stale
+/// evictions and the treasury output from the data it will see. So this checker
+/// simply ensures that there is nothing left.
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, TypeInfo)]
+pub struct CollectRent;
+
+impl SimpleConstraintChecker for CollectRent {
+    type Error = RentError;
+
+    fn check(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        evicted_input_data: &[DynamicallyTypedData],
+        peek_data: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> Result<TransactionPriority, Self::Error> {
+        ensure!(
+            input_data.is_empty()
+                && evicted_input_data.is_empty()
+                && peek_data.is_empty()
+                && output_data.is_empty(),
+            RentError::UnexpectedData
+        );
+
+        Ok(0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{dynamic_typing::testing::Bogus, verifier::UpForGrabs};
+
+    #[test]
+    fn collect_rent_with_nothing_left_works() {
+        assert_eq!(CollectRent.check(&[], &[], &[], &[]), Ok(0));
+    }
+
+    #[test]
+    fn collect_rent_with_leftover_input_fails() {
+        assert_eq!(
+            CollectRent.check(&[Bogus.into()], &[], &[], &[]),
+            Err(RentError::UnexpectedData)
+        );
+    }
+
+    #[test]
+    fn collect_rent_with_leftover_output_fails() {
+        assert_eq!(
+            CollectRent.check(&[], &[], &[], &[Bogus.into()]),
+            Err(RentError::UnexpectedData)
+        );
+    }
+
+    #[test]
+    fn unit_policy_never_stale() {
+        let output = Output {
+            payload: Bogus.into(),
+            verifier: UpForGrabs,
+        };
+
+        assert!(!<() as RentPolicy<UpForGrabs>>::is_stale(
+            &output,
+            Some(BlockNumber::MAX)
+        ));
+        assert!(<() as RentPolicy<UpForGrabs>>::treasury_output(&[output]).is_none());
+    }
+}
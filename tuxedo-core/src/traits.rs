@@ -1,6 +1,11 @@
 //! General-purpose runtime traits for describing common types of on-chain logic.
 //! Tuxedo piece implementations may loosely couple through these traits.
 
+use crate::{
+    dynamic_typing::{DynamicallyTypedData, UtxoData},
+    ensure,
+};
+
 /// A trait for UTXOs that can act like coins, or bank notes.
 pub trait Cash {
     /// Get the value of this token.
@@ -9,4 +14,71 @@ pub trait Cash {
     /// A 1-byte unique identifier for this coin.
     /// Might need more than 1 byte eventually...
     const ID: u8;
+
+    /// Sum the values of some coins of this type, making sure none of them has zero value.
+    ///
+    /// Pieces that accept any `Cash` use this to total up payments and change.
+    fn total_value<'a>(
+        coins: impl IntoIterator<Item = &'a DynamicallyTypedData>,
+    ) -> Result<u128, CashError>
+    where
+        Self: UtxoData + Sized,
+    {
+        let mut total: u128 = 0;
+        for coin in coins {
+            let value = coin
+                .extract::<Self>()
+                .map_err(|_| CashError::BadlyTyped)?
+                .value();
+            ensure!(value > 0, CashError::ZeroValueCoin);
+            total = total.checked_add(value).ok_or(CashError::ValueOverflow)?;
+        }
+        Ok(total)
+    }
+}
+
+/// Reasons that the coins given to `Cash::total_value` may not add up.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CashError {
+    /// One of the coins is not of the expected type, or failed to decode.
+    BadlyTyped,
+    /// One of the coins has zero value. This is not allowed because it wastes state space.
+    ZeroValueCoin,
+    /// The total value of the coins overflows the value type.
+    ValueOverflow,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dynamic_typing::testing::{Bogus, Gold};
+
+    #[test]
+    fn total_value_of_no_coins_is_zero() {
+        assert_eq!(Gold::total_value(&[]), Ok(0));
+    }
+
+    #[test]
+    fn total_value_sums_coins() {
+        let coins: Vec<DynamicallyTypedData> = vec![Gold(3).into(), Gold(4).into()];
+        assert_eq!(Gold::total_value(&coins), Ok(7));
+    }
+
+    #[test]
+    fn total_value_of_wrong_type_fails() {
+        let coins: Vec<DynamicallyTypedData> = vec![Gold(3).into(), Bogus.into()];
+        assert_eq!(Gold::total_value(&coins), Err(CashError::BadlyTyped));
+    }
+
+    #[test]
+    fn total_value_of_worthless_coin_fails() {
+        let coins: Vec<DynamicallyTypedData> = vec![Gold(3).into(), Gold(0).into()];
+        assert_eq!(Gold::total_value(&coins), Err(CashError::ZeroValueCoin));
+    }
+
+    #[test]
+    fn total_value_overflow_fails() {
+        let coins: Vec<DynamicallyTypedData> = vec![Gold(u128::MAX).into(), Gold(1).into()];
+        assert_eq!(Gold::total_value(&coins), Err(CashError::ValueOverflow));
+    }
 }
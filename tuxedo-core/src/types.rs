@@ -1,6 +1,9 @@
 //! The common types that will be used across a Tuxedo runtime, and not specific to any one piece
 
-use crate::{dynamic_typing::DynamicallyTypedData, ConstraintChecker, Verifier};
+use crate::{
+    dynamic_typing::{DynamicTypingError, DynamicallyTypedData, UtxoPayload},
+    ConstraintChecker, Verifier,
+};
 use parity_scale_codec::{Decode, Encode};
 use scale_info::TypeInfo;
 use serde::{Deserialize, Serialize};
@@ -26,7 +29,7 @@ pub type Block<V, C> = sp_runtime::generic::Block<Header, Transaction<V, C>>;
 pub type OpaqueBlock = sp_runtime::generic::Block<Header, sp_runtime::OpaqueExtrinsic>;
 
 /// A reference to a output that is expected to exist in the state.
-#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, TypeInfo)]
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Hash, Clone, TypeInfo)]
 pub struct OutputRef {
     /// A hash of the transaction that created this output
     pub tx_hash: H256,
@@ -73,6 +76,37 @@ impl<V: Clone, C: Clone> Transaction<V, C> {
             checker: self.checker.clone().into(),
         }
     }
+
+    /// A fallible version of `transform`. This is useful when moving down the aggregation tree
+    /// where the transaction may belong to a different branch than the one requested.
+    pub fn try_transform<D: TryFrom<C>>(&self) -> Result<Transaction<V, D>, D::Error> {
+        Ok(Transaction {
+            inputs: self.inputs.clone(),
+            peeks: self.peeks.clone(),
+            outputs: self.outputs.clone(),
+            checker: self.checker.clone().try_into()?,
+        })
+    }
+}
+
+/// The error returned when unwrapping an aggregate type, such as an outer verifier or
+/// constraint checker, into one of its inner types while it holds a different variant.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct WrongVariant {
+    /// The variant that the conversion expected to find.
+    pub expected: &'static str,
+    /// The variant that was actually found.
+    pub found: &'static str,
+}
+
+impl sp_std::fmt::Display for WrongVariant {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "expected aggregate variant {} but found {}",
+            self.expected, self.found
+        )
+    }
 }
 
 // Manually implement Encode and Decode for the Transaction type
@@ -198,6 +232,9 @@ pub enum UtxoError<ConstraintCheckerError> {
     VerifierError,
     /// One or more of the inputs required by this transaction is not present in the UTXO set
     MissingInput,
+    /// This transaction evicts stale UTXOs, but its final output is not the treasury output
+    /// required by the runtime's rent policy.
+    MissingTreasuryOutput,
 }
 
 // Substrate requires this supposedly reusable error type, but it is actually tied pretty tightly
@@ -210,6 +247,7 @@ impl<ConstraintCheckerError> From<UtxoError<ConstraintCheckerError>> for Invalid
             UtxoError::ConstraintCheckerError(_) => InvalidTransaction::Custom(0),
             UtxoError::VerifierError => InvalidTransaction::BadProof,
             UtxoError::MissingInput => InvalidTransaction::Future,
+            UtxoError::MissingTreasuryOutput => InvalidTransaction::Custom(253),
         }
     }
 }
@@ -221,12 +259,36 @@ pub type DispatchResult<ConstraintCheckerError> = Result<(), UtxoError<Constrain
 /// the verifier is checked, strongly typed data will be extracted and passed to the constraint checker.
 /// In a cryptocurrency, the data represents a single coin. In Tuxedo, the type of
 /// the contained data is generic.
+///
+/// By default the payload is dynamically typed, which is how outputs are stored. Clients and
+/// pieces may instead use a strongly typed payload, such as a runtime's outer payload enum, and
+/// convert to and from the dynamically typed form with `into_dynamic` and `try_into_typed`.
 #[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, TypeInfo)]
-pub struct Output<V> {
-    pub payload: DynamicallyTypedData,
+pub struct Output<V, P = DynamicallyTypedData> {
+    pub payload: P,
     pub verifier: V,
 }
 
+impl<V, P: UtxoPayload> Output<V, P> {
+    /// Convert this output into the dynamically typed form in which it is stored.
+    pub fn into_dynamic(self) -> Output<V> {
+        Output {
+            payload: self.payload.to_dynamic(),
+            verifier: self.verifier,
+        }
+    }
+}
+
+impl<V> Output<V> {
+    /// Attempt to convert this output into one with a strongly typed payload.
+    pub fn try_into_typed<P: UtxoPayload>(self) -> Result<Output<V, P>, DynamicTypingError> {
+        Ok(Output {
+            payload: P::from_dynamic(&self.payload)?,
+            verifier: self.verifier,
+        })
+    }
+}
+
 impl<V: Default> From<DynamicallyTypedData> for Output<V> {
     fn from(payload: DynamicallyTypedData) -> Self {
         Self {
@@ -305,4 +367,34 @@ pub mod tests {
         assert_eq!(e, tx);
         assert_eq!(e.is_signed(), Some(false));
     }
+
+    #[test]
+    fn typed_output_round_trip() {
+        use crate::dynamic_typing::testing::Bogus;
+
+        let typed: Output<TestVerifier, Bogus> = Output {
+            payload: Bogus,
+            verifier: TestVerifier { verifies: true },
+        };
+        let dynamic = typed.clone().into_dynamic();
+
+        assert_eq!(dynamic.payload, Bogus.into());
+        assert_eq!(dynamic.try_into_typed::<Bogus>(), Ok(typed));
+    }
+
+    #[test]
+    fn typed_output_wrong_type() {
+        let output: Output<TestVerifier> = Output {
+            payload: DynamicallyTypedData {
+                data: Vec::new(),
+                type_id: *b"nope",
+            },
+            verifier: TestVerifier { verifies: true },
+        };
+
+        assert_eq!(
+            output.try_into_typed::<crate::dynamic_typing::testing::Bogus>(),
+            Err(DynamicTypingError::WrongType)
+        );
+    }
 }
@@ -5,12 +5,12 @@
 //!
 
 use crate::{
-    types::{Output, OutputRef},
+    types::{BlockNumber, Output, OutputRef},
     verifier::Verifier,
-    LOG_TARGET,
+    CREATION_HEIGHT_PREFIX, LOG_TARGET,
 };
 use parity_scale_codec::{Decode, Encode};
-use sp_std::marker::PhantomData;
+use sp_std::{marker::PhantomData, vec::Vec};
 
 pub struct TransparentUtxoSet<Verifier>(PhantomData<Verifier>);
 
@@ -41,4 +41,25 @@ impl<V: Verifier> TransparentUtxoSet<V> {
         );
         sp_io::storage::set(&key, &output.encode());
     }
+
+    /// Fetch the height at which a utxo was created.
+    /// Heights are only recorded when the runtime's rent policy tracks ages.
+    pub fn creation_height(output_ref: &OutputRef) -> Option<BlockNumber> {
+        sp_io::storage::get(&Self::creation_height_key(output_ref))
+            .and_then(|d| BlockNumber::decode(&mut &*d).ok())
+    }
+
+    /// Record the height at which a utxo was created.
+    pub fn store_creation_height(output_ref: &OutputRef, height: BlockNumber) {
+        sp_io::storage::set(&Self::creation_height_key(output_ref), &height.encode());
+    }
+
+    /// Forget the height at which a utxo was created. Called when the utxo is consumed.
+    pub fn clear_creation_height(output_ref: &OutputRef) {
+        sp_io::storage::clear(&Self::creation_height_key(output_ref));
+    }
+
+    fn creation_height_key(output_ref: &OutputRef) -> Vec<u8> {
+        (CREATION_HEIGHT_PREFIX, output_ref).encode()
+    }
 }
@@ -12,10 +12,12 @@ use sp_std::fmt::Debug;
 
 mod htlc;
 mod multi_signature;
+mod pay_to_verifier_hash;
 mod simple_signature;
 
 pub use htlc::{BlakeTwoHashLock, TimeLock};
 pub use multi_signature::ThresholdMultiSignature;
+pub use pay_to_verifier_hash::{PayToVerifierHash, VerifierHashRedeemer};
 pub use simple_signature::{Sr25519Signature, P2PKH};
 
 /// A means of checking that an output can be spent. This check is made on a
@@ -0,0 +1,160 @@
+//! This module contains a `Verifier` that commits to an arbitrary inner verifier by its hash.
+//!
+//! It is the natural generalization of P2PKH. Rather than committing to the hash of a public key,
+//! the output commits to the hash of an entire encoded verifier, usually the runtime's own aggregate
+//! verifier. The inner verifier is not revealed until spend time. This is similar in spirit to
+//! Bitcoin's P2SH, and is useful for keeping multisig participants, hash locks, and other spending
+//! conditions private until the output is consumed.
+
+use super::Verifier;
+use parity_scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use serde::{Deserialize, Serialize};
+use sp_core::H256;
+use sp_runtime::traits::{BlakeTwo256, Hash};
+use sp_std::{marker::PhantomData, vec::Vec};
+
+/// Pay To Verifier Hash
+///
+/// Require that the spender reveal an inner verifier whose hash matches the one given, and
+/// then satisfy that inner verifier.
+///
+/// The type parameter is the type of verifier that will be revealed. Typically this is the
+/// runtime's aggregate verifier so that any of the runtime's verifiers may be committed to.
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, TypeInfo)]
+#[scale_info(skip_type_params(V))]
+pub struct PayToVerifierHash<V> {
+    /// The BlakeTwo256 hash of the SCALE encoded inner verifier.
+    pub verifier_hash: H256,
+    #[serde(skip)]
+    _ph_data: PhantomData<V>,
+}
+
+impl<V: Verifier> PayToVerifierHash<V> {
+    /// Create a new instance that commits to the given inner verifier.
+    pub fn new(inner: &V) -> Self {
+        Self::from_hash(BlakeTwo256::hash_of(inner))
+    }
+
+    /// Create a new instance directly from the hash of an inner verifier.
+    /// This is useful when the verifier itself is not known to the creator of the output.
+    pub fn from_hash(verifier_hash: H256) -> Self {
+        Self {
+            verifier_hash,
+            _ph_data: PhantomData,
+        }
+    }
+}
+
+/// The redeemer for a `PayToVerifierHash` output.
+///
+/// The inner redeemer is stored in its encoded form. When the type parameter is the runtime's
+/// own aggregate verifier, storing the inner redeemer directly would make the aggregate redeemer
+/// type contain itself, and thus have infinite size.
+#[derive(Encode, Decode, Debug, PartialEq, Eq, Clone, TypeInfo)]
+pub struct VerifierHashRedeemer<V> {
+    /// The inner verifier being revealed.
+    pub verifier: V,
+    /// The SCALE encoded redeemer that satisfies the inner verifier.
+    pub redeemer: Vec<u8>,
+}
+
+impl<V: Verifier> VerifierHashRedeemer<V> {
+    /// Create a new redeemer that reveals the inner verifier and satisfies it with the inner redeemer.
+    pub fn new(verifier: V, redeemer: &V::Redeemer) -> Self {
+        Self {
+            verifier,
+            redeemer: redeemer.encode(),
+        }
+    }
+}
+
+impl<V: Verifier> Verifier for PayToVerifierHash<V> {
+    type Redeemer = VerifierHashRedeemer<V>;
+
+    fn verify(&self, simplified_tx: &[u8], block_height: u32, redeemer: &Self::Redeemer) -> bool {
+        if BlakeTwo256::hash_of(&redeemer.verifier) != self.verifier_hash {
+            return false;
+        }
+
+        let Ok(inner_redeemer) = V::Redeemer::decode(&mut &redeemer.redeemer[..]) else {
+            return false;
+        };
+
+        redeemer
+            .verifier
+            .verify(simplified_tx, block_height, &inner_redeemer)
+    }
+
+    fn new_unspendable() -> Option<Self> {
+        Some(Self::from_hash(H256::zero()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::verifier::{BlakeTwoHashLock, Sr25519Signature, TestVerifier, UpForGrabs};
+    use sp_core::{crypto::Pair as _, sr25519::Pair};
+
+    #[test]
+    fn p2vh_reveals_correct_verifier_that_passes() {
+        let inner = UpForGrabs;
+        let p2vh = PayToVerifierHash::new(&inner);
+        let redeemer = VerifierHashRedeemer::new(inner, &());
+
+        assert!(p2vh.verify(&[], 0, &redeemer));
+    }
+
+    #[test]
+    fn p2vh_reveals_correct_verifier_that_fails() {
+        let inner = TestVerifier { verifies: false };
+        let p2vh = PayToVerifierHash::new(&inner);
+        let redeemer = VerifierHashRedeemer::new(inner, &());
+
+        assert!(!p2vh.verify(&[], 0, &redeemer));
+    }
+
+    #[test]
+    fn p2vh_reveals_wrong_verifier() {
+        let p2vh = PayToVerifierHash::new(&TestVerifier { verifies: false });
+        let redeemer = VerifierHashRedeemer::new(TestVerifier { verifies: true }, &());
+
+        assert!(!p2vh.verify(&[], 0, &redeemer));
+    }
+
+    #[test]
+    fn p2vh_with_signature_inside() {
+        let pair = Pair::from_seed(&[0u8; 32]);
+        let simplified_tx = b"hello world".as_slice();
+        let sig = pair.sign(simplified_tx);
+
+        let inner = Sr25519Signature::new(pair.public());
+        let p2vh = PayToVerifierHash::new(&inner);
+        let redeemer = VerifierHashRedeemer::new(inner, &sig);
+
+        assert!(p2vh.verify(simplified_tx, 0, &redeemer));
+    }
+
+    #[test]
+    fn p2vh_undecodable_inner_redeemer() {
+        let secret = b"secret".to_vec();
+        let inner = BlakeTwoHashLock::new_from_secret(secret);
+        let p2vh = PayToVerifierHash::new(&inner);
+        let redeemer = VerifierHashRedeemer {
+            verifier: inner,
+            // A compact length prefix claiming far more bytes than are present
+            redeemer: vec![0xff],
+        };
+
+        assert!(!p2vh.verify(&[], 0, &redeemer));
+    }
+
+    #[test]
+    fn p2vh_new_unspendable_cannot_be_spent() {
+        let p2vh = PayToVerifierHash::<UpForGrabs>::new_unspendable().unwrap();
+        let redeemer = VerifierHashRedeemer::new(UpForGrabs, &());
+
+        assert!(!p2vh.verify(&[], 0, &redeemer));
+    }
+}
@@ -14,7 +14,7 @@ pub trait ParachainExecutiveExtension {
     fn collect_collation_info(header: &Header) -> cumulus_primitives_core::CollationInfo;
 }
 
-impl<V, C> ParachainExecutiveExtension for Executive<V, C> {
+impl<V, C, R, F> ParachainExecutiveExtension for Executive<V, C, R, F> {
     fn collect_collation_info(header: &Header) -> cumulus_primitives_core::CollationInfo {
         // The implementation here is simple. Most of the fields are related to xcm and parachain runtime upgrades,
         // neither or which are supported in the PoC, so they are left blank.
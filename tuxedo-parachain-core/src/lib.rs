@@ -134,8 +134,9 @@ pub struct MemoryOptimizedValidationParams {
 /// Expects as parameters a Verifier, a non-yet-parachain-ready ConstraintChecker, and a ParaId.
 pub use tuxedo_parachainify::parachainify;
 
-// Having to do this wrapping is one more reason to abandon this UtxoData trait,
-// and go for a more strongly typed aggregate type approach.
+// Even with strongly typed payloads (`#[tuxedo_payload]`) this wrapping is still necessary
+// because every variant of a payload enum must be `UtxoData` (or a payload itself), and the
+// orphan rule does not let us implement that for the foreign Cumulus type.
 // Tracking issue: https://github.com/Off-Narrative-Labs/Tuxedo/issues/153
 /// A wrapper type around Cumulus's ParachainInherentData type that can be stored.
 #[derive(Encode, Decode, DebugNoBound, CloneNoBound, scale_info::TypeInfo)]
@@ -5,7 +5,10 @@ pub use super::WASM_BINARY;
 
 use super::{ParachainConstraintChecker, Transaction};
 use hex_literal::hex;
-use inner_runtime::{money::Coin, OuterConstraintChecker as InnerConstraintChecker};
+use inner_runtime::{
+    money::{Coin, MintAuthority},
+    OuterConstraintChecker as InnerConstraintChecker,
+};
 use sp_std::{vec, vec::Vec};
 use tuxedo_parachain_core::tuxedo_core::{
     verifier::{Sr25519Signature, ThresholdMultiSignature},
@@ -22,16 +25,22 @@ pub fn development_genesis_transactions() -> Vec<Transaction> {
 
     let user_genesis_transactions = [
         // Money Transactions
-        Coin::<0>::mint::<_, _, InnerConstraintChecker>(
+        Coin::<0>::genesis::<_, _, InnerConstraintChecker>(
             100,
             Sr25519Signature::new(SHAWN_PUB_KEY_BYTES),
         )
         .transform(),
-        Coin::<0>::mint::<_, _, InnerConstraintChecker>(
+        Coin::<0>::genesis::<_, _, InnerConstraintChecker>(
             100,
             ThresholdMultiSignature::new(1, signatories),
         )
         .transform(),
+        // Shawn may mint more coins, for example with the wallet's `mint-coins` command
+        MintAuthority::<0>::genesis::<_, _, InnerConstraintChecker>(
+            None,
+            Sr25519Signature::new(SHAWN_PUB_KEY_BYTES),
+        )
+        .transform(),
         // No Kitty or anything else in this one. Keep it simple.
     ]
     .into_iter()
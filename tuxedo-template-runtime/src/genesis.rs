@@ -1,13 +1,57 @@
 //! Helper module to build a genesis configuration for the template runtime.
+//!
+//! The genesis configuration has a section for each piece that has something to store at genesis.
+//! Each section is compiled into that piece's genesis transactions, so nobody has to write
+//! transactions with encoded payloads by hand. For example, this configuration gives Shawn a coin
+//! and makes a 1-of-2 multisig the upgrade council.
+//!
+//! ```json
+//! {
+//!   "money": {
+//!     "coins": [{
+//!       "owner": {
+//!         "Sr25519Signature": {
+//!           "owner_pubkey": "0xd2bf4b844dfefd6772a8843e669f943408966a977e3ae2af1dd78e0f55f4df67"
+//!         }
+//!       },
+//!       "amount": 100
+//!     }]
+//!   },
+//!   "runtimeUpgrade": {
+//!     "council": {
+//!       "ThresholdMultiSignature": {
+//!         "threshold": 1,
+//!         "signatories": [
+//!           "0xd2bf4b844dfefd6772a8843e669f943408966a977e3ae2af1dd78e0f55f4df67",
+//!           "0xbaa81e58b1b4d053c2e86d93045765036f9d265c7dfe8b9693bbc2c0f048d93a"
+//!         ]
+//!       }
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! Named presets are provided for development, local testnets, and staging networks.
+//!
+//! Before this configuration existed, chain specs contained the complete list of genesis
+//! transactions instead. Such a list is still parsed (see [`genesis_transactions_from_json`]), but
+//! it is used exactly as it is. Old lists do not register any validators, so a chain built from one
+//! has an empty authority set and cannot author blocks. Existing chain specs must be migrated: move
+//! any transactions that a section covers into that section, and keep the rest in
+//! `extraTransactions`. Leave out the inherents' genesis transactions, which are now added
+//! automatically.
 
 #[cfg(feature = "std")]
 pub use super::WASM_BINARY;
 use super::{
-    kitties::{KittyData, Parent},
-    money::Coin,
-    OuterConstraintChecker, Transaction,
+    authorities::{self, AuthoritiesConfig, AuthorityKeys},
+    kitties::{self, Parent},
+    money::{self, Coin},
+    runtime_upgrade, OuterConstraintChecker, OuterVerifier, Runtime, Transaction,
 };
 use hex_literal::hex;
+use serde::{Deserialize, Serialize};
+use sp_core::{ed25519, sr25519};
 use sp_std::{vec, vec::Vec};
 use tuxedo_core::{
     verifier::{Sr25519Signature, ThresholdMultiSignature, UpForGrabs},
@@ -19,27 +63,202 @@ const SHAWN_PUB_KEY_BYTES: [u8; 32] =
 const ANDREW_PUB_KEY_BYTES: [u8; 32] =
     hex!("baa81e58b1b4d053c2e86d93045765036f9d265c7dfe8b9693bbc2c0f048d93a");
 
+// The session keys of the well-known identities that work with the CLI flags such as `--alice`,
+// `--bob`, etc. Only Alice is registered as a validator in the development preset, which makes
+// things work nicely in a `--dev` node. Other validators may register by bonding coins and
+// signing with their session keys once the chain is running.
+const ALICE_AURA_KEY_BYTES: [u8; 32] =
+    hex!("d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d");
+const ALICE_GRANDPA_KEY_BYTES: [u8; 32] =
+    hex!("88dc3417d5058ec4b4503e0c12ea1a0a89be200fe98922423d4334014fa6b0ee");
+const BOB_AURA_KEY_BYTES: [u8; 32] =
+    hex!("8eaf04151687736326c9fea17e25fc5287613693c912909cb226aa4794f26a48");
+const BOB_GRANDPA_KEY_BYTES: [u8; 32] =
+    hex!("d17c2d7823ebf260fd138f2d7e27d114c0145d968b5ff5006125f2414fadae69");
+
+/// The genesis configuration of the template runtime.
+///
+/// Sections that are left out of the JSON are empty.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields, default)]
+pub struct RuntimeGenesisConfig {
+    /// The main coin, `Coin<0>`, and its mint authority.
+    pub money: money::GenesisConfig<OuterVerifier>,
+    /// The council that may approve runtime upgrades.
+    pub runtime_upgrade: runtime_upgrade::GenesisConfig<OuterVerifier>,
+    /// The second token, `Coin<1>`, and its mint authority.
+    pub tokens: money::GenesisConfig<OuterVerifier>,
+    /// The initial validators, who bond the main coin.
+    pub authorities: authorities::GenesisConfig<OuterVerifier, Coin<0>>,
+    /// The kitties that are around from the start.
+    pub kitties: kitties::GenesisConfig<OuterVerifier>,
+    /// Any other transactions to include in the genesis block, for outputs that no section covers.
+    /// They must not have any inputs or peeks.
+    pub extra_transactions: Vec<Transaction>,
+}
+
+impl RuntimeGenesisConfig {
+    /// Compile this configuration into the transactions to be included in the genesis block.
+    /// The resulting transactions are ordered: inherents first, then extrinsics.
+    pub fn genesis_transactions(self) -> Vec<Transaction> {
+        // The inherents are computed using the appropriate method, and placed before the extrinsics.
+        let mut genesis_transactions = OuterConstraintChecker::genesis_transactions();
+
+        genesis_transactions.extend(self.money.genesis_transactions::<0, _, _>());
+        genesis_transactions.extend(self.runtime_upgrade.genesis_transactions::<Runtime, _, _>());
+        genesis_transactions.extend(self.tokens.genesis_transactions::<1, _, _>());
+        genesis_transactions.extend(self.authorities.genesis_transactions::<Runtime, _, _>());
+        genesis_transactions.extend(self.kitties.genesis_transactions());
+        genesis_transactions.extend(self.extra_transactions);
+
+        genesis_transactions
+    }
+
+    /// A development chain, where Alice is the only validator, and Shawn and Andrew have some
+    /// coins and kitties to play with.
+    pub fn development() -> Self {
+        let signatories = vec![SHAWN_PUB_KEY_BYTES.into(), ANDREW_PUB_KEY_BYTES.into()];
+        let shawn: OuterVerifier = Sr25519Signature::new(SHAWN_PUB_KEY_BYTES).into();
+
+        Self {
+            money: money::GenesisConfig {
+                coins: vec![
+                    coin(shawn.clone(), 100),
+                    coin(ThresholdMultiSignature::new(1, signatories).into(), 100),
+                ],
+                // Shawn may mint more coins, for example with the wallet's `mint-coins` command
+                mint_authority: Some(money::GenesisMintAuthority {
+                    owner: shawn.clone(),
+                    supply_cap: Some(1_000_000),
+                }),
+            },
+            // The same council may approve runtime upgrades without a coin vote
+            runtime_upgrade: runtime_upgrade::GenesisConfig {
+                council: Some(council()),
+            },
+            // Some of the second token for Shawn to trade on the exchange
+            tokens: money::GenesisConfig {
+                coins: vec![coin(shawn, 100)],
+                mint_authority: None,
+            },
+            authorities: authorities::GenesisConfig {
+                validators: vec![validator(ALICE_AURA_KEY_BYTES, ALICE_GRANDPA_KEY_BYTES)],
+            },
+            kitties: kitties::GenesisConfig {
+                kitties: vec![
+                    kitty(Parent::mom(), "mother"),
+                    kitty(Parent::dad(), "father"),
+                ],
+            },
+            extra_transactions: Vec::new(),
+        }
+    }
+
+    /// A local testnet, which is the same as the development chain, except that Alice and Bob
+    /// are both validators.
+    pub fn local_testnet() -> Self {
+        let mut config = Self::development();
+        config
+            .authorities
+            .validators
+            .push(validator(BOB_AURA_KEY_BYTES, BOB_GRANDPA_KEY_BYTES));
+        config
+    }
+
+    /// A staging network, which starts with no coins or kitties. The council controls the mint
+    /// authority and runtime upgrades. The validators are Alice and Bob, and the council is Shawn
+    /// and Andrew, whose keys are all public. So this is only for rehearsing a launch locally.
+    /// Replace the keys with your own before launching a real network.
+    pub fn staging() -> Self {
+        Self {
+            money: money::GenesisConfig {
+                coins: Vec::new(),
+                mint_authority: Some(money::GenesisMintAuthority {
+                    owner: council(),
+                    supply_cap: None,
+                }),
+            },
+            runtime_upgrade: runtime_upgrade::GenesisConfig {
+                council: Some(council()),
+            },
+            authorities: authorities::GenesisConfig {
+                validators: vec![
+                    validator(ALICE_AURA_KEY_BYTES, ALICE_GRANDPA_KEY_BYTES),
+                    validator(BOB_AURA_KEY_BYTES, BOB_GRANDPA_KEY_BYTES),
+                ],
+            },
+            ..Default::default()
+        }
+    }
+}
+
+/// Shawn and Andrew's 2-of-2 multisig, which governs the presets' chains.
+fn council() -> OuterVerifier {
+    ThresholdMultiSignature::new(
+        2,
+        vec![SHAWN_PUB_KEY_BYTES.into(), ANDREW_PUB_KEY_BYTES.into()],
+    )
+    .into()
+}
+
+fn coin(owner: OuterVerifier, amount: u128) -> money::GenesisCoin<OuterVerifier> {
+    money::GenesisCoin { owner, amount }
+}
+
+fn kitty(parent: Parent, name: &str) -> kitties::GenesisKitty<OuterVerifier> {
+    kitties::GenesisKitty {
+        owner: UpForGrabs.into(),
+        parent,
+        name: name.into(),
+    }
+}
+
+/// A validator who bonds the minimum, and controls its registration with the same sr25519 key
+/// that it authors blocks with.
+fn validator(
+    aura: [u8; 32],
+    grandpa: [u8; 32],
+) -> authorities::GenesisValidator<OuterVerifier, Coin<0>> {
+    authorities::GenesisValidator {
+        keys: AuthorityKeys {
+            aura: sr25519::Public::from_raw(aura).into(),
+            grandpa: ed25519::Public::from_raw(grandpa).into(),
+        },
+        owner: Sr25519Signature::new(aura).into(),
+        bond: vec![Coin::<0>::new(<Runtime as AuthoritiesConfig>::MIN_BOND)],
+    }
+}
+
+/// The genesis transactions described by the given JSON, which is either a genesis config, or a
+/// complete list of genesis transactions in the format that chain specs used before the config
+/// existed. Such a list is used exactly as it is.
+pub fn genesis_transactions_from_json(json: &[u8]) -> Result<Vec<Transaction>, &'static str> {
+    if let Ok(config) = serde_json::from_slice::<RuntimeGenesisConfig>(json) {
+        return Ok(config.genesis_transactions());
+    }
+
+    serde_json::from_slice::<Vec<Transaction>>(json)
+        .map_err(|_| "The input JSON is neither a valid genesis config nor a list of Transactions.")
+}
+
 /// This function returns a list of valid transactions to be included in the genesis block.
-/// It is called by the `ChainSpec::build` method, via the `development_genesis_config` function.
 /// The resulting transactions must be ordered: inherent first, then extrinsics.
 pub fn development_genesis_transactions() -> Vec<Transaction> {
-    let signatories = vec![SHAWN_PUB_KEY_BYTES.into(), ANDREW_PUB_KEY_BYTES.into()];
-
-    // The inherents are computed using the appropriate method, and placed before the extrinsics.
-    let mut genesis_transactions = OuterConstraintChecker::genesis_transactions();
+    RuntimeGenesisConfig::development().genesis_transactions()
+}
 
-    genesis_transactions.extend([
-        // Money Transactions
-        Coin::<0>::mint(100, Sr25519Signature::new(SHAWN_PUB_KEY_BYTES)),
-        Coin::<0>::mint(100, ThresholdMultiSignature::new(1, signatories)),
-        // Kitty Transactions
-        KittyData::mint(Parent::mom(), b"mother", UpForGrabs),
-        KittyData::mint(Parent::dad(), b"father", UpForGrabs),
-    ]);
+/// The genesis config patch for a development chain.
+/// It is used by the `ChainSpec::build` method.
+pub fn development_genesis_config() -> serde_json::Value {
+    serde_json::json!(RuntimeGenesisConfig::development())
+}
 
-    genesis_transactions
+/// The genesis config patch for a local testnet.
+pub fn local_testnet_genesis_config() -> serde_json::Value {
+    serde_json::json!(RuntimeGenesisConfig::local_testnet())
 }
 
-pub fn development_genesis_config() -> serde_json::Value {
-    serde_json::json!(development_genesis_transactions())
+/// The genesis config patch for a staging network.
+pub fn staging_genesis_config() -> serde_json::Value {
+    serde_json::json!(RuntimeGenesisConfig::staging())
 }
@@ -15,16 +15,15 @@ use parity_scale_codec::{Decode, Encode};
 use scale_info::TypeInfo;
 use serde::{Deserialize, Serialize};
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
-use sp_consensus_grandpa::AuthorityId as GrandpaId;
 
 use sp_api::impl_runtime_apis;
-use sp_core::OpaqueMetadata;
+use sp_core::{OpaqueMetadata, H256};
 use sp_inherents::InherentData;
 use sp_runtime::{
     create_runtime_str, impl_opaque_keys,
     traits::Block as BlockT,
     transaction_validity::{TransactionPriority, TransactionSource, TransactionValidity},
-    ApplyExtrinsicResult, BoundToRuntimeAppPublic,
+    ApplyExtrinsicResult, BoundToRuntimeAppPublic, DigestItem,
 };
 use sp_std::prelude::*;
 
@@ -33,19 +32,28 @@ use sp_version::NativeVersion;
 use sp_version::RuntimeVersion;
 
 use tuxedo_core::{
+    dynamic_typing::{DynamicallyTypedData, UtxoData},
     genesis::TuxedoGenesisConfigBuilder,
-    tuxedo_constraint_checker, tuxedo_verifier,
+    tuxedo_constraint_checker, tuxedo_payload, tuxedo_verifier,
     types::Transaction as TuxedoTransaction,
-    verifier::{Sr25519Signature, ThresholdMultiSignature, UpForGrabs},
+    verifier::{PayToVerifierHash, Sr25519Signature, ThresholdMultiSignature, UpForGrabs},
     InherentAdapter, TuxedoMetadata,
 };
 
 pub use amoeba;
+pub use authorities;
+pub use coinbase;
+pub use dex;
+pub use difficulty;
 pub use kitties;
+pub use kitty_market;
 pub use money;
+pub use nft;
 pub use poe;
 pub use runtime_upgrade;
 pub use timestamp;
+pub use token_factory;
+pub use voting;
 
 /// Opaque types. These are used by the CLI to instantiate machinery that don't need to know
 /// the specifics of the runtime. They can then be made to be agnostic over specific formats
@@ -100,8 +108,11 @@ pub fn native_version() -> NativeVersion {
 
 pub type Transaction = TuxedoTransaction<OuterVerifier, OuterConstraintChecker>;
 pub type Block = tuxedo_core::types::Block<OuterVerifier, OuterConstraintChecker>;
-pub type Executive = tuxedo_core::Executive<OuterVerifier, OuterConstraintChecker>;
+pub type Executive =
+    tuxedo_core::Executive<OuterVerifier, OuterConstraintChecker, Runtime, Runtime>;
 pub type Output = tuxedo_core::types::Output<OuterVerifier>;
+/// An output whose payload has been decoded into one of the runtime's known data types.
+pub type TypedOutput = tuxedo_core::types::Output<OuterVerifier, OuterPayload>;
 
 /// The Aura slot duration. When things are working well, this will also be the block time.
 const BLOCK_TIME: u64 = 3000;
@@ -114,8 +125,45 @@ pub enum OuterVerifier {
     Sr25519Signature(Sr25519Signature),
     UpForGrabs(UpForGrabs),
     ThresholdMultiSignature(ThresholdMultiSignature),
+    PayToVerifierHash(PayToVerifierHash<OuterVerifier>),
 }
 
+/// The publicly known types of data that may be stored in this runtime's UTXOs.
+/// Clients can decode any output into this type instead of matching on type ids themselves.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[tuxedo_payload]
+pub enum OuterPayload {
+    Coin(money::Coin<0>),
+    MintAuthority(money::MintAuthority<0>),
+    Token(money::Coin<1>),
+    TokenOffer(TokenOffer),
+    Kitty(kitties::KittyData),
+    ListedKitty(kitty_market::ListedKitty),
+    Amoeba(amoeba::AmoebaDetails),
+    Timestamp(timestamp::Timestamp),
+    TimestampLocked(timestamp::TimestampLocked),
+    AssetDefinition(token_factory::AssetDefinition),
+    AssetCoin(token_factory::AssetCoin),
+    Collection(nft::Collection),
+    Nft(nft::Nft),
+    ListedNft(nft::ListedNft),
+    UpgradeProposal(runtime_upgrade::UpgradeProposal),
+    UpgradeCouncil(runtime_upgrade::UpgradeCouncil),
+    PassedUpgrade(runtime_upgrade::PassedUpgrade),
+    WasmChunk(runtime_upgrade::WasmChunk),
+    VotingProposal(voting::Proposal),
+    Vote(voting::Vote),
+    VoteResult(voting::VoteResult),
+    ValidatorRegistration(authorities::ValidatorRegistration),
+    Unbonding(authorities::Unbonding),
+    Slash(authorities::Slash),
+    BlockReward(coinbase::BlockReward),
+    Difficulty(difficulty::Difficulty),
+}
+
+/// An offer to sell the template's second token, `Coin<1>`, in exchange for its main coin, `Coin<0>`.
+pub type TokenOffer = dex::Offer<money::Coin<1>, money::Coin<0>>;
+
 impl poe::PoeConfig for Runtime {
     fn block_height() -> u32 {
         Executive::block_height()
@@ -126,6 +174,135 @@ impl timestamp::TimestampConfig for Runtime {
     fn block_height() -> u32 {
         Executive::block_height()
     }
+
+    const MEDIAN_TIME_PAST_WINDOW: u32 = 11;
+}
+
+impl runtime_upgrade::RuntimeUpgradeConfig for Runtime {
+    fn block_height() -> u32 {
+        Executive::block_height()
+    }
+
+    const MIN_QUORUM: u128 = 100;
+}
+
+impl voting::VotingConfig for Runtime {
+    fn block_height() -> u32 {
+        Executive::block_height()
+    }
+
+    const MIN_QUORUM: u128 = 100;
+}
+
+impl authorities::AuthoritiesConfig for Runtime {
+    fn block_height() -> u32 {
+        Executive::block_height()
+    }
+
+    fn deposit_log(log: DigestItem) {
+        Executive::deposit_log(log)
+    }
+}
+
+impl difficulty::DifficultyConfig for Runtime {
+    fn block_height() -> u32 {
+        Executive::block_height()
+    }
+}
+
+// The template rewards block authors in its main coin, and pays out the fees collected by the executive.
+impl coinbase::CoinbaseConfig for Runtime {
+    fn block_height() -> u32 {
+        Executive::block_height()
+    }
+
+    const BLOCK_REWARD: u128 = 10;
+
+    fn collected_fees() -> u128 {
+        tuxedo_core::fees::collected_fees()
+    }
+
+    // The author of a slot is chosen the same way Aura chooses it, and is paid with a signature check.
+    fn is_slot_author<V: tuxedo_core::Verifier>(
+        slot: sp_consensus_aura::Slot,
+        verifier: &V,
+    ) -> bool {
+        let authorities = authorities::aura_authorities();
+        let Some(author) = authorities.get((*slot % authorities.len().max(1) as u64) as usize)
+        else {
+            return false;
+        };
+        let expected: OuterVerifier =
+            Sr25519Signature::new(H256::from_slice(author.as_ref())).into();
+
+        // The verifier is this runtime's outer verifier, so equal encodings mean equal verifiers.
+        verifier.encode() == expected.encode()
+    }
+}
+
+// The template burns the coins paid for breeding kitties.
+impl kitties::PaidBreedingConfig for Runtime {
+    type Coin = money::Coin<0>;
+
+    fn beneficiary() -> Option<H256> {
+        None
+    }
+}
+
+/// Amoebas that are this many blocks old die of old age and may be evicted by anyone.
+/// With the template's three second block time, this is roughly one week.
+const AMOEBA_LIFESPAN: u32 = 7 * 24 * 60 * 20;
+
+// The template only expires amoebas, so it only pays to track the ages of amoebas. Chains that want
+// to bound the growth of the rest of their state may also expire dust coins, and route their value
+// to a treasury output.
+impl tuxedo_core::rent::RentPolicy<OuterVerifier> for Runtime {
+    const TRACK_AGE: bool = true;
+
+    fn track_age_of(output: &Output) -> bool {
+        output.payload.type_id == <amoeba::AmoebaDetails as UtxoData>::TYPE_ID
+    }
+
+    fn is_stale(output: &Output, age: Option<u32>) -> bool {
+        output.payload.extract::<amoeba::AmoebaDetails>().is_ok()
+            && age.is_some_and(|age| age >= AMOEBA_LIFESPAN)
+    }
+}
+
+// The template collects the value burned by spends of its main coin as fees, including spends that
+// are part of a compound transaction. Burning the second token pays no fees, because the coinbase
+// only pays out the main coin. The coinbase pays out the collected fees.
+impl tuxedo_core::fees::FeePolicy<OuterConstraintChecker> for Runtime {
+    const COLLECT_FEES: bool = true;
+
+    fn fee(
+        checker: &OuterConstraintChecker,
+        input_data: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> u128 {
+        match checker {
+            OuterConstraintChecker::Money(spend) => spend.burned(input_data, output_data),
+            OuterConstraintChecker::Compound(compound) => {
+                // The transaction has been checked, so its parts claim consecutive runs of the
+                // redeemed inputs and the outputs, just like when they were checked.
+                let (mut inputs, mut outputs) = (input_data, output_data);
+                compound.0.iter().fold(0, |total: u128, part| {
+                    let (part_inputs, rest) =
+                        inputs.split_at((part.inputs as usize).min(inputs.len()));
+                    inputs = rest;
+                    let (part_outputs, rest) =
+                        outputs.split_at((part.outputs as usize).min(outputs.len()));
+                    outputs = rest;
+                    total.saturating_add(Self::fee(&part.checker, part_inputs, part_outputs))
+                })
+            }
+            _ => 0,
+        }
+    }
+
+    fn pays_out_fees(checker: &OuterConstraintChecker) -> bool {
+        matches!(checker, OuterConstraintChecker::Coinbase(_))
+    }
 }
 
 // Observation: For some applications, it will be invalid to simply delete
@@ -138,7 +315,8 @@ impl timestamp::TimestampConfig for Runtime {
 #[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, TypeInfo)]
 #[tuxedo_constraint_checker]
 pub enum OuterConstraintChecker {
-    /// Checks monetary transactions in a basic fungible cryptocurrency
+    /// Checks monetary transactions in a basic fungible cryptocurrency.
+    /// Coins may only be minted by consuming a mint authority.
     Money(money::MoneyConstraintChecker<0>),
     /// Checks Free Kitty transactions
     FreeKittyConstraintChecker(kitties::FreeKittyConstraintChecker),
@@ -157,74 +335,94 @@ pub enum OuterConstraintChecker {
     PoeDispute(poe::PoeDispute),
     /// Set the block's timestamp via an inherent extrinsic.
     SetTimestamp(InherentAdapter<timestamp::SetTimestamp<Runtime>>),
-    /// Upgrade the Wasm Runtime
-    RuntimeUpgrade(runtime_upgrade::RuntimeUpgrade),
+    /// Enact a scheduled runtime upgrade via an inherent extrinsic.
+    EnactUpgrade(InherentAdapter<runtime_upgrade::EnactUpgrade<Runtime>>),
+    /// Evict stale UTXOs according to the runtime's rent policy
+    CollectRent(tuxedo_core::rent::CollectRent),
+    /// Check several parts of a single atomic transaction with different constraint checkers
+    Compound(tuxedo_core::compound::CompoundConstraintChecker<OuterConstraintChecker>),
+    /// Issue, mint, and spend assets that are defined at runtime
+    TokenFactory(token_factory::TokenFactoryConstraintChecker),
+    /// Checks monetary transactions in a second token that can be traded for the main coin
+    Token(money::MoneyConstraintChecker<1>),
+    /// Locks some tokens into an offer priced in the main coin
+    MakeOffer(dex::MakeOffer<money::Coin<1>, money::Coin<0>>),
+    /// Fills all or part of a token offer by paying its maker
+    TakeOffer(dex::TakeOffer<money::Coin<1>, money::Coin<0>>),
+    /// Returns the tokens locked in an offer to its maker
+    CancelOffer(dex::CancelOffer<money::Coin<1>, money::Coin<0>>),
+    /// Creates a new collection of non-fungible tokens
+    CreateCollection(nft::CreateCollection),
+    /// Mints new tokens in a collection on behalf of its creator
+    MintNfts(nft::MintNfts),
+    /// Gives tokens to new owners
+    TransferNfts(nft::TransferNfts),
+    /// Lists tokens for sale
+    ListNfts(nft::ListNfts),
+    /// Takes listed tokens off the market and returns them to their sellers
+    DelistNfts(nft::DelistNfts),
+    /// Buys a listed token by paying its seller and the creator's royalty
+    BuyNft(nft::BuyNft<money::Coin<0>>),
+    /// Destroys tokens
+    BurnNfts(nft::BurnNfts),
+    /// Checks Kitty breedings that are paid for with coins once the free breedings run out
+    PaidKittyConstraintChecker(kitties::PaidKittyConstraintChecker<Runtime>),
+    /// Lists kitties for sale in the marketplace
+    ListKitty(kitty_market::ListKitty),
+    /// Takes listed kitties off the market and returns them to their sellers
+    DelistKitty(kitty_market::DelistKitty),
+    /// Buys a listed kitty by paying its seller
+    BuyKitty(kitty_market::BuyKitty<money::Coin<0>>),
+    /// Checks that proofs of existence are transferred to new owners unchanged.
+    PoeTransfer(poe::PoeTransfer),
+    /// Checks that proofs of existence are only evicted once they have expired.
+    PoeExpire(poe::PoeExpire<Runtime>),
+    /// Locks data, such as coins, until a wall-clock time
+    LockUntilTimestamp(timestamp::LockUntilTimestamp),
+    /// Unlocks data whose unlock time is proven to have passed by a noted timestamp
+    UnlockWithTimestamp(timestamp::UnlockWithTimestamp),
+    /// Proposes a runtime upgrade
+    ProposeUpgrade(runtime_upgrade::ProposeUpgrade<Runtime>),
+    /// Passes a runtime upgrade proposal on the council's authority
+    CouncilApproveUpgrade(runtime_upgrade::CouncilApproveUpgrade<Runtime>),
+    /// Passes a runtime upgrade proposal that won its coin vote
+    PassUpgradeByVote(runtime_upgrade::PassUpgradeByVote<Runtime>),
+    /// Uploads chunks of the wasm code for a passed runtime upgrade
+    UploadWasmChunk(runtime_upgrade::UploadWasmChunk<Runtime>),
+    /// Assembles the uploaded wasm code and schedules the runtime upgrade for enactment
+    ScheduleUpgrade(runtime_upgrade::ScheduleUpgrade<Runtime>),
+    /// Cleans up passed runtime upgrades and wasm chunks that have expired
+    CleanUpExpiredUpgrade(runtime_upgrade::CleanUpExpiredUpgrade<Runtime>),
+    /// Creates a proposal for coin holders to vote on
+    CreateProposal(voting::CreateProposal<Runtime>),
+    /// Votes on a proposal by locking coins
+    CastVote(voting::CastVote<money::Coin<0>, Runtime>),
+    /// Returns the coins locked in votes once voting has ended
+    ReclaimVotes(voting::ReclaimVotes<Runtime>),
+    /// Replaces a proposal with its result once voting has ended
+    TallyVotes(voting::TallyVotes<Runtime>),
+    /// Registers a validator by bonding coins
+    RegisterValidator(authorities::RegisterValidator<money::Coin<0>, Runtime>),
+    /// Deregisters a validator and locks its bond for the unbonding period
+    DeregisterValidator(authorities::DeregisterValidator<Runtime>),
+    /// Returns deregistered validators' bonds once the unbonding period is over
+    WithdrawBond(authorities::WithdrawBond<Runtime>),
+    /// Slashes the bond of a validator that equivocated in GRANDPA
+    ReportEquivocation(authorities::ReportEquivocation<Runtime>),
+    /// Rotate the authority set at session boundaries via an inherent extrinsic.
+    RotateAuthorities(InherentAdapter<authorities::RotateAuthorities<Runtime>>),
+    /// Reward the block author via an inherent extrinsic.
+    Coinbase(InherentAdapter<coinbase::Coinbase<Runtime>>),
+    /// Claims matured block rewards as coins
+    ClaimReward(coinbase::ClaimReward<money::Coin<0>, Runtime>),
+    /// Note the proof-of-work difficulty for the next block via an inherent extrinsic.
+    AdjustDifficulty(InherentAdapter<difficulty::AdjustDifficulty<Runtime>>),
 }
 
 /// The main struct in this module.
 #[derive(Encode, Decode, PartialEq, Eq, Clone, TypeInfo)]
 pub struct Runtime;
 
-// Here we hard-code consensus authority IDs for the well-known identities that work with the CLI flags
-// Such as `--alice`, `--bob`, etc. Only Alice is enabled by default which makes things work nicely
-// in a `--dev` node. You may enable more authorities to test more interesting networks, or replace
-// these IDs entirely.
-impl Runtime {
-    /// Aura authority IDs
-    fn aura_authorities() -> Vec<AuraId> {
-        use hex_literal::hex;
-        use sp_application_crypto::ByteArray;
-
-        [
-            // Alice
-            hex!("d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d"),
-            // Bob
-            // hex!("8eaf04151687736326c9fea17e25fc5287613693c912909cb226aa4794f26a48"),
-            // Charlie
-            // hex!("90b5ab205c6974c9ea841be688864633dc9ca8a357843eeacf2314649965fe22"),
-            // Dave
-            // hex!("306721211d5404bd9da88e0204360a1a9ab8b87c66c1bc2fcdd37f3c2222cc20"),
-            // Eve
-            // hex!("e659a7a1628cdd93febc04a4e0646ea20e9f5f0ce097d9a05290d4a9e054df4e"),
-            // Ferdie
-            // hex!("1cbd2d43530a44705ad088af313e18f80b53ef16b36177cd4b77b846f2a5f07c"),
-        ]
-        .iter()
-        .map(|hex| AuraId::from_slice(hex.as_ref()).expect("Valid Aura authority hex was provided"))
-        .collect()
-    }
-
-    ///Grandpa Authority IDs - All equally weighted
-    fn grandpa_authorities() -> sp_consensus_grandpa::AuthorityList {
-        use hex_literal::hex;
-        use sp_application_crypto::ByteArray;
-
-        [
-            // Alice
-            hex!("88dc3417d5058ec4b4503e0c12ea1a0a89be200fe98922423d4334014fa6b0ee"),
-            // Bob
-            // hex!("d17c2d7823ebf260fd138f2d7e27d114c0145d968b5ff5006125f2414fadae69"),
-            // Charlie
-            // hex!("439660b36c6c03afafca027b910b4fecf99801834c62a5e6006f27d978de234f"),
-            // Dave
-            // hex!("5e639b43e0052c47447dac87d6fd2b6ec50bdd4d0f614e4299c665249bbd09d9"),
-            // Eve
-            // hex!("1dfe3e22cc0d45c70779c1095f7489a8ef3cf52d62fbd8c2fa38c9f1723502b5"),
-            // Ferdie
-            // hex!("568cb4a574c6d178feb39c27dfc8b3f789e5f5423e19c71633c748b9acf086b5"),
-        ]
-        .iter()
-        .map(|hex| {
-            (
-                GrandpaId::from_slice(hex.as_ref())
-                    .expect("Valid Grandpa authority hex was provided"),
-                1,
-            )
-        })
-        .collect()
-    }
-}
-
 impl_runtime_apis! {
     // https://substrate.dev/rustdocs/master/sp_api/trait.Core.html
     impl sp_api::Core<Block> for Runtime {
@@ -233,7 +431,7 @@ impl_runtime_apis! {
         }
 
         fn execute_block(block: Block) {
-            Executive::execute_block(block)
+            Executive::execute_block_checking_digest(block)
         }
 
         fn initialize_block(header: &<Block as BlockT>::Header) -> sp_runtime::ExtrinsicInclusionMode {
@@ -306,46 +504,99 @@ impl_runtime_apis! {
         }
 
         fn authorities() -> Vec<AuraId> {
-            Self::aura_authorities()
+            authorities::aura_authorities()
         }
     }
 
     impl sp_consensus_grandpa::GrandpaApi<Block> for Runtime {
         fn grandpa_authorities() -> sp_consensus_grandpa::AuthorityList {
-            Self::grandpa_authorities()
+            authorities::grandpa_authorities()
         }
 
         fn current_set_id() -> sp_consensus_grandpa::SetId {
-            0u64
+            authorities::current_set().set_id
         }
 
         fn submit_report_equivocation_unsigned_extrinsic(
-            _equivocation_proof: sp_consensus_grandpa::EquivocationProof<
+            equivocation_proof: sp_consensus_grandpa::EquivocationProof<
                 <Block as BlockT>::Hash,
                 sp_runtime::traits::NumberFor<Block>,
             >,
-            _key_owner_proof: sp_consensus_grandpa::OpaqueKeyOwnershipProof,
+            key_owner_proof: sp_consensus_grandpa::OpaqueKeyOwnershipProof,
         ) -> Option<()> {
-            None
+            use tuxedo_core::{
+                types::{Input, OutputRef, RedemptionStrategy},
+                Verifier,
+            };
+
+            // The key ownership proof is the location of the offender's bond.
+            let bond: OutputRef = key_owner_proof.decode()?;
+            let keys = authorities::reportable_set(equivocation_proof.set_id())?
+                .authorities
+                .into_iter()
+                .find(|keys| keys.grandpa == *equivocation_proof.offender())?;
+            let slash = authorities::Slash {
+                keys,
+                set_id: equivocation_proof.set_id(),
+            };
+
+            let report = Transaction {
+                inputs: vec![Input {
+                    output_ref: bond,
+                    redeemer: RedemptionStrategy::Eviction,
+                }],
+                peeks: Vec::new(),
+                outputs: vec![(slash, OuterVerifier::new_unspendable()?).into()],
+                checker: authorities::ReportEquivocation::new(equivocation_proof).into(),
+            };
+
+            sp_io::offchain::submit_transaction(report.encode()).ok()
         }
 
         fn generate_key_ownership_proof(
-            _set_id: sp_consensus_grandpa::SetId,
-            _authority_id: sp_consensus_grandpa::AuthorityId,
+            set_id: sp_consensus_grandpa::SetId,
+            authority_id: sp_consensus_grandpa::AuthorityId,
         ) -> Option<sp_consensus_grandpa::OpaqueKeyOwnershipProof> {
-            None
+            // Only equivocations in the active set or a recent one can be reported.
+            authorities::reportable_set(set_id)?;
+
+            let bond = authorities::find_bond(&authority_id)?;
+            Some(sp_consensus_grandpa::OpaqueKeyOwnershipProof::new(bond.encode()))
+        }
+    }
+
+    impl timestamp::TimestampApi<Block> for Runtime {
+        fn median_time_past() -> Option<u64> {
+            timestamp::median_time_past()
+        }
+    }
+
+    impl difficulty::DifficultyApi<Block> for Runtime {
+        fn difficulty() -> u128 {
+            difficulty::current_difficulty::<Runtime>()
         }
     }
 
     impl sp_genesis_builder::GenesisBuilder<Block> for Runtime {
         fn create_default_config() -> Vec<u8> {
-            serde_json::to_vec(&genesis::development_genesis_transactions())
-                .expect("Development genesis transactions are valid.")
+            // An empty config has no validators, so no chain built from it could author a block.
+            serde_json::to_vec(&genesis::RuntimeGenesisConfig::development())
+                .expect("The development genesis config is valid.")
         }
 
         fn build_config(config: Vec<u8>) -> sp_genesis_builder::Result {
-            let genesis_transactions = serde_json::from_slice::<Vec<Transaction>>(config.as_slice())
-                .map_err(|_| "The input JSON is not a valid list of Transactions.")?;
+            let genesis_transactions = genesis::genesis_transactions_from_json(&config)?;
+
+            // Genesis transactions are not checked, so the initial authority set is stored explicitly.
+            authorities::initialize_genesis_set::<money::Coin<0>, Runtime>(
+                genesis_transactions
+                    .iter()
+                    .flat_map(|tx| tx.outputs.iter())
+                    .filter_map(|output| {
+                        output.payload.extract::<authorities::ValidatorRegistration>().ok()
+                    }),
+            );
+
             TuxedoGenesisConfigBuilder::build(genesis_transactions)
         }
     }
@@ -50,7 +50,8 @@ pub enum Command {
     /// Demonstrate creating an amoeba and performing mitosis on it.
     AmoebaDemo,
 
-    /// Mint coins , optionally amount and publicKey of owner can be passed
+    /// Mint coins with a mint authority owned by a key in the keystore.
+    /// Optionally amount and publicKey of owner can be passed
     /// if amount is not passed , 100 coins are minted
     /// If publickKey of owner is not passed , then by default SHAWN_PUB_KEY is used.
     #[command(verbatim_doc_comment)]
@@ -105,11 +106,31 @@ pub enum Command {
     #[command(verbatim_doc_comment)]
     ShowBalance,
 
+    /// For each key tracked by the wallet, shows the sum of all token factory coins owned by
+    /// that key, separately for each asset.
+    #[command(verbatim_doc_comment)]
+    ShowAssetBalances,
+
     /// Show the complete list of UTXOs known to the wallet.
     ShowAllOutputs,
 
     /// Show the latest on-chain timestamp.
     ShowTimestamp,
+
+    /// Show all open offers to sell tokens for coins, cheapest first.
+    ShowOffers,
+
+    /// Lock some tokens into an offer to sell them for coins.
+    /// Any tokens not included in the offer are returned to the owner as change.
+    #[command(verbatim_doc_comment)]
+    MakeOffer(MakeOfferArgs),
+
+    /// Take all or part of an open offer, paying for the tokens with coins from the wallet.
+    #[command(verbatim_doc_comment)]
+    TakeOffer(TakeOfferArgs),
+
+    /// Show all kitties listed for sale in the marketplace, cheapest first.
+    ShowKittyListings,
 }
 
 #[derive(Debug, Args)]
@@ -123,6 +144,11 @@ pub struct MintCoinArgs {
     /// Hex encoded address (sr25519 pubkey) of the owner.
     #[arg(long, short, verbatim_doc_comment, value_parser = h256_from_string, default_value = SHAWN_PUB_KEY)]
     pub owner: H256,
+
+    /// A hex-encoded output reference of the mint authority to mint with.
+    /// When not specified, any mint authority owned by a key in the keystore is used.
+    #[arg(long, verbatim_doc_comment, value_parser = output_ref_from_string)]
+    pub authority: Option<OutputRef>,
 }
 
 #[derive(Debug, Args)]
@@ -149,3 +175,38 @@ pub struct SpendArgs {
     #[arg(long, short, verbatim_doc_comment, action = Append)]
     pub output_amount: Vec<u128>,
 }
+
+#[derive(Debug, Args)]
+pub struct MakeOfferArgs {
+    /// A token to be locked into the offer. This argument may be specified multiple times.
+    /// They must all be tokens, not coins.
+    #[arg(long, short, verbatim_doc_comment, value_parser = output_ref_from_string)]
+    pub input: Vec<OutputRef>,
+
+    /// The number of tokens to offer.
+    #[arg(long)]
+    pub offered: u128,
+
+    /// The number of coins asked in exchange for all of the offered tokens.
+    #[arg(long)]
+    pub asked: u128,
+
+    /// Hex encoded address (sr25519 pubkey) of the maker, who receives the payment and may cancel the offer.
+    #[arg(long, short, verbatim_doc_comment, value_parser = h256_from_string, default_value = SHAWN_PUB_KEY)]
+    pub owner: H256,
+}
+
+#[derive(Debug, Args)]
+pub struct TakeOfferArgs {
+    /// A hex-encoded output reference of the offer to take.
+    #[arg(value_parser = output_ref_from_string)]
+    pub offer: OutputRef,
+
+    /// The number of tokens to take. When not specified, the whole offer is taken.
+    #[arg(long, short)]
+    pub amount: Option<u128>,
+
+    /// Hex encoded address (sr25519 pubkey) of the recipient of the tokens and any change.
+    #[arg(long, short, verbatim_doc_comment, value_parser = h256_from_string, default_value = SHAWN_PUB_KEY)]
+    pub recipient: H256,
+}
@@ -0,0 +1,327 @@
+//! Wallet features related to the exchange between the template's second token and its main coin.
+//!
+//! Every offer the wallet sees during sync is recorded, regardless of who made it,
+//! so that users can browse the order book and take offers.
+
+use crate::{
+    cli::{MakeOfferArgs, TakeOfferArgs},
+    rpc::fetch_storage,
+    sync,
+};
+
+use anyhow::anyhow;
+use jsonrpsee::{core::client::ClientT, http_client::HttpClient, rpc_params};
+use parity_scale_codec::{Decode, Encode};
+use runtime::{
+    dex::{MakeOffer, TakeOffer},
+    money::Coin,
+    OuterConstraintChecker, OuterVerifier, OuterVerifierRedeemer, TokenOffer,
+};
+use sc_keystore::LocalKeystore;
+use sled::Db;
+use sp_core::sr25519::Public;
+use sp_runtime::traits::{BlakeTwo256, Hash};
+use tuxedo_core::{
+    types::{Input, Output, OutputRef, RedemptionStrategy, Transaction},
+    verifier::Sr25519Signature,
+    ConstraintChecker,
+};
+
+/// The identifier for the open offers tree in the db.
+/// output_ref => (offer:TokenOffer, maker:OuterVerifier)
+const OFFERS: &str = "offers";
+
+/// The identifier for the closed offers tree in the db.
+/// output_ref => (offer:TokenOffer, maker:OuterVerifier)
+const CLOSED_OFFERS: &str = "closed_offers";
+
+/// Apply a transaction to the local database, recording a new offer.
+pub(crate) fn apply_offer(
+    db: &Db,
+    output_ref: &OutputRef,
+    output: &Output<OuterVerifier>,
+) -> anyhow::Result<()> {
+    let offer = output.payload.extract::<TokenOffer>()?;
+    let offers_tree = db.open_tree(OFFERS)?;
+    offers_tree.insert(output_ref.encode(), (offer, &output.verifier).encode())?;
+    Ok(())
+}
+
+/// Mark an offer as closed, if it is known. This happens whether it was taken or cancelled.
+pub(crate) fn close_offer(db: &Db, output_ref: &OutputRef) -> anyhow::Result<()> {
+    move_offer(db, output_ref, OFFERS, CLOSED_OFFERS)
+}
+
+/// Mark an offer that was previously closed as open again.
+pub(crate) fn reopen_offer(db: &Db, output_ref: &OutputRef) -> anyhow::Result<()> {
+    move_offer(db, output_ref, CLOSED_OFFERS, OFFERS)
+}
+
+/// Drop all record of an open offer.
+pub(crate) fn remove_offer(db: &Db, output_ref: &OutputRef) -> anyhow::Result<()> {
+    let offers_tree = db.open_tree(OFFERS)?;
+    offers_tree.remove(output_ref.encode())?;
+    Ok(())
+}
+
+/// Move an offer's record from one tree to another, if it is present.
+fn move_offer(db: &Db, output_ref: &OutputRef, from: &str, to: &str) -> anyhow::Result<()> {
+    let from_tree = db.open_tree(from)?;
+    let to_tree = db.open_tree(to)?;
+
+    let Some(ivec) = from_tree.remove(output_ref.encode())? else {
+        return Ok(());
+    };
+    to_tree.insert(output_ref.encode(), ivec)?;
+
+    Ok(())
+}
+
+/// Print every open offer known to the wallet, cheapest first.
+pub(crate) fn print_offers(db: &Db) -> anyhow::Result<()> {
+    let offers_tree = db.open_tree(OFFERS)?;
+
+    let mut offers = Vec::new();
+    for raw_data in offers_tree.iter() {
+        let (output_ref_ivec, offer_ivec) = raw_data?;
+        let output_ref = OutputRef::decode(&mut &output_ref_ivec[..])?;
+        let (offer, maker) = <(TokenOffer, OuterVerifier)>::decode(&mut &offer_ivec[..])?;
+        offers.push((output_ref, offer, maker));
+    }
+
+    // Compare prices by cross multiplying to avoid rounding.
+    offers.sort_by(|(_, a, _), (_, b, _)| {
+        (a.asked.saturating_mul(b.offered)).cmp(&b.asked.saturating_mul(a.offered))
+    });
+
+    for (output_ref, offer, maker) in offers {
+        print!(
+            "{}: {} tokens for {} coins, ",
+            hex::encode(output_ref.encode()),
+            offer.offered,
+            offer.asked,
+        );
+        crate::pretty_print_verifier(&maker);
+    }
+
+    Ok(())
+}
+
+/// Create and send a transaction that locks some tokens into a new offer.
+pub async fn make_offer(
+    parachain: bool,
+    client: &HttpClient,
+    keystore: &LocalKeystore,
+    args: MakeOfferArgs,
+) -> anyhow::Result<()> {
+    if parachain {
+        make_offer_helper::<crate::ParachainConstraintChecker>(client, keystore, args).await
+    } else {
+        make_offer_helper::<crate::OuterConstraintChecker>(client, keystore, args).await
+    }
+}
+
+pub async fn make_offer_helper<Checker: ConstraintChecker + From<OuterConstraintChecker>>(
+    client: &HttpClient,
+    keystore: &LocalKeystore,
+    args: MakeOfferArgs,
+) -> anyhow::Result<()> {
+    log::debug!("The args are:: {:?}", args);
+
+    let maker = OuterVerifier::Sr25519Signature(Sr25519Signature {
+        owner_pubkey: args.owner,
+    });
+
+    // Make sure the inputs are tokens that can cover the offer.
+    let mut total_input_amount = 0;
+    for output_ref in &args.input {
+        let utxo = fetch_storage::<OuterVerifier>(output_ref, client).await?;
+        total_input_amount += utxo.payload.extract::<Coin<1>>()?.0;
+    }
+    if total_input_amount < args.offered {
+        return Err(anyhow!(
+            "Inputs are worth {total_input_amount} tokens, which is not enough to offer {}",
+            args.offered
+        ));
+    }
+
+    let mut outputs: Vec<Output<OuterVerifier>> =
+        vec![(TokenOffer::new(args.offered, args.asked), maker.clone()).into()];
+    if total_input_amount > args.offered {
+        outputs.push((Coin::<1>::new(total_input_amount - args.offered), maker).into());
+    }
+
+    let transaction: Transaction<OuterVerifier, Checker> = Transaction {
+        inputs: args
+            .input
+            .into_iter()
+            .map(|output_ref| Input {
+                output_ref,
+                redeemer: Default::default(),
+            })
+            .collect(),
+        peeks: Vec::new(),
+        outputs,
+        checker: OuterConstraintChecker::MakeOffer(MakeOffer::default()).into(),
+    };
+
+    let transaction = sign_and_send(transaction, client, keystore).await?;
+
+    let offer_ref = OutputRef {
+        tx_hash: <BlakeTwo256 as Hash>::hash_of(&transaction.encode()),
+        index: 0,
+    };
+    println!(
+        "Created offer {} selling {} tokens for {} coins.",
+        hex::encode(offer_ref.encode()),
+        args.offered,
+        args.asked
+    );
+
+    Ok(())
+}
+
+/// Create and send a transaction that takes all or part of an existing offer.
+pub async fn take_offer(
+    parachain: bool,
+    db: &Db,
+    client: &HttpClient,
+    keystore: &LocalKeystore,
+    args: TakeOfferArgs,
+) -> anyhow::Result<()> {
+    if parachain {
+        take_offer_helper::<crate::ParachainConstraintChecker>(db, client, keystore, args).await
+    } else {
+        take_offer_helper::<crate::OuterConstraintChecker>(db, client, keystore, args).await
+    }
+}
+
+pub async fn take_offer_helper<Checker: ConstraintChecker + From<OuterConstraintChecker>>(
+    db: &Db,
+    client: &HttpClient,
+    keystore: &LocalKeystore,
+    args: TakeOfferArgs,
+) -> anyhow::Result<()> {
+    log::debug!("The args are:: {:?}", args);
+
+    // Look up the offer in the node's storage to make sure it is still open.
+    let offer_utxo = fetch_storage::<OuterVerifier>(&args.offer, client).await?;
+    let offer = offer_utxo.payload.extract::<TokenOffer>()?;
+    let maker = offer_utxo.verifier;
+
+    let amount = args.amount.unwrap_or(offer.offered);
+    if amount == 0 || amount > offer.offered {
+        return Err(anyhow!(
+            "Can only take between 1 and {} tokens from this offer",
+            offer.offered
+        ));
+    }
+    let price = offer
+        .price_of(amount)
+        .map_err(|e| anyhow!("Could not determine price: {e:?}"))?;
+
+    // The price is rounded up, so a partial fill may leave a residual offer that asks for nothing,
+    // which the chain rejects.
+    if amount < offer.offered && price >= offer.asked {
+        return Err(anyhow!(
+            "Taking {amount} tokens would cost the full price of {price} coins; take all {} tokens instead",
+            offer.offered
+        ));
+    }
+
+    // Pay the maker, and leave the rest of the offer for someone else.
+    let recipient = OuterVerifier::Sr25519Signature(Sr25519Signature {
+        owner_pubkey: args.recipient,
+    });
+    let mut outputs: Vec<Output<OuterVerifier>> =
+        vec![(Coin::<0>::new(price), maker.clone()).into()];
+    if amount < offer.offered {
+        outputs.push(
+            (
+                TokenOffer::new(offer.offered - amount, offer.asked - price),
+                maker,
+            )
+                .into(),
+        );
+    }
+    outputs.push((Coin::<1>::new(amount), recipient.clone()).into());
+
+    // Choose coins from the local db to pay with, and return any change to the recipient.
+    let coin_refs = sync::get_arbitrary_unspent_set(db, price)?
+        .ok_or(anyhow!("Not enough value in database to pay {price} coins"))?;
+    let mut total_input_amount = 0;
+    for output_ref in &coin_refs {
+        let (_owner_pubkey, amount) = sync::get_unspent(db, output_ref)?
+            .ok_or(anyhow!("selected coin is missing from local database"))?;
+        total_input_amount += amount;
+    }
+    if total_input_amount > price {
+        outputs.push((Coin::<0>::new(total_input_amount - price), recipient).into());
+    }
+
+    let transaction: Transaction<OuterVerifier, Checker> = Transaction {
+        inputs: coin_refs
+            .into_iter()
+            .map(|output_ref| Input {
+                output_ref,
+                redeemer: Default::default(),
+            })
+            .chain(std::iter::once(Input {
+                output_ref: args.offer,
+                redeemer: RedemptionStrategy::Eviction,
+            }))
+            .collect(),
+        peeks: Vec::new(),
+        outputs,
+        checker: OuterConstraintChecker::TakeOffer(TakeOffer::default()).into(),
+    };
+
+    let transaction = sign_and_send(transaction, client, keystore).await?;
+
+    let tx_hash = <BlakeTwo256 as Hash>::hash_of(&transaction.encode());
+    println!("Took {amount} tokens for {price} coins in transaction {tx_hash:?}.");
+
+    Ok(())
+}
+
+/// Sign every redeemed input of the transaction with the keystore and send it to the node.
+///
+/// Evicted inputs, such as a taken offer, do not need a signature.
+async fn sign_and_send<Checker: ConstraintChecker>(
+    mut transaction: Transaction<OuterVerifier, Checker>,
+    client: &HttpClient,
+    keystore: &LocalKeystore,
+) -> anyhow::Result<Transaction<OuterVerifier, Checker>> {
+    // Keep a copy of the stripped encoded transaction for signing purposes.
+    // All redeemers are stripped, including evictions, just like the executive does.
+    let mut stripped = transaction.clone();
+    for input in &mut stripped.inputs {
+        input.redeemer = Default::default();
+    }
+    let stripped_encoded_transaction = stripped.encode();
+
+    for input in &mut transaction.inputs {
+        if input.redeemer == RedemptionStrategy::Eviction {
+            continue;
+        }
+        let utxo = fetch_storage::<OuterVerifier>(&input.output_ref, client).await?;
+
+        let redeemer = match utxo.verifier {
+            OuterVerifier::Sr25519Signature(Sr25519Signature { owner_pubkey }) => {
+                let public = Public::from_h256(owner_pubkey);
+                let signature =
+                    crate::keystore::sign_with(keystore, &public, &stripped_encoded_transaction)?;
+                OuterVerifierRedeemer::Sr25519Signature(signature)
+            }
+            OuterVerifier::UpForGrabs(_) => OuterVerifierRedeemer::UpForGrabs(()),
+            _ => return Err(anyhow!("Can only sign for inputs owned by a single key")),
+        };
+        input.redeemer = RedemptionStrategy::Redemption(redeemer.encode());
+    }
+
+    let params = rpc_params![hex::encode(transaction.encode())];
+    let response: Result<String, _> = client.request("author_submitExtrinsic", params).await;
+    log::info!("Node's response to exchange transaction: {:?}", response);
+
+    Ok(transaction)
+}
@@ -0,0 +1,85 @@
+//! Wallet features related to the kitty marketplace.
+//!
+//! Every listing the wallet sees during sync is recorded, regardless of who the seller is,
+//! so that users can browse the kitties that are for sale.
+
+use parity_scale_codec::{Decode, Encode};
+use runtime::{kitty_market::ListedKitty, OuterVerifier};
+use sled::Db;
+use tuxedo_core::types::{Output, OutputRef};
+
+/// The identifier for the open listings tree in the db.
+/// output_ref => (listing:ListedKitty, seller:OuterVerifier)
+const KITTY_LISTINGS: &str = "kitty_listings";
+
+/// The identifier for the closed listings tree in the db.
+/// output_ref => (listing:ListedKitty, seller:OuterVerifier)
+const CLOSED_KITTY_LISTINGS: &str = "closed_kitty_listings";
+
+/// Apply a transaction to the local database, recording a new listing.
+pub(crate) fn apply_listing(
+    db: &Db,
+    output_ref: &OutputRef,
+    output: &Output<OuterVerifier>,
+) -> anyhow::Result<()> {
+    let listing = output.payload.extract::<ListedKitty>()?;
+    let listings_tree = db.open_tree(KITTY_LISTINGS)?;
+    listings_tree.insert(output_ref.encode(), (listing, &output.verifier).encode())?;
+    Ok(())
+}
+
+/// Mark a listing as closed, if it is known. This happens whether the kitty was bought or delisted.
+pub(crate) fn close_listing(db: &Db, output_ref: &OutputRef) -> anyhow::Result<()> {
+    move_listing(db, output_ref, KITTY_LISTINGS, CLOSED_KITTY_LISTINGS)
+}
+
+/// Mark a listing that was previously closed as open again.
+pub(crate) fn reopen_listing(db: &Db, output_ref: &OutputRef) -> anyhow::Result<()> {
+    move_listing(db, output_ref, CLOSED_KITTY_LISTINGS, KITTY_LISTINGS)
+}
+
+/// Drop all record of an open listing.
+pub(crate) fn remove_listing(db: &Db, output_ref: &OutputRef) -> anyhow::Result<()> {
+    let listings_tree = db.open_tree(KITTY_LISTINGS)?;
+    listings_tree.remove(output_ref.encode())?;
+    Ok(())
+}
+
+/// Move a listing's record from one tree to another, if it is present.
+fn move_listing(db: &Db, output_ref: &OutputRef, from: &str, to: &str) -> anyhow::Result<()> {
+    let from_tree = db.open_tree(from)?;
+    let to_tree = db.open_tree(to)?;
+
+    let Some(ivec) = from_tree.remove(output_ref.encode())? else {
+        return Ok(());
+    };
+    to_tree.insert(output_ref.encode(), ivec)?;
+
+    Ok(())
+}
+
+/// Print every open listing known to the wallet, cheapest first.
+pub(crate) fn print_listings(db: &Db) -> anyhow::Result<()> {
+    let listings_tree = db.open_tree(KITTY_LISTINGS)?;
+
+    let mut listings = Vec::new();
+    for raw_data in listings_tree.iter() {
+        let (output_ref_ivec, listing_ivec) = raw_data?;
+        let output_ref = OutputRef::decode(&mut &output_ref_ivec[..])?;
+        let (listing, seller) = <(ListedKitty, OuterVerifier)>::decode(&mut &listing_ivec[..])?;
+        listings.push((output_ref, listing, seller));
+    }
+    listings.sort_by_key(|(_, listing, _)| listing.price);
+
+    for (output_ref, listing, seller) in listings {
+        print!(
+            "{}: kitty with dna {:?} for {} coins, ",
+            hex::encode(output_ref.encode()),
+            listing.kitty.dna.0,
+            listing.price,
+        );
+        crate::pretty_print_verifier(&seller);
+    }
+
+    Ok(())
+}
@@ -10,12 +10,15 @@ use tuxedo_core::{types::OutputRef, verifier::*};
 
 mod amoeba;
 mod cli;
+mod dex;
 mod keystore;
+mod kitty_market;
 mod money;
 mod parachain;
 mod rpc;
 mod sync;
 mod timestamp;
+mod token_factory;
 
 use cli::{Cli, Command};
 use parachain::ParachainConstraintChecker;
@@ -119,7 +122,7 @@ async fn main() -> anyhow::Result<()> {
         Some(Command::AmoebaDemo) => amoeba::amoeba_demo(metadata.is_parachain(), &client).await,
         // Command::MultiSigDemo => multi_sig::multi_sig_demo(&client).await,
         Some(Command::MintCoins(args)) => {
-            money::mint_coins(metadata.is_parachain(), &client, args).await
+            money::mint_coins(metadata.is_parachain(), &db, &client, &keystore, args).await
         }
         Some(Command::VerifyCoin { output_ref }) => {
             println!("Details of coin {}:", hex::encode(output_ref.encode()));
@@ -185,6 +188,24 @@ async fn main() -> anyhow::Result<()> {
 
             Ok(())
         }
+        Some(Command::ShowOffers) => {
+            println!("###### Open offers ###########");
+            dex::print_offers(&db)
+        }
+        Some(Command::MakeOffer(args)) => {
+            dex::make_offer(metadata.is_parachain(), &client, &keystore, args).await
+        }
+        Some(Command::TakeOffer(args)) => {
+            dex::take_offer(metadata.is_parachain(), &db, &client, &keystore, args).await
+        }
+        Some(Command::ShowKittyListings) => {
+            println!("###### Kitties for sale ###########");
+            kitty_market::print_listings(&db)
+        }
+        Some(Command::ShowAssetBalances) => {
+            println!("Asset Balance Summary");
+            token_factory::print_balances(&db)
+        }
         Some(Command::ShowAllOutputs) => {
             println!("###### Unspent outputs ###########");
             sync::print_unspent_tree(&db)?;
@@ -287,5 +308,8 @@ pub fn pretty_print_verifier(v: &OuterVerifier) {
                 string_sigs, multi_sig.threshold
             );
         }
+        OuterVerifier::PayToVerifierHash(p2vh) => {
+            println!("locked behind verifier hash {}", p2vh.verifier_hash)
+        }
     }
 }
@@ -4,9 +4,9 @@ use crate::{cli::MintCoinArgs, cli::SpendArgs, rpc::fetch_storage, sync};
 
 use anyhow::anyhow;
 use jsonrpsee::{core::client::ClientT, http_client::HttpClient, rpc_params};
-use parity_scale_codec::Encode;
+use parity_scale_codec::{Decode, Encode};
 use runtime::{
-    money::{Coin, MoneyConstraintChecker},
+    money::{Coin, MintAuthority, MoneyConstraintChecker},
     OuterConstraintChecker, OuterVerifier, OuterVerifierRedeemer,
 };
 use sc_keystore::LocalKeystore;
@@ -19,38 +19,92 @@ use tuxedo_core::{
     ConstraintChecker,
 };
 
+/// The identifier for the open mint authorities tree in the db.
+/// output_ref => owner_pubkey:H256
+const MINT_AUTHORITIES: &str = "mint_authorities";
+
+/// The identifier for the spent mint authorities tree in the db.
+/// output_ref => owner_pubkey:H256
+const SPENT_MINT_AUTHORITIES: &str = "spent_mint_authorities";
+
 /// Create and send a transaction that mints the coins on the network
 pub async fn mint_coins(
     parachain: bool,
+    db: &Db,
     client: &HttpClient,
+    keystore: &LocalKeystore,
     args: MintCoinArgs,
 ) -> anyhow::Result<()> {
     if parachain {
-        mint_coins_helper::<crate::ParachainConstraintChecker>(client, args).await
+        mint_coins_helper::<crate::ParachainConstraintChecker>(db, client, keystore, args).await
     } else {
-        mint_coins_helper::<crate::OuterConstraintChecker>(client, args).await
+        mint_coins_helper::<crate::OuterConstraintChecker>(db, client, keystore, args).await
     }
 }
 
 pub async fn mint_coins_helper<Checker: ConstraintChecker + From<OuterConstraintChecker>>(
+    db: &Db,
     client: &HttpClient,
+    keystore: &LocalKeystore,
     args: MintCoinArgs,
 ) -> anyhow::Result<()> {
     log::debug!("The args are:: {:?}", args);
 
-    let transaction: tuxedo_core::types::Transaction<OuterVerifier, Checker> = Transaction {
-        inputs: Vec::new(),
+    // Minting requires consuming a mint authority that one of our keys owns.
+    let authority_ref = match args.authority {
+        Some(output_ref) => output_ref,
+        None => get_mint_authority(db)?
+            .ok_or(anyhow!("No mint authority owned by a key in the keystore"))?,
+    };
+    let authority_utxo = fetch_storage::<OuterVerifier>(&authority_ref, client).await?;
+    let mut authority = authority_utxo.payload.extract::<MintAuthority<0>>()?;
+    authority.minted = authority.minted.checked_add(args.amount).ok_or(anyhow!(
+        "Minting {} coins overflows the minted total",
+        args.amount
+    ))?;
+    if authority
+        .supply_cap
+        .is_some_and(|cap| authority.minted > cap)
+    {
+        return Err(anyhow!(
+            "Minting {} coins would exceed the authority's supply cap",
+            args.amount
+        ));
+    }
+
+    let mut transaction: tuxedo_core::types::Transaction<OuterVerifier, Checker> = Transaction {
+        inputs: vec![Input {
+            output_ref: authority_ref,
+            redeemer: Default::default(),
+        }],
         peeks: Vec::new(),
-        outputs: vec![(
-            Coin::<0>::new(args.amount),
-            OuterVerifier::Sr25519Signature(Sr25519Signature {
-                owner_pubkey: args.owner,
-            }),
-        )
-            .into()],
-        checker: OuterConstraintChecker::Money(MoneyConstraintChecker::Mint).into(),
+        outputs: vec![
+            (authority, authority_utxo.verifier.clone()).into(),
+            (
+                Coin::<0>::new(args.amount),
+                OuterVerifier::Sr25519Signature(Sr25519Signature {
+                    owner_pubkey: args.owner,
+                }),
+            )
+                .into(),
+        ],
+        checker: OuterConstraintChecker::Money(MoneyConstraintChecker::AuthorizedMint).into(),
     };
 
+    // Sign for the authority with the key that owns it.
+    let stripped_encoded_transaction = transaction.clone().encode();
+    let OuterVerifier::Sr25519Signature(Sr25519Signature { owner_pubkey }) =
+        authority_utxo.verifier
+    else {
+        return Err(anyhow!(
+            "Can only mint with an authority owned by a single key"
+        ));
+    };
+    let public = Public::from_h256(owner_pubkey);
+    let signature = crate::keystore::sign_with(keystore, &public, &stripped_encoded_transaction)?;
+    transaction.inputs[0].redeemer =
+        RedemptionStrategy::Redemption(OuterVerifierRedeemer::Sr25519Signature(signature).encode());
+
     let encoded_tx = hex::encode(transaction.encode());
     let params = rpc_params![encoded_tx];
     let _spawn_response: Result<String, _> = client.request("author_submitExtrinsic", params).await;
@@ -62,9 +116,9 @@ pub async fn mint_coins_helper<Checker: ConstraintChecker + From<OuterConstraint
 
     let minted_coin_ref = OutputRef {
         tx_hash: <BlakeTwo256 as Hash>::hash_of(&transaction.encode()),
-        index: 0,
+        index: 1,
     };
-    let output = &transaction.outputs[0];
+    let output = &transaction.outputs[1];
     let amount = output.payload.extract::<Coin<0>>()?.0;
     print!(
         "Minted {:?} worth {amount}. ",
@@ -173,7 +227,12 @@ pub async fn spend_coins_helper<Checker: ConstraintChecker + From<OuterConstrain
                 OuterVerifierRedeemer::Sr25519Signature(signature)
             }
             OuterVerifier::UpForGrabs(_) => OuterVerifierRedeemer::UpForGrabs(()),
-            OuterVerifier::ThresholdMultiSignature(_) => todo!(),
+            OuterVerifier::ThresholdMultiSignature(_) | OuterVerifier::PayToVerifierHash(_) => {
+                return Err(anyhow!(
+                    "Can only sign for inputs owned by a single key, but {:?} is not",
+                    input.output_ref
+                ))
+            }
         };
 
         // insert the proof
@@ -243,3 +302,63 @@ pub(crate) fn apply_transaction(
         _ => Err(anyhow!("{:?}", ())),
     }
 }
+
+/// Apply a transaction to the local database, recording a mint authority that one of our keys owns.
+pub(crate) fn apply_mint_authority(
+    db: &Db,
+    output_ref: &OutputRef,
+    output: &Output<OuterVerifier>,
+) -> anyhow::Result<()> {
+    let OuterVerifier::Sr25519Signature(Sr25519Signature { owner_pubkey }) = output.verifier else {
+        return Ok(());
+    };
+    let authorities_tree = db.open_tree(MINT_AUTHORITIES)?;
+    authorities_tree.insert(output_ref.encode(), owner_pubkey.encode())?;
+    Ok(())
+}
+
+/// Mark a mint authority as spent, if the wallet is tracking it.
+pub(crate) fn spend_mint_authority(db: &Db, output_ref: &OutputRef) -> anyhow::Result<()> {
+    move_mint_authority(db, output_ref, MINT_AUTHORITIES, SPENT_MINT_AUTHORITIES)
+}
+
+/// Mark a mint authority that was previously spent back as unspent.
+pub(crate) fn unspend_mint_authority(db: &Db, output_ref: &OutputRef) -> anyhow::Result<()> {
+    move_mint_authority(db, output_ref, SPENT_MINT_AUTHORITIES, MINT_AUTHORITIES)
+}
+
+/// Drop all record of an unspent mint authority.
+pub(crate) fn remove_mint_authority(db: &Db, output_ref: &OutputRef) -> anyhow::Result<()> {
+    let authorities_tree = db.open_tree(MINT_AUTHORITIES)?;
+    authorities_tree.remove(output_ref.encode())?;
+    Ok(())
+}
+
+/// Move a mint authority's record from one tree to another, if it is present.
+fn move_mint_authority(
+    db: &Db,
+    output_ref: &OutputRef,
+    from: &str,
+    to: &str,
+) -> anyhow::Result<()> {
+    let from_tree = db.open_tree(from)?;
+    let to_tree = db.open_tree(to)?;
+
+    let Some(ivec) = from_tree.remove(output_ref.encode())? else {
+        return Ok(());
+    };
+    to_tree.insert(output_ref.encode(), ivec)?;
+
+    Ok(())
+}
+
+/// Get any unspent mint authority that one of our keys owns.
+fn get_mint_authority(db: &Db) -> anyhow::Result<Option<OutputRef>> {
+    let authorities_tree = db.open_tree(MINT_AUTHORITIES)?;
+    let Some(raw_data) = authorities_tree.iter().next() else {
+        return Ok(None);
+    };
+    let (output_ref_ivec, _owner_ivec) = raw_data?;
+
+    Ok(Some(OutputRef::decode(&mut &output_ref_ivec[..])?))
+}
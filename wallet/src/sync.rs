@@ -30,7 +30,13 @@ use tuxedo_core::{
 };
 
 use jsonrpsee::http_client::HttpClient;
-use runtime::{money::Coin, timestamp::Timestamp, Block, OuterVerifier};
+use runtime::{
+    kitty_market::ListedKitty,
+    money::{Coin, MintAuthority},
+    timestamp::Timestamp,
+    token_factory::{AssetCoin, AssetDefinition},
+    Block, OuterVerifier, TokenOffer,
+};
 
 /// The identifier for the blocks tree in the db.
 const BLOCKS: &str = "blocks";
@@ -281,16 +287,52 @@ async fn apply_transaction<F: Fn(&OuterVerifier) -> bool, C: ConstraintChecker>(
 
     // Insert all new outputs
     for (index, output) in tx.outputs.iter().enumerate() {
-        // For now the wallet only supports simple coins and timestamp
+        // For now the wallet only supports simple coins, mint authorities, timestamp, token
+        // factory assets, offers, and kitty listings
         match output.payload.type_id {
             Coin::<0>::TYPE_ID => {
                 if filter(&output.verifier) {
                     crate::money::apply_transaction(db, tx_hash, index as u32, output)?;
                 }
             }
+            MintAuthority::<0>::TYPE_ID => {
+                if filter(&output.verifier) {
+                    let output_ref = OutputRef {
+                        tx_hash,
+                        index: index as u32,
+                    };
+                    crate::money::apply_mint_authority(db, &output_ref, output)?;
+                }
+            }
             Timestamp::TYPE_ID => {
                 crate::timestamp::apply_transaction(db, output)?;
             }
+            TokenOffer::TYPE_ID => {
+                let output_ref = OutputRef {
+                    tx_hash,
+                    index: index as u32,
+                };
+                crate::dex::apply_offer(db, &output_ref, output)?;
+            }
+            ListedKitty::TYPE_ID => {
+                let output_ref = OutputRef {
+                    tx_hash,
+                    index: index as u32,
+                };
+                crate::kitty_market::apply_listing(db, &output_ref, output)?;
+            }
+            AssetDefinition::TYPE_ID => {
+                crate::token_factory::apply_definition(db, output)?;
+            }
+            AssetCoin::TYPE_ID => {
+                if filter(&output.verifier) {
+                    let output_ref = OutputRef {
+                        tx_hash,
+                        index: index as u32,
+                    };
+                    crate::token_factory::apply_coin(db, &output_ref, output)?;
+                }
+            }
             _ => continue,
         }
     }
@@ -319,6 +361,11 @@ pub(crate) fn add_unspent_output(
 
 /// Remove an output from the database updating all tables.
 fn remove_unspent_output(db: &Db, output_ref: &OutputRef) -> anyhow::Result<()> {
+    crate::money::remove_mint_authority(db, output_ref)?;
+    crate::token_factory::remove_coin(db, output_ref)?;
+    crate::dex::remove_offer(db, output_ref)?;
+    crate::kitty_market::remove_listing(db, output_ref)?;
+
     let unspent_tree = db.open_tree(UNSPENT)?;
 
     unspent_tree.remove(output_ref.encode())?;
@@ -329,6 +376,11 @@ fn remove_unspent_output(db: &Db, output_ref: &OutputRef) -> anyhow::Result<()>
 /// Mark an existing output as spent. This does not purge all record of the output from the db.
 /// It just moves the record from the unspent table to the spent table
 fn spend_output(db: &Db, output_ref: &OutputRef) -> anyhow::Result<()> {
+    crate::money::spend_mint_authority(db, output_ref)?;
+    crate::token_factory::spend_coin(db, output_ref)?;
+    crate::dex::close_offer(db, output_ref)?;
+    crate::kitty_market::close_listing(db, output_ref)?;
+
     let unspent_tree = db.open_tree(UNSPENT)?;
     let spent_tree = db.open_tree(SPENT)?;
 
@@ -343,6 +395,11 @@ fn spend_output(db: &Db, output_ref: &OutputRef) -> anyhow::Result<()> {
 
 /// Mark an output that was previously spent back as unspent.
 fn unspend_output(db: &Db, output_ref: &OutputRef) -> anyhow::Result<()> {
+    crate::money::unspend_mint_authority(db, output_ref)?;
+    crate::token_factory::unspend_coin(db, output_ref)?;
+    crate::dex::reopen_offer(db, output_ref)?;
+    crate::kitty_market::reopen_listing(db, output_ref)?;
+
     let unspent_tree = db.open_tree(UNSPENT)?;
     let spent_tree = db.open_tree(SPENT)?;
 
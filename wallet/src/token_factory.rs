@@ -0,0 +1,143 @@
+//! Wallet features related to assets issued through the token factory.
+//!
+//! The wallet tracks the definition of every asset it sees, regardless of who owns it,
+//! so that balances can be shown with the asset's name and decimals. Coins are only
+//! tracked when they are owned by a key in the keystore.
+
+use std::collections::BTreeMap;
+
+use anyhow::anyhow;
+use parity_scale_codec::{Decode, Encode};
+use runtime::{
+    token_factory::{AssetCoin, AssetDefinition},
+    OuterVerifier,
+};
+use sled::Db;
+use sp_core::H256;
+use tuxedo_core::{
+    types::{Output, OutputRef},
+    verifier::Sr25519Signature,
+};
+
+/// The identifier for the asset definitions tree in the db.
+/// asset_id:H256 => definition:AssetDefinition
+const ASSET_DEFINITIONS: &str = "asset_definitions";
+
+/// The identifier for the unspent asset coins tree in the db.
+/// output_ref => (owner_pubkey:H256, coin:AssetCoin)
+const UNSPENT_ASSET_COINS: &str = "unspent_asset_coins";
+
+/// The identifier for the spent asset coins tree in the db.
+/// output_ref => (owner_pubkey:H256, coin:AssetCoin)
+const SPENT_ASSET_COINS: &str = "spent_asset_coins";
+
+/// Apply a transaction to the local database, recording a new asset definition.
+///
+/// Definitions are never removed, even if the block that issued them is reverted.
+/// They are only used to look up names and decimals for display.
+pub(crate) fn apply_definition(db: &Db, output: &Output<OuterVerifier>) -> anyhow::Result<()> {
+    let definition = output.payload.extract::<AssetDefinition>()?;
+    let asset_id = definition.asset_id(&output.verifier);
+    let definitions_tree = db.open_tree(ASSET_DEFINITIONS)?;
+    definitions_tree.insert(asset_id.encode(), definition.encode())?;
+    Ok(())
+}
+
+/// Apply a transaction to the local database, storing a new asset coin.
+pub(crate) fn apply_coin(
+    db: &Db,
+    output_ref: &OutputRef,
+    output: &Output<OuterVerifier>,
+) -> anyhow::Result<()> {
+    let coin = output.payload.extract::<AssetCoin>()?;
+    let OuterVerifier::Sr25519Signature(Sr25519Signature { owner_pubkey }) = output.verifier else {
+        return Ok(());
+    };
+    let unspent_tree = db.open_tree(UNSPENT_ASSET_COINS)?;
+    unspent_tree.insert(output_ref.encode(), (owner_pubkey, coin).encode())?;
+    Ok(())
+}
+
+/// Mark an asset coin as spent, if the wallet is tracking it.
+pub(crate) fn spend_coin(db: &Db, output_ref: &OutputRef) -> anyhow::Result<()> {
+    move_coin(db, output_ref, UNSPENT_ASSET_COINS, SPENT_ASSET_COINS)
+}
+
+/// Mark an asset coin that was previously spent back as unspent.
+pub(crate) fn unspend_coin(db: &Db, output_ref: &OutputRef) -> anyhow::Result<()> {
+    move_coin(db, output_ref, SPENT_ASSET_COINS, UNSPENT_ASSET_COINS)
+}
+
+/// Drop all record of an unspent asset coin.
+pub(crate) fn remove_coin(db: &Db, output_ref: &OutputRef) -> anyhow::Result<()> {
+    let unspent_tree = db.open_tree(UNSPENT_ASSET_COINS)?;
+    unspent_tree.remove(output_ref.encode())?;
+    Ok(())
+}
+
+/// Move a coin's record from one tree to another, if it is present.
+fn move_coin(db: &Db, output_ref: &OutputRef, from: &str, to: &str) -> anyhow::Result<()> {
+    let from_tree = db.open_tree(from)?;
+    let to_tree = db.open_tree(to)?;
+
+    let Some(ivec) = from_tree.remove(output_ref.encode())? else {
+        return Ok(());
+    };
+    to_tree.insert(output_ref.encode(), ivec)?;
+
+    Ok(())
+}
+
+/// Look up the definition of an asset that the wallet has seen.
+fn get_definition(db: &Db, asset_id: &H256) -> anyhow::Result<Option<AssetDefinition>> {
+    let definitions_tree = db.open_tree(ASSET_DEFINITIONS)?;
+    let Some(ivec) = definitions_tree.get(asset_id.encode())? else {
+        return Ok(None);
+    };
+    Ok(Some(AssetDefinition::decode(&mut &ivec[..])?))
+}
+
+/// Iterate the unspent asset coins summing their values per owner and per asset.
+pub(crate) fn get_balances(db: &Db) -> anyhow::Result<BTreeMap<(H256, H256), u128>> {
+    let mut balances = BTreeMap::<(H256, H256), u128>::new();
+
+    let unspent_tree = db.open_tree(UNSPENT_ASSET_COINS)?;
+    for raw_data in unspent_tree.iter() {
+        let (_output_ref_ivec, owner_coin_ivec) = raw_data?;
+        let (owner, coin) = <(H256, AssetCoin)>::decode(&mut &owner_coin_ivec[..])?;
+
+        let balance = balances.entry((owner, coin.asset_id)).or_default();
+        *balance = balance.checked_add(coin.amount).ok_or(anyhow!(
+            "Balance of asset {:?} owned by {owner:?} overflows",
+            coin.asset_id
+        ))?;
+    }
+
+    Ok(balances)
+}
+
+/// Print the multi-asset balance of each key tracked by the wallet.
+pub(crate) fn print_balances(db: &Db) -> anyhow::Result<()> {
+    for ((owner, asset_id), amount) in get_balances(db)? {
+        match get_definition(db, &asset_id)? {
+            Some(definition) => println!(
+                "{owner}: {} {}",
+                format_amount(amount, definition.decimals),
+                String::from_utf8_lossy(&definition.name),
+            ),
+            None => println!("{owner}: {amount} of unknown asset {asset_id:?}"),
+        }
+    }
+
+    Ok(())
+}
+
+/// Format an amount in an asset's smallest unit as a decimal number.
+fn format_amount(amount: u128, decimals: u8) -> String {
+    if decimals == 0 {
+        return amount.to_string();
+    }
+    let digits = format!("{amount:0>width$}", width = decimals as usize + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals as usize);
+    format!("{whole}.{fraction}")
+}
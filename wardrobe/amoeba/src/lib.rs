@@ -15,8 +15,9 @@ use scale_info::TypeInfo;
 use serde::{Deserialize, Serialize};
 use sp_runtime::transaction_validity::TransactionPriority;
 use tuxedo_core::{
+    constraint_checker::BadlyTypedData,
     dynamic_typing::{DynamicallyTypedData, UtxoData},
-    ensure, SimpleConstraintChecker,
+    ensure, TypedConstraintChecker,
 };
 
 #[cfg(test)]
@@ -75,6 +76,15 @@ pub enum ConstraintCheckerError {
     WrongGeneration,
 }
 
+impl From<BadlyTypedData> for ConstraintCheckerError {
+    fn from(e: BadlyTypedData) -> Self {
+        match e {
+            BadlyTypedData::Output => Self::BadlyTypedOutput,
+            _ => Self::BadlyTypedInput,
+        }
+    }
+}
+
 /// A constraint checker for the process of amoeba mitosis
 /// The mitosis is valid is the following criteria are met
 /// 1. There is exactly one mother amoeba.
@@ -83,15 +93,16 @@ pub enum ConstraintCheckerError {
 #[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, TypeInfo)]
 pub struct AmoebaMitosis;
 
-impl SimpleConstraintChecker for AmoebaMitosis {
+impl TypedConstraintChecker for AmoebaMitosis {
+    type Payload = AmoebaDetails;
     type Error = ConstraintCheckerError;
 
     fn check(
         &self,
-        input_data: &[DynamicallyTypedData],
-        evicted_input_data: &[DynamicallyTypedData],
+        input_data: &[AmoebaDetails],
+        evicted_input_data: &[AmoebaDetails],
         _peeks: &[DynamicallyTypedData],
-        output_data: &[DynamicallyTypedData],
+        output_data: &[AmoebaDetails],
     ) -> Result<TransactionPriority, ConstraintCheckerError> {
         // Can't evict anything
         ensure!(
@@ -104,21 +115,15 @@ impl SimpleConstraintChecker for AmoebaMitosis {
             input_data.len() == 1,
             ConstraintCheckerError::WrongNumberOfMothers
         );
-        let mother = input_data[0]
-            .extract::<AmoebaDetails>()
-            .map_err(|_| ConstraintCheckerError::BadlyTypedInput)?;
+        let mother = &input_data[0];
 
         // Make sure there are exactly two daughters.
         ensure!(
             output_data.len() == 2,
             ConstraintCheckerError::WrongNumberOfDaughters
         );
-        let first_daughter = output_data[0]
-            .extract::<AmoebaDetails>()
-            .map_err(|_| ConstraintCheckerError::BadlyTypedOutput)?;
-        let second_daughter = output_data[1]
-            .extract::<AmoebaDetails>()
-            .map_err(|_| ConstraintCheckerError::BadlyTypedOutput)?;
+        let first_daughter = &output_data[0];
+        let second_daughter = &output_data[1];
 
         // Make sure the generations are correct
         ensure!(
@@ -148,15 +153,16 @@ impl SimpleConstraintChecker for AmoebaMitosis {
 #[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, TypeInfo)]
 pub struct AmoebaDeath;
 
-impl SimpleConstraintChecker for AmoebaDeath {
+impl TypedConstraintChecker for AmoebaDeath {
+    type Payload = AmoebaDetails;
     type Error = ConstraintCheckerError;
 
     fn check(
         &self,
-        input_data: &[DynamicallyTypedData],
-        evicted_input_data: &[DynamicallyTypedData],
+        input_data: &[AmoebaDetails],
+        evicted_input_data: &[AmoebaDetails],
         _peeks: &[DynamicallyTypedData],
-        output_data: &[DynamicallyTypedData],
+        output_data: &[AmoebaDetails],
     ) -> Result<TransactionPriority, Self::Error> {
         // Can't evict anything
         ensure!(
@@ -171,12 +177,6 @@ impl SimpleConstraintChecker for AmoebaDeath {
             ConstraintCheckerError::TooManyVictims
         );
 
-        // We don't actually need to check any details of the victim, but we do need to make sure
-        // we have the correct type.
-        let _victim = input_data[0]
-            .extract::<AmoebaDetails>()
-            .map_err(|_| ConstraintCheckerError::BadlyTypedInput)?;
-
         // Make sure there are no outputs
         ensure!(
             output_data.is_empty(),
@@ -194,15 +194,16 @@ impl SimpleConstraintChecker for AmoebaDeath {
 #[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, TypeInfo)]
 pub struct AmoebaCreation;
 
-impl SimpleConstraintChecker for AmoebaCreation {
+impl TypedConstraintChecker for AmoebaCreation {
+    type Payload = AmoebaDetails;
     type Error = ConstraintCheckerError;
 
     fn check(
         &self,
-        input_data: &[DynamicallyTypedData],
-        evicted_input_data: &[DynamicallyTypedData],
+        input_data: &[AmoebaDetails],
+        evicted_input_data: &[AmoebaDetails],
         _peeks: &[DynamicallyTypedData],
-        output_data: &[DynamicallyTypedData],
+        output_data: &[AmoebaDetails],
     ) -> Result<TransactionPriority, Self::Error> {
         // Can't evict anything
         ensure!(
@@ -219,9 +220,7 @@ impl SimpleConstraintChecker for AmoebaCreation {
             output_data.len() == 1,
             ConstraintCheckerError::CreatedTooMany
         );
-        let eve = output_data[0]
-            .extract::<AmoebaDetails>()
-            .map_err(|_| ConstraintCheckerError::BadlyTypedOutput)?;
+        let eve = &output_data[0];
 
         // Make sure the newly created amoeba has generation 0
         ensure!(eve.generation == 0, ConstraintCheckerError::WrongGeneration);
@@ -1,7 +1,7 @@
 //! Unit tests for the Amoeba piece
 
 use super::*;
-use tuxedo_core::dynamic_typing::testing::Bogus;
+use tuxedo_core::{dynamic_typing::testing::Bogus, SimpleConstraintChecker};
 
 #[test]
 fn creation_valid_transaction_works() {
@@ -13,7 +13,7 @@ fn creation_valid_transaction_works() {
     let output_data = vec![to_spawn.into()];
 
     assert_eq!(
-        AmoebaCreation.check(&input_data, &[], &[], &output_data),
+        SimpleConstraintChecker::check(&AmoebaCreation, &input_data, &[], &[], &output_data),
         Ok(0)
     );
 }
@@ -28,7 +28,7 @@ fn creation_invalid_generation_fails() {
     let output_data = vec![to_spawn.into()];
 
     assert_eq!(
-        AmoebaCreation.check(&input_data, &[], &[], &output_data),
+        SimpleConstraintChecker::check(&AmoebaCreation, &input_data, &[], &[], &output_data),
         Err(ConstraintCheckerError::WrongGeneration),
     );
 }
@@ -43,7 +43,7 @@ fn creation_with_inputs_fails() {
     let output_data = vec![example.into()];
 
     assert_eq!(
-        AmoebaCreation.check(&input_data, &[], &[], &output_data),
+        SimpleConstraintChecker::check(&AmoebaCreation, &input_data, &[], &[], &output_data),
         Err(ConstraintCheckerError::CreationMayNotConsume),
     );
 }
@@ -58,7 +58,13 @@ fn creation_with_evictions_fails() {
     let output_data = vec![example.into()];
 
     assert_eq!(
-        AmoebaCreation.check(&[], &evicted_input_data, &[], &output_data),
+        SimpleConstraintChecker::check(
+            &AmoebaCreation,
+            &[],
+            &evicted_input_data,
+            &[],
+            &output_data
+        ),
         Err(ConstraintCheckerError::NoEvictionsAllowed),
     );
 }
@@ -69,7 +75,7 @@ fn creation_with_badly_typed_output_fails() {
     let output_data = vec![Bogus.into()];
 
     assert_eq!(
-        AmoebaCreation.check(&input_data, &[], &[], &output_data),
+        SimpleConstraintChecker::check(&AmoebaCreation, &input_data, &[], &[], &output_data),
         Err(ConstraintCheckerError::BadlyTypedOutput),
     );
 }
@@ -84,7 +90,7 @@ fn creation_multiple_fails() {
     let output_data = vec![to_spawn.clone().into(), to_spawn.into()];
 
     assert_eq!(
-        AmoebaCreation.check(&input_data, &[], &[], &output_data),
+        SimpleConstraintChecker::check(&AmoebaCreation, &input_data, &[], &[], &output_data),
         Err(ConstraintCheckerError::CreatedTooMany),
     );
 }
@@ -95,7 +101,7 @@ fn creation_with_no_output_fails() {
     let output_data = Vec::new();
 
     assert_eq!(
-        AmoebaCreation.check(&input_data, &[], &[], &output_data),
+        SimpleConstraintChecker::check(&AmoebaCreation, &input_data, &[], &[], &output_data),
         Err(ConstraintCheckerError::CreatedNothing),
     );
 }
@@ -118,7 +124,7 @@ fn mitosis_valid_transaction_works() {
     let output_data = vec![d1.into(), d2.into()];
 
     assert_eq!(
-        AmoebaMitosis.check(&input_data, &[], &[], &output_data),
+        SimpleConstraintChecker::check(&AmoebaMitosis, &input_data, &[], &[], &output_data),
         Ok(0)
     );
 }
@@ -142,7 +148,13 @@ fn mitosis_with_evictions_fails() {
     let output_data = vec![d1.into(), d2.into()];
 
     assert_eq!(
-        AmoebaMitosis.check(&input_data, &evicted_input_data, &[], &output_data),
+        SimpleConstraintChecker::check(
+            &AmoebaMitosis,
+            &input_data,
+            &evicted_input_data,
+            &[],
+            &output_data
+        ),
         Err(ConstraintCheckerError::NoEvictionsAllowed)
     );
 }
@@ -165,7 +177,7 @@ fn mitosis_wrong_generation_first_daughter() {
     let output_data = vec![d1.into(), d2.into()];
 
     assert_eq!(
-        AmoebaMitosis.check(&input_data, &[], &[], &output_data),
+        SimpleConstraintChecker::check(&AmoebaMitosis, &input_data, &[], &[], &output_data),
         Err(ConstraintCheckerError::WrongGeneration),
     );
 }
@@ -188,7 +200,7 @@ fn mitosis_wrong_generation_second_daughter() {
     let output_data = vec![d1.into(), d2.into()];
 
     assert_eq!(
-        AmoebaMitosis.check(&input_data, &[], &[], &output_data),
+        SimpleConstraintChecker::check(&AmoebaMitosis, &input_data, &[], &[], &output_data),
         Err(ConstraintCheckerError::WrongGeneration),
     );
 }
@@ -208,7 +220,7 @@ fn mitosis_badly_typed_input() {
     let output_data = vec![d1.into(), d2.into()];
 
     assert_eq!(
-        AmoebaMitosis.check(&input_data, &[], &[], &output_data),
+        SimpleConstraintChecker::check(&AmoebaMitosis, &input_data, &[], &[], &output_data),
         Err(ConstraintCheckerError::BadlyTypedInput),
     );
 }
@@ -227,7 +239,7 @@ fn mitosis_no_input() {
     let output_data = vec![d1.into(), d2.into()];
 
     assert_eq!(
-        AmoebaMitosis.check(&input_data, &[], &[], &output_data),
+        SimpleConstraintChecker::check(&AmoebaMitosis, &input_data, &[], &[], &output_data),
         Err(ConstraintCheckerError::WrongNumberOfMothers),
     );
 }
@@ -247,7 +259,7 @@ fn mitosis_badly_typed_first_daughter() {
     let output_data = vec![d1.into(), d2.into()];
 
     assert_eq!(
-        AmoebaMitosis.check(&input_data, &[], &[], &output_data),
+        SimpleConstraintChecker::check(&AmoebaMitosis, &input_data, &[], &[], &output_data),
         Err(ConstraintCheckerError::BadlyTypedOutput),
     );
 }
@@ -267,7 +279,7 @@ fn mitosis_badly_typed_second_daughter() {
     let output_data = vec![d1.into(), d2.into()];
 
     assert_eq!(
-        AmoebaMitosis.check(&input_data, &[], &[], &output_data),
+        SimpleConstraintChecker::check(&AmoebaMitosis, &input_data, &[], &[], &output_data),
         Err(ConstraintCheckerError::BadlyTypedOutput),
     );
 }
@@ -287,7 +299,7 @@ fn mitosis_only_one_output() {
     let output_data = vec![d1.into()];
 
     assert_eq!(
-        AmoebaMitosis.check(&input_data, &[], &[], &output_data),
+        SimpleConstraintChecker::check(&AmoebaMitosis, &input_data, &[], &[], &output_data),
         Err(ConstraintCheckerError::WrongNumberOfDaughters),
     );
 }
@@ -315,7 +327,7 @@ fn mitosis_too_many_outputs() {
     let output_data = vec![d1.into(), d2.into(), d3.into()];
 
     assert_eq!(
-        AmoebaMitosis.check(&input_data, &[], &[], &output_data),
+        SimpleConstraintChecker::check(&AmoebaMitosis, &input_data, &[], &[], &output_data),
         Err(ConstraintCheckerError::WrongNumberOfDaughters),
     );
 }
@@ -330,7 +342,7 @@ fn death_valid_transaction_works() {
     let output_data = vec![];
 
     assert_eq!(
-        AmoebaDeath.check(&input_data, &[], &[], &output_data),
+        SimpleConstraintChecker::check(&AmoebaDeath, &input_data, &[], &[], &output_data),
         Ok(0)
     );
 }
@@ -341,7 +353,7 @@ fn death_no_input() {
     let output_data = vec![];
 
     assert_eq!(
-        AmoebaDeath.check(&input_data, &[], &[], &output_data),
+        SimpleConstraintChecker::check(&AmoebaDeath, &input_data, &[], &[], &output_data),
         Err(ConstraintCheckerError::NoVictim),
     );
 }
@@ -361,7 +373,13 @@ fn death_eviction_not_allowed() {
     let output_data = vec![];
 
     assert_eq!(
-        AmoebaDeath.check(&input_data, &evicted_input_data, &[], &output_data),
+        SimpleConstraintChecker::check(
+            &AmoebaDeath,
+            &input_data,
+            &evicted_input_data,
+            &[],
+            &output_data
+        ),
         Err(ConstraintCheckerError::NoEvictionsAllowed),
     );
 }
@@ -380,7 +398,7 @@ fn death_multiple_inputs() {
     let output_data = vec![];
 
     assert_eq!(
-        AmoebaDeath.check(&input_data, &[], &[], &output_data),
+        SimpleConstraintChecker::check(&AmoebaDeath, &input_data, &[], &[], &output_data),
         Err(ConstraintCheckerError::TooManyVictims),
     );
 }
@@ -395,7 +413,7 @@ fn death_with_output() {
     let output_data = vec![example.into()];
 
     assert_eq!(
-        AmoebaDeath.check(&input_data, &[], &[], &output_data),
+        SimpleConstraintChecker::check(&AmoebaDeath, &input_data, &[], &[], &output_data),
         Err(ConstraintCheckerError::DeathMayNotCreate),
     );
 }
@@ -407,7 +425,7 @@ fn death_badly_typed_input() {
     let output_data = vec![];
 
     assert_eq!(
-        AmoebaDeath.check(&input_data, &[], &[], &output_data),
+        SimpleConstraintChecker::check(&AmoebaDeath, &input_data, &[], &[], &output_data),
         Err(ConstraintCheckerError::BadlyTypedInput),
     );
 }
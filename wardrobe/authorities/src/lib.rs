@@ -0,0 +1,942 @@
+//! Manages the Aura and GRANDPA authority sets through bonded validator registrations.
+//!
+//! Anyone who wants to author and finalize blocks registers their session keys in a
+//! [`ValidatorRegistration`] and bonds coins in it. The registration must be signed by both
+//! session keys, which proves that the validator controls them. Registrations and deregistrations
+//! take effect at the next session boundary, when the [`RotateAuthorities`] inherent makes the
+//! registered validators with the largest bonds the active authority set and signals the change
+//! to the client with Aura and GRANDPA consensus digests.
+//!
+//! A deregistered validator's bond is not returned immediately. It stays in an [`Unbonding`]
+//! UTXO for an unbonding period, so that the validator is still accountable for what it did
+//! while it was an authority.
+//!
+//! It is not possible to adhere perfectly to the UTXO model here, because the consensus engines
+//! need to look up the current authorities cheaply, without scanning the UTXO set. So
+//! registrations and deregistrations also update the list of registered validators in a
+//! dedicated storage key as a side effect, and the inherent selects the active set from it.
+//!
+//! A validator that equivocates in GRANDPA, by voting for two different blocks in the same round,
+//! loses its bond. Anyone holding the two signed votes may submit a [`ReportEquivocation`] that
+//! evicts the offender's registration or unbonding and removes it from the registered validators.
+//! The last few authority sets are remembered, so equivocations may still be reported for a while
+//! after the set changes.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use core::marker::PhantomData;
+
+use parity_scale_codec::{Decode, DecodeAll, Encode};
+use scale_info::TypeInfo;
+use serde::{Deserialize, Serialize};
+use sp_consensus_aura::AURA_ENGINE_ID;
+use sp_consensus_grandpa::{ScheduledChange, GRANDPA_ENGINE_ID};
+use sp_core::{ed25519, sr25519, H256};
+use sp_inherents::{CheckInherentsResult, InherentData, MakeFatalError};
+use sp_runtime::{transaction_validity::TransactionPriority, DigestItem};
+use sp_std::{vec, vec::Vec};
+use tuxedo_core::{
+    dynamic_typing::{DynamicallyTypedData, UtxoData, UtxoType},
+    ensure,
+    inherents::{InherentError, InherentHooks},
+    support_macros::{CloneNoBound, DebugNoBound, DefaultNoBound},
+    traits::{Cash, CashError},
+    types::{BlockNumber, Output, OutputRef, Transaction},
+    SimpleConstraintChecker, Verifier, VerifierAwareConstraintChecker,
+};
+
+pub use sp_consensus_aura::sr25519::AuthorityId as AuraId;
+pub use sp_consensus_grandpa::{AuthorityId as GrandpaId, AuthorityList, SetId};
+
+/// Proof that a GRANDPA voter signed two conflicting votes in the same round.
+pub type EquivocationProof = sp_consensus_grandpa::EquivocationProof<H256, BlockNumber>;
+
+#[cfg(test)]
+mod tests;
+
+/// The storage key where the active authority set is kept.
+/// It holds the encoded [`AuthoritySet`].
+const AUTHORITIES_KEY: &[u8] = b"authorities";
+
+/// The storage key where all registered validators are kept, in the order they registered.
+/// Those with the largest bonds become the active authorities at the next session boundary.
+/// It holds the encoded `Vec<Candidate>`.
+const CANDIDATES_KEY: &[u8] = b"candidates";
+
+/// The storage key where the most recent authority sets before the active one are kept, oldest first.
+/// It holds the encoded `Vec<AuthoritySet>`.
+const PAST_SETS_KEY: &[u8] = b"past_authorities";
+
+/// The context that is signed, along with the registration, to prove ownership of session keys.
+const OWNERSHIP_CONTEXT: &[u8] = b"tuxedo validator registration";
+
+/// Options to configure the authority set in your runtime.
+pub trait AuthoritiesConfig {
+    /// A means of getting the current block height.
+    /// Probably this will be the Tuxedo Executive
+    fn block_height() -> u32;
+
+    /// A means of adding a log to the current block's digest.
+    /// Probably this will be the Tuxedo Executive
+    fn deposit_log(log: DigestItem);
+
+    /// The least value that must be bonded to register as a validator.
+    ///
+    /// Default is 100.
+    const MIN_BOND: u128 = 100;
+
+    /// The most validators in the active authority set. At each session boundary, the registered
+    /// validators with the largest bonds are selected.
+    ///
+    /// Default is 32.
+    const MAX_AUTHORITIES: u32 = 32;
+
+    /// The most validators that may be registered at once, whether or not they are selected.
+    ///
+    /// Default is 256.
+    const MAX_CANDIDATES: u32 = 256;
+
+    /// The number of blocks in a session. The authority set may only change at session boundaries.
+    ///
+    /// Default is 100.
+    const SESSION_LENGTH: u32 = 100;
+
+    /// The number of blocks that a deregistered validator's bond stays locked.
+    /// This should be longer than a session, so that the validator has left the active set
+    /// before its bond can be withdrawn.
+    ///
+    /// Default is 1000.
+    const UNBONDING_PERIOD: u32 = 1_000;
+
+    /// The number of authority sets before the active one in which equivocations may still be reported.
+    /// The set may change at most once per session, so the default covers the default unbonding period.
+    ///
+    /// Default is 10.
+    const REPORTABLE_PAST_SETS: u32 = 10;
+}
+
+/// The session keys that a validator uses to author and finalize blocks.
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, TypeInfo)]
+pub struct AuthorityKeys {
+    /// The key used to author blocks with Aura.
+    pub aura: AuraId,
+    /// The key used to finalize blocks with GRANDPA.
+    pub grandpa: GrandpaId,
+}
+
+impl AuthorityKeys {
+    /// Whether these keys share either key with the other keys.
+    fn overlaps(&self, other: &Self) -> bool {
+        self.aura == other.aura || self.grandpa == other.grandpa
+    }
+}
+
+/// A registered validator, which is a candidate for the active authority set.
+#[derive(Encode, Decode, Debug, PartialEq, Eq, Clone, TypeInfo)]
+pub struct Candidate {
+    /// The validator's session keys.
+    pub keys: AuthorityKeys,
+    /// The total value of the validator's bond.
+    pub bond: u128,
+}
+
+/// The authorities that are currently authoring and finalizing blocks.
+#[derive(Encode, Decode, Debug, Default, PartialEq, Eq, Clone, TypeInfo)]
+pub struct AuthoritySet {
+    /// The GRANDPA set id, which increases by one every time the set changes.
+    pub set_id: SetId,
+    /// The keys of each authority. All authorities are weighted equally.
+    pub authorities: Vec<AuthorityKeys>,
+}
+
+/// A validator's registration, along with the coins it has bonded.
+/// The registration's verifier belongs to the validator, and is needed to deregister.
+#[derive(
+    Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, TypeInfo, UtxoData,
+)]
+pub struct ValidatorRegistration {
+    /// The validator's session keys.
+    pub keys: AuthorityKeys,
+    /// The bonded coins, exactly as they were before they were bonded.
+    pub bond: Vec<DynamicallyTypedData>,
+}
+
+impl ValidatorRegistration {
+    /// Create a genesis transaction that registers this validator, protected by the given verifier.
+    ///
+    /// The bond is created out of thin air, and the session keys do not sign anything, so this
+    /// transaction would be rejected after genesis, but genesis transactions are not checked.
+    /// The runtime must also pass the genesis registrations to [`initialize_genesis_set`].
+    pub fn genesis<C, T, V, OV, OC>(self, v: V) -> Transaction<OV, OC>
+    where
+        C: Cash + UtxoData,
+        T: AuthoritiesConfig,
+        V: Verifier,
+        OV: Verifier + From<V>,
+        OC: tuxedo_core::ConstraintChecker + From<RegisterValidator<C, T>>,
+    {
+        Transaction {
+            inputs: vec![],
+            peeks: vec![],
+            outputs: vec![(self, v).into()],
+            checker: RegisterValidator::<C, T>::new(
+                sr25519::Signature::from_raw([0; 64]),
+                ed25519::Signature::from_raw([0; 64]),
+            )
+            .into(),
+        }
+    }
+}
+
+/// This piece's section of a runtime's genesis configuration.
+#[derive(Serialize, Deserialize, DefaultNoBound, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields, default)]
+pub struct GenesisConfig<V, C> {
+    /// The validators that are registered at genesis. They make up the initial authority set.
+    pub validators: Vec<GenesisValidator<V, C>>,
+}
+
+/// A validator that is registered at genesis.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct GenesisValidator<V, C> {
+    /// The validator's session keys.
+    pub keys: AuthorityKeys,
+    /// The verifier that protects the registration, and is needed to deregister.
+    pub owner: V,
+    /// The bonded coins. Genesis is not checked, so they should be worth at least the minimum bond.
+    pub bond: Vec<C>,
+}
+
+impl<V: Verifier, C: Cash + UtxoData> GenesisConfig<V, C> {
+    /// Compile this section into genesis transactions.
+    ///
+    /// The runtime must also pass the registrations to [`initialize_genesis_set`].
+    pub fn genesis_transactions<T, OV, OC>(self) -> Vec<Transaction<OV, OC>>
+    where
+        T: AuthoritiesConfig,
+        OV: Verifier + From<V>,
+        OC: tuxedo_core::ConstraintChecker + From<RegisterValidator<C, T>>,
+    {
+        self.validators
+            .into_iter()
+            .map(|validator| {
+                ValidatorRegistration {
+                    keys: validator.keys,
+                    bond: validator.bond.into_iter().map(Into::into).collect(),
+                }
+                .genesis::<C, T, _, _, _>(validator.owner)
+            })
+            .collect()
+    }
+}
+
+/// A deregistered validator's bond, which may be withdrawn once the unbonding period is over.
+/// Its verifier belongs to the validator.
+#[derive(
+    Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, TypeInfo, UtxoData,
+)]
+pub struct Unbonding {
+    /// The session keys that the validator was registered with.
+    pub keys: AuthorityKeys,
+    /// The block height from which the bond may be withdrawn.
+    pub unlocks_at: u32,
+    /// The bonded coins, exactly as they were before they were bonded.
+    pub bond: Vec<DynamicallyTypedData>,
+}
+
+/// A record that a validator's bond was slashed for equivocating.
+/// The bond itself is burned, so this record holds no value.
+#[derive(
+    Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, TypeInfo, UtxoData,
+)]
+pub struct Slash {
+    /// The session keys of the validator that equivocated.
+    pub keys: AuthorityKeys,
+    /// The GRANDPA set in which it equivocated.
+    pub set_id: SetId,
+}
+
+/// The active authority set. It is empty if no set was configured at genesis.
+pub fn current_set() -> AuthoritySet {
+    sp_io::storage::get(AUTHORITIES_KEY)
+        .and_then(|d| AuthoritySet::decode(&mut &*d).ok())
+        .unwrap_or_default()
+}
+
+/// The most recent authority sets before the active one, oldest first.
+pub fn past_sets() -> Vec<AuthoritySet> {
+    sp_io::storage::get(PAST_SETS_KEY)
+        .and_then(|d| Vec::<AuthoritySet>::decode(&mut &*d).ok())
+        .unwrap_or_default()
+}
+
+/// The authority set with the given id, if it is the active set or one of the [`past_sets`].
+/// Equivocations may only be reported in these sets.
+pub fn reportable_set(set_id: SetId) -> Option<AuthoritySet> {
+    let set = current_set();
+    if set.set_id == set_id {
+        return Some(set);
+    }
+    past_sets().into_iter().find(|set| set.set_id == set_id)
+}
+
+/// All registered validators, in the order they registered.
+pub fn candidates() -> Vec<Candidate> {
+    sp_io::storage::get(CANDIDATES_KEY)
+        .and_then(|d| Vec::<Candidate>::decode(&mut &*d).ok())
+        .unwrap_or_default()
+}
+
+/// The keys of the registered validators that become the active authorities at the next session
+/// boundary. These are the `T::MAX_AUTHORITIES` largest bonds, largest first. Equal bonds are
+/// ordered by who registered first.
+pub fn next_authorities<T: AuthoritiesConfig>() -> Vec<AuthorityKeys> {
+    let mut candidates = candidates();
+    // The sort is stable, so earlier registrations win ties.
+    candidates.sort_by(|a, b| b.bond.cmp(&a.bond));
+    candidates
+        .into_iter()
+        .take(T::MAX_AUTHORITIES as usize)
+        .map(|candidate| candidate.keys)
+        .collect()
+}
+
+/// The message that a validator's session keys sign to prove that the validator controls them.
+///
+/// It includes the verifier that will protect the registration, so that the signatures can not
+/// be replayed to register the same keys to somebody else.
+pub fn ownership_message<V: Encode>(keys: &AuthorityKeys, owner: &V) -> Vec<u8> {
+    (OWNERSHIP_CONTEXT, keys, owner).encode()
+}
+
+/// The Aura keys of the active authorities.
+pub fn aura_authorities() -> Vec<AuraId> {
+    current_set()
+        .authorities
+        .into_iter()
+        .map(|keys| keys.aura)
+        .collect()
+}
+
+/// The GRANDPA keys of the active authorities, all equally weighted.
+pub fn grandpa_authorities() -> AuthorityList {
+    current_set()
+        .authorities
+        .into_iter()
+        .map(|keys| (keys.grandpa, 1))
+        .collect()
+}
+
+/// The keys of the validator whose bond is held in the given registration or unbonding.
+fn bonded_keys(data: &DynamicallyTypedData) -> Option<AuthorityKeys> {
+    data.extract::<ValidatorRegistration>()
+        .map(|registration| registration.keys)
+        .or_else(|_| data.extract::<Unbonding>().map(|unbonding| unbonding.keys))
+        .ok()
+}
+
+/// Find the registration or unbonding that holds the bond of the validator with the given GRANDPA key.
+///
+/// Bonds are not indexed by key, so this scans the whole UTXO set. It is meant for runtime APIs
+/// that are rarely called, such as preparing an equivocation report, and never for block execution.
+pub fn find_bond(grandpa: &GrandpaId) -> Option<OutputRef> {
+    let mut key = Vec::new();
+    while let Some(next_key) = sp_io::storage::next_key(&key) {
+        key = next_key;
+
+        // UTXOs are stored under their encoded output refs. Skip everything else.
+        let Ok(output_ref) = OutputRef::decode_all(&mut &key[..]) else {
+            continue;
+        };
+        // An output's payload is encoded first, so there is no need to know the verifier type.
+        let Some(payload) = sp_io::storage::get(&key)
+            .and_then(|value| DynamicallyTypedData::decode(&mut &value[..]).ok())
+        else {
+            continue;
+        };
+
+        if matches!(bonded_keys(&payload), Some(keys) if keys.grandpa == *grandpa) {
+            return Some(output_ref);
+        }
+    }
+
+    None
+}
+
+/// Store the authorities that were registered at genesis as the initial authority set.
+///
+/// Genesis transactions are not checked, so their side effects do not happen. The runtime's
+/// genesis builder must call this with the registrations from its genesis transactions instead.
+/// Bonds that are not made of coins of type `C` count as worthless.
+pub fn initialize_genesis_set<C: Cash + UtxoData, T: AuthoritiesConfig>(
+    registrations: impl IntoIterator<Item = ValidatorRegistration>,
+) {
+    let candidates: Vec<Candidate> = registrations
+        .into_iter()
+        .map(|registration| Candidate {
+            bond: C::total_value(&registration.bond).unwrap_or_default(),
+            keys: registration.keys,
+        })
+        .collect();
+    sp_io::storage::set(CANDIDATES_KEY, &candidates.encode());
+
+    sp_io::storage::set(
+        AUTHORITIES_KEY,
+        &AuthoritySet {
+            set_id: 0,
+            authorities: next_authorities::<T>(),
+        }
+        .encode(),
+    );
+}
+
+/// Errors that can occur when checking authority set transactions.
+#[derive(
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Encode,
+    Decode,
+    Hash,
+    Debug,
+    TypeInfo,
+)]
+pub enum AuthoritiesError {
+    /// An input data has the wrong type.
+    BadlyTypedInput,
+    /// An output data has the wrong type.
+    BadlyTypedOutput,
+    /// Wrong number of inputs were provided to the constraint checker.
+    WrongNumberInputs,
+    /// Wrong number of outputs were provided to the constraint checker.
+    WrongNumberOutputs,
+    /// Registrations and bonds may only be consumed normally, not evicted.
+    NoEvictionsAllowed,
+    /// The transaction attempted to bond a coin of zero value.
+    ZeroValueCoin,
+    /// The value of the bonded coins overflows the value type.
+    ValueOverflow,
+    /// The bonded coins are worth less than the minimum bond.
+    InsufficientBond,
+    /// A registration must hold exactly the coins that were bonded.
+    BondNotPreserved,
+    /// A validator with one of these keys is already registered.
+    AlreadyRegistered,
+    /// The session keys did not sign the registration, so the validator may not control them.
+    InvalidOwnershipProof,
+    /// The maximum number of validators are already registered.
+    TooManyCandidates,
+    /// No validator with these keys is registered.
+    NotRegistered,
+    /// The last registered validator may not deregister, because the chain would have no authorities.
+    LastAuthority,
+    /// Deregistering must lock the whole bond, with the same keys, for exactly the unbonding period.
+    UnbondingNotRecorded,
+    /// The bond may not be withdrawn until the unbonding period is over.
+    BondStillLocked,
+    /// Withdrawing bonds must return exactly the coins that were bonded, in order.
+    CoinsNotReturned,
+    /// The authority rotation inherent must not consume, peek at, or create anything.
+    RotationTouchesState,
+    /// The equivocation proof could not be decoded, or its votes are not a valid equivocation.
+    InvalidEquivocationProof,
+    /// The equivocation was not committed by a member of the active authority set or a recent one.
+    NotAnAuthority,
+    /// The evicted bond does not belong to the validator that equivocated.
+    WrongOffender,
+    /// Slashing must record the offender's keys and the set in which it equivocated.
+    SlashNotRecorded,
+}
+
+impl From<CashError> for AuthoritiesError {
+    fn from(e: CashError) -> Self {
+        match e {
+            CashError::BadlyTyped => Self::BadlyTypedInput,
+            CashError::ZeroValueCoin => Self::ZeroValueCoin,
+            CashError::ValueOverflow => Self::ValueOverflow,
+        }
+    }
+}
+
+/// Register as a validator by bonding coins of type `C`.
+///
+/// The inputs are the coins to bond, and the only output is the registration. Both session keys
+/// must sign the [`ownership_message`] for the registration's keys and verifier.
+///
+/// This constraint checker is somewhat non-standard in that it has a side-effect that
+/// adds the validator to the registered validators in storage.
+#[derive(
+    Serialize, Deserialize, Encode, Decode, DebugNoBound, CloneNoBound, PartialEq, Eq, TypeInfo,
+)]
+#[scale_info(skip_type_params(C, T))]
+pub struct RegisterValidator<C, T> {
+    /// The Aura key's signature of the ownership message.
+    pub aura_signature: sr25519::Signature,
+    /// The GRANDPA key's signature of the ownership message.
+    pub grandpa_signature: ed25519::Signature,
+    _config: PhantomData<(C, T)>,
+}
+
+impl<C, T> RegisterValidator<C, T> {
+    /// Register with the given signatures of the ownership message.
+    pub fn new(aura_signature: sr25519::Signature, grandpa_signature: ed25519::Signature) -> Self {
+        Self {
+            aura_signature,
+            grandpa_signature,
+            _config: PhantomData,
+        }
+    }
+}
+
+impl<C: Cash + UtxoData, T: AuthoritiesConfig> VerifierAwareConstraintChecker
+    for RegisterValidator<C, T>
+{
+    type Error = AuthoritiesError;
+
+    fn check<V: Verifier>(
+        &self,
+        inputs: &[Output<V>],
+        evicted_inputs: &[Output<V>],
+        _peeks: &[Output<V>],
+        outputs: &[Output<V>],
+    ) -> Result<TransactionPriority, Self::Error> {
+        ensure!(
+            evicted_inputs.is_empty(),
+            AuthoritiesError::NoEvictionsAllowed
+        );
+
+        // Add up the bonded coins
+        let total = C::total_value(inputs.iter().map(|input| &input.payload))?;
+        ensure!(total >= T::MIN_BOND, AuthoritiesError::InsufficientBond);
+
+        ensure!(outputs.len() == 1, AuthoritiesError::WrongNumberOutputs);
+        let registration = outputs[0]
+            .payload
+            .extract::<ValidatorRegistration>()
+            .map_err(|_| AuthoritiesError::BadlyTypedOutput)?;
+        ensure!(
+            registration.bond == bond,
+            AuthoritiesError::BondNotPreserved
+        );
+
+        let mut registered = candidates();
+        ensure!(
+            !registered
+                .iter()
+                .any(|candidate| candidate.keys.overlaps(&registration.keys)),
+            AuthoritiesError::AlreadyRegistered
+        );
+        ensure!(
+            registered.len() < T::MAX_CANDIDATES as usize,
+            AuthoritiesError::TooManyCandidates
+        );
+
+        // The signatures are checked last, because it is the most expensive part.
+        let message = ownership_message(&registration.keys, &outputs[0].verifier);
+        ensure!(
+            sp_io::crypto::sr25519_verify(
+                &self.aura_signature,
+                &message,
+                &registration.keys.aura.clone().into()
+            ) && sp_io::crypto::ed25519_verify(
+                &self.grandpa_signature,
+                &message,
+                &registration.keys.grandpa.clone().into()
+            ),
+            AuthoritiesError::InvalidOwnershipProof
+        );
+
+        // SIDE EFFECT: Add the validator to the registered validators
+        registered.push(Candidate {
+            keys: registration.keys,
+            bond: total,
+        });
+        sp_io::storage::set(CANDIDATES_KEY, &registered.encode());
+
+        Ok(0)
+    }
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![UtxoType::of::<ValidatorRegistration>(), UtxoType::of::<C>()]
+    }
+
+    // Registering adds a candidate while checking, so it must check a whole transaction.
+    const COMPOUNDABLE: bool = false;
+}
+
+/// Deregister as a validator.
+///
+/// The only input is the registration, which is consumed normally so the validator's verifier
+/// must be satisfied. The only output locks the bond until the unbonding period is over.
+///
+/// This constraint checker is somewhat non-standard in that it has a side-effect that
+/// removes the validator from the registered validators in storage.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DebugNoBound,
+    DefaultNoBound,
+    CloneNoBound,
+    PartialEq,
+    Eq,
+    TypeInfo,
+)]
+#[scale_info(skip_type_params(T))]
+pub struct DeregisterValidator<T>(PhantomData<T>);
+
+impl<T: AuthoritiesConfig> SimpleConstraintChecker for DeregisterValidator<T> {
+    type Error = AuthoritiesError;
+
+    fn check(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        evicted_input_data: &[DynamicallyTypedData],
+        _peeks: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> Result<TransactionPriority, Self::Error> {
+        ensure!(
+            evicted_input_data.is_empty(),
+            AuthoritiesError::NoEvictionsAllowed
+        );
+        ensure!(input_data.len() == 1, AuthoritiesError::WrongNumberInputs);
+        let registration = input_data[0]
+            .extract::<ValidatorRegistration>()
+            .map_err(|_| AuthoritiesError::BadlyTypedInput)?;
+
+        ensure!(output_data.len() == 1, AuthoritiesError::WrongNumberOutputs);
+        let unbonding = output_data[0]
+            .extract::<Unbonding>()
+            .map_err(|_| AuthoritiesError::BadlyTypedOutput)?;
+        ensure!(
+            unbonding.keys == registration.keys
+                && unbonding.bond == registration.bond
+                && unbonding.unlocks_at == T::block_height().saturating_add(T::UNBONDING_PERIOD),
+            AuthoritiesError::UnbondingNotRecorded
+        );
+
+        let mut registered = candidates();
+        let index = registered
+            .iter()
+            .position(|candidate| candidate.keys == registration.keys)
+            .ok_or(AuthoritiesError::NotRegistered)?;
+        ensure!(registered.len() > 1, AuthoritiesError::LastAuthority);
+
+        // SIDE EFFECT: Remove the validator from the registered validators
+        registered.remove(index);
+        sp_io::storage::set(CANDIDATES_KEY, &registered.encode());
+
+        Ok(0)
+    }
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![
+            UtxoType::of::<ValidatorRegistration>(),
+            UtxoType::of::<Unbonding>(),
+        ]
+    }
+
+    // Deregistering removes a candidate while checking.
+    const COMPOUNDABLE: bool = false;
+}
+
+/// Withdraw the bonds of deregistered validators once their unbonding periods are over.
+///
+/// The inputs are unbondings, which are consumed normally so the validator's verifier must be
+/// satisfied. The outputs are all of their bonded coins, in order.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DebugNoBound,
+    DefaultNoBound,
+    CloneNoBound,
+    PartialEq,
+    Eq,
+    TypeInfo,
+)]
+#[scale_info(skip_type_params(T))]
+pub struct WithdrawBond<T>(PhantomData<T>);
+
+impl<T: AuthoritiesConfig> SimpleConstraintChecker for WithdrawBond<T> {
+    type Error = AuthoritiesError;
+
+    fn check(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        evicted_input_data: &[DynamicallyTypedData],
+        _peeks: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> Result<TransactionPriority, Self::Error> {
+        ensure!(
+            evicted_input_data.is_empty(),
+            AuthoritiesError::NoEvictionsAllowed
+        );
+        ensure!(!input_data.is_empty(), AuthoritiesError::WrongNumberInputs);
+
+        let mut bonded_coins = Vec::new();
+        for input in input_data {
+            let unbonding = input
+                .extract::<Unbonding>()
+                .map_err(|_| AuthoritiesError::BadlyTypedInput)?;
+            ensure!(
+                T::block_height() >= unbonding.unlocks_at,
+                AuthoritiesError::BondStillLocked
+            );
+            bonded_coins.extend(unbonding.bond);
+        }
+
+        ensure!(
+            bonded_coins == output_data,
+            AuthoritiesError::CoinsNotReturned
+        );
+
+        Ok(0)
+    }
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![UtxoType::of::<Unbonding>()]
+    }
+}
+
+/// Report a validator that equivocated in GRANDPA, and slash its bond.
+///
+/// The only input is the offender's registration or unbonding, which is evicted, so anyone may
+/// submit the report. The bond is burned, and the only output records the [`Slash`]. The votes must
+/// have been cast by a member of the active authority set or of one of the recent [`past_sets`],
+/// so equivocations must be reported before that set is forgotten.
+///
+/// This constraint checker is somewhat non-standard in that it has a side-effect that
+/// removes the offender from the registered validators in storage, so it leaves the active set at
+/// the next session boundary. The last registered validator is slashed but stays registered,
+/// because the chain would otherwise have no authorities.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DebugNoBound,
+    DefaultNoBound,
+    CloneNoBound,
+    PartialEq,
+    Eq,
+    TypeInfo,
+)]
+#[scale_info(skip_type_params(T))]
+pub struct ReportEquivocation<T> {
+    /// The encoded [`EquivocationProof`]. It is kept encoded because the proof does not support serde.
+    pub proof: Vec<u8>,
+    _config: PhantomData<T>,
+}
+
+impl<T> ReportEquivocation<T> {
+    /// Report the equivocation in the given proof.
+    pub fn new(proof: EquivocationProof) -> Self {
+        Self {
+            proof: proof.encode(),
+            _config: PhantomData,
+        }
+    }
+}
+
+impl<T: AuthoritiesConfig> SimpleConstraintChecker for ReportEquivocation<T> {
+    type Error = AuthoritiesError;
+
+    fn check(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        evicted_input_data: &[DynamicallyTypedData],
+        _peeks: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> Result<TransactionPriority, Self::Error> {
+        let proof = EquivocationProof::decode_all(&mut &self.proof[..])
+            .map_err(|_| AuthoritiesError::InvalidEquivocationProof)?;
+        let set = reportable_set(proof.set_id()).ok_or(AuthoritiesError::NotAnAuthority)?;
+        ensure!(
+            set.authorities
+                .iter()
+                .any(|keys| keys.grandpa == *proof.offender()),
+            AuthoritiesError::NotAnAuthority
+        );
+
+        // Find the offender's bond
+        ensure!(
+            input_data.is_empty() && evicted_input_data.len() == 1,
+            AuthoritiesError::WrongNumberInputs
+        );
+        let offender =
+            bonded_keys(&evicted_input_data[0]).ok_or(AuthoritiesError::BadlyTypedInput)?;
+        ensure!(
+            offender.grandpa == *proof.offender(),
+            AuthoritiesError::WrongOffender
+        );
+
+        ensure!(output_data.len() == 1, AuthoritiesError::WrongNumberOutputs);
+        let slash = output_data[0]
+            .extract::<Slash>()
+            .map_err(|_| AuthoritiesError::BadlyTypedOutput)?;
+        ensure!(
+            slash.keys == offender && slash.set_id == proof.set_id(),
+            AuthoritiesError::SlashNotRecorded
+        );
+
+        // The signatures are checked last, because it is the most expensive part.
+        ensure!(
+            sp_consensus_grandpa::check_equivocation_proof(proof),
+            AuthoritiesError::InvalidEquivocationProof
+        );
+
+        // A deregistered validator has already left the registered validators, and the last
+        // one stays so that the chain keeps an authority. Its bond is burned all the same.
+        let mut registered = candidates();
+        if let Some(index) = registered
+            .iter()
+            .position(|candidate| candidate.keys == offender)
+            .filter(|_| registered.len() > 1)
+        {
+            // SIDE EFFECT: Remove the offender from the registered validators
+            registered.remove(index);
+            sp_io::storage::set(CANDIDATES_KEY, &registered.encode());
+        }
+
+        Ok(0)
+    }
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![
+            UtxoType::of::<ValidatorRegistration>(),
+            UtxoType::of::<Unbonding>(),
+            UtxoType::of::<Slash>(),
+        ]
+    }
+
+    // Slashing removes the offender from the candidates while checking.
+    const COMPOUNDABLE: bool = false;
+}
+
+/// The inherent that selects the active authority set from the registered validators at each session boundary.
+///
+/// It is included in every block, and does not consume, peek at, or create anything.
+/// When the [`next_authorities`] differ from the active set at a session boundary, it replaces
+/// the active set, increments the set id, and deposits Aura and GRANDPA digests announcing the
+/// new set. Aura reads the authorities from the parent block's state, so the new set authors from
+/// the next block on. GRANDPA enacts the change once the signalling block is finalized.
+///
+/// This constraint checker is somewhat non-standard in that it has side-effects that
+/// write the active and past sets to storage and deposit logs in the block's digest.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DebugNoBound,
+    DefaultNoBound,
+    CloneNoBound,
+    PartialEq,
+    Eq,
+    TypeInfo,
+)]
+#[scale_info(skip_type_params(T))]
+pub struct RotateAuthorities<T>(PhantomData<T>);
+
+impl<T: AuthoritiesConfig> SimpleConstraintChecker for RotateAuthorities<T> {
+    type Error = AuthoritiesError;
+
+    fn check(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        evicted_input_data: &[DynamicallyTypedData],
+        peek_data: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> Result<TransactionPriority, Self::Error> {
+        ensure!(
+            input_data.is_empty()
+                && evicted_input_data.is_empty()
+                && peek_data.is_empty()
+                && output_data.is_empty(),
+            AuthoritiesError::RotationTouchesState
+        );
+
+        if T::block_height() % T::SESSION_LENGTH.max(1) != 0 {
+            return Ok(0);
+        }
+
+        let next = next_authorities::<T>();
+        let mut set = current_set();
+        if next == set.authorities {
+            return Ok(0);
+        }
+
+        // SIDE EFFECT: Remember the outgoing set so its equivocations may still be reported
+        let mut past = past_sets();
+        past.push(set.clone());
+        let forgotten = past.len().saturating_sub(T::REPORTABLE_PAST_SETS as usize);
+        past.drain(..forgotten);
+        sp_io::storage::set(PAST_SETS_KEY, &past.encode());
+
+        // SIDE EFFECT: Make the selected validators the active set
+        set.set_id += 1;
+        set.authorities = next;
+        sp_io::storage::set(AUTHORITIES_KEY, &set.encode());
+
+        // SIDE EFFECT: Announce the new set to the client
+        let aura_log = sp_consensus_aura::ConsensusLog::AuthoritiesChange(aura_authorities());
+        T::deposit_log(DigestItem::Consensus(AURA_ENGINE_ID, aura_log.encode()));
+        let grandpa_log =
+            sp_consensus_grandpa::ConsensusLog::<u32>::ScheduledChange(ScheduledChange {
+                next_authorities: grandpa_authorities(),
+                delay: 0,
+            });
+        T::deposit_log(DigestItem::Consensus(
+            GRANDPA_ENGINE_ID,
+            grandpa_log.encode(),
+        ));
+
+        Ok(0)
+    }
+
+    // Rotating the authorities is an inherent that announces the new set while checking.
+    const COMPOUNDABLE: bool = false;
+}
+
+impl<T: AuthoritiesConfig + 'static> InherentHooks for RotateAuthorities<T> {
+    type Error = MakeFatalError<()>;
+    const INHERENT_IDENTIFIER: sp_inherents::InherentIdentifier = *b"rotation";
+
+    fn create_inherent<V: Verifier>(
+        _authoring_inherent_data: &InherentData,
+        _previous_inherent: (Transaction<V, Self>, H256),
+    ) -> Result<Transaction<V, Self>, InherentError> {
+        Ok(Transaction {
+            inputs: Vec::new(),
+            peeks: Vec::new(),
+            outputs: Vec::new(),
+            checker: Self::default(),
+        })
+    }
+
+    fn check_inherent<V: Verifier>(
+        _importing_inherent_data: &InherentData,
+        _inherent: Transaction<V, Self>,
+        _result: &mut CheckInherentsResult,
+    ) {
+        // There is nothing to check off-chain. Whether the set changes depends only on the chain state.
+    }
+
+    fn genesis_transactions<V: Verifier>() -> Vec<Transaction<V, Self>> {
+        // Each block's inherent is created from the previous one, so there must be one in genesis too.
+        vec![Transaction {
+            inputs: Vec::new(),
+            peeks: Vec::new(),
+            outputs: Vec::new(),
+            checker: Self::default(),
+        }]
+    }
+}
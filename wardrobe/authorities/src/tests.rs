@@ -0,0 +1,1064 @@
+//! Unit tests for the authorities piece
+
+use super::*;
+use sp_core::{ed25519, sr25519, Pair};
+use sp_io::TestExternalities;
+use tuxedo_core::{
+    dynamic_typing::testing::{Bogus, Gold},
+    verifier::{TestVerifier, UpForGrabs},
+};
+use AuthoritiesError::*;
+
+/// A storage key where the mock config keeps the deposited logs so tests can inspect them.
+const TEST_LOGS_KEY: &[u8] = b"test_logs";
+
+/// The mock config always says the block number is two hundred, which is a session boundary.
+pub struct AlwaysBlockTwoHundred;
+
+impl AuthoritiesConfig for AlwaysBlockTwoHundred {
+    fn block_height() -> u32 {
+        200
+    }
+
+    fn deposit_log(log: DigestItem) {
+        let mut logs = deposited_logs();
+        logs.push(log);
+        sp_io::storage::set(TEST_LOGS_KEY, &logs.encode());
+    }
+
+    const MIN_BOND: u128 = 10;
+    const MAX_AUTHORITIES: u32 = 2;
+    const MAX_CANDIDATES: u32 = 3;
+    const UNBONDING_PERIOD: u32 = 50;
+    const REPORTABLE_PAST_SETS: u32 = 1;
+}
+
+/// The same as the other mock config, but in the middle of a session.
+pub struct AlwaysBlockTwoHundredOne;
+
+impl AuthoritiesConfig for AlwaysBlockTwoHundredOne {
+    fn block_height() -> u32 {
+        201
+    }
+
+    fn deposit_log(log: DigestItem) {
+        AlwaysBlockTwoHundred::deposit_log(log)
+    }
+}
+
+fn deposited_logs() -> Vec<DigestItem> {
+    sp_io::storage::get(TEST_LOGS_KEY)
+        .and_then(|d| Vec::<DigestItem>::decode(&mut &*d).ok())
+        .unwrap_or_default()
+}
+
+type Register = RegisterValidator<Gold, AlwaysBlockTwoHundred>;
+type Deregister = DeregisterValidator<AlwaysBlockTwoHundred>;
+type Withdraw = WithdrawBond<AlwaysBlockTwoHundred>;
+type Rotate = RotateAuthorities<AlwaysBlockTwoHundred>;
+type Report = ReportEquivocation<AlwaysBlockTwoHundred>;
+
+/// The Aura key pair of the validator with the given seed, so tests can sign registrations.
+fn aura_pair(seed: u8) -> sr25519::Pair {
+    sr25519::Pair::from_seed(&[seed; 32])
+}
+
+/// The GRANDPA key pair of the validator with the given seed, so tests can sign votes.
+fn grandpa_pair(seed: u8) -> ed25519::Pair {
+    ed25519::Pair::from_seed(&[seed; 32])
+}
+
+fn keys(seed: u8) -> AuthorityKeys {
+    AuthorityKeys {
+        aura: aura_pair(seed).public().into(),
+        grandpa: grandpa_pair(seed).public().into(),
+    }
+}
+
+/// A registration checker whose signatures are made by the validator with the given seed,
+/// for registrations of its keys that anyone may spend.
+fn signed_by(seed: u8) -> Register {
+    let message = ownership_message(&keys(seed), &UpForGrabs);
+    Register::new(
+        aura_pair(seed).sign(&message),
+        grandpa_pair(seed).sign(&message),
+    )
+}
+
+/// Check a registration in which all the UTXOs may be spent by anyone.
+fn register(
+    checker: &Register,
+    inputs: &[DynamicallyTypedData],
+    evicted_inputs: &[DynamicallyTypedData],
+    outputs: &[DynamicallyTypedData],
+) -> Result<TransactionPriority, AuthoritiesError> {
+    let up_for_grabs = |data: &[DynamicallyTypedData]| -> Vec<Output<UpForGrabs>> {
+        data.iter()
+            .map(|d| (d.clone(), UpForGrabs).into())
+            .collect()
+    };
+    VerifierAwareConstraintChecker::check(
+        checker,
+        &up_for_grabs(inputs),
+        &up_for_grabs(evicted_inputs),
+        &[],
+        &up_for_grabs(outputs),
+    )
+}
+
+fn registration(seed: u8, bond: Vec<DynamicallyTypedData>) -> ValidatorRegistration {
+    ValidatorRegistration {
+        keys: keys(seed),
+        bond,
+    }
+}
+
+/// Test externalities in which validators with the given seeds are registered with the minimum
+/// bond. Those that fit in the active set are active.
+fn ext_with_authorities(seeds: &[u8]) -> TestExternalities {
+    let mut ext = TestExternalities::default();
+    ext.execute_with(|| {
+        initialize_genesis_set::<Gold, AlwaysBlockTwoHundred>(
+            seeds
+                .iter()
+                .map(|seed| registration(*seed, vec![Gold(10).into()])),
+        );
+    });
+    ext
+}
+
+#[test]
+fn genesis_set_is_active_and_registered() {
+    ext_with_authorities(&[1, 2]).execute_with(|| {
+        assert_eq!(
+            current_set(),
+            AuthoritySet {
+                set_id: 0,
+                authorities: vec![keys(1), keys(2)],
+            }
+        );
+        assert_eq!(
+            next_authorities::<AlwaysBlockTwoHundred>(),
+            vec![keys(1), keys(2)]
+        );
+        assert_eq!(aura_authorities(), vec![keys(1).aura, keys(2).aura]);
+        assert_eq!(
+            grandpa_authorities(),
+            vec![(keys(1).grandpa, 1), (keys(2).grandpa, 1)]
+        );
+    });
+}
+
+#[test]
+fn register_happy_path() {
+    ext_with_authorities(&[1]).execute_with(|| {
+        let bond: Vec<DynamicallyTypedData> = vec![Gold(6).into(), Gold(4).into()];
+
+        assert_eq!(
+            register(
+                &signed_by(2),
+                &bond,
+                &[],
+                &[registration(2, bond.clone()).into()]
+            ),
+            Ok(0)
+        );
+        assert_eq!(
+            candidates(),
+            vec![
+                Candidate {
+                    keys: keys(1),
+                    bond: 10
+                },
+                Candidate {
+                    keys: keys(2),
+                    bond: 10
+                },
+            ]
+        );
+        // The active set does not change until the next session boundary
+        assert_eq!(current_set().authorities, vec![keys(1)]);
+    });
+}
+
+#[test]
+fn register_with_insufficient_bond_fails() {
+    ext_with_authorities(&[1]).execute_with(|| {
+        let bond: Vec<DynamicallyTypedData> = vec![Gold(9).into()];
+
+        assert_eq!(
+            register(
+                &signed_by(2),
+                &bond,
+                &[],
+                &[registration(2, bond.clone()).into()]
+            ),
+            Err(InsufficientBond)
+        );
+        assert_eq!(candidates().len(), 1);
+    });
+}
+
+#[test]
+fn register_with_zero_value_coin_fails() {
+    ext_with_authorities(&[1]).execute_with(|| {
+        let bond: Vec<DynamicallyTypedData> = vec![Gold(10).into(), Gold(0).into()];
+
+        assert_eq!(
+            register(
+                &signed_by(2),
+                &bond,
+                &[],
+                &[registration(2, bond.clone()).into()]
+            ),
+            Err(ZeroValueCoin)
+        );
+    });
+}
+
+#[test]
+fn register_with_bogus_coin_fails() {
+    ext_with_authorities(&[1]).execute_with(|| {
+        let bond: Vec<DynamicallyTypedData> = vec![Bogus.into()];
+
+        assert_eq!(
+            register(
+                &signed_by(2),
+                &bond,
+                &[],
+                &[registration(2, bond.clone()).into()]
+            ),
+            Err(BadlyTypedInput)
+        );
+    });
+}
+
+#[test]
+fn register_keeping_coins_fails() {
+    ext_with_authorities(&[1]).execute_with(|| {
+        let bond: Vec<DynamicallyTypedData> = vec![Gold(10).into(), Gold(5).into()];
+
+        assert_eq!(
+            register(
+                &signed_by(2),
+                &bond,
+                &[],
+                &[registration(2, vec![Gold(10).into()]).into()]
+            ),
+            Err(BondNotPreserved)
+        );
+    });
+}
+
+#[test]
+fn register_bogus_registration_fails() {
+    ext_with_authorities(&[1]).execute_with(|| {
+        assert_eq!(
+            register(&signed_by(2), &[Gold(10).into()], &[], &[Bogus.into()]),
+            Err(BadlyTypedOutput)
+        );
+    });
+}
+
+#[test]
+fn register_with_eviction_fails() {
+    ext_with_authorities(&[1]).execute_with(|| {
+        let bond: Vec<DynamicallyTypedData> = vec![Gold(10).into()];
+
+        assert_eq!(
+            register(
+                &signed_by(2),
+                &bond,
+                &[Bogus.into()],
+                &[registration(2, bond.clone()).into()]
+            ),
+            Err(NoEvictionsAllowed)
+        );
+    });
+}
+
+#[test]
+fn register_same_keys_twice_fails() {
+    ext_with_authorities(&[1]).execute_with(|| {
+        let bond: Vec<DynamicallyTypedData> = vec![Gold(10).into()];
+
+        assert_eq!(
+            register(
+                &signed_by(1),
+                &bond,
+                &[],
+                &[registration(1, bond.clone()).into()]
+            ),
+            Err(AlreadyRegistered)
+        );
+    });
+}
+
+#[test]
+fn register_reused_grandpa_key_fails() {
+    ext_with_authorities(&[1]).execute_with(|| {
+        let bond: Vec<DynamicallyTypedData> = vec![Gold(10).into()];
+        let reused = ValidatorRegistration {
+            keys: AuthorityKeys {
+                aura: keys(2).aura,
+                grandpa: keys(1).grandpa,
+            },
+            bond: bond.clone(),
+        };
+
+        assert_eq!(
+            register(&signed_by(2), &bond, &[], &[reused.into()]),
+            Err(AlreadyRegistered)
+        );
+    });
+}
+
+#[test]
+fn register_someone_elses_keys_fails() {
+    ext_with_authorities(&[1]).execute_with(|| {
+        let bond: Vec<DynamicallyTypedData> = vec![Gold(10).into()];
+
+        assert_eq!(
+            register(
+                &signed_by(3),
+                &bond,
+                &[],
+                &[registration(2, bond.clone()).into()]
+            ),
+            Err(InvalidOwnershipProof)
+        );
+        assert_eq!(candidates().len(), 1);
+    });
+}
+
+#[test]
+fn register_with_only_aura_signature_fails() {
+    ext_with_authorities(&[1]).execute_with(|| {
+        let bond: Vec<DynamicallyTypedData> = vec![Gold(10).into()];
+        let checker = Register::new(signed_by(2).aura_signature, signed_by(3).grandpa_signature);
+
+        assert_eq!(
+            register(
+                &checker,
+                &bond,
+                &[],
+                &[registration(2, bond.clone()).into()]
+            ),
+            Err(InvalidOwnershipProof)
+        );
+    });
+}
+
+#[test]
+fn register_replaying_signatures_for_another_owner_fails() {
+    ext_with_authorities(&[1]).execute_with(|| {
+        let bond: Vec<DynamicallyTypedData> = vec![Gold(10).into()];
+        let outputs: Vec<Output<TestVerifier>> = vec![(
+            registration(2, bond.clone()),
+            TestVerifier { verifies: true },
+        )
+            .into()];
+        let inputs: Vec<Output<TestVerifier>> = bond
+            .iter()
+            .map(|coin| (coin.clone(), TestVerifier { verifies: true }).into())
+            .collect();
+
+        assert_eq!(
+            VerifierAwareConstraintChecker::check(&signed_by(2), &inputs, &[], &[], &outputs),
+            Err(InvalidOwnershipProof)
+        );
+    });
+}
+
+#[test]
+fn register_too_many_fails() {
+    ext_with_authorities(&[1, 2, 3]).execute_with(|| {
+        let bond: Vec<DynamicallyTypedData> = vec![Gold(10).into()];
+
+        assert_eq!(
+            register(
+                &signed_by(4),
+                &bond,
+                &[],
+                &[registration(4, bond.clone()).into()]
+            ),
+            Err(TooManyCandidates)
+        );
+    });
+}
+
+#[test]
+fn largest_bonds_are_selected() {
+    ext_with_authorities(&[1, 2]).execute_with(|| {
+        let bond: Vec<DynamicallyTypedData> = vec![Gold(11).into()];
+        register(
+            &signed_by(3),
+            &bond,
+            &[],
+            &[registration(3, bond.clone()).into()],
+        )
+        .unwrap();
+
+        // The new validator has the largest bond, and the tie is won by the earlier registration.
+        assert_eq!(
+            next_authorities::<AlwaysBlockTwoHundred>(),
+            vec![keys(3), keys(1)]
+        );
+    });
+}
+
+#[test]
+fn deregister_happy_path() {
+    ext_with_authorities(&[1, 2]).execute_with(|| {
+        let bond: Vec<DynamicallyTypedData> = vec![Gold(10).into()];
+        let unbonding = Unbonding {
+            keys: keys(2),
+            unlocks_at: 250,
+            bond: bond.clone(),
+        };
+
+        assert_eq!(
+            Deregister::default().check(
+                &[registration(2, bond).into()],
+                &[],
+                &[],
+                &[unbonding.into()]
+            ),
+            Ok(0)
+        );
+        assert_eq!(next_authorities::<AlwaysBlockTwoHundred>(), vec![keys(1)]);
+        assert_eq!(current_set().authorities, vec![keys(1), keys(2)]);
+    });
+}
+
+#[test]
+fn deregister_last_authority_fails() {
+    ext_with_authorities(&[1]).execute_with(|| {
+        let unbonding = Unbonding {
+            keys: keys(1),
+            unlocks_at: 250,
+            bond: vec![],
+        };
+
+        assert_eq!(
+            Deregister::default().check(
+                &[registration(1, vec![]).into()],
+                &[],
+                &[],
+                &[unbonding.into()]
+            ),
+            Err(LastAuthority)
+        );
+    });
+}
+
+#[test]
+fn deregister_unknown_validator_fails() {
+    ext_with_authorities(&[1, 2]).execute_with(|| {
+        let unbonding = Unbonding {
+            keys: keys(3),
+            unlocks_at: 250,
+            bond: vec![],
+        };
+
+        assert_eq!(
+            Deregister::default().check(
+                &[registration(3, vec![]).into()],
+                &[],
+                &[],
+                &[unbonding.into()]
+            ),
+            Err(NotRegistered)
+        );
+    });
+}
+
+#[test]
+fn deregister_with_short_unbonding_fails() {
+    ext_with_authorities(&[1, 2]).execute_with(|| {
+        let unbonding = Unbonding {
+            keys: keys(2),
+            unlocks_at: 249,
+            bond: vec![],
+        };
+
+        assert_eq!(
+            Deregister::default().check(
+                &[registration(2, vec![]).into()],
+                &[],
+                &[],
+                &[unbonding.into()]
+            ),
+            Err(UnbondingNotRecorded)
+        );
+    });
+}
+
+#[test]
+fn deregister_releasing_bond_fails() {
+    ext_with_authorities(&[1, 2]).execute_with(|| {
+        assert_eq!(
+            Deregister::default().check(
+                &[registration(2, vec![Gold(10).into()]).into()],
+                &[],
+                &[],
+                &[Gold(10).into()]
+            ),
+            Err(BadlyTypedOutput)
+        );
+    });
+}
+
+#[test]
+fn withdraw_happy_path() {
+    let first = Unbonding {
+        keys: keys(1),
+        unlocks_at: 150,
+        bond: vec![Gold(10).into()],
+    };
+    let second = Unbonding {
+        keys: keys(2),
+        unlocks_at: 200,
+        bond: vec![Gold(6).into(), Gold(7).into()],
+    };
+
+    assert_eq!(
+        Withdraw::default().check(
+            &[first.into(), second.into()],
+            &[],
+            &[],
+            &[Gold(10).into(), Gold(6).into(), Gold(7).into()]
+        ),
+        Ok(0)
+    );
+}
+
+#[test]
+fn withdraw_too_early_fails() {
+    let unbonding = Unbonding {
+        keys: keys(1),
+        unlocks_at: 201,
+        bond: vec![Gold(10).into()],
+    };
+
+    assert_eq!(
+        Withdraw::default().check(&[unbonding.into()], &[], &[], &[Gold(10).into()]),
+        Err(BondStillLocked)
+    );
+}
+
+#[test]
+fn withdraw_more_than_bonded_fails() {
+    let unbonding = Unbonding {
+        keys: keys(1),
+        unlocks_at: 200,
+        bond: vec![Gold(10).into()],
+    };
+
+    assert_eq!(
+        Withdraw::default().check(&[unbonding.into()], &[], &[], &[Gold(11).into()]),
+        Err(CoinsNotReturned)
+    );
+}
+
+#[test]
+fn withdraw_registration_fails() {
+    assert_eq!(
+        Withdraw::default().check(
+            &[registration(1, vec![Gold(10).into()]).into()],
+            &[],
+            &[],
+            &[Gold(10).into()]
+        ),
+        Err(BadlyTypedInput)
+    );
+}
+
+#[test]
+fn rotate_without_changes_does_nothing() {
+    ext_with_authorities(&[1]).execute_with(|| {
+        assert_eq!(Rotate::default().check(&[], &[], &[], &[]), Ok(0));
+
+        assert_eq!(current_set().set_id, 0);
+        assert!(deposited_logs().is_empty());
+    });
+}
+
+#[test]
+fn rotate_at_session_boundary_changes_set() {
+    ext_with_authorities(&[1]).execute_with(|| {
+        let bond: Vec<DynamicallyTypedData> = vec![Gold(10).into()];
+        register(
+            &signed_by(2),
+            &bond,
+            &[],
+            &[registration(2, bond.clone()).into()],
+        )
+        .unwrap();
+
+        assert_eq!(Rotate::default().check(&[], &[], &[], &[]), Ok(0));
+
+        assert_eq!(
+            current_set(),
+            AuthoritySet {
+                set_id: 1,
+                authorities: vec![keys(1), keys(2)],
+            }
+        );
+        assert_eq!(
+            past_sets(),
+            vec![AuthoritySet {
+                set_id: 0,
+                authorities: vec![keys(1)],
+            }]
+        );
+
+        let aura_log =
+            sp_consensus_aura::ConsensusLog::AuthoritiesChange(vec![keys(1).aura, keys(2).aura]);
+        let grandpa_log =
+            sp_consensus_grandpa::ConsensusLog::<u32>::ScheduledChange(ScheduledChange {
+                next_authorities: vec![(keys(1).grandpa, 1), (keys(2).grandpa, 1)],
+                delay: 0,
+            });
+        assert_eq!(
+            deposited_logs(),
+            vec![
+                DigestItem::Consensus(AURA_ENGINE_ID, aura_log.encode()),
+                DigestItem::Consensus(GRANDPA_ENGINE_ID, grandpa_log.encode()),
+            ]
+        );
+    });
+}
+
+#[test]
+fn rotate_forgets_old_sets() {
+    ext_with_authorities(&[1]).execute_with(|| {
+        let bond: Vec<DynamicallyTypedData> = vec![Gold(10).into()];
+        register(
+            &signed_by(2),
+            &bond,
+            &[],
+            &[registration(2, bond.clone()).into()],
+        )
+        .unwrap();
+        Rotate::default().check(&[], &[], &[], &[]).unwrap();
+
+        let unbonding = Unbonding {
+            keys: keys(1),
+            unlocks_at: 250,
+            bond: vec![Gold(10).into()],
+        };
+        Deregister::default()
+            .check(
+                &[registration(1, vec![Gold(10).into()]).into()],
+                &[],
+                &[],
+                &[unbonding.into()],
+            )
+            .unwrap();
+        Rotate::default().check(&[], &[], &[], &[]).unwrap();
+
+        // The mock config only remembers one past set
+        assert_eq!(
+            past_sets(),
+            vec![AuthoritySet {
+                set_id: 1,
+                authorities: vec![keys(1), keys(2)],
+            }]
+        );
+        assert_eq!(reportable_set(0), None);
+        assert_eq!(reportable_set(2), Some(current_set()));
+    });
+}
+
+#[test]
+fn rotate_mid_session_does_nothing() {
+    ext_with_authorities(&[1]).execute_with(|| {
+        let bond: Vec<DynamicallyTypedData> = vec![Gold(10).into()];
+        register(
+            &signed_by(2),
+            &bond,
+            &[],
+            &[registration(2, bond.clone()).into()],
+        )
+        .unwrap();
+
+        assert_eq!(
+            RotateAuthorities::<AlwaysBlockTwoHundredOne>::default().check(&[], &[], &[], &[]),
+            Ok(0)
+        );
+
+        assert_eq!(current_set().set_id, 0);
+        assert_eq!(current_set().authorities, vec![keys(1)]);
+        assert!(deposited_logs().is_empty());
+    });
+}
+
+#[test]
+fn rotate_touching_state_fails() {
+    ext_with_authorities(&[1]).execute_with(|| {
+        assert_eq!(
+            Rotate::default().check(&[], &[], &[], &[Bogus.into()]),
+            Err(RotationTouchesState)
+        );
+    });
+}
+
+/// Build a proof that the offender cast prevotes for both given blocks in the same round,
+/// with the votes actually signed by the signer.
+///
+/// The proof is assembled from its encoding, because its inner types come from the `finality-grandpa`
+/// crate, which this piece does not depend on.
+fn proof_signed_by(
+    signer: u8,
+    offender: u8,
+    set_id: SetId,
+    first: H256,
+    second: H256,
+) -> EquivocationProof {
+    let round: u64 = 5;
+    let vote = |target_hash: H256| {
+        let prevote = (target_hash, 7u32);
+        // Prevotes are the first variant of a GRANDPA message.
+        let payload = sp_consensus_grandpa::localized_payload(round, set_id, &(0u8, prevote));
+        (prevote, grandpa_pair(signer).sign(&payload))
+    };
+
+    // Prevote equivocations are the first variant of an equivocation.
+    let encoded = (
+        set_id,
+        0u8,
+        round,
+        keys(offender).grandpa,
+        vote(first),
+        vote(second),
+    )
+        .encode();
+    EquivocationProof::decode(&mut &encoded[..]).unwrap()
+}
+
+/// A valid proof that the validator with the given seed equivocated in the given set.
+fn equivocation_proof(seed: u8, set_id: SetId) -> EquivocationProof {
+    proof_signed_by(
+        seed,
+        seed,
+        set_id,
+        H256::repeat_byte(1),
+        H256::repeat_byte(2),
+    )
+}
+
+fn slash(seed: u8, set_id: SetId) -> DynamicallyTypedData {
+    Slash {
+        keys: keys(seed),
+        set_id,
+    }
+    .into()
+}
+
+#[test]
+fn report_registered_validator_works() {
+    ext_with_authorities(&[1, 2]).execute_with(|| {
+        let bond: Vec<DynamicallyTypedData> = vec![Gold(10).into()];
+
+        assert_eq!(
+            Report::new(equivocation_proof(2, 0)).check(
+                &[],
+                &[registration(2, bond).into()],
+                &[],
+                &[slash(2, 0)]
+            ),
+            Ok(0)
+        );
+        assert_eq!(next_authorities::<AlwaysBlockTwoHundred>(), vec![keys(1)]);
+        // The offender stays in the active set until the next session boundary
+        assert_eq!(current_set().authorities, vec![keys(1), keys(2)]);
+    });
+}
+
+#[test]
+fn report_unbonding_validator_works() {
+    ext_with_authorities(&[1, 2]).execute_with(|| {
+        let unbonding = Unbonding {
+            keys: keys(2),
+            unlocks_at: 250,
+            bond: vec![Gold(10).into()],
+        };
+        Deregister::default()
+            .check(
+                &[registration(2, vec![Gold(10).into()]).into()],
+                &[],
+                &[],
+                &[unbonding.clone().into()],
+            )
+            .unwrap();
+
+        assert_eq!(
+            Report::new(equivocation_proof(2, 0)).check(
+                &[],
+                &[unbonding.into()],
+                &[],
+                &[slash(2, 0)]
+            ),
+            Ok(0)
+        );
+        assert_eq!(next_authorities::<AlwaysBlockTwoHundred>(), vec![keys(1)]);
+    });
+}
+
+#[test]
+fn report_undecodable_proof_fails() {
+    ext_with_authorities(&[1, 2]).execute_with(|| {
+        let report = Report {
+            proof: vec![1, 2, 3],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            report.check(&[], &[registration(2, vec![]).into()], &[], &[slash(2, 0)]),
+            Err(InvalidEquivocationProof)
+        );
+    });
+}
+
+#[test]
+fn report_same_vote_twice_fails() {
+    ext_with_authorities(&[1, 2]).execute_with(|| {
+        let proof = proof_signed_by(2, 2, 0, H256::repeat_byte(1), H256::repeat_byte(1));
+
+        assert_eq!(
+            Report::new(proof).check(&[], &[registration(2, vec![]).into()], &[], &[slash(2, 0)]),
+            Err(InvalidEquivocationProof)
+        );
+        assert_eq!(candidates().len(), 2);
+    });
+}
+
+#[test]
+fn report_votes_signed_by_someone_else_fails() {
+    ext_with_authorities(&[1, 2]).execute_with(|| {
+        let proof = proof_signed_by(1, 2, 0, H256::repeat_byte(1), H256::repeat_byte(2));
+
+        assert_eq!(
+            Report::new(proof).check(&[], &[registration(2, vec![]).into()], &[], &[slash(2, 0)]),
+            Err(InvalidEquivocationProof)
+        );
+        assert_eq!(candidates().len(), 2);
+    });
+}
+
+#[test]
+fn report_from_past_set_works() {
+    ext_with_authorities(&[1, 2]).execute_with(|| {
+        // A larger bond pushes the offender out of the active set
+        let bond: Vec<DynamicallyTypedData> = vec![Gold(20).into()];
+        register(
+            &signed_by(3),
+            &bond,
+            &[],
+            &[registration(3, bond.clone()).into()],
+        )
+        .unwrap();
+        Rotate::default().check(&[], &[], &[], &[]).unwrap();
+        assert_eq!(current_set().authorities, vec![keys(3), keys(1)]);
+
+        assert_eq!(
+            Report::new(equivocation_proof(2, 0)).check(
+                &[],
+                &[registration(2, vec![Gold(10).into()]).into()],
+                &[],
+                &[slash(2, 0)]
+            ),
+            Ok(0)
+        );
+        assert_eq!(candidates().len(), 2);
+    });
+}
+
+#[test]
+fn report_from_unknown_set_fails() {
+    ext_with_authorities(&[1, 2]).execute_with(|| {
+        assert_eq!(
+            Report::new(equivocation_proof(2, 1)).check(
+                &[],
+                &[registration(2, vec![]).into()],
+                &[],
+                &[slash(2, 1)]
+            ),
+            Err(NotAnAuthority)
+        );
+    });
+}
+
+#[test]
+fn report_non_authority_fails() {
+    ext_with_authorities(&[1, 2]).execute_with(|| {
+        assert_eq!(
+            Report::new(equivocation_proof(3, 0)).check(
+                &[],
+                &[registration(3, vec![]).into()],
+                &[],
+                &[slash(3, 0)]
+            ),
+            Err(NotAnAuthority)
+        );
+    });
+}
+
+#[test]
+fn report_evicting_someone_elses_bond_fails() {
+    ext_with_authorities(&[1, 2]).execute_with(|| {
+        assert_eq!(
+            Report::new(equivocation_proof(2, 0)).check(
+                &[],
+                &[registration(1, vec![]).into()],
+                &[],
+                &[slash(2, 0)]
+            ),
+            Err(WrongOffender)
+        );
+    });
+}
+
+#[test]
+fn report_consuming_bond_normally_fails() {
+    ext_with_authorities(&[1, 2]).execute_with(|| {
+        assert_eq!(
+            Report::new(equivocation_proof(2, 0)).check(
+                &[registration(2, vec![]).into()],
+                &[],
+                &[],
+                &[slash(2, 0)]
+            ),
+            Err(WrongNumberInputs)
+        );
+    });
+}
+
+#[test]
+fn report_evicting_bogus_fails() {
+    ext_with_authorities(&[1, 2]).execute_with(|| {
+        assert_eq!(
+            Report::new(equivocation_proof(2, 0)).check(&[], &[Bogus.into()], &[], &[slash(2, 0)]),
+            Err(BadlyTypedInput)
+        );
+    });
+}
+
+#[test]
+fn report_keeping_bond_fails() {
+    ext_with_authorities(&[1, 2]).execute_with(|| {
+        assert_eq!(
+            Report::new(equivocation_proof(2, 0)).check(
+                &[],
+                &[registration(2, vec![Gold(10).into()]).into()],
+                &[],
+                &[slash(2, 0), Gold(10).into()]
+            ),
+            Err(WrongNumberOutputs)
+        );
+    });
+}
+
+#[test]
+fn report_with_wrong_slash_record_fails() {
+    ext_with_authorities(&[1, 2]).execute_with(|| {
+        assert_eq!(
+            Report::new(equivocation_proof(2, 0)).check(
+                &[],
+                &[registration(2, vec![]).into()],
+                &[],
+                &[slash(1, 0)]
+            ),
+            Err(SlashNotRecorded)
+        );
+    });
+}
+
+#[test]
+fn report_last_authority_works() {
+    ext_with_authorities(&[1]).execute_with(|| {
+        assert_eq!(
+            Report::new(equivocation_proof(1, 0)).check(
+                &[],
+                &[registration(1, vec![]).into()],
+                &[],
+                &[slash(1, 0)]
+            ),
+            Ok(0)
+        );
+        // The bond is gone, but the chain keeps its only authority
+        assert_eq!(next_authorities::<AlwaysBlockTwoHundred>(), vec![keys(1)]);
+    });
+}
+
+#[test]
+fn find_bond_works() {
+    TestExternalities::default().execute_with(|| {
+        let output_ref = OutputRef {
+            tx_hash: H256::repeat_byte(9),
+            index: 1,
+        };
+        let output: Output<UpForGrabs> = (registration(2, vec![]), UpForGrabs).into();
+        sp_io::storage::set(&output_ref.encode(), &output.encode());
+
+        assert_eq!(find_bond(&keys(2).grandpa), Some(output_ref));
+        assert_eq!(find_bond(&keys(1).grandpa), None);
+    });
+}
+
+#[test]
+fn find_bond_of_unbonding_works() {
+    TestExternalities::default().execute_with(|| {
+        let output_ref = OutputRef {
+            tx_hash: H256::repeat_byte(9),
+            index: 0,
+        };
+        let unbonding = Unbonding {
+            keys: keys(2),
+            unlocks_at: 250,
+            bond: vec![],
+        };
+        let output: Output<UpForGrabs> = (unbonding, UpForGrabs).into();
+        sp_io::storage::set(&output_ref.encode(), &output.encode());
+        // Storage that is not a UTXO is skipped
+        initialize_genesis_set::<Gold, AlwaysBlockTwoHundred>([registration(2, vec![])]);
+
+        assert_eq!(find_bond(&keys(2).grandpa), Some(output_ref));
+    });
+}
+
+#[test]
+fn genesis_config_registers_validators() {
+    let config = GenesisConfig {
+        validators: vec![
+            GenesisValidator {
+                keys: keys(1),
+                owner: UpForGrabs,
+                bond: vec![Gold(10)],
+            },
+            GenesisValidator {
+                keys: keys(2),
+                owner: UpForGrabs,
+                bond: vec![Gold(6), Gold(4)],
+            },
+        ],
+    };
+
+    let transactions: Vec<Transaction<UpForGrabs, Register>> =
+        config.genesis_transactions::<AlwaysBlockTwoHundred, _, _>();
+    let outputs: Vec<Output<UpForGrabs>> =
+        transactions.into_iter().flat_map(|tx| tx.outputs).collect();
+
+    assert_eq!(
+        outputs,
+        vec![
+            (registration(1, vec![Gold(10).into()]), UpForGrabs).into(),
+            (
+                registration(2, vec![Gold(6).into(), Gold(4).into()]),
+                UpForGrabs
+            )
+                .into(),
+        ]
+    );
+}
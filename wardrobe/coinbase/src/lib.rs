@@ -0,0 +1,395 @@
+//! Reward block authors with newly minted coins and the fees collected from transactions.
+//!
+//! In each block, the author includes a single [`Coinbase`] inherent that creates a [`BlockReward`]
+//! worth the configured block reward plus the fees that were collected since the previous coinbase.
+//! The reward is protected by a verifier of the author's choosing, which the author's node supplies
+//! through the [`CoinbaseInherentDataProvider`]. A node running Aura will typically use the key of
+//! the authority that owns the current slot. When a block is imported, Aura supplies the slot from the
+//! block's pre-runtime digest, and the reward must be protected by the verifier that the runtime
+//! names for that slot's author (see [`CoinbaseConfig::is_slot_author`]).
+//!
+//! Inherents are applied at the beginning of the block, so the fees collected in one block are paid
+//! out by the coinbase of the next block.
+//!
+//! Rewards are not spendable right away. Once the maturity period has passed, the author claims them
+//! with a [`ClaimReward`] transaction that turns them into ordinary coins. This gives the chain time
+//! to settle before newly minted value can move, just like the coinbase maturity rule in Bitcoin.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use core::marker::PhantomData;
+
+use parity_scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use serde::{Deserialize, Serialize};
+use sp_consensus_aura::{inherents::INHERENT_IDENTIFIER as AURA_SLOT_IDENTIFIER, Slot};
+use sp_core::H256;
+use sp_inherents::{
+    CheckInherentsResult, InherentData, InherentIdentifier, IsFatalError, MakeFatalError,
+};
+use sp_runtime::transaction_validity::TransactionPriority;
+use sp_std::{vec, vec::Vec};
+use tuxedo_core::{
+    dynamic_typing::{DynamicallyTypedData, UtxoData, UtxoType},
+    ensure,
+    inherents::{InherentError, InherentHooks},
+    support_macros::{CloneNoBound, DebugNoBound, DefaultNoBound},
+    traits::Cash,
+    types::{Output, Transaction},
+    SimpleConstraintChecker, Verifier,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// The identifier under which the author's node supplies the verifier that protects the reward.
+pub const COINBASE_INHERENT_IDENTIFIER: InherentIdentifier = *b"coinbase";
+
+/// An inherent data provider that supplies the verifier that should protect this block's reward.
+/// This is typically a signature check with the block author's key.
+#[cfg(feature = "std")]
+pub struct CoinbaseInherentDataProvider<V>(pub V);
+
+#[cfg(feature = "std")]
+#[async_trait::async_trait]
+impl<V: Encode + Send + Sync> sp_inherents::InherentDataProvider
+    for CoinbaseInherentDataProvider<V>
+{
+    async fn provide_inherent_data(
+        &self,
+        inherent_data: &mut InherentData,
+    ) -> Result<(), sp_inherents::Error> {
+        inherent_data.put_data(COINBASE_INHERENT_IDENTIFIER, &self.0)
+    }
+
+    async fn try_handle_error(
+        &self,
+        _identifier: &InherentIdentifier,
+        _error: &[u8],
+    ) -> Option<Result<(), sp_inherents::Error>> {
+        // The coinbase inherent is not checked off-chain, so there are no errors to handle.
+        None
+    }
+}
+
+/// Options to configure the coinbase in your runtime.
+pub trait CoinbaseConfig {
+    /// A means of getting the current block height.
+    /// Probably this will be the Tuxedo Executive
+    fn block_height() -> u32;
+
+    /// The value that is newly minted to the author of each block.
+    const BLOCK_REWARD: u128;
+
+    /// The number of blocks after which a reward may be claimed.
+    ///
+    /// Default is 100.
+    const MATURITY: u32 = 100;
+
+    /// The fees that have been collected since the previous coinbase, and are paid out with the
+    /// next reward. Probably this will come from the executive's fee policy, which should also
+    /// clear them once the coinbase has been applied.
+    ///
+    /// Default is that no fees are collected.
+    fn collected_fees() -> u128 {
+        0
+    }
+
+    /// Whether the given verifier belongs to the author of a block in the given Aura slot.
+    /// Probably this is a signature check with the key of the Aura authority that owns the slot.
+    ///
+    /// Default is that any verifier will do, so the author may direct its reward wherever it likes.
+    fn is_slot_author<V: Verifier>(_slot: Slot, _verifier: &V) -> bool {
+        true
+    }
+}
+
+/// A reward for authoring a block, which may be claimed as coins once it has matured.
+/// Its verifier was chosen by the block author.
+#[derive(
+    Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, TypeInfo, UtxoData,
+)]
+pub struct BlockReward {
+    /// The value of the reward, including the fees that were paid out with it.
+    pub value: u128,
+    /// The block height from which the reward may be claimed.
+    pub matures_at: u32,
+}
+
+/// Errors that can occur when checking coinbase transactions.
+#[derive(
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Encode,
+    Decode,
+    Hash,
+    Debug,
+    TypeInfo,
+)]
+pub enum CoinbaseError {
+    /// An input data has the wrong type.
+    BadlyTypedInput,
+    /// An output data has the wrong type.
+    BadlyTypedOutput,
+    /// Wrong number of inputs were provided to the constraint checker.
+    WrongNumberInputs,
+    /// Wrong number of outputs were provided to the constraint checker.
+    WrongNumberOutputs,
+    /// Rewards may only be claimed normally, not evicted.
+    NoEvictionsAllowed,
+    /// The coinbase inherent must not consume or peek at anything.
+    CoinbaseConsumesState,
+    /// The reward is not worth exactly the block reward plus the collected fees.
+    WrongRewardValue,
+    /// The reward does not mature after exactly the maturity period.
+    WrongMaturity,
+    /// The reward may not be claimed until it has matured.
+    RewardNotMature,
+    /// The transaction attempted to create a coin of zero value.
+    ZeroValueCoin,
+    /// The value of the rewards or coins overflows the value type.
+    ValueOverflow,
+    /// The claimed coins are not worth exactly as much as the rewards.
+    ValueNotPreserved,
+    /// The reward is not protected by the verifier of the author of the block's Aura slot.
+    RewardNotToAuthor,
+}
+
+impl IsFatalError for CoinbaseError {
+    fn is_fatal_error(&self) -> bool {
+        true
+    }
+}
+
+/// The inherent that rewards the block author.
+///
+/// It does not consume or peek at anything. When the block reward plus the collected fees is
+/// worth anything, its only output is the [`BlockReward`]. Otherwise it creates nothing.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DebugNoBound,
+    DefaultNoBound,
+    CloneNoBound,
+    PartialEq,
+    Eq,
+    TypeInfo,
+)]
+#[scale_info(skip_type_params(T))]
+pub struct Coinbase<T>(PhantomData<T>);
+
+impl<T: CoinbaseConfig> Coinbase<T> {
+    /// The reward that the current block's coinbase must create, if any.
+    pub fn expected_reward() -> Option<BlockReward> {
+        let value = T::BLOCK_REWARD.saturating_add(T::collected_fees());
+        (value > 0).then(|| BlockReward {
+            value,
+            matures_at: T::block_height().saturating_add(T::MATURITY),
+        })
+    }
+}
+
+impl<T: CoinbaseConfig> SimpleConstraintChecker for Coinbase<T> {
+    type Error = CoinbaseError;
+
+    fn check(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        evicted_input_data: &[DynamicallyTypedData],
+        peek_data: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> Result<TransactionPriority, Self::Error> {
+        ensure!(
+            input_data.is_empty() && evicted_input_data.is_empty() && peek_data.is_empty(),
+            CoinbaseError::CoinbaseConsumesState
+        );
+
+        let Some(expected) = Self::expected_reward() else {
+            ensure!(output_data.is_empty(), CoinbaseError::WrongNumberOutputs);
+            return Ok(0);
+        };
+
+        ensure!(output_data.len() == 1, CoinbaseError::WrongNumberOutputs);
+        let reward = output_data[0]
+            .extract::<BlockReward>()
+            .map_err(|_| CoinbaseError::BadlyTypedOutput)?;
+        ensure!(
+            reward.value == expected.value,
+            CoinbaseError::WrongRewardValue
+        );
+        ensure!(
+            reward.matures_at == expected.matures_at,
+            CoinbaseError::WrongMaturity
+        );
+
+        Ok(0)
+    }
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![UtxoType::of::<BlockReward>()]
+    }
+
+    // The coinbase is an inherent, so it always stands alone.
+    const COMPOUNDABLE: bool = false;
+}
+
+impl<T: CoinbaseConfig + 'static> InherentHooks for Coinbase<T> {
+    type Error = MakeFatalError<()>;
+    const INHERENT_IDENTIFIER: InherentIdentifier = COINBASE_INHERENT_IDENTIFIER;
+
+    fn create_inherent<V: Verifier>(
+        authoring_inherent_data: &InherentData,
+        _previous_inherent: (Transaction<V, Self>, H256),
+    ) -> Result<Transaction<V, Self>, InherentError> {
+        let outputs = match Self::expected_reward() {
+            Some(reward) => {
+                // If the author did not say where the reward should go, it is burned.
+                let verifier = authoring_inherent_data
+                    .get_data::<V>(&COINBASE_INHERENT_IDENTIFIER)
+                    .map_err(|_| InherentError::MissingInherentData)?
+                    .or_else(V::new_unspendable)
+                    .ok_or(InherentError::NoUnspendableVerifier)?;
+
+                vec![Output {
+                    payload: reward.into(),
+                    verifier,
+                }]
+            }
+            None => Vec::new(),
+        };
+
+        Ok(Transaction {
+            inputs: Vec::new(),
+            peeks: Vec::new(),
+            outputs,
+            checker: Self::default(),
+        })
+    }
+
+    fn check_inherent<V: Verifier>(
+        importing_inherent_data: &InherentData,
+        inherent: Transaction<V, Self>,
+        result: &mut CheckInherentsResult,
+    ) {
+        // When importing, Aura supplies the slot from the block's pre-runtime digest. Without Aura,
+        // for example with proof of work, nobody owns the slot, so there is nothing to check.
+        let slot = match importing_inherent_data.get_data::<Slot>(&AURA_SLOT_IDENTIFIER) {
+            Ok(Some(slot)) => slot,
+            Ok(None) => return,
+            Err(_) => {
+                result
+                    .put_error(
+                        Self::INHERENT_IDENTIFIER,
+                        &InherentError::MissingInherentData,
+                    )
+                    .expect("Should be able to push some error");
+                return;
+            }
+        };
+
+        // The reward itself is checked on-chain. Here we only check who it goes to.
+        let Some(reward) = inherent.outputs.first() else {
+            return;
+        };
+        if !T::is_slot_author(slot, &reward.verifier) {
+            result
+                .put_error(Self::INHERENT_IDENTIFIER, &CoinbaseError::RewardNotToAuthor)
+                .expect("Should be able to push some error");
+        }
+    }
+
+    fn genesis_transactions<V: Verifier>() -> Vec<Transaction<V, Self>> {
+        // Nobody authored the genesis block, so there is nothing to reward.
+        vec![Transaction {
+            inputs: Vec::new(),
+            peeks: Vec::new(),
+            outputs: Vec::new(),
+            checker: Self::default(),
+        }]
+    }
+}
+
+/// Claim matured block rewards as coins of type `C`.
+///
+/// The inputs are rewards, which are consumed normally so the author's verifier must be
+/// satisfied. The outputs are coins worth exactly as much as the rewards, split however the
+/// claimant likes.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DebugNoBound,
+    DefaultNoBound,
+    CloneNoBound,
+    PartialEq,
+    Eq,
+    TypeInfo,
+)]
+#[scale_info(skip_type_params(C, T))]
+pub struct ClaimReward<C, T>(PhantomData<(C, T)>);
+
+impl<C: Cash + UtxoData, T: CoinbaseConfig> SimpleConstraintChecker for ClaimReward<C, T> {
+    type Error = CoinbaseError;
+
+    fn check(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        evicted_input_data: &[DynamicallyTypedData],
+        _peeks: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> Result<TransactionPriority, Self::Error> {
+        ensure!(
+            evicted_input_data.is_empty(),
+            CoinbaseError::NoEvictionsAllowed
+        );
+        ensure!(!input_data.is_empty(), CoinbaseError::WrongNumberInputs);
+
+        // Add up the matured rewards
+        let mut total_rewards: u128 = 0;
+        for input in input_data {
+            let reward = input
+                .extract::<BlockReward>()
+                .map_err(|_| CoinbaseError::BadlyTypedInput)?;
+            ensure!(
+                T::block_height() >= reward.matures_at,
+                CoinbaseError::RewardNotMature
+            );
+            total_rewards = total_rewards
+                .checked_add(reward.value)
+                .ok_or(CoinbaseError::ValueOverflow)?;
+        }
+
+        // Add up the claimed coins
+        ensure!(!output_data.is_empty(), CoinbaseError::WrongNumberOutputs);
+        let mut total_coins: u128 = 0;
+        for output in output_data {
+            let value = output
+                .extract::<C>()
+                .map_err(|_| CoinbaseError::BadlyTypedOutput)?
+                .value();
+            ensure!(value > 0, CoinbaseError::ZeroValueCoin);
+            total_coins = total_coins
+                .checked_add(value)
+                .ok_or(CoinbaseError::ValueOverflow)?;
+        }
+
+        ensure!(
+            total_coins == total_rewards,
+            CoinbaseError::ValueNotPreserved
+        );
+
+        Ok(0)
+    }
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![UtxoType::of::<BlockReward>(), UtxoType::of::<C>()]
+    }
+}
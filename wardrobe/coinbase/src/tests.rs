@@ -0,0 +1,437 @@
+//! Unit tests for the coinbase piece
+
+use super::*;
+use tuxedo_core::{
+    dynamic_typing::testing::{Bogus, Gold},
+    verifier::{TestVerifier, Unspendable, UpForGrabs},
+};
+use CoinbaseError::*;
+
+/// The mock config always says the block number is two hundred, and that 5 in fees were collected.
+/// Every slot is authored by the test verifier that verifies.
+pub struct AlwaysBlockTwoHundred;
+
+impl CoinbaseConfig for AlwaysBlockTwoHundred {
+    fn block_height() -> u32 {
+        200
+    }
+
+    const BLOCK_REWARD: u128 = 50;
+    const MATURITY: u32 = 10;
+
+    fn collected_fees() -> u128 {
+        5
+    }
+
+    fn is_slot_author<V: Verifier>(_slot: Slot, verifier: &V) -> bool {
+        verifier.encode() == TestVerifier { verifies: true }.encode()
+    }
+}
+
+/// A config with no block reward and the default of no fees, so there is nothing to pay out.
+pub struct NoReward;
+
+impl CoinbaseConfig for NoReward {
+    fn block_height() -> u32 {
+        200
+    }
+
+    const BLOCK_REWARD: u128 = 0;
+}
+
+type Claim = ClaimReward<Gold, AlwaysBlockTwoHundred>;
+
+fn reward(value: u128, matures_at: u32) -> DynamicallyTypedData {
+    BlockReward { value, matures_at }.into()
+}
+
+#[test]
+fn coinbase_pays_reward_and_fees_works() {
+    let outputs = vec![reward(55, 210)];
+
+    assert_eq!(
+        Coinbase::<AlwaysBlockTwoHundred>::default().check(&[], &[], &[], &outputs),
+        Ok(0)
+    );
+}
+
+#[test]
+fn coinbase_with_nothing_to_pay_creates_nothing() {
+    assert_eq!(
+        Coinbase::<NoReward>::default().check(&[], &[], &[], &[]),
+        Ok(0)
+    );
+}
+
+#[test]
+fn coinbase_with_nothing_to_pay_may_not_create_reward() {
+    let outputs = vec![reward(0, 300)];
+
+    assert_eq!(
+        Coinbase::<NoReward>::default().check(&[], &[], &[], &outputs),
+        Err(WrongNumberOutputs)
+    );
+}
+
+#[test]
+fn coinbase_without_reward_fails() {
+    assert_eq!(
+        Coinbase::<AlwaysBlockTwoHundred>::default().check(&[], &[], &[], &[]),
+        Err(WrongNumberOutputs)
+    );
+}
+
+#[test]
+fn coinbase_with_two_rewards_fails() {
+    let outputs = vec![reward(55, 210), reward(55, 210)];
+
+    assert_eq!(
+        Coinbase::<AlwaysBlockTwoHundred>::default().check(&[], &[], &[], &outputs),
+        Err(WrongNumberOutputs)
+    );
+}
+
+#[test]
+fn coinbase_with_badly_typed_output_fails() {
+    let outputs = vec![Bogus.into()];
+
+    assert_eq!(
+        Coinbase::<AlwaysBlockTwoHundred>::default().check(&[], &[], &[], &outputs),
+        Err(BadlyTypedOutput)
+    );
+}
+
+#[test]
+fn coinbase_ignoring_fees_fails() {
+    let outputs = vec![reward(50, 210)];
+
+    assert_eq!(
+        Coinbase::<AlwaysBlockTwoHundred>::default().check(&[], &[], &[], &outputs),
+        Err(WrongRewardValue)
+    );
+}
+
+#[test]
+fn coinbase_minting_too_much_fails() {
+    let outputs = vec![reward(56, 210)];
+
+    assert_eq!(
+        Coinbase::<AlwaysBlockTwoHundred>::default().check(&[], &[], &[], &outputs),
+        Err(WrongRewardValue)
+    );
+}
+
+#[test]
+fn coinbase_maturing_early_fails() {
+    let outputs = vec![reward(55, 209)];
+
+    assert_eq!(
+        Coinbase::<AlwaysBlockTwoHundred>::default().check(&[], &[], &[], &outputs),
+        Err(WrongMaturity)
+    );
+}
+
+#[test]
+fn coinbase_consuming_input_fails() {
+    let inputs = vec![reward(55, 100)];
+    let outputs = vec![reward(55, 210)];
+
+    assert_eq!(
+        Coinbase::<AlwaysBlockTwoHundred>::default().check(&inputs, &[], &[], &outputs),
+        Err(CoinbaseConsumesState)
+    );
+}
+
+#[test]
+fn coinbase_evicting_fails() {
+    let evictions = vec![reward(55, 100)];
+    let outputs = vec![reward(55, 210)];
+
+    assert_eq!(
+        Coinbase::<AlwaysBlockTwoHundred>::default().check(&[], &evictions, &[], &outputs),
+        Err(CoinbaseConsumesState)
+    );
+}
+
+#[test]
+fn coinbase_peeking_fails() {
+    let peeks = vec![reward(55, 100)];
+    let outputs = vec![reward(55, 210)];
+
+    assert_eq!(
+        Coinbase::<AlwaysBlockTwoHundred>::default().check(&[], &[], &peeks, &outputs),
+        Err(CoinbaseConsumesState)
+    );
+}
+
+#[test]
+fn create_inherent_pays_the_supplied_verifier() {
+    let author = TestVerifier { verifies: true };
+    let mut inherent_data = InherentData::new();
+    inherent_data
+        .put_data(COINBASE_INHERENT_IDENTIFIER, &author)
+        .unwrap();
+    let previous = Coinbase::<AlwaysBlockTwoHundred>::genesis_transactions::<TestVerifier>()
+        .pop()
+        .unwrap();
+
+    let tx = Coinbase::<AlwaysBlockTwoHundred>::create_inherent(
+        &inherent_data,
+        (previous, H256::zero()),
+    )
+    .unwrap();
+
+    assert!(tx.inputs.is_empty());
+    assert!(tx.peeks.is_empty());
+    assert_eq!(tx.outputs, vec![(reward(55, 210), author).into()]);
+}
+
+#[test]
+fn create_inherent_without_author_burns_the_reward() {
+    let previous = Coinbase::<AlwaysBlockTwoHundred>::genesis_transactions::<Unspendable>()
+        .pop()
+        .unwrap();
+
+    let tx = Coinbase::<AlwaysBlockTwoHundred>::create_inherent(
+        &InherentData::new(),
+        (previous, H256::zero()),
+    )
+    .unwrap();
+
+    assert_eq!(tx.outputs, vec![(reward(55, 210), Unspendable).into()]);
+}
+
+#[test]
+fn create_inherent_with_undecodable_author_fails() {
+    let mut inherent_data = InherentData::new();
+    // Not a valid encoding of the test verifier's boolean.
+    inherent_data
+        .put_data(COINBASE_INHERENT_IDENTIFIER, &2u8)
+        .unwrap();
+    let previous = Coinbase::<AlwaysBlockTwoHundred>::genesis_transactions::<TestVerifier>()
+        .pop()
+        .unwrap();
+
+    assert_eq!(
+        Coinbase::<AlwaysBlockTwoHundred>::create_inherent(
+            &inherent_data,
+            (previous, H256::zero()),
+        )
+        .err(),
+        Some(InherentError::MissingInherentData)
+    );
+}
+
+#[test]
+fn create_inherent_with_nothing_to_pay_needs_no_verifier() {
+    let previous = Coinbase::<NoReward>::genesis_transactions::<UpForGrabs>()
+        .pop()
+        .unwrap();
+
+    let tx = Coinbase::<NoReward>::create_inherent(&InherentData::new(), (previous, H256::zero()))
+        .unwrap();
+
+    assert!(tx.outputs.is_empty());
+}
+
+/// Inherent data in which Aura supplies the given slot.
+fn inherent_data_in_slot(slot: u64) -> InherentData {
+    let mut inherent_data = InherentData::new();
+    inherent_data
+        .put_data(AURA_SLOT_IDENTIFIER, &Slot::from(slot))
+        .unwrap();
+    inherent_data
+}
+
+/// A coinbase that pays the expected reward to the given verifier.
+fn coinbase_paying(
+    verifier: TestVerifier,
+) -> Transaction<TestVerifier, Coinbase<AlwaysBlockTwoHundred>> {
+    Transaction {
+        inputs: Vec::new(),
+        peeks: Vec::new(),
+        outputs: vec![(reward(55, 210), verifier).into()],
+        checker: Coinbase::default(),
+    }
+}
+
+#[test]
+fn check_inherent_paying_slot_author_works() {
+    let mut result = CheckInherentsResult::new();
+    Coinbase::<AlwaysBlockTwoHundred>::check_inherent(
+        &inherent_data_in_slot(7),
+        coinbase_paying(TestVerifier { verifies: true }),
+        &mut result,
+    );
+
+    assert!(result.ok());
+}
+
+#[test]
+fn check_inherent_paying_someone_else_fails() {
+    let mut result = CheckInherentsResult::new();
+    Coinbase::<AlwaysBlockTwoHundred>::check_inherent(
+        &inherent_data_in_slot(7),
+        coinbase_paying(TestVerifier { verifies: false }),
+        &mut result,
+    );
+
+    assert!(result.fatal_error());
+    assert_eq!(
+        result
+            .get_error::<CoinbaseError>(&COINBASE_INHERENT_IDENTIFIER)
+            .unwrap(),
+        Some(RewardNotToAuthor)
+    );
+}
+
+#[test]
+fn check_inherent_without_slot_accepts_any_author() {
+    let mut result = CheckInherentsResult::new();
+    Coinbase::<AlwaysBlockTwoHundred>::check_inherent(
+        &InherentData::new(),
+        coinbase_paying(TestVerifier { verifies: false }),
+        &mut result,
+    );
+
+    assert!(result.ok());
+}
+
+#[test]
+fn check_inherent_with_undecodable_slot_fails() {
+    let mut inherent_data = InherentData::new();
+    inherent_data.put_data(AURA_SLOT_IDENTIFIER, &1u8).unwrap();
+
+    let mut result = CheckInherentsResult::new();
+    Coinbase::<AlwaysBlockTwoHundred>::check_inherent(
+        &inherent_data,
+        coinbase_paying(TestVerifier { verifies: true }),
+        &mut result,
+    );
+
+    assert!(result.fatal_error());
+}
+
+#[test]
+fn claim_matured_reward_works() {
+    let inputs = vec![reward(55, 200)];
+    let outputs = vec![Gold(55).into()];
+
+    assert_eq!(Claim::default().check(&inputs, &[], &[], &outputs), Ok(0));
+}
+
+#[test]
+fn claim_several_rewards_into_several_coins_works() {
+    let inputs = vec![reward(55, 150), reward(45, 190)];
+    let outputs = vec![Gold(30).into(), Gold(70).into()];
+
+    assert_eq!(Claim::default().check(&inputs, &[], &[], &outputs), Ok(0));
+}
+
+#[test]
+fn claim_immature_reward_fails() {
+    let inputs = vec![reward(55, 150), reward(55, 201)];
+    let outputs = vec![Gold(110).into()];
+
+    assert_eq!(
+        Claim::default().check(&inputs, &[], &[], &outputs),
+        Err(RewardNotMature)
+    );
+}
+
+#[test]
+fn claim_nothing_fails() {
+    let outputs = vec![Gold(55).into()];
+
+    assert_eq!(
+        Claim::default().check(&[], &[], &[], &outputs),
+        Err(WrongNumberInputs)
+    );
+}
+
+#[test]
+fn claim_without_coins_fails() {
+    let inputs = vec![reward(55, 200)];
+
+    assert_eq!(
+        Claim::default().check(&inputs, &[], &[], &[]),
+        Err(WrongNumberOutputs)
+    );
+}
+
+#[test]
+fn claim_evicted_reward_fails() {
+    let evictions = vec![reward(55, 200)];
+    let outputs = vec![Gold(55).into()];
+
+    assert_eq!(
+        Claim::default().check(&[], &evictions, &[], &outputs),
+        Err(NoEvictionsAllowed)
+    );
+}
+
+#[test]
+fn claim_badly_typed_input_fails() {
+    let inputs = vec![Bogus.into()];
+    let outputs = vec![Gold(55).into()];
+
+    assert_eq!(
+        Claim::default().check(&inputs, &[], &[], &outputs),
+        Err(BadlyTypedInput)
+    );
+}
+
+#[test]
+fn claim_badly_typed_output_fails() {
+    let inputs = vec![reward(55, 200)];
+    let outputs = vec![Bogus.into()];
+
+    assert_eq!(
+        Claim::default().check(&inputs, &[], &[], &outputs),
+        Err(BadlyTypedOutput)
+    );
+}
+
+#[test]
+fn claim_zero_value_coin_fails() {
+    let inputs = vec![reward(55, 200)];
+    let outputs = vec![Gold(55).into(), Gold(0).into()];
+
+    assert_eq!(
+        Claim::default().check(&inputs, &[], &[], &outputs),
+        Err(ZeroValueCoin)
+    );
+}
+
+#[test]
+fn claim_too_much_fails() {
+    let inputs = vec![reward(55, 200)];
+    let outputs = vec![Gold(56).into()];
+
+    assert_eq!(
+        Claim::default().check(&inputs, &[], &[], &outputs),
+        Err(ValueNotPreserved)
+    );
+}
+
+#[test]
+fn claim_too_little_fails() {
+    let inputs = vec![reward(55, 200)];
+    let outputs = vec![Gold(54).into()];
+
+    assert_eq!(
+        Claim::default().check(&inputs, &[], &[], &outputs),
+        Err(ValueNotPreserved)
+    );
+}
+
+#[test]
+fn claim_overflowing_rewards_fails() {
+    let inputs = vec![reward(u128::MAX, 200), reward(1, 200)];
+    let outputs = vec![Gold(55).into()];
+
+    assert_eq!(
+        Claim::default().check(&inputs, &[], &[], &outputs),
+        Err(ValueOverflow)
+    );
+}
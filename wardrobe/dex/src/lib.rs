@@ -0,0 +1,378 @@
+//! An order book exchange between two kinds of coins.
+//!
+//! A maker locks some coins of type `A` into an [`Offer`] that states how many coins of type `B`
+//! they want for them. The offer is protected by the maker's own verifier, so the maker may cancel
+//! it at any time simply by consuming it.
+//!
+//! A taker fills an offer by evicting it, which does not require satisfying its verifier. The
+//! transaction must pay the maker in its first output, which is protected by the very same verifier
+//! as the offer. The taker may fill only part of the offer, in which case the second output is the
+//! residual offer, also protected by the maker's verifier, at the same price. The rest of the outputs
+//! are the taker's `A` coins along with any `B` change.
+//!
+//! Any two coin types that implement the `Cash` trait may be traded. Each runtime chooses which
+//! pairs to support by including the corresponding constraint checkers. Several offers can be filled
+//! atomically by combining take transactions with the compound constraint checker.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use core::marker::PhantomData;
+
+use parity_scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use serde::{Deserialize, Serialize};
+use sp_runtime::transaction_validity::TransactionPriority;
+use sp_std::{vec, vec::Vec};
+use tuxedo_core::{
+    dynamic_typing::{DynamicallyTypedData, UtxoData, UtxoType},
+    ensure,
+    support_macros::{CloneNoBound, DebugNoBound, DefaultNoBound},
+    traits::{Cash, CashError},
+    types::Output,
+    SimpleConstraintChecker, Verifier, VerifierAwareConstraintChecker,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// An offer to sell `offered` coins of type `A` for `asked` coins of type `B`.
+///
+/// The offer's verifier belongs to the maker. It is used both to cancel the offer
+/// and to receive payment when the offer is taken.
+#[derive(
+    Serialize, Deserialize, Encode, Decode, DebugNoBound, CloneNoBound, PartialEq, Eq, TypeInfo,
+)]
+#[scale_info(skip_type_params(A, B))]
+pub struct Offer<A, B> {
+    /// The value of `A` coins locked in this offer.
+    pub offered: u128,
+    /// The value of `B` coins the maker wants in exchange for all of the offered coins.
+    pub asked: u128,
+    #[serde(skip)]
+    _phantom: PhantomData<(A, B)>,
+}
+
+impl<A, B> Offer<A, B> {
+    pub fn new(offered: u128, asked: u128) -> Self {
+        Self {
+            offered,
+            asked,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// The value of `B` coins that must be paid to take `amount` of the offered coins.
+    ///
+    /// The price is rounded up so that partial fills never favor the taker.
+    pub fn price_of(&self, amount: u128) -> Result<u128, DexError> {
+        ensure!(self.offered > 0, DexError::ZeroValueOffer);
+        let numerator = amount
+            .checked_mul(self.asked)
+            .and_then(|n| n.checked_add(self.offered - 1))
+            .ok_or(DexError::ValueOverflow)?;
+        Ok(numerator / self.offered)
+    }
+}
+
+// The derive does not mix in type parameters, so each trading pair's coin ids are included by hand.
+impl<A: Cash, B: Cash> UtxoData for Offer<A, B> {
+    const TYPE_ID: [u8; 4] = [b'o', b'f', A::ID, B::ID];
+}
+
+/// Errors that can occur when checking exchange transactions.
+#[derive(
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Encode,
+    Decode,
+    Hash,
+    Debug,
+    TypeInfo,
+)]
+pub enum DexError {
+    /// An input data has the wrong type.
+    BadlyTypedInput,
+    /// An output data has the wrong type.
+    BadlyTypedOutput,
+    /// Only take transactions may evict inputs, and they must evict exactly one offer.
+    WrongNumberOfOffers,
+    /// Coins can not be traded for coins of the same type.
+    SameCoinType,
+    /// A make transaction must create an offer as its first output.
+    MissingOffer,
+    /// Offers must offer and ask for a non-zero value.
+    ZeroValueOffer,
+    /// A cancel transaction does not consume any offers.
+    CancellingNothing,
+    /// A take transaction's first output must be a coin that pays the maker,
+    /// protected by the same verifier as the offer.
+    MakerNotPaid,
+    /// The maker was paid less than the price of the coins that were taken.
+    InsufficientPayment,
+    /// The residual offer left after a partial fill does not belong to the maker, does not
+    /// offer fewer coins than the original, or does not keep the original price.
+    InvalidResidualOffer,
+    /// The value of the output coins exceeds the value of the input coins.
+    OutputsExceedInputs,
+    /// The value consumed or created by this transaction overflows the value type.
+    ValueOverflow,
+    /// The transaction attempted to create a coin with zero value. This is not allowed
+    /// because it wastes state space.
+    ZeroValueCoin,
+}
+
+impl From<CashError> for DexError {
+    fn from(e: CashError) -> Self {
+        match e {
+            CashError::BadlyTyped => Self::BadlyTypedInput,
+            CashError::ZeroValueCoin => Self::ZeroValueCoin,
+            CashError::ValueOverflow => Self::ValueOverflow,
+        }
+    }
+}
+
+/// Lock coins of type `A` into an offer.
+///
+/// The inputs are the maker's `A` coins. The first output is the new offer and
+/// the remaining outputs are `A` coins returned to the maker as change.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DebugNoBound,
+    DefaultNoBound,
+    CloneNoBound,
+    PartialEq,
+    Eq,
+    TypeInfo,
+)]
+#[scale_info(skip_type_params(A, B))]
+pub struct MakeOffer<A, B>(PhantomData<(A, B)>);
+
+impl<A, B> SimpleConstraintChecker for MakeOffer<A, B>
+where
+    A: Cash + UtxoData + 'static,
+    B: Cash + UtxoData + 'static,
+{
+    type Error = DexError;
+
+    fn check(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        evicted_input_data: &[DynamicallyTypedData],
+        _peeks: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> Result<TransactionPriority, Self::Error> {
+        ensure!(A::ID != B::ID, DexError::SameCoinType);
+        ensure!(evicted_input_data.is_empty(), DexError::WrongNumberOfOffers);
+
+        let (offer, change) = output_data.split_first().ok_or(DexError::MissingOffer)?;
+        let offer = offer
+            .extract::<Offer<A, B>>()
+            .map_err(|_| DexError::MissingOffer)?;
+        ensure!(
+            offer.offered > 0 && offer.asked > 0,
+            DexError::ZeroValueOffer
+        );
+
+        let total_input_value = A::total_value(input_data)?;
+        let total_output_value = A::total_value(change)
+            .map_err(|e| match e {
+                CashError::BadlyTyped => DexError::BadlyTypedOutput,
+                e => e.into(),
+            })?
+            .checked_add(offer.offered)
+            .ok_or(DexError::ValueOverflow)?;
+        ensure!(
+            total_output_value <= total_input_value,
+            DexError::OutputsExceedInputs
+        );
+
+        Ok(0)
+    }
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![UtxoType::of::<Offer<A, B>>(), UtxoType::of::<A>()]
+    }
+}
+
+/// Cancel offers and reclaim the locked coins.
+///
+/// The inputs are the offers, which are consumed normally so the maker's verifier must be
+/// satisfied. The outputs are `A` coins worth no more than the offers held in total.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DebugNoBound,
+    DefaultNoBound,
+    CloneNoBound,
+    PartialEq,
+    Eq,
+    TypeInfo,
+)]
+#[scale_info(skip_type_params(A, B))]
+pub struct CancelOffer<A, B>(PhantomData<(A, B)>);
+
+impl<A, B> SimpleConstraintChecker for CancelOffer<A, B>
+where
+    A: Cash + UtxoData + 'static,
+    B: Cash + UtxoData + 'static,
+{
+    type Error = DexError;
+
+    fn check(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        evicted_input_data: &[DynamicallyTypedData],
+        _peeks: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> Result<TransactionPriority, Self::Error> {
+        ensure!(evicted_input_data.is_empty(), DexError::WrongNumberOfOffers);
+        ensure!(!input_data.is_empty(), DexError::CancellingNothing);
+
+        let mut total_offered: u128 = 0;
+        for offer in input_data {
+            let offer = offer
+                .extract::<Offer<A, B>>()
+                .map_err(|_| DexError::BadlyTypedInput)?;
+            total_offered = total_offered
+                .checked_add(offer.offered)
+                .ok_or(DexError::ValueOverflow)?;
+        }
+
+        let total_output_value = A::total_value(output_data).map_err(|e| match e {
+            CashError::BadlyTyped => DexError::BadlyTypedOutput,
+            e => e.into(),
+        })?;
+        ensure!(
+            total_output_value <= total_offered,
+            DexError::OutputsExceedInputs
+        );
+
+        Ok(0)
+    }
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![UtxoType::of::<Offer<A, B>>(), UtxoType::of::<A>()]
+    }
+}
+
+/// Fill all or part of an offer.
+///
+/// The single evicted input is the offer. The normal inputs are the taker's `B` coins.
+/// The first output pays the maker. If the offer is only partially filled, the second output
+/// is the residual offer. The remaining outputs may be any mix of `A` coins worth no more than
+/// the amount taken and `B` coins as change.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DebugNoBound,
+    DefaultNoBound,
+    CloneNoBound,
+    PartialEq,
+    Eq,
+    TypeInfo,
+)]
+#[scale_info(skip_type_params(A, B))]
+pub struct TakeOffer<A, B>(PhantomData<(A, B)>);
+
+impl<A, B> VerifierAwareConstraintChecker for TakeOffer<A, B>
+where
+    A: Cash + UtxoData + 'static,
+    B: Cash + UtxoData + 'static,
+{
+    type Error = DexError;
+
+    fn check<V: Verifier>(
+        &self,
+        inputs: &[Output<V>],
+        evicted_inputs: &[Output<V>],
+        _peeks: &[Output<V>],
+        outputs: &[Output<V>],
+    ) -> Result<TransactionPriority, Self::Error> {
+        ensure!(evicted_inputs.len() == 1, DexError::WrongNumberOfOffers);
+        let offer = evicted_inputs[0]
+            .payload
+            .extract::<Offer<A, B>>()
+            .map_err(|_| DexError::BadlyTypedInput)?;
+        let maker = evicted_inputs[0].verifier.encode();
+
+        // The maker is paid first.
+        let (payment, rest) = outputs.split_first().ok_or(DexError::MakerNotPaid)?;
+        ensure!(payment.verifier.encode() == maker, DexError::MakerNotPaid);
+        let payment = payment
+            .payload
+            .extract::<B>()
+            .map_err(|_| DexError::MakerNotPaid)?
+            .value();
+
+        // If there is a residual offer, it comes next and determines how much was taken.
+        let (taken, rest) = match rest.split_first() {
+            Some((residual_utxo, remaining)) => {
+                match residual_utxo.payload.extract::<Offer<A, B>>() {
+                    Ok(residual) => {
+                        ensure!(
+                            residual_utxo.verifier.encode() == maker
+                                && residual.offered > 0
+                                && residual.offered < offer.offered,
+                            DexError::InvalidResidualOffer
+                        );
+                        let taken = offer.offered - residual.offered;
+                        let price = offer.price_of(taken)?;
+                        ensure!(
+                            residual.asked > 0 && residual.asked == offer.asked - price,
+                            DexError::InvalidResidualOffer
+                        );
+                        (taken, remaining)
+                    }
+                    Err(_) => (offer.offered, rest),
+                }
+            }
+            None => (offer.offered, rest),
+        };
+        ensure!(
+            payment >= offer.price_of(taken)?,
+            DexError::InsufficientPayment
+        );
+
+        // The taker may not receive more than they took, or spend more than they brought.
+        let mut total_a_output: u128 = 0;
+        let mut total_b_output: u128 = payment;
+        for utxo in rest {
+            let (total, value) = if let Ok(coin) = utxo.payload.extract::<A>() {
+                (&mut total_a_output, coin.value())
+            } else if let Ok(coin) = utxo.payload.extract::<B>() {
+                (&mut total_b_output, coin.value())
+            } else {
+                return Err(DexError::BadlyTypedOutput);
+            };
+            ensure!(value > 0, DexError::ZeroValueCoin);
+            *total = total.checked_add(value).ok_or(DexError::ValueOverflow)?;
+        }
+        let total_b_input = B::total_value(inputs.iter().map(|input| &input.payload))?;
+        ensure!(
+            total_a_output <= taken && total_b_output <= total_b_input,
+            DexError::OutputsExceedInputs
+        );
+
+        Ok(0)
+    }
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![
+            UtxoType::of::<Offer<A, B>>(),
+            UtxoType::of::<A>(),
+            UtxoType::of::<B>(),
+        ]
+    }
+}
@@ -0,0 +1,378 @@
+//! Unit tests for the DEX piece
+
+use super::*;
+use tuxedo_core::{
+    dynamic_typing::testing::{Bogus, Gold},
+    verifier::TestVerifier,
+};
+
+/// The coin that makers ask for in these tests.
+#[derive(Encode, Decode, Debug, PartialEq, Eq, Clone)]
+struct Silver(u128);
+
+impl UtxoData for Silver {
+    const TYPE_ID: [u8; 4] = *b"slvr";
+}
+
+impl Cash for Silver {
+    fn value(&self) -> u128 {
+        self.0
+    }
+
+    const ID: u8 = 2;
+}
+
+type GoldForSilver = Offer<Gold, Silver>;
+
+/// The maker is represented by a verifier that passes, and the taker by one that doesn't.
+const MAKER: TestVerifier = TestVerifier { verifies: true };
+const TAKER: TestVerifier = TestVerifier { verifies: false };
+
+fn take(
+    inputs: &[Output<TestVerifier>],
+    offer: Output<TestVerifier>,
+    outputs: &[Output<TestVerifier>],
+) -> Result<TransactionPriority, DexError> {
+    VerifierAwareConstraintChecker::check(
+        &TakeOffer::<Gold, Silver>::default(),
+        inputs,
+        &[offer],
+        &[],
+        outputs,
+    )
+}
+
+#[test]
+fn price_rounds_up() {
+    let offer = GoldForSilver::new(3, 10);
+    assert_eq!(offer.price_of(3), Ok(10));
+    assert_eq!(offer.price_of(1), Ok(4));
+    assert_eq!(offer.price_of(0), Ok(0));
+}
+
+#[test]
+fn make_offer_with_change_works() {
+    let input_data = vec![Gold(5).into(), Gold(7).into()];
+    let output_data = vec![GoldForSilver::new(10, 20).into(), Gold(2).into()];
+
+    assert_eq!(
+        SimpleConstraintChecker::check(
+            &MakeOffer::<Gold, Silver>::default(),
+            &input_data,
+            &[],
+            &[],
+            &output_data
+        ),
+        Ok(0)
+    );
+}
+
+#[test]
+fn make_offer_worth_more_than_inputs_fails() {
+    let input_data = vec![Gold(5).into()];
+    let output_data = vec![GoldForSilver::new(6, 20).into()];
+
+    assert_eq!(
+        SimpleConstraintChecker::check(
+            &MakeOffer::<Gold, Silver>::default(),
+            &input_data,
+            &[],
+            &[],
+            &output_data
+        ),
+        Err(DexError::OutputsExceedInputs)
+    );
+}
+
+#[test]
+fn make_offer_with_wrong_input_type_fails() {
+    let input_data = vec![Silver(5).into()];
+    let output_data = vec![GoldForSilver::new(5, 20).into()];
+
+    assert_eq!(
+        SimpleConstraintChecker::check(
+            &MakeOffer::<Gold, Silver>::default(),
+            &input_data,
+            &[],
+            &[],
+            &output_data
+        ),
+        Err(DexError::BadlyTypedInput)
+    );
+}
+
+#[test]
+fn make_offer_without_offer_fails() {
+    let input_data = vec![Gold(5).into()];
+    let output_data = vec![Gold(5).into()];
+
+    assert_eq!(
+        SimpleConstraintChecker::check(
+            &MakeOffer::<Gold, Silver>::default(),
+            &input_data,
+            &[],
+            &[],
+            &output_data
+        ),
+        Err(DexError::MissingOffer)
+    );
+}
+
+#[test]
+fn make_offer_for_nothing_fails() {
+    let input_data = vec![Gold(5).into()];
+    let output_data = vec![GoldForSilver::new(5, 0).into()];
+
+    assert_eq!(
+        SimpleConstraintChecker::check(
+            &MakeOffer::<Gold, Silver>::default(),
+            &input_data,
+            &[],
+            &[],
+            &output_data
+        ),
+        Err(DexError::ZeroValueOffer)
+    );
+}
+
+#[test]
+fn make_offer_for_same_coin_fails() {
+    let input_data = vec![Gold(5).into()];
+    let output_data = vec![Offer::<Gold, Gold>::new(5, 5).into()];
+
+    assert_eq!(
+        SimpleConstraintChecker::check(
+            &MakeOffer::<Gold, Gold>::default(),
+            &input_data,
+            &[],
+            &[],
+            &output_data
+        ),
+        Err(DexError::SameCoinType)
+    );
+}
+
+#[test]
+fn cancel_offers_works() {
+    let input_data = vec![
+        GoldForSilver::new(5, 20).into(),
+        GoldForSilver::new(3, 1).into(),
+    ];
+    let output_data = vec![Gold(8).into()];
+
+    assert_eq!(
+        SimpleConstraintChecker::check(
+            &CancelOffer::<Gold, Silver>::default(),
+            &input_data,
+            &[],
+            &[],
+            &output_data
+        ),
+        Ok(0)
+    );
+}
+
+#[test]
+fn cancel_offer_reclaiming_too_much_fails() {
+    let input_data = vec![GoldForSilver::new(5, 20).into()];
+    let output_data = vec![Gold(6).into()];
+
+    assert_eq!(
+        SimpleConstraintChecker::check(
+            &CancelOffer::<Gold, Silver>::default(),
+            &input_data,
+            &[],
+            &[],
+            &output_data
+        ),
+        Err(DexError::OutputsExceedInputs)
+    );
+}
+
+#[test]
+fn cancel_nothing_fails() {
+    assert_eq!(
+        SimpleConstraintChecker::check(&CancelOffer::<Gold, Silver>::default(), &[], &[], &[], &[]),
+        Err(DexError::CancellingNothing)
+    );
+}
+
+#[test]
+fn cancel_with_wrong_input_type_fails() {
+    let input_data = vec![Gold(5).into()];
+
+    assert_eq!(
+        SimpleConstraintChecker::check(
+            &CancelOffer::<Gold, Silver>::default(),
+            &input_data,
+            &[],
+            &[],
+            &[]
+        ),
+        Err(DexError::BadlyTypedInput)
+    );
+}
+
+#[test]
+fn take_whole_offer_works() {
+    assert_eq!(
+        take(
+            &[(Silver(25), TAKER).into()],
+            (GoldForSilver::new(10, 20), MAKER).into(),
+            &[
+                (Silver(20), MAKER).into(),
+                (Gold(10), TAKER).into(),
+                (Silver(5), TAKER).into(),
+            ]
+        ),
+        Ok(0)
+    );
+}
+
+#[test]
+fn take_part_of_offer_works() {
+    assert_eq!(
+        take(
+            &[(Silver(10), TAKER).into()],
+            (GoldForSilver::new(10, 20), MAKER).into(),
+            &[
+                (Silver(8), MAKER).into(),
+                (GoldForSilver::new(6, 12), MAKER).into(),
+                (Gold(4), TAKER).into(),
+                (Silver(2), TAKER).into(),
+            ]
+        ),
+        Ok(0)
+    );
+}
+
+#[test]
+fn take_without_paying_maker_fails() {
+    assert_eq!(
+        take(
+            &[(Silver(20), TAKER).into()],
+            (GoldForSilver::new(10, 20), MAKER).into(),
+            &[(Silver(20), TAKER).into(), (Gold(10), TAKER).into()]
+        ),
+        Err(DexError::MakerNotPaid)
+    );
+}
+
+#[test]
+fn take_with_underpayment_fails() {
+    assert_eq!(
+        take(
+            &[(Silver(20), TAKER).into()],
+            (GoldForSilver::new(10, 20), MAKER).into(),
+            &[(Silver(19), MAKER).into(), (Gold(10), TAKER).into()]
+        ),
+        Err(DexError::InsufficientPayment)
+    );
+}
+
+#[test]
+fn take_more_than_offered_fails() {
+    assert_eq!(
+        take(
+            &[(Silver(20), TAKER).into()],
+            (GoldForSilver::new(10, 20), MAKER).into(),
+            &[(Silver(20), MAKER).into(), (Gold(11), TAKER).into()]
+        ),
+        Err(DexError::OutputsExceedInputs)
+    );
+}
+
+#[test]
+fn take_paying_with_value_not_brought_fails() {
+    assert_eq!(
+        take(
+            &[(Silver(15), TAKER).into()],
+            (GoldForSilver::new(10, 20), MAKER).into(),
+            &[(Silver(20), MAKER).into(), (Gold(10), TAKER).into()]
+        ),
+        Err(DexError::OutputsExceedInputs)
+    );
+}
+
+#[test]
+fn take_with_residual_at_worse_price_fails() {
+    // Taking 4 gold costs 8 silver, so the residual must ask for 12.
+    assert_eq!(
+        take(
+            &[(Silver(10), TAKER).into()],
+            (GoldForSilver::new(10, 20), MAKER).into(),
+            &[
+                (Silver(8), MAKER).into(),
+                (GoldForSilver::new(6, 20), MAKER).into(),
+                (Gold(4), TAKER).into(),
+            ]
+        ),
+        Err(DexError::InvalidResidualOffer)
+    );
+}
+
+#[test]
+fn take_with_residual_for_someone_else_fails() {
+    assert_eq!(
+        take(
+            &[(Silver(10), TAKER).into()],
+            (GoldForSilver::new(10, 20), MAKER).into(),
+            &[
+                (Silver(8), MAKER).into(),
+                (GoldForSilver::new(6, 12), TAKER).into(),
+                (Gold(4), TAKER).into(),
+            ]
+        ),
+        Err(DexError::InvalidResidualOffer)
+    );
+}
+
+#[test]
+fn take_leaving_free_residual_fails() {
+    // Taking a single gold already costs all of the silver.
+    assert_eq!(
+        take(
+            &[(Silver(1), TAKER).into()],
+            (GoldForSilver::new(10, 1), MAKER).into(),
+            &[
+                (Silver(1), MAKER).into(),
+                (GoldForSilver::new(9, 0), MAKER).into(),
+                (Gold(1), TAKER).into(),
+            ]
+        ),
+        Err(DexError::InvalidResidualOffer)
+    );
+}
+
+#[test]
+fn take_without_evicting_offer_fails() {
+    assert_eq!(
+        VerifierAwareConstraintChecker::check(
+            &TakeOffer::<Gold, Silver>::default(),
+            &[
+                (Silver(20), TAKER).into(),
+                (GoldForSilver::new(10, 20), MAKER).into()
+            ],
+            &[],
+            &[],
+            &[(Silver(20), MAKER).into(), (Gold(10), TAKER).into()],
+        ),
+        Err(DexError::WrongNumberOfOffers)
+    );
+}
+
+#[test]
+fn take_with_wrong_output_type_fails() {
+    assert_eq!(
+        take(
+            &[(Silver(20), TAKER).into()],
+            (GoldForSilver::new(10, 20), MAKER).into(),
+            &[
+                (Silver(20), MAKER).into(),
+                (Gold(10), TAKER).into(),
+                (Bogus, TAKER).into(),
+            ]
+        ),
+        Err(DexError::BadlyTypedOutput)
+    );
+}
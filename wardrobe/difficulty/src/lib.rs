@@ -0,0 +1,369 @@
+//! Adjust the proof-of-work difficulty and keep it on chain.
+//!
+//! The difficulty is stored in a UTXO. In each block, the author includes a single [`AdjustDifficulty`]
+//! inherent that evicts the previous block's difficulty and creates a new one. The difficulty noted in
+//! a block is the difficulty that its children must meet. The inherent also keeps it in a well-known
+//! storage key, so the client's proof-of-work algorithm reads it from the parent block's state through
+//! the [`DifficultyApi`].
+//!
+//! Like Bitcoin, the difficulty only changes at the start of each adjustment period. It is scaled by how
+//! much faster or slower than the target the previous period's blocks came, within a maximum factor.
+//!
+//! There is no way for the inherent to find the timestamp noted in the same block, so it notes its own
+//! time from the same client-side timestamp inherent data. The time must never decrease, and nodes refuse
+//! blocks whose time is too far in the future, just like they do for the timestamp itself.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use core::marker::PhantomData;
+
+use parity_scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use serde::{Deserialize, Serialize};
+use sp_core::H256;
+use sp_inherents::{CheckInherentsResult, InherentData, InherentIdentifier};
+use sp_runtime::transaction_validity::TransactionPriority;
+use sp_std::{vec, vec::Vec};
+use sp_timestamp::InherentError::TooFarInFuture;
+use tuxedo_core::{
+    dynamic_typing::{DynamicallyTypedData, UtxoData, UtxoType},
+    ensure,
+    inherents::{InherentError, InherentHooks},
+    support_macros::{CloneNoBound, DebugNoBound, DefaultNoBound},
+    types::{Input, Output, OutputRef, RedemptionStrategy, Transaction},
+    SimpleConstraintChecker, Verifier,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// The storage key where the difficulty noted in the latest block is kept.
+const DIFFICULTY_KEY: &[u8] = b"difficulty";
+
+/// The difficulty in effect after some block, along with what is needed to adjust it.
+#[derive(
+    Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, TypeInfo, UtxoData,
+)]
+pub struct Difficulty {
+    /// The difficulty that the children of this block must meet.
+    /// Roughly, it is the number of hashes needed to find a valid seal.
+    pub difficulty: u128,
+    /// The block in which this difficulty was noted.
+    pub block: u32,
+    /// The time, in milliseconds since the unix epoch, that the block's author noted.
+    pub time: u64,
+    /// The time at which the current adjustment period started.
+    pub period_start: u64,
+}
+
+/// Options to configure the difficulty adjustment in your runtime.
+pub trait DifficultyConfig {
+    /// A means of getting the current block height.
+    /// Probably this will be the Tuxedo Executive
+    fn block_height() -> u32;
+
+    /// The difficulty from genesis until the first adjustment.
+    ///
+    /// Default is 1_000_000, which a single CPU thread can meet in about a second.
+    const INITIAL_DIFFICULTY: u128 = 1_000_000;
+
+    /// The time, in milliseconds, that blocks should take on average.
+    ///
+    /// Default is 6 seconds.
+    const TARGET_BLOCK_TIME: u64 = 6_000;
+
+    /// The number of blocks between difficulty adjustments. Bitcoin uses 2016.
+    ///
+    /// Default is 10.
+    const ADJUSTMENT_PERIOD: u32 = 10;
+
+    /// The most that the difficulty may grow or shrink by in a single adjustment. Bitcoin uses 4.
+    ///
+    /// Default is 4.
+    const MAX_ADJUSTMENT_FACTOR: u64 = 4;
+
+    /// The maximum amount by which a block's noted time may be ahead of a node's local time
+    /// for the node to accept it.
+    ///
+    /// Default is 1 minute.
+    const MAX_DRIFT: u64 = 60_000;
+}
+
+/// Scale a difficulty by how long an adjustment period actually took compared to the target.
+///
+/// The elapsed time is clamped so that the difficulty changes by at most the maximum factor,
+/// and the difficulty never drops below one.
+pub fn adjust<T: DifficultyConfig>(difficulty: u128, elapsed: u64) -> u128 {
+    let target = T::TARGET_BLOCK_TIME
+        .saturating_mul(T::ADJUSTMENT_PERIOD.into())
+        .max(1);
+    let factor = T::MAX_ADJUSTMENT_FACTOR.max(1);
+    let elapsed = elapsed
+        .clamp(target / factor, target.saturating_mul(factor))
+        .max(1);
+
+    (difficulty.saturating_mul(target.into()) / u128::from(elapsed)).max(1)
+}
+
+/// The difficulty that must be noted in the given block, at the given time, after the previous difficulty.
+///
+/// The first block after genesis starts the first adjustment period without adjusting, because the
+/// genesis difficulty has no meaningful time.
+pub fn next_difficulty<T: DifficultyConfig>(
+    previous: &Difficulty,
+    block: u32,
+    time: u64,
+) -> Difficulty {
+    let (difficulty, period_start) = if previous.block == 0 {
+        (previous.difficulty, time)
+    } else if block % T::ADJUSTMENT_PERIOD.max(1) == 0 {
+        let elapsed = time.saturating_sub(previous.period_start);
+        (adjust::<T>(previous.difficulty, elapsed), time)
+    } else {
+        (previous.difficulty, previous.period_start)
+    };
+
+    Difficulty {
+        difficulty,
+        block,
+        time,
+        period_start,
+    }
+}
+
+/// The difficulty that children of the latest block must meet.
+///
+/// Genesis transactions are not checked, so nothing is stored until the first block. Until then,
+/// this is the initial difficulty.
+pub fn current_difficulty<T: DifficultyConfig>() -> u128 {
+    sp_io::storage::get(DIFFICULTY_KEY)
+        .and_then(|d| u128::decode(&mut &*d).ok())
+        .unwrap_or(T::INITIAL_DIFFICULTY)
+}
+
+sp_api::decl_runtime_apis! {
+    /// Gives proof-of-work clients access to the chain's difficulty.
+    pub trait DifficultyApi {
+        /// The difficulty that children of the block at which the API is called must meet.
+        fn difficulty() -> u128;
+    }
+}
+
+/// Reasons that adjusting the difficulty may go wrong.
+#[derive(
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Encode,
+    Decode,
+    Hash,
+    Debug,
+    TypeInfo,
+)]
+pub enum DifficultyError {
+    /// UTXO data has an unexpected type.
+    BadlyTyped,
+    /// The previous difficulty must be evicted, but normal inputs were supplied.
+    InputsNotAllowed,
+    /// Exactly one previous difficulty must be evicted.
+    MissingPreviousDifficulty,
+    /// The evicted difficulty was not noted in the previous block.
+    PreviousDifficultyWrongHeight,
+    /// Exactly one new difficulty must be created.
+    MissingNewDifficulty,
+    /// The new difficulty was not noted at the current block height.
+    NewDifficultyWrongHeight,
+    /// The noted time is earlier than the previous block's.
+    TimeNotIncreasing,
+    /// The new difficulty or period start does not follow from the previous difficulty.
+    WrongDifficulty,
+}
+
+/// The inherent that notes the difficulty for the next block.
+///
+/// It evicts the previous block's difficulty and creates exactly one new difficulty, which must be
+/// adjusted correctly from the previous one.
+///
+/// This constraint checker is somewhat non-standard in that it has a side-effect that
+/// writes the new difficulty to storage, where the client can read it without the block.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DebugNoBound,
+    DefaultNoBound,
+    CloneNoBound,
+    PartialEq,
+    Eq,
+    TypeInfo,
+)]
+#[scale_info(skip_type_params(T))]
+pub struct AdjustDifficulty<T>(PhantomData<T>);
+
+impl<T: DifficultyConfig> SimpleConstraintChecker for AdjustDifficulty<T> {
+    type Error = DifficultyError;
+
+    fn check(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        evicted_input_data: &[DynamicallyTypedData],
+        _peeks: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> Result<TransactionPriority, Self::Error> {
+        ensure!(input_data.is_empty(), DifficultyError::InputsNotAllowed);
+
+        ensure!(
+            evicted_input_data.len() == 1,
+            DifficultyError::MissingPreviousDifficulty
+        );
+        let previous = evicted_input_data[0]
+            .extract::<Difficulty>()
+            .map_err(|_| DifficultyError::BadlyTyped)?;
+        ensure!(
+            previous.block + 1 == T::block_height(),
+            DifficultyError::PreviousDifficultyWrongHeight
+        );
+
+        ensure!(
+            output_data.len() == 1,
+            DifficultyError::MissingNewDifficulty
+        );
+        let new = output_data[0]
+            .extract::<Difficulty>()
+            .map_err(|_| DifficultyError::BadlyTyped)?;
+        ensure!(
+            new.block == T::block_height(),
+            DifficultyError::NewDifficultyWrongHeight
+        );
+        ensure!(
+            new.time >= previous.time,
+            DifficultyError::TimeNotIncreasing
+        );
+
+        ensure!(
+            new == next_difficulty::<T>(&previous, new.block, new.time),
+            DifficultyError::WrongDifficulty
+        );
+
+        // SIDE EFFECT: Note the new difficulty for the client
+        sp_io::storage::set(DIFFICULTY_KEY, &new.difficulty.encode());
+
+        Ok(0)
+    }
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![UtxoType::of::<Difficulty>()]
+    }
+
+    // Adjusting the difficulty is an inherent that notes the new difficulty while checking.
+    const COMPOUNDABLE: bool = false;
+}
+
+impl<T: DifficultyConfig + 'static> InherentHooks for AdjustDifficulty<T> {
+    type Error = sp_timestamp::InherentError;
+    const INHERENT_IDENTIFIER: InherentIdentifier = *b"difficty";
+
+    fn create_inherent<V: Verifier>(
+        authoring_inherent_data: &InherentData,
+        (previous_inherent, previous_id): (Transaction<V, Self>, H256),
+    ) -> Result<Transaction<V, Self>, InherentError> {
+        let time: u64 = authoring_inherent_data
+            .get_data(&sp_timestamp::INHERENT_IDENTIFIER)
+            .ok()
+            .flatten()
+            .ok_or(InherentError::MissingInherentData)?;
+
+        // There is always exactly 1 output, so we know right where to find the previous difficulty.
+        let previous = previous_inherent
+            .outputs
+            .first()
+            .and_then(|output| output.payload.extract::<Difficulty>().ok())
+            .ok_or(InherentError::MalformedInherent)?;
+        let input = Input {
+            output_ref: OutputRef {
+                tx_hash: previous_id,
+                index: 0,
+            },
+            redeemer: RedemptionStrategy::Eviction,
+        };
+
+        // The time must never decrease, even if the local clock is behind the previous block's author.
+        let time = time.max(previous.time);
+        let new_output = Output {
+            payload: next_difficulty::<T>(&previous, T::block_height(), time).into(),
+            verifier: V::new_unspendable().ok_or(InherentError::NoUnspendableVerifier)?,
+        };
+
+        Ok(Transaction {
+            inputs: vec![input],
+            peeks: Vec::new(),
+            outputs: vec![new_output],
+            checker: Self::default(),
+        })
+    }
+
+    fn check_inherent<V: Verifier>(
+        importing_inherent_data: &InherentData,
+        inherent: Transaction<V, Self>,
+        result: &mut CheckInherentsResult,
+    ) {
+        let Some(local_time) = importing_inherent_data
+            .get_data::<u64>(&sp_timestamp::INHERENT_IDENTIFIER)
+            .ok()
+            .flatten()
+        else {
+            result
+                .put_error(
+                    Self::INHERENT_IDENTIFIER,
+                    &InherentError::MissingInherentData,
+                )
+                .expect("Should be able to push some error");
+            return;
+        };
+
+        // The block has not been executed yet, so the inherent may not even contain a difficulty.
+        let Some(on_chain_difficulty) = inherent
+            .outputs
+            .first()
+            .and_then(|output| output.payload.extract::<Difficulty>().ok())
+        else {
+            result
+                .put_error(Self::INHERENT_IDENTIFIER, &InherentError::MalformedInherent)
+                .expect("Should be able to push some error");
+            return;
+        };
+
+        // Whether the difficulty follows from the previous one is checked on-chain.
+        // Whether the noted time is honest can only be checked against the local clock.
+        if on_chain_difficulty.time > local_time + T::MAX_DRIFT {
+            result
+                .put_error(Self::INHERENT_IDENTIFIER, &TooFarInFuture)
+                .expect("Should be able to push some error");
+        }
+    }
+
+    fn genesis_transactions<V: Verifier>() -> Vec<Transaction<V, Self>> {
+        vec![Transaction {
+            inputs: Vec::new(),
+            peeks: Vec::new(),
+            outputs: vec![Output {
+                payload: Difficulty {
+                    difficulty: T::INITIAL_DIFFICULTY,
+                    block: 0,
+                    time: 0,
+                    period_start: 0,
+                }
+                .into(),
+                verifier: V::new_unspendable().expect(
+                    "Must be able to create unspendable verifier to use difficulty inherent.",
+                ),
+            }],
+            checker: Self::default(),
+        }]
+    }
+}
@@ -0,0 +1,481 @@
+//! Unit tests for the difficulty piece
+
+use super::*;
+use sp_io::TestExternalities;
+use tuxedo_core::{dynamic_typing::testing::Bogus, verifier::Unspendable};
+use DifficultyError::*;
+
+/// The mock config always says the block number is twenty, which starts an adjustment period.
+pub struct AlwaysBlockTwenty;
+
+impl DifficultyConfig for AlwaysBlockTwenty {
+    fn block_height() -> u32 {
+        20
+    }
+
+    const TARGET_BLOCK_TIME: u64 = 1_000;
+}
+
+/// The same as the other mock config, but in the middle of an adjustment period.
+pub struct AlwaysBlockTwentyOne;
+
+impl DifficultyConfig for AlwaysBlockTwentyOne {
+    fn block_height() -> u32 {
+        21
+    }
+
+    const TARGET_BLOCK_TIME: u64 = 1_000;
+}
+
+/// A mock config for the first block after genesis.
+pub struct AlwaysBlockOne;
+
+impl DifficultyConfig for AlwaysBlockOne {
+    fn block_height() -> u32 {
+        1
+    }
+}
+
+fn difficulty(difficulty: u128, block: u32, time: u64, period_start: u64) -> Difficulty {
+    Difficulty {
+        difficulty,
+        block,
+        time,
+        period_start,
+    }
+}
+
+#[test]
+fn adjust_on_target_keeps_difficulty() {
+    assert_eq!(adjust::<AlwaysBlockTwenty>(1_000, 10_000), 1_000);
+}
+
+#[test]
+fn adjust_after_slow_period_lowers_difficulty() {
+    assert_eq!(adjust::<AlwaysBlockTwenty>(1_000, 20_000), 500);
+}
+
+#[test]
+fn adjust_after_fast_period_raises_difficulty() {
+    assert_eq!(adjust::<AlwaysBlockTwenty>(1_000, 5_000), 2_000);
+}
+
+#[test]
+fn adjust_is_limited_to_max_factor() {
+    assert_eq!(adjust::<AlwaysBlockTwenty>(1_000, 1_000_000), 250);
+    assert_eq!(adjust::<AlwaysBlockTwenty>(1_000, 0), 4_000);
+}
+
+#[test]
+fn adjust_never_reaches_zero() {
+    assert_eq!(adjust::<AlwaysBlockTwenty>(1, 1_000_000), 1);
+}
+
+#[test]
+fn next_difficulty_after_genesis_starts_period() {
+    let genesis = difficulty(1_000, 0, 0, 0);
+
+    assert_eq!(
+        next_difficulty::<AlwaysBlockOne>(&genesis, 1, 1_700_000_000_000),
+        difficulty(1_000, 1, 1_700_000_000_000, 1_700_000_000_000)
+    );
+}
+
+#[test]
+fn next_difficulty_mid_period_keeps_difficulty() {
+    let previous = difficulty(1_000, 20, 20_000, 20_000);
+
+    assert_eq!(
+        next_difficulty::<AlwaysBlockTwentyOne>(&previous, 21, 21_000),
+        difficulty(1_000, 21, 21_000, 20_000)
+    );
+}
+
+#[test]
+fn next_difficulty_at_period_start_adjusts() {
+    let previous = difficulty(1_000, 19, 14_000, 10_000);
+
+    assert_eq!(
+        next_difficulty::<AlwaysBlockTwenty>(&previous, 20, 15_000),
+        difficulty(2_000, 20, 15_000, 15_000)
+    );
+}
+
+#[test]
+fn adjust_difficulty_mid_period_works() {
+    TestExternalities::default().execute_with(|| {
+        let evicted = vec![difficulty(1_000, 20, 20_000, 20_000).into()];
+        let outputs = vec![difficulty(1_000, 21, 21_000, 20_000).into()];
+
+        assert_eq!(
+            AdjustDifficulty::<AlwaysBlockTwentyOne>::default().check(&[], &evicted, &[], &outputs),
+            Ok(0)
+        );
+        assert_eq!(current_difficulty::<AlwaysBlockTwentyOne>(), 1_000);
+    });
+}
+
+#[test]
+fn adjust_difficulty_at_period_start_works() {
+    TestExternalities::default().execute_with(|| {
+        let evicted = vec![difficulty(1_000, 19, 14_000, 10_000).into()];
+        let outputs = vec![difficulty(2_000, 20, 15_000, 15_000).into()];
+
+        assert_eq!(
+            AdjustDifficulty::<AlwaysBlockTwenty>::default().check(&[], &evicted, &[], &outputs),
+            Ok(0)
+        );
+        assert_eq!(current_difficulty::<AlwaysBlockTwenty>(), 2_000);
+    });
+}
+
+#[test]
+fn adjust_difficulty_after_genesis_works() {
+    TestExternalities::default().execute_with(|| {
+        let evicted = vec![difficulty(1_000_000, 0, 0, 0).into()];
+        let outputs = vec![difficulty(1_000_000, 1, 5_000, 5_000).into()];
+
+        assert_eq!(
+            AdjustDifficulty::<AlwaysBlockOne>::default().check(&[], &evicted, &[], &outputs),
+            Ok(0)
+        );
+        assert_eq!(current_difficulty::<AlwaysBlockOne>(), 1_000_000);
+    });
+}
+
+#[test]
+fn current_difficulty_before_first_block_is_initial() {
+    TestExternalities::default().execute_with(|| {
+        assert_eq!(current_difficulty::<AlwaysBlockOne>(), 1_000_000);
+    });
+}
+
+#[test]
+fn adjust_difficulty_without_adjusting_fails() {
+    let evicted = vec![difficulty(1_000, 19, 14_000, 10_000).into()];
+    let outputs = vec![difficulty(1_000, 20, 15_000, 10_000).into()];
+
+    assert_eq!(
+        AdjustDifficulty::<AlwaysBlockTwenty>::default().check(&[], &evicted, &[], &outputs),
+        Err(WrongDifficulty)
+    );
+}
+
+#[test]
+fn adjust_difficulty_mid_period_fails() {
+    let evicted = vec![difficulty(1_000, 20, 20_000, 20_000).into()];
+    let outputs = vec![difficulty(900, 21, 21_000, 20_000).into()];
+
+    assert_eq!(
+        AdjustDifficulty::<AlwaysBlockTwentyOne>::default().check(&[], &evicted, &[], &outputs),
+        Err(WrongDifficulty)
+    );
+}
+
+#[test]
+fn adjust_difficulty_moving_period_start_fails() {
+    let evicted = vec![difficulty(1_000, 20, 20_000, 20_000).into()];
+    let outputs = vec![difficulty(1_000, 21, 21_000, 21_000).into()];
+
+    assert_eq!(
+        AdjustDifficulty::<AlwaysBlockTwentyOne>::default().check(&[], &evicted, &[], &outputs),
+        Err(WrongDifficulty)
+    );
+}
+
+#[test]
+fn adjust_difficulty_going_back_in_time_fails() {
+    let evicted = vec![difficulty(1_000, 20, 20_000, 20_000).into()];
+    let outputs = vec![difficulty(1_000, 21, 19_999, 20_000).into()];
+
+    assert_eq!(
+        AdjustDifficulty::<AlwaysBlockTwentyOne>::default().check(&[], &evicted, &[], &outputs),
+        Err(TimeNotIncreasing)
+    );
+}
+
+#[test]
+fn adjust_difficulty_with_normal_input_fails() {
+    let inputs = vec![difficulty(1_000, 20, 20_000, 20_000).into()];
+    let outputs = vec![difficulty(1_000, 21, 21_000, 20_000).into()];
+
+    assert_eq!(
+        AdjustDifficulty::<AlwaysBlockTwentyOne>::default().check(&inputs, &[], &[], &outputs),
+        Err(InputsNotAllowed)
+    );
+}
+
+#[test]
+fn adjust_difficulty_without_previous_fails() {
+    let outputs = vec![difficulty(1_000, 21, 21_000, 20_000).into()];
+
+    assert_eq!(
+        AdjustDifficulty::<AlwaysBlockTwentyOne>::default().check(&[], &[], &[], &outputs),
+        Err(MissingPreviousDifficulty)
+    );
+}
+
+#[test]
+fn adjust_difficulty_with_badly_typed_previous_fails() {
+    let evicted = vec![Bogus.into()];
+    let outputs = vec![difficulty(1_000, 21, 21_000, 20_000).into()];
+
+    assert_eq!(
+        AdjustDifficulty::<AlwaysBlockTwentyOne>::default().check(&[], &evicted, &[], &outputs),
+        Err(BadlyTyped)
+    );
+}
+
+#[test]
+fn adjust_difficulty_with_old_previous_fails() {
+    let evicted = vec![difficulty(1_000, 19, 19_000, 10_000).into()];
+    let outputs = vec![difficulty(1_000, 21, 21_000, 10_000).into()];
+
+    assert_eq!(
+        AdjustDifficulty::<AlwaysBlockTwentyOne>::default().check(&[], &evicted, &[], &outputs),
+        Err(PreviousDifficultyWrongHeight)
+    );
+}
+
+#[test]
+fn adjust_difficulty_without_new_fails() {
+    let evicted = vec![difficulty(1_000, 20, 20_000, 20_000).into()];
+
+    assert_eq!(
+        AdjustDifficulty::<AlwaysBlockTwentyOne>::default().check(&[], &evicted, &[], &[]),
+        Err(MissingNewDifficulty)
+    );
+}
+
+#[test]
+fn adjust_difficulty_with_two_new_fails() {
+    let evicted = vec![difficulty(1_000, 20, 20_000, 20_000).into()];
+    let outputs = vec![
+        difficulty(1_000, 21, 21_000, 20_000).into(),
+        difficulty(1_000, 21, 21_000, 20_000).into(),
+    ];
+
+    assert_eq!(
+        AdjustDifficulty::<AlwaysBlockTwentyOne>::default().check(&[], &evicted, &[], &outputs),
+        Err(MissingNewDifficulty)
+    );
+}
+
+#[test]
+fn adjust_difficulty_with_badly_typed_new_fails() {
+    let evicted = vec![difficulty(1_000, 20, 20_000, 20_000).into()];
+    let outputs = vec![Bogus.into()];
+
+    assert_eq!(
+        AdjustDifficulty::<AlwaysBlockTwentyOne>::default().check(&[], &evicted, &[], &outputs),
+        Err(BadlyTyped)
+    );
+}
+
+#[test]
+fn adjust_difficulty_at_wrong_height_fails() {
+    let evicted = vec![difficulty(1_000, 20, 20_000, 20_000).into()];
+    let outputs = vec![difficulty(1_000, 22, 21_000, 20_000).into()];
+
+    assert_eq!(
+        AdjustDifficulty::<AlwaysBlockTwentyOne>::default().check(&[], &evicted, &[], &outputs),
+        Err(NewDifficultyWrongHeight)
+    );
+}
+
+#[test]
+fn create_inherent_evicts_previous_and_adjusts() {
+    let previous = Transaction::<Unspendable, AdjustDifficulty<AlwaysBlockTwenty>> {
+        inputs: Vec::new(),
+        peeks: Vec::new(),
+        outputs: vec![(difficulty(1_000, 19, 14_000, 10_000), Unspendable).into()],
+        checker: Default::default(),
+    };
+    let previous_id = H256::repeat_byte(7);
+    let mut inherent_data = InherentData::new();
+    inherent_data
+        .put_data(sp_timestamp::INHERENT_IDENTIFIER, &15_000u64)
+        .unwrap();
+
+    let tx = AdjustDifficulty::<AlwaysBlockTwenty>::create_inherent(
+        &inherent_data,
+        (previous, previous_id),
+    )
+    .unwrap();
+
+    assert_eq!(
+        tx.inputs,
+        vec![Input {
+            output_ref: OutputRef {
+                tx_hash: previous_id,
+                index: 0,
+            },
+            redeemer: RedemptionStrategy::Eviction,
+        }]
+    );
+    assert_eq!(
+        tx.outputs,
+        vec![(difficulty(2_000, 20, 15_000, 15_000), Unspendable).into()]
+    );
+}
+
+#[test]
+fn create_inherent_never_goes_back_in_time() {
+    let previous = Transaction::<Unspendable, AdjustDifficulty<AlwaysBlockTwentyOne>> {
+        inputs: Vec::new(),
+        peeks: Vec::new(),
+        outputs: vec![(difficulty(1_000, 20, 20_000, 20_000), Unspendable).into()],
+        checker: Default::default(),
+    };
+    let mut inherent_data = InherentData::new();
+    inherent_data
+        .put_data(sp_timestamp::INHERENT_IDENTIFIER, &19_000u64)
+        .unwrap();
+
+    let tx = AdjustDifficulty::<AlwaysBlockTwentyOne>::create_inherent(
+        &inherent_data,
+        (previous, H256::zero()),
+    );
+
+    assert_eq!(
+        tx.outputs,
+        vec![(difficulty(1_000, 21, 20_000, 20_000), Unspendable).into()]
+    );
+}
+
+#[test]
+fn check_inherent_rejects_time_too_far_in_future() {
+    let inherent = Transaction::<Unspendable, AdjustDifficulty<AlwaysBlockTwentyOne>> {
+        inputs: Vec::new(),
+        peeks: Vec::new(),
+        outputs: vec![(difficulty(1_000, 21, 100_001, 20_000), Unspendable).into()],
+        checker: Default::default(),
+    };
+    let mut inherent_data = InherentData::new();
+    inherent_data
+        .put_data(sp_timestamp::INHERENT_IDENTIFIER, &40_000u64)
+        .unwrap();
+    let mut result = CheckInherentsResult::new();
+
+    AdjustDifficulty::<AlwaysBlockTwentyOne>::check_inherent(&inherent_data, inherent, &mut result);
+
+    assert!(!result.ok());
+}
+
+#[test]
+fn check_inherent_accepts_time_within_drift() {
+    let inherent = Transaction::<Unspendable, AdjustDifficulty<AlwaysBlockTwentyOne>> {
+        inputs: Vec::new(),
+        peeks: Vec::new(),
+        outputs: vec![(difficulty(1_000, 21, 100_000, 20_000), Unspendable).into()],
+        checker: Default::default(),
+    };
+    let mut inherent_data = InherentData::new();
+    inherent_data
+        .put_data(sp_timestamp::INHERENT_IDENTIFIER, &40_000u64)
+        .unwrap();
+    let mut result = CheckInherentsResult::new();
+
+    AdjustDifficulty::<AlwaysBlockTwentyOne>::check_inherent(&inherent_data, inherent, &mut result);
+
+    assert!(result.ok());
+}
+
+#[test]
+fn create_inherent_without_time_fails() {
+    let previous = Transaction::<Unspendable, AdjustDifficulty<AlwaysBlockTwentyOne>> {
+        inputs: Vec::new(),
+        peeks: Vec::new(),
+        outputs: vec![(difficulty(1_000, 20, 20_000, 20_000), Unspendable).into()],
+        checker: Default::default(),
+    };
+
+    assert_eq!(
+        AdjustDifficulty::<AlwaysBlockTwentyOne>::create_inherent(
+            &InherentData::new(),
+            (previous, H256::zero()),
+        )
+        .err(),
+        Some(InherentError::MissingInherentData)
+    );
+}
+
+#[test]
+fn create_inherent_with_malformed_previous_fails() {
+    let previous = Transaction::<Unspendable, AdjustDifficulty<AlwaysBlockTwentyOne>> {
+        inputs: Vec::new(),
+        peeks: Vec::new(),
+        outputs: Vec::new(),
+        checker: Default::default(),
+    };
+    let mut inherent_data = InherentData::new();
+    inherent_data
+        .put_data(sp_timestamp::INHERENT_IDENTIFIER, &19_000u64)
+        .unwrap();
+
+    assert_eq!(
+        AdjustDifficulty::<AlwaysBlockTwentyOne>::create_inherent(
+            &inherent_data,
+            (previous, H256::zero()),
+        )
+        .err(),
+        Some(InherentError::MalformedInherent)
+    );
+}
+
+#[test]
+fn check_inherent_without_difficulty_fails() {
+    let inherent = Transaction::<Unspendable, AdjustDifficulty<AlwaysBlockTwentyOne>> {
+        inputs: Vec::new(),
+        peeks: Vec::new(),
+        outputs: Vec::new(),
+        checker: Default::default(),
+    };
+    let mut inherent_data = InherentData::new();
+    inherent_data
+        .put_data(sp_timestamp::INHERENT_IDENTIFIER, &40_000u64)
+        .unwrap();
+    let mut result = CheckInherentsResult::new();
+
+    AdjustDifficulty::<AlwaysBlockTwentyOne>::check_inherent(&inherent_data, inherent, &mut result);
+
+    assert!(result.fatal_error());
+}
+
+#[test]
+fn check_inherent_with_badly_typed_output_fails() {
+    let inherent = Transaction::<Unspendable, AdjustDifficulty<AlwaysBlockTwentyOne>> {
+        inputs: Vec::new(),
+        peeks: Vec::new(),
+        outputs: vec![(Bogus, Unspendable).into()],
+        checker: Default::default(),
+    };
+    let mut inherent_data = InherentData::new();
+    inherent_data
+        .put_data(sp_timestamp::INHERENT_IDENTIFIER, &40_000u64)
+        .unwrap();
+    let mut result = CheckInherentsResult::new();
+
+    AdjustDifficulty::<AlwaysBlockTwentyOne>::check_inherent(&inherent_data, inherent, &mut result);
+
+    assert!(result.fatal_error());
+}
+
+#[test]
+fn check_inherent_without_local_time_fails() {
+    let inherent = Transaction::<Unspendable, AdjustDifficulty<AlwaysBlockTwentyOne>> {
+        inputs: Vec::new(),
+        peeks: Vec::new(),
+        outputs: vec![(difficulty(1_000, 21, 20_000, 20_000), Unspendable).into()],
+        checker: Default::default(),
+    };
+    let mut result = CheckInherentsResult::new();
+
+    AdjustDifficulty::<AlwaysBlockTwentyOne>::check_inherent(
+        &InherentData::new(),
+        inherent,
+        &mut result,
+    );
+
+    assert!(result.fatal_error());
+}
@@ -13,10 +13,17 @@
 //!         BlakeTwo256::hash_of(MomDna, DadDna, MomCurrNumBreedings, DadCurrNumberBreedings)
 //!
 //! There are a only a finite amount of free breedings available before it starts to cost money
-//! to breed kitties.
+//! to breed kitties. Paid breedings do not use up the parents' free breedings. Instead the breeder
+//! includes coins worth at least the cost as extra inputs, and the runtime decides whether that
+//! payment is burned or sent to a beneficiary.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
+
+use alloc::string::String;
+use core::marker::PhantomData;
+
 use parity_scale_codec::{Decode, Encode};
 use scale_info::TypeInfo;
 use serde::{Deserialize, Serialize};
@@ -27,9 +34,11 @@ use sp_runtime::{
 };
 use sp_std::prelude::*;
 use tuxedo_core::{
-    dynamic_typing::{DynamicallyTypedData, UtxoData},
+    dynamic_typing::{DynamicallyTypedData, UtxoData, UtxoType},
     ensure,
-    types::Transaction,
+    support_macros::{CloneNoBound, DebugNoBound, DefaultNoBound},
+    traits::{Cash, CashError},
+    types::{Output, Transaction},
     SimpleConstraintChecker, Verifier,
 };
 
@@ -52,6 +61,40 @@ mod tests;
 )]
 pub struct FreeKittyConstraintChecker;
 
+/// Configuration items for paid kitty breeding when it is instantiated in a concrete runtime.
+pub trait PaidBreedingConfig {
+    /// The coin that breeders pay with.
+    type Coin: Cash + UtxoData + 'static;
+
+    /// The BlakeTwo256 hash of the encoded verifier that receives breeding payments.
+    /// When this is `None`, payments are burned instead.
+    fn beneficiary() -> Option<H256>;
+}
+
+/// A constraint checker for breeding kitties by paying for it.
+///
+/// The first two inputs are the Mom and Dad and the remaining inputs are coins. The first three
+/// outputs are the new family, exactly as in a free breeding, except that the parents keep all of
+/// their free breedings. The remaining outputs are coins. When the runtime configures a beneficiary,
+/// the coins paid to it must cover the cost. Otherwise the value burned must cover the cost.
+///
+/// Paid breeding is only for parents that cannot breed for free, so at least one of them must have
+/// used up all of its free breedings. None of the coins may be worth nothing.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DebugNoBound,
+    DefaultNoBound,
+    CloneNoBound,
+    PartialEq,
+    Eq,
+    TypeInfo,
+)]
+#[scale_info(skip_type_params(T))]
+pub struct PaidKittyConstraintChecker<T>(PhantomData<T>);
+
 #[derive(
     Serialize,
     Deserialize,
@@ -192,6 +235,40 @@ impl KittyData {
     }
 }
 
+/// This piece's section of a runtime's genesis configuration.
+#[derive(Serialize, Deserialize, DefaultNoBound, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields, default)]
+pub struct GenesisConfig<V> {
+    /// The kitties that exist at genesis.
+    pub kitties: Vec<GenesisKitty<V>>,
+}
+
+/// A kitty that exists at genesis.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct GenesisKitty<V> {
+    /// The verifier that protects the kitty.
+    pub owner: V,
+    /// Whether the kitty is a mom or a dad.
+    pub parent: Parent,
+    /// The kitty's name. Its DNA is the hash of the name, so every kitty needs a different one.
+    pub name: String,
+}
+
+impl<V: Verifier> GenesisConfig<V> {
+    /// Compile this section into genesis transactions.
+    pub fn genesis_transactions<OV, OC>(self) -> Vec<Transaction<OV, OC>>
+    where
+        OV: Verifier + From<V>,
+        OC: tuxedo_core::ConstraintChecker + From<FreeKittyConstraintChecker>,
+    {
+        self.kitties
+            .into_iter()
+            .map(|kitty| KittyData::mint(kitty.parent, kitty.name.as_bytes(), kitty.owner))
+            .collect()
+    }
+}
+
 impl Default for KittyData {
     fn default() -> Self {
         Self {
@@ -263,6 +340,24 @@ pub enum ConstraintCheckerError {
     TooManyBreedingsForKitty,
     /// Not enough free breedings available for these parents.
     NotEnoughFreeBreedings,
+    /// Both parents still have free breedings, so they must breed for free rather than pay.
+    FreeBreedingsAvailable,
+    /// The value of the output coins exceeds the value of the input coins.
+    OutputsExceedInputs,
+    /// The value consumed or created by this transaction overflows the value type.
+    ValueOverflow,
+    /// A coin used to pay for breeding, or returned as change, is worth nothing.
+    ZeroValueCoin,
+}
+
+impl From<CashError> for ConstraintCheckerError {
+    fn from(e: CashError) -> Self {
+        match e {
+            CashError::BadlyTyped => Self::BadlyTyped,
+            CashError::ZeroValueCoin => Self::ZeroValueCoin,
+            CashError::ValueOverflow => Self::ValueOverflow,
+        }
+    }
 }
 
 trait Breed {
@@ -281,15 +376,25 @@ trait Breed {
     /// Makes sure each parent has a non-zero number of free breedings.
     fn check_free_breedings(mom: &KittyData, dad: &KittyData) -> Result<(), Self::Error>;
     /// Checks outputs which consists of (Mom, Dad, Child) is correctly formulated.
+    /// Each parent uses up `free_breedings_used` of their free breedings.
     fn check_new_family(
         old_mom: &KittyData,
         old_dad: &KittyData,
         new_family: &[DynamicallyTypedData],
+        free_breedings_used: u64,
     ) -> Result<(), Self::Error>;
     /// Checks if new mom matches the old ones DNA and changes state correctly.
-    fn check_new_mom(old_mom: &KittyData, new_mom: &KittyData) -> Result<(), Self::Error>;
+    fn check_new_mom(
+        old_mom: &KittyData,
+        new_mom: &KittyData,
+        free_breedings_used: u64,
+    ) -> Result<(), Self::Error>;
     /// Checks if new dad matches the old ones DNA and changes state correctly.
-    fn check_new_dad(old_dad: &KittyData, new_dad: &KittyData) -> Result<(), Self::Error>;
+    fn check_new_dad(
+        old_dad: &KittyData,
+        new_dad: &KittyData,
+        free_breedings_used: u64,
+    ) -> Result<(), Self::Error>;
     /// Checks if new child DNA is formulated correctly and is initialized to the proper state.
     fn check_child(
         new_mom: &KittyData,
@@ -368,25 +473,30 @@ impl Breed for KittyHelpers {
         old_mom: &KittyData,
         old_dad: &KittyData,
         new_family: &[DynamicallyTypedData],
+        free_breedings_used: u64,
     ) -> Result<(), Self::Error> {
         // Output Side
         ensure!(new_family.len() == 3, Self::Error::NotEnoughFamilyMembers);
         let new_mom = KittyData::try_from(&new_family[0])?;
         let new_dad = KittyData::try_from(&new_family[1])?;
         let child = KittyData::try_from(&new_family[2])?;
-        Self::check_new_mom(old_mom, &new_mom)?;
-        Self::check_new_dad(old_dad, &new_dad)?;
+        Self::check_new_mom(old_mom, &new_mom, free_breedings_used)?;
+        Self::check_new_dad(old_dad, &new_dad, free_breedings_used)?;
         Self::check_child(&new_mom, &new_dad, &child)?;
         Ok(())
     }
 
     /// Checks:
     ///     - Mom is now in `HadBirthRecently`
-    ///     - Mom has 1 less `free_breedings`
+    ///     - Mom has used up the right number of `free_breedings`
     ///     - Mom's DNA matches old Mom
     ///     - Mom's num breedings is incremented
     ///
-    fn check_new_mom(old_mom: &KittyData, new_mom: &KittyData) -> Result<(), Self::Error> {
+    fn check_new_mom(
+        old_mom: &KittyData,
+        new_mom: &KittyData,
+        free_breedings_used: u64,
+    ) -> Result<(), Self::Error> {
         match &new_mom.parent {
             Parent::Mom(status) => {
                 if let MomKittyStatus::RearinToGo = status {
@@ -397,7 +507,7 @@ impl Breed for KittyHelpers {
         }
 
         ensure!(
-            new_mom.free_breedings == old_mom.free_breedings - 1,
+            old_mom.free_breedings.checked_sub(free_breedings_used) == Some(new_mom.free_breedings),
             Self::Error::NewParentFreeBreedingsIncorrect
         );
         ensure!(
@@ -414,11 +524,15 @@ impl Breed for KittyHelpers {
 
     /// Checks:
     ///     - Dad is now `Tired`
-    ///     - Dad has 1 less `free_breedings`
+    ///     - Dad has used up the right number of `free_breedings`
     ///     - Dad's DNA matches old Dad
     ///     - Dad's num breedings is incremented
     ///
-    fn check_new_dad(old_dad: &KittyData, new_dad: &KittyData) -> Result<(), Self::Error> {
+    fn check_new_dad(
+        old_dad: &KittyData,
+        new_dad: &KittyData,
+        free_breedings_used: u64,
+    ) -> Result<(), Self::Error> {
         match &new_dad.parent {
             Parent::Dad(status) => {
                 if let DadKittyStatus::RearinToGo = status {
@@ -429,7 +543,7 @@ impl Breed for KittyHelpers {
         }
 
         ensure!(
-            new_dad.free_breedings == old_dad.free_breedings - 1,
+            old_dad.free_breedings.checked_sub(free_breedings_used) == Some(new_dad.free_breedings),
             Self::Error::NewParentFreeBreedingsIncorrect
         );
         ensure!(
@@ -502,6 +616,11 @@ impl TryFrom<&DynamicallyTypedData> for KittyData {
 
 impl SimpleConstraintChecker for FreeKittyConstraintChecker {
     type Error = ConstraintCheckerError;
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![UtxoType::of::<KittyData>()]
+    }
+
     /// Checks:
     ///     - `input_data` is of length 2
     ///     - `output_data` is of length 3
@@ -529,7 +648,88 @@ impl SimpleConstraintChecker for FreeKittyConstraintChecker {
         // Output must be Mom, Dad, Child
         ensure!(output_data.len() == 3, Self::Error::NotEnoughFamilyMembers);
 
-        KittyHelpers::check_new_family(&mom, &dad, output_data)?;
+        KittyHelpers::check_new_family(&mom, &dad, output_data, 1)?;
+
+        Ok(0)
+    }
+}
+
+// The verifier aware trait is not imported because it would make calls to the free checker's
+// `check` method ambiguous.
+impl<T: PaidBreedingConfig> tuxedo_core::VerifierAwareConstraintChecker
+    for PaidKittyConstraintChecker<T>
+{
+    type Error = ConstraintCheckerError;
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![UtxoType::of::<KittyData>(), UtxoType::of::<T::Coin>()]
+    }
+
+    /// Checks:
+    ///     - `inputs` are a Mom, a Dad, and some coins
+    ///     - `outputs` are the new family and some coins
+    ///     - the breeding is paid for
+    ///
+    fn check<V: Verifier>(
+        &self,
+        inputs: &[Output<V>],
+        evicted_inputs: &[Output<V>],
+        _peeks: &[Output<V>],
+        outputs: &[Output<V>],
+    ) -> Result<TransactionPriority, Self::Error> {
+        // Can't evict anything
+        ensure!(
+            evicted_inputs.is_empty(),
+            ConstraintCheckerError::NoEvictionsAllowed
+        );
+
+        // Input must be a Mom and a Dad followed by the payment
+        ensure!(inputs.len() >= 2, Self::Error::TwoParentsDoNotExist);
+        let (parents, input_coins) = inputs.split_at(2);
+
+        let mom = KittyData::try_from(&parents[0].payload)?;
+        let dad = KittyData::try_from(&parents[1].payload)?;
+        KittyHelpers::check_mom_can_breed(&mom)?;
+        KittyHelpers::check_dad_can_breed(&dad)?;
+
+        // Parents that could breed for free must do so
+        ensure!(
+            KittyHelpers::check_free_breedings(&mom, &dad).is_err(),
+            Self::Error::FreeBreedingsAvailable
+        );
+
+        // Output must be Mom, Dad, Child followed by any coins
+        ensure!(outputs.len() >= 3, Self::Error::NotEnoughFamilyMembers);
+        let (new_family, output_coins) = outputs.split_at(3);
+        let new_family: Vec<DynamicallyTypedData> = new_family
+            .iter()
+            .map(|output| output.payload.clone())
+            .collect();
+
+        KittyHelpers::check_new_family(&mom, &dad, &new_family, 0)?;
+
+        let total_input_value =
+            T::Coin::total_value(input_coins.iter().map(|input| &input.payload))?;
+        let total_output_value =
+            T::Coin::total_value(output_coins.iter().map(|output| &output.payload))?;
+        ensure!(
+            total_output_value <= total_input_value,
+            Self::Error::OutputsExceedInputs
+        );
+
+        let payment = match T::beneficiary() {
+            Some(beneficiary) => T::Coin::total_value(
+                output_coins
+                    .iter()
+                    .filter(|output| BlakeTwo256::hash_of(&output.verifier) == beneficiary)
+                    .map(|output| &output.payload),
+            )?,
+            None => total_input_value - total_output_value,
+        };
+        ensure!(
+            payment >= KittyHelpers::COST,
+            Self::Error::MinimumSpendAndBreedNotMet
+        );
 
         Ok(0)
     }
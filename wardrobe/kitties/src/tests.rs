@@ -1,6 +1,7 @@
 //! Tests for the Crypto Kitties Piece
 
 use super::*;
+use tuxedo_core::verifier::TestVerifier;
 /// A bogus data type used in tests for type validation
 #[derive(Encode, Decode)]
 struct Bogus;
@@ -516,3 +517,267 @@ fn check_child_num_breedings_non_zero_fails() {
         Err(ConstraintCheckerError::NewChildHasNonZeroBreedings)
     );
 }
+
+/// The coin that breeders pay with in the paid breeding tests.
+#[derive(Encode, Decode, Debug, PartialEq, Eq, Clone)]
+struct TestCoin(u128);
+
+impl UtxoData for TestCoin {
+    const TYPE_ID: [u8; 4] = *b"tcon";
+}
+
+impl Cash for TestCoin {
+    fn value(&self) -> u128 {
+        self.0
+    }
+
+    const ID: u8 = 0;
+}
+
+const BENEFICIARY: TestVerifier = TestVerifier { verifies: true };
+const BREEDER: TestVerifier = TestVerifier { verifies: false };
+
+/// A runtime that burns breeding payments.
+struct BurningRuntime;
+
+impl PaidBreedingConfig for BurningRuntime {
+    type Coin = TestCoin;
+
+    fn beneficiary() -> Option<H256> {
+        None
+    }
+}
+
+/// A runtime that sends breeding payments to a beneficiary.
+struct PayingRuntime;
+
+impl PaidBreedingConfig for PayingRuntime {
+    type Coin = TestCoin;
+
+    fn beneficiary() -> Option<H256> {
+        Some(BlakeTwo256::hash_of(&BENEFICIARY))
+    }
+}
+
+/// Parents that have used up all of their free breedings.
+fn exhausted_parents() -> Vec<Output<TestVerifier>> {
+    let mut mom = KittyData::default();
+    mom.free_breedings = 0;
+    let mut dad = KittyData::default_dad();
+    dad.free_breedings = 0;
+
+    vec![(mom, BREEDER).into(), (dad, BREEDER).into()]
+}
+
+/// The family after a paid breeding of the exhausted parents.
+fn paid_family() -> Vec<Output<TestVerifier>> {
+    let new_family = *KittyData::default_family();
+    new_family
+        .into_iter()
+        .enumerate()
+        .map(|(i, mut kitty)| {
+            if i < 2 {
+                kitty.free_breedings = 0;
+            }
+            (kitty, BREEDER).into()
+        })
+        .collect()
+}
+
+fn paid_breed<T: PaidBreedingConfig>(
+    inputs: &[Output<TestVerifier>],
+    outputs: &[Output<TestVerifier>],
+) -> Result<TransactionPriority, ConstraintCheckerError> {
+    tuxedo_core::VerifierAwareConstraintChecker::check(
+        &PaidKittyConstraintChecker::<T>::default(),
+        inputs,
+        &[],
+        &[], // no peeks
+        outputs,
+    )
+}
+
+#[test]
+fn paid_breed_burning_payment_works() {
+    let mut inputs = exhausted_parents();
+    inputs.push((TestCoin(8), BREEDER).into());
+    let mut outputs = paid_family();
+    outputs.push((TestCoin(3), BREEDER).into());
+
+    assert_eq!(paid_breed::<BurningRuntime>(&inputs, &outputs), Ok(0));
+}
+
+#[test]
+fn paid_breed_burning_too_little_fails() {
+    let mut inputs = exhausted_parents();
+    inputs.push((TestCoin(8), BREEDER).into());
+    let mut outputs = paid_family();
+    outputs.push((TestCoin(4), BREEDER).into());
+
+    assert_eq!(
+        paid_breed::<BurningRuntime>(&inputs, &outputs),
+        Err(ConstraintCheckerError::MinimumSpendAndBreedNotMet)
+    );
+}
+
+#[test]
+fn paid_breed_paying_beneficiary_works() {
+    let mut inputs = exhausted_parents();
+    inputs.push((TestCoin(5), BREEDER).into());
+    let mut outputs = paid_family();
+    outputs.push((TestCoin(5), BENEFICIARY).into());
+
+    assert_eq!(paid_breed::<PayingRuntime>(&inputs, &outputs), Ok(0));
+}
+
+#[test]
+fn paid_breed_burning_when_beneficiary_configured_fails() {
+    let mut inputs = exhausted_parents();
+    inputs.push((TestCoin(5), BREEDER).into());
+
+    assert_eq!(
+        paid_breed::<PayingRuntime>(&inputs, &paid_family()),
+        Err(ConstraintCheckerError::MinimumSpendAndBreedNotMet)
+    );
+}
+
+#[test]
+fn paid_breed_paying_more_than_inputs_fails() {
+    let mut inputs = exhausted_parents();
+    inputs.push((TestCoin(4), BREEDER).into());
+    let mut outputs = paid_family();
+    outputs.push((TestCoin(5), BENEFICIARY).into());
+
+    assert_eq!(
+        paid_breed::<PayingRuntime>(&inputs, &outputs),
+        Err(ConstraintCheckerError::OutputsExceedInputs)
+    );
+}
+
+#[test]
+fn paid_breed_with_one_exhausted_parent_works() {
+    let mut inputs = exhausted_parents();
+    inputs[1] = (KittyData::default_dad(), BREEDER).into();
+    inputs.push((TestCoin(5), BREEDER).into());
+    let mut outputs = paid_family();
+    let mut new_dad = outputs[1].payload.extract::<KittyData>().unwrap();
+    new_dad.free_breedings = KittyHelpers::NUM_FREE_BREEDINGS;
+    outputs[1] = (new_dad, BREEDER).into();
+
+    assert_eq!(paid_breed::<BurningRuntime>(&inputs, &outputs), Ok(0));
+}
+
+#[test]
+fn paid_breed_with_free_breedings_available_fails() {
+    let inputs = vec![
+        (KittyData::default(), BREEDER).into(),
+        (KittyData::default_dad(), BREEDER).into(),
+        (TestCoin(5), BREEDER).into(),
+    ];
+    let new_family = *KittyData::default_family();
+    let outputs: Vec<Output<TestVerifier>> = new_family
+        .into_iter()
+        .map(|kitty| (kitty, BREEDER).into())
+        .collect();
+
+    assert_eq!(
+        paid_breed::<BurningRuntime>(&inputs, &outputs),
+        Err(ConstraintCheckerError::FreeBreedingsAvailable)
+    );
+}
+
+#[test]
+fn paid_breed_using_free_breedings_fails() {
+    // A paid breeding leaves the parents' free breedings untouched.
+    let mut inputs = exhausted_parents();
+    inputs[1] = (KittyData::default_dad(), BREEDER).into();
+    inputs.push((TestCoin(5), BREEDER).into());
+    let mut outputs = paid_family();
+    let mut new_dad = outputs[1].payload.extract::<KittyData>().unwrap();
+    new_dad.free_breedings = KittyHelpers::NUM_FREE_BREEDINGS - 1;
+    outputs[1] = (new_dad, BREEDER).into();
+
+    assert_eq!(
+        paid_breed::<BurningRuntime>(&inputs, &outputs),
+        Err(ConstraintCheckerError::NewParentFreeBreedingsIncorrect)
+    );
+}
+
+#[test]
+fn paid_breed_with_zero_value_change_fails() {
+    let mut inputs = exhausted_parents();
+    inputs.push((TestCoin(5), BREEDER).into());
+    let mut outputs = paid_family();
+    outputs.push((TestCoin(0), BREEDER).into());
+
+    assert_eq!(
+        paid_breed::<BurningRuntime>(&inputs, &outputs),
+        Err(ConstraintCheckerError::ZeroValueCoin)
+    );
+}
+
+#[test]
+fn paid_breed_with_zero_value_payment_fails() {
+    let mut inputs = exhausted_parents();
+    inputs.push((TestCoin(5), BREEDER).into());
+    inputs.push((TestCoin(0), BREEDER).into());
+
+    assert_eq!(
+        paid_breed::<BurningRuntime>(&inputs, &paid_family()),
+        Err(ConstraintCheckerError::ZeroValueCoin)
+    );
+}
+
+#[test]
+fn paid_breed_with_wrong_payment_type_fails() {
+    let mut inputs = exhausted_parents();
+    inputs.push((Bogus, BREEDER).into());
+
+    assert_eq!(
+        paid_breed::<BurningRuntime>(&inputs, &paid_family()),
+        Err(ConstraintCheckerError::BadlyTyped)
+    );
+}
+
+#[test]
+fn paid_breed_tired_dad_fails() {
+    let mut inputs = exhausted_parents();
+    let mut dad = inputs[1].payload.extract::<KittyData>().unwrap();
+    dad.parent = Parent::Dad(DadKittyStatus::Tired);
+    inputs[1] = (dad, BREEDER).into();
+    inputs.push((TestCoin(5), BREEDER).into());
+
+    assert_eq!(
+        paid_breed::<BurningRuntime>(&inputs, &paid_family()),
+        Err(ConstraintCheckerError::DadTooTired)
+    );
+}
+
+#[test]
+fn genesis_config_mints_kitties_named_by_dna() {
+    let config = GenesisConfig {
+        kitties: vec![
+            GenesisKitty {
+                owner: TestVerifier { verifies: true },
+                parent: Parent::mom(),
+                name: "mother".into(),
+            },
+            GenesisKitty {
+                owner: TestVerifier { verifies: false },
+                parent: Parent::dad(),
+                name: "father".into(),
+            },
+        ],
+    };
+
+    let transactions: Vec<Transaction<TestVerifier, FreeKittyConstraintChecker>> =
+        config.genesis_transactions();
+
+    assert_eq!(
+        transactions,
+        vec![
+            KittyData::mint(Parent::mom(), b"mother", TestVerifier { verifies: true }),
+            KittyData::mint(Parent::dad(), b"father", TestVerifier { verifies: false }),
+        ]
+    );
+}
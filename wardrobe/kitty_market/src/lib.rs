@@ -0,0 +1,335 @@
+//! A marketplace where kitties are bought and sold for coins.
+//!
+//! An owner lists a kitty for sale by consuming it and creating a [`ListedKitty`] that records the
+//! asking price. The listing is protected by the seller's own verifier, so the seller may delist
+//! it at any time simply by consuming it and taking the kitty back.
+//!
+//! A buyer purchases a kitty by evicting its listing, which does not require satisfying the seller's
+//! verifier. In exchange, the transaction's first output must pay the price to the very same
+//! verifier that protected the listing, and its second output is the kitty itself, unchanged, for
+//! the buyer. The buyer's coins are the normal inputs and any change follows. Because everything
+//! happens in a single transaction, neither side can be cheated.
+//!
+//! Kitties may be sold for any coin type that implements the `Cash` trait. Each runtime chooses
+//! which coins to support by including the corresponding buy checkers.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use core::marker::PhantomData;
+
+use kitties::KittyData;
+use parity_scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use serde::{Deserialize, Serialize};
+use sp_runtime::transaction_validity::TransactionPriority;
+use sp_std::prelude::*;
+use tuxedo_core::{
+    dynamic_typing::{DynamicallyTypedData, UtxoData, UtxoType},
+    ensure,
+    support_macros::{CloneNoBound, DebugNoBound, DefaultNoBound},
+    traits::{Cash, CashError},
+    types::Output,
+    SimpleConstraintChecker, Verifier, VerifierAwareConstraintChecker,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// A kitty that is listed for sale. The listing's verifier belongs to the seller.
+#[derive(
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Encode,
+    Decode,
+    Hash,
+    Debug,
+    TypeInfo,
+    UtxoData,
+)]
+pub struct ListedKitty {
+    /// The kitty that is for sale.
+    pub kitty: KittyData,
+    /// The value of coins the seller wants for the kitty.
+    pub price: u128,
+}
+
+impl ListedKitty {
+    pub fn new(kitty: KittyData, price: u128) -> Self {
+        Self { kitty, price }
+    }
+}
+
+/// Errors that can occur when checking marketplace transactions.
+#[derive(
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Encode,
+    Decode,
+    Hash,
+    Debug,
+    TypeInfo,
+)]
+pub enum KittyMarketError {
+    /// An input data has the wrong type.
+    BadlyTypedInput,
+    /// An output data has the wrong type.
+    BadlyTypedOutput,
+    /// Only buy transactions may evict inputs, and they must evict exactly one listing.
+    WrongNumberOfListings,
+    /// A list or delist transaction does not consume anything.
+    NothingToList,
+    /// A list or delist transaction must output exactly the kitties it consumes, in the same order.
+    KittiesNotPreserved,
+    /// Kitties may not be listed for free.
+    ZeroPrice,
+    /// A buy transaction's first output must be a coin that pays the seller,
+    /// protected by the same verifier as the listing.
+    SellerNotPaid,
+    /// The seller was paid less than the listing's price.
+    InsufficientPayment,
+    /// A buy transaction's second output must be the kitty that was listed, unchanged.
+    KittyNotDelivered,
+    /// The value of the output coins exceeds the value of the input coins.
+    OutputsExceedInputs,
+    /// The value consumed or created by this transaction overflows the value type.
+    ValueOverflow,
+    /// The transaction attempted to create a coin with zero value. This is not allowed
+    /// because it wastes state space.
+    ZeroValueCoin,
+}
+
+impl From<CashError> for KittyMarketError {
+    fn from(e: CashError) -> Self {
+        match e {
+            CashError::BadlyTyped => Self::BadlyTypedInput,
+            CashError::ZeroValueCoin => Self::ZeroValueCoin,
+            CashError::ValueOverflow => Self::ValueOverflow,
+        }
+    }
+}
+
+/// List kitties for sale.
+///
+/// The inputs are kitties and the outputs are listings of the same kitties, in the same order.
+/// The verifier on each listing becomes its seller.
+#[derive(
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Encode,
+    Decode,
+    Hash,
+    Debug,
+    TypeInfo,
+)]
+pub struct ListKitty;
+
+impl SimpleConstraintChecker for ListKitty {
+    type Error = KittyMarketError;
+
+    fn check(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        evicted_input_data: &[DynamicallyTypedData],
+        _peeks: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> Result<TransactionPriority, Self::Error> {
+        ensure!(
+            evicted_input_data.is_empty(),
+            KittyMarketError::WrongNumberOfListings
+        );
+        ensure!(!input_data.is_empty(), KittyMarketError::NothingToList);
+        ensure!(
+            input_data.len() == output_data.len(),
+            KittyMarketError::KittiesNotPreserved
+        );
+
+        for (input, output) in input_data.iter().zip(output_data) {
+            let kitty = input
+                .extract::<KittyData>()
+                .map_err(|_| KittyMarketError::BadlyTypedInput)?;
+            let listing = output
+                .extract::<ListedKitty>()
+                .map_err(|_| KittyMarketError::BadlyTypedOutput)?;
+            ensure!(
+                listing.kitty == kitty,
+                KittyMarketError::KittiesNotPreserved
+            );
+            ensure!(listing.price > 0, KittyMarketError::ZeroPrice);
+        }
+
+        Ok(0)
+    }
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![UtxoType::of::<KittyData>(), UtxoType::of::<ListedKitty>()]
+    }
+}
+
+/// Take kitties off the market.
+///
+/// The inputs are listings, which are consumed normally so the seller's verifier must be satisfied.
+/// The outputs are the listed kitties, in the same order.
+#[derive(
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Encode,
+    Decode,
+    Hash,
+    Debug,
+    TypeInfo,
+)]
+pub struct DelistKitty;
+
+impl SimpleConstraintChecker for DelistKitty {
+    type Error = KittyMarketError;
+
+    fn check(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        evicted_input_data: &[DynamicallyTypedData],
+        _peeks: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> Result<TransactionPriority, Self::Error> {
+        ensure!(
+            evicted_input_data.is_empty(),
+            KittyMarketError::WrongNumberOfListings
+        );
+        ensure!(!input_data.is_empty(), KittyMarketError::NothingToList);
+        ensure!(
+            input_data.len() == output_data.len(),
+            KittyMarketError::KittiesNotPreserved
+        );
+
+        for (input, output) in input_data.iter().zip(output_data) {
+            let listing = input
+                .extract::<ListedKitty>()
+                .map_err(|_| KittyMarketError::BadlyTypedInput)?;
+            let kitty = output
+                .extract::<KittyData>()
+                .map_err(|_| KittyMarketError::BadlyTypedOutput)?;
+            ensure!(
+                listing.kitty == kitty,
+                KittyMarketError::KittiesNotPreserved
+            );
+        }
+
+        Ok(0)
+    }
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![UtxoType::of::<KittyData>(), UtxoType::of::<ListedKitty>()]
+    }
+}
+
+/// Buy a listed kitty with coins of type `C`.
+///
+/// The single evicted input is the listing and the normal inputs are the buyer's coins.
+/// The first output pays the seller and the second output is the kitty. Any remaining
+/// outputs are coins returned to the buyer as change.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DebugNoBound,
+    DefaultNoBound,
+    CloneNoBound,
+    PartialEq,
+    Eq,
+    TypeInfo,
+)]
+#[scale_info(skip_type_params(C))]
+pub struct BuyKitty<C>(PhantomData<C>);
+
+impl<C: Cash + UtxoData + 'static> VerifierAwareConstraintChecker for BuyKitty<C> {
+    type Error = KittyMarketError;
+
+    fn check<V: Verifier>(
+        &self,
+        inputs: &[Output<V>],
+        evicted_inputs: &[Output<V>],
+        _peeks: &[Output<V>],
+        outputs: &[Output<V>],
+    ) -> Result<TransactionPriority, Self::Error> {
+        ensure!(
+            evicted_inputs.len() == 1,
+            KittyMarketError::WrongNumberOfListings
+        );
+        let listing = evicted_inputs[0]
+            .payload
+            .extract::<ListedKitty>()
+            .map_err(|_| KittyMarketError::BadlyTypedInput)?;
+
+        // The seller is paid first.
+        let (payment, rest) = outputs
+            .split_first()
+            .ok_or(KittyMarketError::SellerNotPaid)?;
+        ensure!(
+            payment.verifier.encode() == evicted_inputs[0].verifier.encode(),
+            KittyMarketError::SellerNotPaid
+        );
+        let payment = payment
+            .payload
+            .extract::<C>()
+            .map_err(|_| KittyMarketError::SellerNotPaid)?
+            .value();
+        ensure!(
+            payment >= listing.price,
+            KittyMarketError::InsufficientPayment
+        );
+
+        // Then the buyer receives the kitty.
+        let (kitty, change) = rest
+            .split_first()
+            .ok_or(KittyMarketError::KittyNotDelivered)?;
+        let kitty = kitty
+            .payload
+            .extract::<KittyData>()
+            .map_err(|_| KittyMarketError::KittyNotDelivered)?;
+        ensure!(kitty == listing.kitty, KittyMarketError::KittyNotDelivered);
+
+        // The buyer may not spend more than they brought.
+        let total_input_value = C::total_value(inputs.iter().map(|input| &input.payload))?;
+        let total_output_value = C::total_value(change.iter().map(|output| &output.payload))
+            .map_err(|e| match e {
+                CashError::BadlyTyped => KittyMarketError::BadlyTypedOutput,
+                e => e.into(),
+            })?
+            .checked_add(payment)
+            .ok_or(KittyMarketError::ValueOverflow)?;
+        ensure!(
+            total_output_value <= total_input_value,
+            KittyMarketError::OutputsExceedInputs
+        );
+
+        Ok(0)
+    }
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![
+            UtxoType::of::<KittyData>(),
+            UtxoType::of::<ListedKitty>(),
+            UtxoType::of::<C>(),
+        ]
+    }
+}
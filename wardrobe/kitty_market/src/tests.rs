@@ -0,0 +1,241 @@
+//! Unit tests for the Kitty Market piece
+
+use super::*;
+use kitties::{DadKittyStatus, Parent};
+use tuxedo_core::{
+    dynamic_typing::testing::{Bogus, Gold},
+    verifier::TestVerifier,
+};
+
+/// The seller is represented by a verifier that passes, and the buyer by one that doesn't.
+const SELLER: TestVerifier = TestVerifier { verifies: true };
+const BUYER: TestVerifier = TestVerifier { verifies: false };
+
+fn other_kitty() -> KittyData {
+    KittyData {
+        parent: Parent::Dad(DadKittyStatus::RearinToGo),
+        ..Default::default()
+    }
+}
+
+fn buy(
+    inputs: &[Output<TestVerifier>],
+    outputs: &[Output<TestVerifier>],
+) -> Result<TransactionPriority, KittyMarketError> {
+    VerifierAwareConstraintChecker::check(
+        &BuyKitty::<Gold>::default(),
+        inputs,
+        &[(ListedKitty::new(KittyData::default(), 10), SELLER).into()],
+        &[],
+        outputs,
+    )
+}
+
+#[test]
+fn list_kitties_works() {
+    let input_data = vec![KittyData::default().into(), other_kitty().into()];
+    let output_data = vec![
+        ListedKitty::new(KittyData::default(), 10).into(),
+        ListedKitty::new(other_kitty(), 20).into(),
+    ];
+
+    assert_eq!(
+        SimpleConstraintChecker::check(&ListKitty, &input_data, &[], &[], &output_data),
+        Ok(0)
+    );
+}
+
+#[test]
+fn list_nothing_fails() {
+    assert_eq!(
+        SimpleConstraintChecker::check(&ListKitty, &[], &[], &[], &[]),
+        Err(KittyMarketError::NothingToList)
+    );
+}
+
+#[test]
+fn list_different_kitty_fails() {
+    let input_data = vec![KittyData::default().into()];
+    let output_data = vec![ListedKitty::new(other_kitty(), 10).into()];
+
+    assert_eq!(
+        SimpleConstraintChecker::check(&ListKitty, &input_data, &[], &[], &output_data),
+        Err(KittyMarketError::KittiesNotPreserved)
+    );
+}
+
+#[test]
+fn list_dropping_kitty_fails() {
+    let input_data = vec![KittyData::default().into(), other_kitty().into()];
+    let output_data = vec![ListedKitty::new(KittyData::default(), 10).into()];
+
+    assert_eq!(
+        SimpleConstraintChecker::check(&ListKitty, &input_data, &[], &[], &output_data),
+        Err(KittyMarketError::KittiesNotPreserved)
+    );
+}
+
+#[test]
+fn list_for_free_fails() {
+    let input_data = vec![KittyData::default().into()];
+    let output_data = vec![ListedKitty::new(KittyData::default(), 0).into()];
+
+    assert_eq!(
+        SimpleConstraintChecker::check(&ListKitty, &input_data, &[], &[], &output_data),
+        Err(KittyMarketError::ZeroPrice)
+    );
+}
+
+#[test]
+fn list_wrong_input_type_fails() {
+    let input_data = vec![Bogus.into()];
+    let output_data = vec![ListedKitty::new(KittyData::default(), 10).into()];
+
+    assert_eq!(
+        SimpleConstraintChecker::check(&ListKitty, &input_data, &[], &[], &output_data),
+        Err(KittyMarketError::BadlyTypedInput)
+    );
+}
+
+#[test]
+fn delist_works() {
+    let input_data = vec![ListedKitty::new(KittyData::default(), 10).into()];
+    let output_data = vec![KittyData::default().into()];
+
+    assert_eq!(
+        SimpleConstraintChecker::check(&DelistKitty, &input_data, &[], &[], &output_data),
+        Ok(0)
+    );
+}
+
+#[test]
+fn delist_different_kitty_fails() {
+    let input_data = vec![ListedKitty::new(KittyData::default(), 10).into()];
+    let output_data = vec![other_kitty().into()];
+
+    assert_eq!(
+        SimpleConstraintChecker::check(&DelistKitty, &input_data, &[], &[], &output_data),
+        Err(KittyMarketError::KittiesNotPreserved)
+    );
+}
+
+#[test]
+fn delist_wrong_output_type_fails() {
+    let input_data = vec![ListedKitty::new(KittyData::default(), 10).into()];
+    let output_data = vec![Bogus.into()];
+
+    assert_eq!(
+        SimpleConstraintChecker::check(&DelistKitty, &input_data, &[], &[], &output_data),
+        Err(KittyMarketError::BadlyTypedOutput)
+    );
+}
+
+#[test]
+fn buy_with_change_works() {
+    assert_eq!(
+        buy(
+            &[(Gold(8), BUYER).into(), (Gold(5), BUYER).into()],
+            &[
+                (Gold(10), SELLER).into(),
+                (KittyData::default(), BUYER).into(),
+                (Gold(3), BUYER).into(),
+            ]
+        ),
+        Ok(0)
+    );
+}
+
+#[test]
+fn buy_without_paying_seller_fails() {
+    assert_eq!(
+        buy(
+            &[(Gold(10), BUYER).into()],
+            &[
+                (Gold(10), BUYER).into(),
+                (KittyData::default(), BUYER).into(),
+            ]
+        ),
+        Err(KittyMarketError::SellerNotPaid)
+    );
+}
+
+#[test]
+fn buy_with_underpayment_fails() {
+    assert_eq!(
+        buy(
+            &[(Gold(10), BUYER).into()],
+            &[
+                (Gold(9), SELLER).into(),
+                (KittyData::default(), BUYER).into(),
+            ]
+        ),
+        Err(KittyMarketError::InsufficientPayment)
+    );
+}
+
+#[test]
+fn buy_without_receiving_kitty_fails() {
+    assert_eq!(
+        buy(&[(Gold(10), BUYER).into()], &[(Gold(10), SELLER).into()]),
+        Err(KittyMarketError::KittyNotDelivered)
+    );
+}
+
+#[test]
+fn buy_receiving_different_kitty_fails() {
+    assert_eq!(
+        buy(
+            &[(Gold(10), BUYER).into()],
+            &[(Gold(10), SELLER).into(), (other_kitty(), BUYER).into()]
+        ),
+        Err(KittyMarketError::KittyNotDelivered)
+    );
+}
+
+#[test]
+fn buy_paying_with_value_not_brought_fails() {
+    assert_eq!(
+        buy(
+            &[(Gold(9), BUYER).into()],
+            &[
+                (Gold(10), SELLER).into(),
+                (KittyData::default(), BUYER).into(),
+            ]
+        ),
+        Err(KittyMarketError::OutputsExceedInputs)
+    );
+}
+
+#[test]
+fn buy_with_wrong_input_type_fails() {
+    assert_eq!(
+        buy(
+            &[(Gold(10), BUYER).into(), (Bogus, BUYER).into()],
+            &[
+                (Gold(10), SELLER).into(),
+                (KittyData::default(), BUYER).into(),
+            ]
+        ),
+        Err(KittyMarketError::BadlyTypedInput)
+    );
+}
+
+#[test]
+fn buy_without_evicting_listing_fails() {
+    assert_eq!(
+        VerifierAwareConstraintChecker::check(
+            &BuyKitty::<Gold>::default(),
+            &[
+                (Gold(10), BUYER).into(),
+                (ListedKitty::new(KittyData::default(), 10), SELLER).into(),
+            ],
+            &[],
+            &[],
+            &[
+                (Gold(10), SELLER).into(),
+                (KittyData::default(), BUYER).into(),
+            ],
+        ),
+        Err(KittyMarketError::WrongNumberOfListings)
+    );
+}
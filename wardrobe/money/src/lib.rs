@@ -8,8 +8,9 @@ use serde::{Deserialize, Serialize};
 use sp_runtime::transaction_validity::TransactionPriority;
 use sp_std::prelude::*;
 use tuxedo_core::{
-    dynamic_typing::{DynamicallyTypedData, UtxoData},
+    dynamic_typing::{DynamicallyTypedData, UtxoData, UtxoType},
     ensure,
+    support_macros::DefaultNoBound,
     traits::Cash,
     types::Transaction,
     SimpleConstraintChecker, Verifier,
@@ -28,7 +29,8 @@ impl<const ID: u8> Cash for Coin<ID> {
 
 // use log::info;
 
-/// The main constraint checker for the money piece. Allows spending and minting tokens.
+/// The main constraint checker for the money piece. Allows spending tokens, and minting them
+/// with a `MintAuthority`.
 #[derive(
     Serialize,
     Deserialize,
@@ -46,14 +48,55 @@ impl<const ID: u8> Cash for Coin<ID> {
 pub enum MoneyConstraintChecker<const ID: u8> {
     /// A typical spend transaction where some coins are consumed and others are created.
     /// Input value must exceed output value. The difference is burned and reflected in the
-    /// transaction's priority.
+    /// transaction's priority. A runtime's fee policy may count it as a fee (see `burned`).
     Spend,
-    /// A mint transaction that creates no coins out of the void. In a real-world chain,
-    /// this should be protected somehow, or not included at all. For now it is publicly
-    /// available. I'm adding it to explore multiple validation paths in a single piece.
+    /// The old public mint transaction, which is no longer allowed. Every transaction with this
+    /// checker is rejected with `MintingRequiresAuthority`. Use `AuthorizedMint` instead, or
+    /// include [`FreeMint`] in a development runtime.
+    ///
+    /// The variant is kept so that the variants after it keep their encoding.
     Mint,
+    /// A mint transaction that is only valid when it consumes a `MintAuthority`.
+    /// The authority must be the only input and must be recreated as the first output
+    /// with its minted total increased by exactly the value of the new coins, which follow it.
+    /// The recreated authority may have a different verifier, which transfers the right to mint.
+    ///
+    /// Because the authority is consumed, its verifier is checked like any other input's.
+    /// Merely peeking at it would prove nothing, since peeks are not verified.
+    AuthorizedMint,
 }
 
+impl<const ID: u8> MoneyConstraintChecker<ID> {
+    /// The value of `Coin<ID>` that a valid transaction with this checker burns.
+    ///
+    /// This is meant for a runtime's fee policy, which is only consulted once the transaction
+    /// has been checked. So any data that is not a `Coin<ID>` is simply ignored here.
+    pub fn burned(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> u128 {
+        let total_value = |data: &[DynamicallyTypedData]| {
+            data.iter()
+                .filter_map(|utxo| utxo.extract::<Coin<ID>>().ok())
+                .fold(0u128, |total, coin| total.saturating_add(coin.0))
+        };
+
+        match self {
+            Self::Spend => total_value(input_data).saturating_sub(total_value(output_data)),
+            Self::Mint | Self::AuthorizedMint => 0,
+        }
+    }
+}
+
+/// A mint transaction that creates coins out of the void, without any authorization.
+///
+/// Anyone may mint with this checker, so it is only suitable for development and test chains.
+/// It is kept separate from `MoneyConstraintChecker` so that including the money piece in a
+/// runtime does not also open minting to the public.
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, TypeInfo)]
+pub struct FreeMint<const ID: u8>;
+
 /// A single coin in the fungible money system.
 /// A new-type wrapper around a `u128` value.
 #[derive(
@@ -77,8 +120,11 @@ impl<const ID: u8> Coin<ID> {
         Coin(amt)
     }
 
-    /// Create a mint transaction for a single Coin.
-    pub fn mint<V, OV, OC>(amt: u128, v: V) -> Transaction<OV, OC>
+    /// Create a genesis transaction that stores a single Coin.
+    ///
+    /// This transaction would be rejected after genesis, but genesis transactions are not checked.
+    /// It allows runtimes that do not include [`FreeMint`] to have coins in the genesis block.
+    pub fn genesis<V, OV, OC>(amt: u128, v: V) -> Transaction<OV, OC>
     where
         V: Verifier,
         OV: Verifier + From<V>,
@@ -88,7 +134,7 @@ impl<const ID: u8> Coin<ID> {
             inputs: vec![],
             peeks: vec![],
             outputs: vec![(Self::new(amt), v).into()],
-            checker: MoneyConstraintChecker::Mint.into(),
+            checker: MoneyConstraintChecker::AuthorizedMint.into(),
         }
     }
 }
@@ -97,6 +143,113 @@ impl<const ID: u8> UtxoData for Coin<ID> {
     const TYPE_ID: [u8; 4] = [b'c', b'o', b'i', ID];
 }
 
+/// The right to mint coins with `MoneyConstraintChecker::AuthorizedMint`.
+///
+/// Whoever can satisfy the verifier protecting this UTXO may mint. Nothing can create a
+/// mint authority after genesis, so it must be included in the genesis block with `genesis`.
+#[derive(
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Encode,
+    Decode,
+    Hash,
+    Debug,
+    TypeInfo,
+    UtxoData,
+)]
+pub struct MintAuthority<const ID: u8> {
+    /// The most value that may ever be minted with this authority, if there is a limit.
+    pub supply_cap: Option<u128>,
+    /// The value that has been minted with this authority so far.
+    pub minted: u128,
+}
+
+impl<const ID: u8> MintAuthority<ID> {
+    pub fn new(supply_cap: Option<u128>) -> Self {
+        Self {
+            supply_cap,
+            minted: 0,
+        }
+    }
+
+    /// Create a genesis transaction that stores a fresh mint authority protected by
+    /// the given verifier, such as a multisig.
+    ///
+    /// This transaction would be rejected after genesis, but genesis transactions are not checked.
+    pub fn genesis<V, OV, OC>(supply_cap: Option<u128>, v: V) -> Transaction<OV, OC>
+    where
+        V: Verifier,
+        OV: Verifier + From<V>,
+        OC: tuxedo_core::ConstraintChecker + From<MoneyConstraintChecker<ID>>,
+    {
+        Transaction {
+            inputs: vec![],
+            peeks: vec![],
+            outputs: vec![(Self::new(supply_cap), v).into()],
+            checker: MoneyConstraintChecker::AuthorizedMint.into(),
+        }
+    }
+}
+
+/// This piece's section of a runtime's genesis configuration.
+///
+/// A runtime with several coins has a section for each of them, which are compiled with
+/// [`GenesisConfig::genesis_transactions`] for the coin's id.
+#[derive(Serialize, Deserialize, DefaultNoBound, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields, default)]
+pub struct GenesisConfig<V> {
+    /// The coins that exist at genesis.
+    pub coins: Vec<GenesisCoin<V>>,
+    /// The authority that may mint more coins after genesis, if there is one.
+    pub mint_authority: Option<GenesisMintAuthority<V>>,
+}
+
+/// A coin that exists at genesis.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct GenesisCoin<V> {
+    /// The verifier that protects the coin, such as a signature check or a multisig.
+    pub owner: V,
+    /// The value of the coin.
+    pub amount: u128,
+}
+
+/// A mint authority that exists at genesis.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct GenesisMintAuthority<V> {
+    /// The verifier that must be satisfied to mint, such as a multisig.
+    pub owner: V,
+    /// The most value that may ever be minted, if there is a limit.
+    #[serde(default)]
+    pub supply_cap: Option<u128>,
+}
+
+impl<V: Verifier> GenesisConfig<V> {
+    /// Compile this section into genesis transactions for `Coin<ID>`.
+    pub fn genesis_transactions<const ID: u8, OV, OC>(self) -> Vec<Transaction<OV, OC>>
+    where
+        OV: Verifier + From<V>,
+        OC: tuxedo_core::ConstraintChecker + From<MoneyConstraintChecker<ID>>,
+    {
+        let coins = self
+            .coins
+            .into_iter()
+            .map(|coin| Coin::<ID>::genesis(coin.amount, coin.owner));
+        let mint_authority = self
+            .mint_authority
+            .into_iter()
+            .map(|authority| MintAuthority::<ID>::genesis(authority.supply_cap, authority.owner));
+
+        coins.chain(mint_authority).collect()
+    }
+}
+
 /// Errors that can occur when checking money transactions.
 #[derive(
     Serialize,
@@ -135,11 +288,27 @@ pub enum ConstraintCheckerError {
     /// The transaction attempted to create a coin with zero value. This is not allowed
     /// because it wastes state space.
     ZeroValueCoin,
+    /// An authorized mint transaction must consume exactly one input, which is the mint authority.
+    MissingMintAuthority,
+    /// An authorized mint transaction must recreate the mint authority as its first output,
+    /// with the same supply cap and with its minted total increased by the value of the new coins.
+    MintAuthorityNotPreserved,
+    /// The mint would bring the total value minted by this authority above its supply cap.
+    SupplyCapExceeded,
+    /// The transaction uses the retired public `Mint`. Minting requires a mint authority.
+    MintingRequiresAuthority,
 }
 
 impl<const ID: u8> SimpleConstraintChecker for MoneyConstraintChecker<ID> {
     type Error = ConstraintCheckerError;
 
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![
+            UtxoType::of::<Coin<ID>>(),
+            UtxoType::of::<MintAuthority<ID>>(),
+        ]
+    }
+
     fn check(
         &self,
         input_data: &[DynamicallyTypedData],
@@ -194,32 +363,65 @@ impl<const ID: u8> SimpleConstraintChecker for MoneyConstraintChecker<ID> {
                 // Priority is based on how many token are burned
                 // Type stuff is kinda ugly. Maybe division would be better?
                 let burned = total_input_value - total_output_value;
+
                 Ok(if burned < u64::MAX as u128 {
                     burned as u64
                 } else {
                     u64::MAX
                 })
             }
-            Self::Mint => {
-                // Make sure there are no inputs being consumed
+            Self::Mint => Err(ConstraintCheckerError::MintingRequiresAuthority),
+            Self::AuthorizedMint => {
+                // The authority must be the only input
                 ensure!(
-                    input_data.is_empty(),
-                    ConstraintCheckerError::MintingWithInputs
+                    input_data.len() == 1,
+                    ConstraintCheckerError::MissingMintAuthority
                 );
+                let authority = input_data[0]
+                    .extract::<MintAuthority<ID>>()
+                    .map_err(|_| ConstraintCheckerError::MissingMintAuthority)?;
 
-                // Make sure there is at least one output being minted
+                // The authority must be recreated first, followed by at least one new coin
+                let (updated_authority, new_coins) = output_data
+                    .split_first()
+                    .ok_or(ConstraintCheckerError::MintAuthorityNotPreserved)?;
+                let updated_authority = updated_authority
+                    .extract::<MintAuthority<ID>>()
+                    .map_err(|_| ConstraintCheckerError::MintAuthorityNotPreserved)?;
                 ensure!(
-                    !output_data.is_empty(),
+                    !new_coins.is_empty(),
                     ConstraintCheckerError::MintingNothing
                 );
 
-                // Make sure the outputs are the right type
-                for utxo in output_data {
+                let mut total_minted: u128 = 0;
+                for utxo in new_coins {
                     let utxo_value = utxo
                         .extract::<Coin<ID>>()
                         .map_err(|_| ConstraintCheckerError::BadlyTyped)?
                         .0;
                     ensure!(utxo_value > 0, ConstraintCheckerError::ZeroValueCoin);
+                    total_minted = total_minted
+                        .checked_add(utxo_value)
+                        .ok_or(ConstraintCheckerError::ValueOverflow)?;
+                }
+
+                // Make sure the authority's bookkeeping is updated correctly and the cap is respected
+                let expected_authority = MintAuthority::<ID> {
+                    supply_cap: authority.supply_cap,
+                    minted: authority
+                        .minted
+                        .checked_add(total_minted)
+                        .ok_or(ConstraintCheckerError::ValueOverflow)?,
+                };
+                ensure!(
+                    updated_authority == expected_authority,
+                    ConstraintCheckerError::MintAuthorityNotPreserved
+                );
+                if let Some(cap) = expected_authority.supply_cap {
+                    ensure!(
+                        expected_authority.minted <= cap,
+                        ConstraintCheckerError::SupplyCapExceeded
+                    );
                 }
 
                 // No priority for minting
@@ -228,3 +430,49 @@ impl<const ID: u8> SimpleConstraintChecker for MoneyConstraintChecker<ID> {
         }
     }
 }
+
+impl<const ID: u8> SimpleConstraintChecker for FreeMint<ID> {
+    type Error = ConstraintCheckerError;
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![UtxoType::of::<Coin<ID>>()]
+    }
+
+    fn check(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        evicted_input_data: &[DynamicallyTypedData],
+        _peeks: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> Result<TransactionPriority, Self::Error> {
+        // Can't evict anything
+        ensure!(
+            evicted_input_data.is_empty(),
+            ConstraintCheckerError::NoEvictionsAllowed
+        );
+
+        // Make sure there are no inputs being consumed
+        ensure!(
+            input_data.is_empty(),
+            ConstraintCheckerError::MintingWithInputs
+        );
+
+        // Make sure there is at least one output being minted
+        ensure!(
+            !output_data.is_empty(),
+            ConstraintCheckerError::MintingNothing
+        );
+
+        // Make sure the outputs are the right type
+        for utxo in output_data {
+            let utxo_value = utxo
+                .extract::<Coin<ID>>()
+                .map_err(|_| ConstraintCheckerError::BadlyTyped)?
+                .0;
+            ensure!(utxo_value > 0, ConstraintCheckerError::ZeroValueCoin);
+        }
+
+        // No priority for minting
+        Ok(0)
+    }
+}
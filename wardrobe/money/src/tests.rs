@@ -42,6 +42,50 @@ fn spend_no_outputs_is_a_burn() {
     );
 }
 
+#[test]
+fn spend_burned_value_is_input_minus_output() {
+    let input_data = vec![Coin::<0>(5).into(), Coin::<0>(7).into()]; // total 12
+    let output_data = vec![Coin::<0>(10).into()]; // total 10
+
+    assert_eq!(
+        MoneyConstraintChecker::<0>::Spend.burned(&input_data, &output_data),
+        2
+    );
+}
+
+#[test]
+fn spend_without_burning_burns_nothing() {
+    let input_data = vec![Coin::<0>(5).into(), Coin::<0>(7).into()]; // total 12
+    let output_data = vec![Coin::<0>(12).into()]; // total 12
+
+    assert_eq!(
+        MoneyConstraintChecker::<0>::Spend.check(&input_data, &[], &[], &output_data),
+        Ok(0),
+    );
+    assert_eq!(
+        MoneyConstraintChecker::<0>::Spend.burned(&input_data, &output_data),
+        0
+    );
+}
+
+#[test]
+fn mint_burns_nothing() {
+    let input_data = vec![MintAuthority::<0>::new(None).into()];
+    let output_data = vec![
+        MintAuthority::<0> {
+            minted: 5,
+            supply_cap: None,
+        }
+        .into(),
+        Coin::<0>(5).into(),
+    ];
+
+    assert_eq!(
+        MoneyConstraintChecker::<0>::AuthorizedMint.burned(&input_data, &output_data),
+        0
+    );
+}
+
 #[test]
 fn spend_no_inputs_fails() {
     let input_data = vec![];
@@ -92,18 +136,28 @@ fn mint_valid_transaction_works() {
     let output_data = vec![Coin::<0>(10).into(), Coin::<0>(1).into()];
 
     assert_eq!(
-        MoneyConstraintChecker::<0>::Mint.check(&input_data, &[], &[], &output_data),
+        FreeMint::<0>.check(&input_data, &[], &[], &output_data),
         Ok(0),
     );
 }
 
+#[test]
+fn mint_with_evictions_fails() {
+    let output_data = vec![Coin::<0>(10).into()];
+
+    assert_eq!(
+        FreeMint::<0>.check(&[], &[Coin::<0>(5).into()], &[], &output_data),
+        Err(ConstraintCheckerError::NoEvictionsAllowed),
+    );
+}
+
 #[test]
 fn mint_with_zero_value_output_fails() {
     let input_data = vec![];
     let output_data = vec![Coin::<0>(0).into()];
 
     assert_eq!(
-        MoneyConstraintChecker::<0>::Mint.check(&input_data, &[], &[], &output_data),
+        FreeMint::<0>.check(&input_data, &[], &[], &output_data),
         Err(ConstraintCheckerError::ZeroValueCoin),
     );
 }
@@ -114,7 +168,7 @@ fn mint_with_inputs_fails() {
     let output_data = vec![Coin::<0>(10).into(), Coin::<0>(1).into()];
 
     assert_eq!(
-        MoneyConstraintChecker::<0>::Mint.check(&input_data, &[], &[], &output_data),
+        FreeMint::<0>.check(&input_data, &[], &[], &output_data),
         Err(ConstraintCheckerError::MintingWithInputs),
     );
 }
@@ -125,7 +179,7 @@ fn mint_with_no_outputs_fails() {
     let output_data = vec![];
 
     assert_eq!(
-        MoneyConstraintChecker::<0>::Mint.check(&input_data, &[], &[], &output_data),
+        FreeMint::<0>.check(&input_data, &[], &[], &output_data),
         Err(ConstraintCheckerError::MintingNothing),
     );
 }
@@ -136,7 +190,258 @@ fn mint_wrong_output_type_fails() {
     let output_data = vec![Coin::<0>(10).into(), Bogus.into()];
 
     assert_eq!(
-        MoneyConstraintChecker::<0>::Mint.check(&input_data, &[], &[], &output_data),
+        FreeMint::<0>.check(&input_data, &[], &[], &output_data),
         Err(ConstraintCheckerError::BadlyTyped),
     );
 }
+
+#[test]
+fn retired_public_mint_fails() {
+    let output_data = vec![Coin::<0>(10).into()];
+
+    assert_eq!(
+        MoneyConstraintChecker::<0>::Mint.check(&[], &[], &[], &output_data),
+        Err(ConstraintCheckerError::MintingRequiresAuthority),
+    );
+}
+
+#[test]
+fn checker_variants_keep_their_encoding() {
+    assert_eq!(MoneyConstraintChecker::<0>::Spend.encode(), vec![0]);
+    assert_eq!(MoneyConstraintChecker::<0>::Mint.encode(), vec![1]);
+    assert_eq!(
+        MoneyConstraintChecker::<0>::AuthorizedMint.encode(),
+        vec![2]
+    );
+}
+
+#[test]
+fn authorized_mint_valid_transaction_works() {
+    let input_data = vec![MintAuthority::<0>::new(None).into()];
+    let output_data = vec![
+        MintAuthority::<0> {
+            supply_cap: None,
+            minted: 11,
+        }
+        .into(),
+        Coin::<0>(10).into(),
+        Coin::<0>(1).into(),
+    ];
+
+    assert_eq!(
+        MoneyConstraintChecker::<0>::AuthorizedMint.check(&input_data, &[], &[], &output_data),
+        Ok(0),
+    );
+}
+
+#[test]
+fn authorized_mint_up_to_supply_cap_works() {
+    let input_data = vec![MintAuthority::<0> {
+        supply_cap: Some(20),
+        minted: 15,
+    }
+    .into()];
+    let output_data = vec![
+        MintAuthority::<0> {
+            supply_cap: Some(20),
+            minted: 20,
+        }
+        .into(),
+        Coin::<0>(5).into(),
+    ];
+
+    assert_eq!(
+        MoneyConstraintChecker::<0>::AuthorizedMint.check(&input_data, &[], &[], &output_data),
+        Ok(0),
+    );
+}
+
+#[test]
+fn authorized_mint_beyond_supply_cap_fails() {
+    let input_data = vec![MintAuthority::<0> {
+        supply_cap: Some(20),
+        minted: 15,
+    }
+    .into()];
+    let output_data = vec![
+        MintAuthority::<0> {
+            supply_cap: Some(20),
+            minted: 21,
+        }
+        .into(),
+        Coin::<0>(6).into(),
+    ];
+
+    assert_eq!(
+        MoneyConstraintChecker::<0>::AuthorizedMint.check(&input_data, &[], &[], &output_data),
+        Err(ConstraintCheckerError::SupplyCapExceeded),
+    );
+}
+
+#[test]
+fn authorized_mint_without_authority_fails() {
+    let input_data = vec![Coin::<0>(5).into()];
+    let output_data = vec![MintAuthority::<0>::new(None).into(), Coin::<0>(10).into()];
+
+    assert_eq!(
+        MoneyConstraintChecker::<0>::AuthorizedMint.check(&input_data, &[], &[], &output_data),
+        Err(ConstraintCheckerError::MissingMintAuthority),
+    );
+}
+
+#[test]
+fn authorized_mint_with_extra_inputs_fails() {
+    let input_data = vec![MintAuthority::<0>::new(None).into(), Coin::<0>(5).into()];
+    let output_data = vec![
+        MintAuthority::<0> {
+            supply_cap: None,
+            minted: 10,
+        }
+        .into(),
+        Coin::<0>(10).into(),
+    ];
+
+    assert_eq!(
+        MoneyConstraintChecker::<0>::AuthorizedMint.check(&input_data, &[], &[], &output_data),
+        Err(ConstraintCheckerError::MissingMintAuthority),
+    );
+}
+
+#[test]
+fn authorized_mint_with_authority_for_other_coin_fails() {
+    let input_data = vec![MintAuthority::<1>::new(None).into()];
+    let output_data = vec![
+        MintAuthority::<0> {
+            supply_cap: None,
+            minted: 10,
+        }
+        .into(),
+        Coin::<0>(10).into(),
+    ];
+
+    assert_eq!(
+        MoneyConstraintChecker::<0>::AuthorizedMint.check(&input_data, &[], &[], &output_data),
+        Err(ConstraintCheckerError::MissingMintAuthority),
+    );
+}
+
+#[test]
+fn authorized_mint_without_recreating_authority_fails() {
+    let input_data = vec![MintAuthority::<0>::new(None).into()];
+    let output_data = vec![Coin::<0>(10).into()];
+
+    assert_eq!(
+        MoneyConstraintChecker::<0>::AuthorizedMint.check(&input_data, &[], &[], &output_data),
+        Err(ConstraintCheckerError::MintAuthorityNotPreserved),
+    );
+}
+
+#[test]
+fn authorized_mint_underreporting_minted_value_fails() {
+    let input_data = vec![MintAuthority::<0>::new(Some(20)).into()];
+    let output_data = vec![
+        MintAuthority::<0> {
+            supply_cap: Some(20),
+            minted: 5,
+        }
+        .into(),
+        Coin::<0>(10).into(),
+    ];
+
+    assert_eq!(
+        MoneyConstraintChecker::<0>::AuthorizedMint.check(&input_data, &[], &[], &output_data),
+        Err(ConstraintCheckerError::MintAuthorityNotPreserved),
+    );
+}
+
+#[test]
+fn authorized_mint_raising_supply_cap_fails() {
+    let input_data = vec![MintAuthority::<0>::new(Some(5)).into()];
+    let output_data = vec![
+        MintAuthority::<0> {
+            supply_cap: Some(10),
+            minted: 10,
+        }
+        .into(),
+        Coin::<0>(10).into(),
+    ];
+
+    assert_eq!(
+        MoneyConstraintChecker::<0>::AuthorizedMint.check(&input_data, &[], &[], &output_data),
+        Err(ConstraintCheckerError::MintAuthorityNotPreserved),
+    );
+}
+
+#[test]
+fn authorized_mint_with_no_new_coins_fails() {
+    let input_data = vec![MintAuthority::<0>::new(None).into()];
+    let output_data = vec![MintAuthority::<0>::new(None).into()];
+
+    assert_eq!(
+        MoneyConstraintChecker::<0>::AuthorizedMint.check(&input_data, &[], &[], &output_data),
+        Err(ConstraintCheckerError::MintingNothing),
+    );
+}
+
+#[test]
+fn authorized_mint_wrong_output_type_fails() {
+    let input_data = vec![MintAuthority::<0>::new(None).into()];
+    let output_data = vec![
+        MintAuthority::<0> {
+            supply_cap: None,
+            minted: 10,
+        }
+        .into(),
+        Coin::<0>(10).into(),
+        Bogus.into(),
+    ];
+
+    assert_eq!(
+        MoneyConstraintChecker::<0>::AuthorizedMint.check(&input_data, &[], &[], &output_data),
+        Err(ConstraintCheckerError::BadlyTyped),
+    );
+}
+
+#[test]
+fn genesis_config_mints_coins_then_authority() {
+    use tuxedo_core::verifier::UpForGrabs;
+
+    let config = GenesisConfig {
+        coins: vec![
+            GenesisCoin {
+                owner: UpForGrabs,
+                amount: 100,
+            },
+            GenesisCoin {
+                owner: UpForGrabs,
+                amount: 5,
+            },
+        ],
+        mint_authority: Some(GenesisMintAuthority {
+            owner: UpForGrabs,
+            supply_cap: Some(1_000),
+        }),
+    };
+
+    let transactions: Vec<Transaction<UpForGrabs, MoneyConstraintChecker<1>>> =
+        config.genesis_transactions::<1, _, _>();
+
+    assert_eq!(
+        transactions,
+        vec![
+            Coin::<1>::genesis(100, UpForGrabs),
+            Coin::<1>::genesis(5, UpForGrabs),
+            MintAuthority::<1>::genesis(Some(1_000), UpForGrabs),
+        ]
+    );
+}
+
+#[test]
+fn empty_genesis_config_creates_nothing() {
+    use tuxedo_core::verifier::UpForGrabs;
+
+    let transactions: Vec<Transaction<UpForGrabs, MoneyConstraintChecker<0>>> =
+        GenesisConfig::<UpForGrabs>::default().genesis_transactions::<0, _, _>();
+
+    assert!(transactions.is_empty());
+}
@@ -0,0 +1,748 @@
+//! Collections of non-fungible tokens with metadata and creator royalties.
+//!
+//! The kitties piece models a single game whose tokens are bred from one another. This piece is
+//! more general. Anyone may create a [`Collection`] by storing it in a UTXO. The verifier protecting
+//! that UTXO is the collection's creator. Only the creator may mint new tokens, because minting
+//! consumes the collection and recreates it with an updated count, just like the token factory's
+//! asset definitions. A collection may cap the number of tokens that will ever be minted.
+//!
+//! Every [`Nft`] records the id of its collection, a sequential token id, and the hash of its
+//! metadata. The metadata itself, such as an image or a JSON document, lives off chain.
+//!
+//! Tokens may be given away with a plain transfer, or sold for coins of any type that implements
+//! the `Cash` trait. To sell a token, its owner lists it by consuming it and creating a [`ListedNft`]
+//! that records the asking price, just like the kitty marketplace. A buyer evicts the listing and
+//! pays the price, and the creator's royalty is taken out of it in the same transaction. The
+//! transaction peeks at the collection to learn the royalty rate and the creator's verifier.
+//!
+//! Transfers and purchases may not be part of compound transactions, so a sale can not be disguised
+//! as a transfer that happens to be bundled with a payment.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use core::marker::PhantomData;
+
+use parity_scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use serde::{Deserialize, Serialize};
+use sp_core::H256;
+use sp_runtime::{
+    traits::{BlakeTwo256, Hash},
+    transaction_validity::TransactionPriority,
+};
+use sp_std::prelude::*;
+use tuxedo_core::{
+    dynamic_typing::{DynamicallyTypedData, UtxoData, UtxoType},
+    ensure,
+    support_macros::{CloneNoBound, DebugNoBound, DefaultNoBound},
+    traits::Cash,
+    types::{Output, Transaction},
+    ConstraintChecker, SimpleConstraintChecker, Verifier, VerifierAwareConstraintChecker,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// The longest name a collection may have, in bytes.
+pub const MAX_NAME_LENGTH: usize = 32;
+
+/// Royalty rates are expressed in basis points, so this rate means the creator receives the full price.
+pub const MAX_ROYALTY: u16 = 10_000;
+
+/// The description of a collection. It is stored in a UTXO whose verifier is the collection's creator.
+#[derive(
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Encode,
+    Decode,
+    Hash,
+    Debug,
+    TypeInfo,
+    UtxoData,
+)]
+pub struct Collection {
+    /// A human readable name for the collection.
+    pub name: Vec<u8>,
+    /// The most tokens that may ever be minted in this collection, if there is a limit.
+    pub max_supply: Option<u32>,
+    /// The share of every sale's price that is owed to the creator, in basis points.
+    pub royalty: u16,
+    /// How many tokens have been minted so far. This is also the id of the next token.
+    pub minted: u32,
+}
+
+impl Collection {
+    pub fn new(name: &[u8], max_supply: Option<u32>, royalty: u16) -> Self {
+        Self {
+            name: name.to_vec(),
+            max_supply,
+            royalty,
+            minted: 0,
+        }
+    }
+
+    /// The id of this collection when it is protected by the given creator.
+    ///
+    /// The creator must be encoded exactly as it is stored on chain, which usually means as the
+    /// runtime's outer verifier. The number of tokens minted so far is not part of the id,
+    /// so it remains the same for the life of the collection.
+    pub fn id<V: Encode>(&self, creator: &V) -> H256 {
+        BlakeTwo256::hash_of(&(&self.name, self.max_supply, self.royalty, creator))
+    }
+
+    /// The royalty owed to the creator when tokens from this collection are sold for `price`.
+    ///
+    /// The royalty is rounded up so that the creator is never short changed.
+    pub fn royalty_on(&self, price: u128) -> Result<u128, NftError> {
+        let numerator = price
+            .checked_mul(self.royalty.into())
+            .and_then(|n| n.checked_add(MAX_ROYALTY as u128 - 1))
+            .ok_or(NftError::ValueOverflow)?;
+        Ok(numerator / MAX_ROYALTY as u128)
+    }
+
+    /// Create a transaction that creates this collection.
+    pub fn create<V, OV, OC>(self, creator: V) -> Transaction<OV, OC>
+    where
+        V: Verifier,
+        OV: Verifier + From<V>,
+        OC: ConstraintChecker + From<CreateCollection>,
+    {
+        Transaction {
+            inputs: vec![],
+            peeks: vec![],
+            outputs: vec![(self, creator).into()],
+            checker: CreateCollection.into(),
+        }
+    }
+}
+
+/// A single non-fungible token.
+#[derive(
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Encode,
+    Decode,
+    Hash,
+    Debug,
+    TypeInfo,
+    UtxoData,
+)]
+pub struct Nft {
+    /// The collection to which this token belongs. See [`Collection::id`].
+    pub collection_id: H256,
+    /// The position of this token within its collection, starting from zero.
+    pub token_id: u32,
+    /// The hash of this token's off-chain metadata.
+    pub metadata: H256,
+}
+
+impl Nft {
+    pub fn new(collection_id: H256, token_id: u32, metadata: H256) -> Self {
+        Self {
+            collection_id,
+            token_id,
+            metadata,
+        }
+    }
+}
+
+/// A token that is listed for sale. The listing's verifier belongs to the seller.
+#[derive(
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Encode,
+    Decode,
+    Hash,
+    Debug,
+    TypeInfo,
+    UtxoData,
+)]
+pub struct ListedNft {
+    /// The token that is for sale.
+    pub token: Nft,
+    /// The value of coins the buyer must pay, including the creator's royalty.
+    pub price: u128,
+}
+
+impl ListedNft {
+    pub fn new(token: Nft, price: u128) -> Self {
+        Self { token, price }
+    }
+}
+
+/// Errors that can occur when checking NFT transactions.
+#[derive(
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Encode,
+    Decode,
+    Hash,
+    Debug,
+    TypeInfo,
+)]
+pub enum NftError {
+    /// An input data has the wrong type.
+    BadlyTypedInput,
+    /// An output data has the wrong type.
+    BadlyTypedOutput,
+    /// The NFT piece does not allow any evictions at all.
+    NoEvictionsAllowed,
+    /// A transaction that creates a collection attempts to consume inputs.
+    CreatingWithInputs,
+    /// A transaction that creates a collection must create exactly one collection and nothing else.
+    WrongNumberOfCollections,
+    /// The collection's name is empty.
+    EmptyName,
+    /// The collection's name is longer than `MAX_NAME_LENGTH`.
+    NameTooLong,
+    /// The collection's maximum supply is zero, so no tokens could ever be minted.
+    ZeroMaxSupply,
+    /// The collection's royalty is higher than `MAX_ROYALTY`.
+    RoyaltyTooHigh,
+    /// A new collection must not claim to have minted any tokens yet.
+    NewCollectionAlreadyMinted,
+    /// A mint transaction must consume exactly one input, which is the collection.
+    WrongNumberOfMintInputs,
+    /// A mint transaction must recreate the collection it consumed as its first output,
+    /// with the same data and the same creator, and with the count of minted tokens updated.
+    CollectionNotPreserved,
+    /// A mint transaction does not create any tokens.
+    MintingNothing,
+    /// A token created by a mint transaction belongs to a different collection than the one
+    /// that was consumed.
+    WrongCollection,
+    /// The tokens created by a mint transaction must be numbered sequentially, starting
+    /// from the number of tokens the collection had already minted.
+    WrongTokenId,
+    /// Minting these tokens would exceed the collection's maximum supply.
+    MaxSupplyExceeded,
+    /// A transfer, list, delist, or burn transaction does not consume any tokens.
+    NoTokens,
+    /// A transfer, list, or delist transaction must output exactly the same tokens that it
+    /// consumes, in the same order.
+    TokensNotPreserved,
+    /// A burn transaction attempts to create outputs.
+    BurnWithOutputs,
+    /// Tokens may not be listed for free.
+    ZeroPrice,
+    /// A buy transaction must evict exactly one listing, and nothing else may be evicted.
+    WrongNumberOfListings,
+    /// A buy transaction's first output must be the token that was listed, unchanged.
+    TokenNotDelivered,
+    /// A buy transaction must peek at the collection of the token being bought.
+    MissingCollection,
+    /// The seller was paid less than the price, minus the creator's royalty.
+    SellerNotPaid,
+    /// The creator was paid less than the royalty owed on the sale.
+    RoyaltyNotPaid,
+    /// The value of the output coins exceeds the value of the input coins.
+    OutputsExceedInputs,
+    /// The value consumed or created by this transaction overflows the value type.
+    ValueOverflow,
+    /// The transaction attempted to create a coin with zero value. This is not allowed
+    /// because it wastes state space.
+    ZeroValueCoin,
+}
+
+/// Create a new, empty collection.
+///
+/// There are no inputs and the single output is the collection. Its verifier becomes the creator.
+#[derive(
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Encode,
+    Decode,
+    Hash,
+    Debug,
+    TypeInfo,
+)]
+pub struct CreateCollection;
+
+impl SimpleConstraintChecker for CreateCollection {
+    type Error = NftError;
+
+    fn check(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        evicted_input_data: &[DynamicallyTypedData],
+        _peeks: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> Result<TransactionPriority, Self::Error> {
+        ensure!(evicted_input_data.is_empty(), NftError::NoEvictionsAllowed);
+        ensure!(input_data.is_empty(), NftError::CreatingWithInputs);
+        ensure!(output_data.len() == 1, NftError::WrongNumberOfCollections);
+
+        let collection = output_data[0]
+            .extract::<Collection>()
+            .map_err(|_| NftError::BadlyTypedOutput)?;
+        ensure!(!collection.name.is_empty(), NftError::EmptyName);
+        ensure!(
+            collection.name.len() <= MAX_NAME_LENGTH,
+            NftError::NameTooLong
+        );
+        ensure!(collection.max_supply != Some(0), NftError::ZeroMaxSupply);
+        ensure!(collection.royalty <= MAX_ROYALTY, NftError::RoyaltyTooHigh);
+        ensure!(collection.minted == 0, NftError::NewCollectionAlreadyMinted);
+
+        Ok(0)
+    }
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![UtxoType::of::<Collection>()]
+    }
+}
+
+/// Mint new tokens in a collection.
+///
+/// The single input is the collection, which must be recreated as the first output with its
+/// count of minted tokens increased. The remaining outputs are the new tokens, numbered in order.
+#[derive(
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Encode,
+    Decode,
+    Hash,
+    Debug,
+    TypeInfo,
+)]
+pub struct MintNfts;
+
+impl VerifierAwareConstraintChecker for MintNfts {
+    type Error = NftError;
+
+    fn check<V: Verifier>(
+        &self,
+        inputs: &[Output<V>],
+        evicted_inputs: &[Output<V>],
+        _peeks: &[Output<V>],
+        outputs: &[Output<V>],
+    ) -> Result<TransactionPriority, Self::Error> {
+        ensure!(evicted_inputs.is_empty(), NftError::NoEvictionsAllowed);
+        ensure!(inputs.len() == 1, NftError::WrongNumberOfMintInputs);
+        let collection_utxo = &inputs[0];
+        let collection = collection_utxo
+            .payload
+            .extract::<Collection>()
+            .map_err(|_| NftError::BadlyTypedInput)?;
+
+        let (recreated_utxo, new_tokens) = outputs
+            .split_first()
+            .ok_or(NftError::CollectionNotPreserved)?;
+        ensure!(!new_tokens.is_empty(), NftError::MintingNothing);
+
+        // The collection must come back out with only its count changed. Comparing the encoded
+        // verifiers ensures the creator can't be swapped out along the way.
+        let recreated = recreated_utxo
+            .payload
+            .extract::<Collection>()
+            .map_err(|_| NftError::CollectionNotPreserved)?;
+        let minted = collection
+            .minted
+            .checked_add(new_tokens.len() as u32)
+            .ok_or(NftError::MaxSupplyExceeded)?;
+        ensure!(
+            recreated
+                == Collection {
+                    minted,
+                    ..collection.clone()
+                }
+                && recreated_utxo.verifier.encode() == collection_utxo.verifier.encode(),
+            NftError::CollectionNotPreserved
+        );
+        ensure!(
+            collection.max_supply.map_or(true, |max| minted <= max),
+            NftError::MaxSupplyExceeded
+        );
+
+        let collection_id = collection.id(&collection_utxo.verifier);
+        for (token_id, utxo) in (collection.minted..).zip(new_tokens) {
+            let token = utxo
+                .payload
+                .extract::<Nft>()
+                .map_err(|_| NftError::BadlyTypedOutput)?;
+            ensure!(
+                token.collection_id == collection_id,
+                NftError::WrongCollection
+            );
+            ensure!(token.token_id == token_id, NftError::WrongTokenId);
+        }
+
+        Ok(0)
+    }
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![UtxoType::of::<Collection>(), UtxoType::of::<Nft>()]
+    }
+}
+
+/// Give tokens to new owners.
+///
+/// The inputs are the tokens and the outputs are the same tokens in the same order. No coins may
+/// change hands, so tokens are sold through listings instead. See [`BuyNft`].
+#[derive(
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Encode,
+    Decode,
+    Hash,
+    Debug,
+    TypeInfo,
+)]
+pub struct TransferNfts;
+
+impl SimpleConstraintChecker for TransferNfts {
+    type Error = NftError;
+
+    fn check(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        evicted_input_data: &[DynamicallyTypedData],
+        _peeks: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> Result<TransactionPriority, Self::Error> {
+        ensure!(evicted_input_data.is_empty(), NftError::NoEvictionsAllowed);
+        ensure!(!input_data.is_empty(), NftError::NoTokens);
+        ensure!(
+            input_data.len() == output_data.len(),
+            NftError::TokensNotPreserved
+        );
+
+        for (input, output) in input_data.iter().zip(output_data) {
+            let token = input
+                .extract::<Nft>()
+                .map_err(|_| NftError::BadlyTypedInput)?;
+            let transferred = output
+                .extract::<Nft>()
+                .map_err(|_| NftError::BadlyTypedOutput)?;
+            ensure!(transferred == token, NftError::TokensNotPreserved);
+        }
+
+        Ok(0)
+    }
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![UtxoType::of::<Nft>()]
+    }
+
+    // A transfer bundled with a payment would be a sale that dodges the royalty.
+    const COMPOUNDABLE: bool = false;
+}
+
+/// List tokens for sale.
+///
+/// The inputs are tokens and the outputs are listings of the same tokens, in the same order.
+/// The verifier on each listing becomes its seller.
+#[derive(
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Encode,
+    Decode,
+    Hash,
+    Debug,
+    TypeInfo,
+)]
+pub struct ListNfts;
+
+impl SimpleConstraintChecker for ListNfts {
+    type Error = NftError;
+
+    fn check(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        evicted_input_data: &[DynamicallyTypedData],
+        _peeks: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> Result<TransactionPriority, Self::Error> {
+        ensure!(evicted_input_data.is_empty(), NftError::NoEvictionsAllowed);
+        ensure!(!input_data.is_empty(), NftError::NoTokens);
+        ensure!(
+            input_data.len() == output_data.len(),
+            NftError::TokensNotPreserved
+        );
+
+        for (input, output) in input_data.iter().zip(output_data) {
+            let token = input
+                .extract::<Nft>()
+                .map_err(|_| NftError::BadlyTypedInput)?;
+            let listing = output
+                .extract::<ListedNft>()
+                .map_err(|_| NftError::BadlyTypedOutput)?;
+            ensure!(listing.token == token, NftError::TokensNotPreserved);
+            ensure!(listing.price > 0, NftError::ZeroPrice);
+        }
+
+        Ok(0)
+    }
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![UtxoType::of::<Nft>(), UtxoType::of::<ListedNft>()]
+    }
+}
+
+/// Take tokens off the market.
+///
+/// The inputs are listings, which are consumed normally so the seller's verifier must be satisfied.
+/// The outputs are the listed tokens, in the same order.
+#[derive(
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Encode,
+    Decode,
+    Hash,
+    Debug,
+    TypeInfo,
+)]
+pub struct DelistNfts;
+
+impl SimpleConstraintChecker for DelistNfts {
+    type Error = NftError;
+
+    fn check(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        evicted_input_data: &[DynamicallyTypedData],
+        _peeks: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> Result<TransactionPriority, Self::Error> {
+        ensure!(evicted_input_data.is_empty(), NftError::NoEvictionsAllowed);
+        ensure!(!input_data.is_empty(), NftError::NoTokens);
+        ensure!(
+            input_data.len() == output_data.len(),
+            NftError::TokensNotPreserved
+        );
+
+        for (input, output) in input_data.iter().zip(output_data) {
+            let listing = input
+                .extract::<ListedNft>()
+                .map_err(|_| NftError::BadlyTypedInput)?;
+            let token = output
+                .extract::<Nft>()
+                .map_err(|_| NftError::BadlyTypedOutput)?;
+            ensure!(listing.token == token, NftError::TokensNotPreserved);
+        }
+
+        Ok(0)
+    }
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![UtxoType::of::<Nft>(), UtxoType::of::<ListedNft>()]
+    }
+}
+
+/// Buy a listed token with coins of type `C`.
+///
+/// The single evicted input is the listing and the normal inputs are the buyer's coins. The first
+/// output is the token, and the remaining outputs are coins. Out of the listed price, the creator
+/// must be paid the royalty and the seller the rest. Any other coins are the buyer's change. The
+/// transaction must peek at the token's collection to learn the royalty rate and the creator.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DebugNoBound,
+    DefaultNoBound,
+    CloneNoBound,
+    PartialEq,
+    Eq,
+    TypeInfo,
+)]
+#[scale_info(skip_type_params(C))]
+pub struct BuyNft<C>(PhantomData<C>);
+
+impl<C: Cash + UtxoData + 'static> VerifierAwareConstraintChecker for BuyNft<C> {
+    type Error = NftError;
+
+    fn check<V: Verifier>(
+        &self,
+        inputs: &[Output<V>],
+        evicted_inputs: &[Output<V>],
+        peeks: &[Output<V>],
+        outputs: &[Output<V>],
+    ) -> Result<TransactionPriority, Self::Error> {
+        ensure!(evicted_inputs.len() == 1, NftError::WrongNumberOfListings);
+        let listing = evicted_inputs[0]
+            .payload
+            .extract::<ListedNft>()
+            .map_err(|_| NftError::BadlyTypedInput)?;
+        let seller = evicted_inputs[0].verifier.encode();
+
+        // The buyer receives the token first.
+        let (token, coin_outputs) = outputs.split_first().ok_or(NftError::TokenNotDelivered)?;
+        let token = token
+            .payload
+            .extract::<Nft>()
+            .map_err(|_| NftError::TokenNotDelivered)?;
+        ensure!(token == listing.token, NftError::TokenNotDelivered);
+
+        let (collection, creator) = peeks
+            .iter()
+            .find_map(|peek| {
+                let collection = peek.payload.extract::<Collection>().ok()?;
+                (collection.id(&peek.verifier) == token.collection_id)
+                    .then(|| (collection, peek.verifier.encode()))
+            })
+            .ok_or(NftError::MissingCollection)?;
+
+        // Add up the coins on both sides, and what was paid to the seller and the creator.
+        let mut total_input_value: u128 = 0;
+        for input in inputs {
+            let coin = input
+                .payload
+                .extract::<C>()
+                .map_err(|_| NftError::BadlyTypedInput)?;
+            total_input_value = total_input_value
+                .checked_add(coin.value())
+                .ok_or(NftError::ValueOverflow)?;
+        }
+
+        let mut total_output_value: u128 = 0;
+        let mut paid_to_seller: u128 = 0;
+        let mut paid_to_creator: u128 = 0;
+        for output in coin_outputs {
+            let value = output
+                .payload
+                .extract::<C>()
+                .map_err(|_| NftError::BadlyTypedOutput)?
+                .value();
+            ensure!(value > 0, NftError::ZeroValueCoin);
+            total_output_value = total_output_value
+                .checked_add(value)
+                .ok_or(NftError::ValueOverflow)?;
+
+            let owner = output.verifier.encode();
+            if owner == seller {
+                paid_to_seller = paid_to_seller
+                    .checked_add(value)
+                    .ok_or(NftError::ValueOverflow)?;
+            } else if owner == creator {
+                paid_to_creator = paid_to_creator
+                    .checked_add(value)
+                    .ok_or(NftError::ValueOverflow)?;
+            }
+        }
+        ensure!(
+            total_output_value <= total_input_value,
+            NftError::OutputsExceedInputs
+        );
+
+        // When the creator sells their own token, they keep the whole price.
+        let royalty = if seller == creator {
+            0
+        } else {
+            collection.royalty_on(listing.price)?.min(listing.price)
+        };
+        ensure!(paid_to_creator >= royalty, NftError::RoyaltyNotPaid);
+        ensure!(
+            paid_to_seller >= listing.price - royalty,
+            NftError::SellerNotPaid
+        );
+
+        Ok(0)
+    }
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![
+            UtxoType::of::<Collection>(),
+            UtxoType::of::<Nft>(),
+            UtxoType::of::<ListedNft>(),
+            UtxoType::of::<C>(),
+        ]
+    }
+
+    // The payment must be checked along with the listing that it pays for.
+    const COMPOUNDABLE: bool = false;
+}
+
+/// Destroy tokens. The inputs are the tokens and there are no outputs.
+///
+/// Burned tokens still count towards their collection's maximum supply.
+#[derive(
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Encode,
+    Decode,
+    Hash,
+    Debug,
+    TypeInfo,
+)]
+pub struct BurnNfts;
+
+impl SimpleConstraintChecker for BurnNfts {
+    type Error = NftError;
+
+    fn check(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        evicted_input_data: &[DynamicallyTypedData],
+        _peeks: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> Result<TransactionPriority, Self::Error> {
+        ensure!(evicted_input_data.is_empty(), NftError::NoEvictionsAllowed);
+        ensure!(!input_data.is_empty(), NftError::NoTokens);
+        ensure!(output_data.is_empty(), NftError::BurnWithOutputs);
+
+        for input in input_data {
+            input
+                .extract::<Nft>()
+                .map_err(|_| NftError::BadlyTypedInput)?;
+        }
+
+        Ok(0)
+    }
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![UtxoType::of::<Nft>()]
+    }
+}
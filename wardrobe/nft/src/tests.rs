@@ -0,0 +1,686 @@
+//! Unit tests for the NFT piece
+
+use super::*;
+use tuxedo_core::{
+    dynamic_typing::testing::{Bogus, Gold},
+    verifier::Sr25519Signature,
+};
+
+fn owner(byte: u8) -> Sr25519Signature {
+    Sr25519Signature::new(H256::repeat_byte(byte))
+}
+
+fn creator() -> Sr25519Signature {
+    owner(1)
+}
+
+fn seller() -> Sr25519Signature {
+    owner(2)
+}
+
+fn buyer() -> Sr25519Signature {
+    owner(3)
+}
+
+/// A collection of at most ten tokens with a 5% royalty.
+fn collection() -> Collection {
+    Collection::new(b"Tuxedo Penguins", Some(10), 500)
+}
+
+fn collection_id() -> H256 {
+    collection().id(&creator())
+}
+
+fn collection_utxo(minted: u32) -> Output<Sr25519Signature> {
+    (
+        Collection {
+            minted,
+            ..collection()
+        },
+        creator(),
+    )
+        .into()
+}
+
+fn token(token_id: u32) -> Nft {
+    Nft::new(collection_id(), token_id, BlakeTwo256::hash_of(&token_id))
+}
+
+fn mint(
+    inputs: &[Output<Sr25519Signature>],
+    outputs: &[Output<Sr25519Signature>],
+) -> Result<TransactionPriority, NftError> {
+    VerifierAwareConstraintChecker::check(&MintNfts, inputs, &[], &[], outputs)
+}
+
+fn buy(
+    inputs: &[Output<Sr25519Signature>],
+    evicted_inputs: &[Output<Sr25519Signature>],
+    peeks: &[Output<Sr25519Signature>],
+    outputs: &[Output<Sr25519Signature>],
+) -> Result<TransactionPriority, NftError> {
+    VerifierAwareConstraintChecker::check(
+        &BuyNft::<Gold>::default(),
+        inputs,
+        evicted_inputs,
+        peeks,
+        outputs,
+    )
+}
+
+/// The first token, listed by the seller for 100 coins.
+fn listing() -> Output<Sr25519Signature> {
+    (ListedNft::new(token(0), 100), seller()).into()
+}
+
+#[test]
+fn collection_id_depends_on_creator() {
+    assert_ne!(collection().id(&creator()), collection().id(&seller()));
+}
+
+#[test]
+fn collection_id_ignores_minted_count() {
+    let minted = Collection {
+        minted: 3,
+        ..collection()
+    };
+    assert_eq!(minted.id(&creator()), collection_id());
+}
+
+#[test]
+fn royalty_rounds_up() {
+    assert_eq!(collection().royalty_on(100), Ok(5));
+    assert_eq!(collection().royalty_on(101), Ok(6));
+    assert_eq!(collection().royalty_on(0), Ok(0));
+}
+
+#[test]
+fn create_collection_works() {
+    let output_data = vec![collection().into()];
+
+    assert_eq!(
+        SimpleConstraintChecker::check(&CreateCollection, &[], &[], &[], &output_data),
+        Ok(0)
+    );
+}
+
+#[test]
+fn create_collection_with_inputs_fails() {
+    let input_data = vec![token(0).into()];
+    let output_data = vec![collection().into()];
+
+    assert_eq!(
+        SimpleConstraintChecker::check(&CreateCollection, &input_data, &[], &[], &output_data),
+        Err(NftError::CreatingWithInputs)
+    );
+}
+
+#[test]
+fn create_collection_with_extra_outputs_fails() {
+    let output_data = vec![collection().into(), token(0).into()];
+
+    assert_eq!(
+        SimpleConstraintChecker::check(&CreateCollection, &[], &[], &[], &output_data),
+        Err(NftError::WrongNumberOfCollections)
+    );
+}
+
+#[test]
+fn create_collection_wrong_output_type_fails() {
+    let output_data = vec![Bogus.into()];
+
+    assert_eq!(
+        SimpleConstraintChecker::check(&CreateCollection, &[], &[], &[], &output_data),
+        Err(NftError::BadlyTypedOutput)
+    );
+}
+
+#[test]
+fn create_collection_with_long_name_fails() {
+    let name = [b'a'; MAX_NAME_LENGTH + 1];
+    let output_data = vec![Collection::new(&name, None, 0).into()];
+
+    assert_eq!(
+        SimpleConstraintChecker::check(&CreateCollection, &[], &[], &[], &output_data),
+        Err(NftError::NameTooLong)
+    );
+}
+
+#[test]
+fn create_collection_with_zero_max_supply_fails() {
+    let output_data = vec![Collection::new(b"Nothing", Some(0), 0).into()];
+
+    assert_eq!(
+        SimpleConstraintChecker::check(&CreateCollection, &[], &[], &[], &output_data),
+        Err(NftError::ZeroMaxSupply)
+    );
+}
+
+#[test]
+fn create_collection_with_excessive_royalty_fails() {
+    let output_data = vec![Collection::new(b"Greedy", None, MAX_ROYALTY + 1).into()];
+
+    assert_eq!(
+        SimpleConstraintChecker::check(&CreateCollection, &[], &[], &[], &output_data),
+        Err(NftError::RoyaltyTooHigh)
+    );
+}
+
+#[test]
+fn create_collection_already_minted_fails() {
+    let output_data = vec![Collection {
+        minted: 1,
+        ..collection()
+    }
+    .into()];
+
+    assert_eq!(
+        SimpleConstraintChecker::check(&CreateCollection, &[], &[], &[], &output_data),
+        Err(NftError::NewCollectionAlreadyMinted)
+    );
+}
+
+#[test]
+fn mint_works() {
+    assert_eq!(
+        mint(
+            &[collection_utxo(2)],
+            &[
+                collection_utxo(4),
+                (token(2), seller()).into(),
+                (token(3), buyer()).into(),
+            ]
+        ),
+        Ok(0)
+    );
+}
+
+#[test]
+fn mint_with_extra_inputs_fails() {
+    assert_eq!(
+        mint(
+            &[collection_utxo(0), (token(7), seller()).into()],
+            &[collection_utxo(1), (token(0), seller()).into()]
+        ),
+        Err(NftError::WrongNumberOfMintInputs)
+    );
+}
+
+#[test]
+fn mint_without_collection_fails() {
+    assert_eq!(
+        mint(
+            &[(token(0), seller()).into()],
+            &[collection_utxo(1), (token(0), seller()).into()]
+        ),
+        Err(NftError::BadlyTypedInput)
+    );
+}
+
+#[test]
+fn mint_nothing_fails() {
+    assert_eq!(
+        mint(&[collection_utxo(0)], &[collection_utxo(0)]),
+        Err(NftError::MintingNothing)
+    );
+}
+
+#[test]
+fn mint_without_updating_count_fails() {
+    assert_eq!(
+        mint(
+            &[collection_utxo(0)],
+            &[collection_utxo(0), (token(0), seller()).into()]
+        ),
+        Err(NftError::CollectionNotPreserved)
+    );
+}
+
+#[test]
+fn mint_changing_royalty_fails() {
+    let changed = Collection {
+        royalty: 0,
+        minted: 1,
+        ..collection()
+    };
+    assert_eq!(
+        mint(
+            &[collection_utxo(0)],
+            &[(changed, creator()).into(), (token(0), seller()).into()]
+        ),
+        Err(NftError::CollectionNotPreserved)
+    );
+}
+
+#[test]
+fn mint_changing_creator_fails() {
+    let recreated = Collection {
+        minted: 1,
+        ..collection()
+    };
+    assert_eq!(
+        mint(
+            &[collection_utxo(0)],
+            &[(recreated, seller()).into(), (token(0), seller()).into()]
+        ),
+        Err(NftError::CollectionNotPreserved)
+    );
+}
+
+#[test]
+fn mint_beyond_max_supply_fails() {
+    assert_eq!(
+        mint(
+            &[collection_utxo(9)],
+            &[
+                collection_utxo(11),
+                (token(9), seller()).into(),
+                (token(10), seller()).into(),
+            ]
+        ),
+        Err(NftError::MaxSupplyExceeded)
+    );
+}
+
+#[test]
+fn mint_out_of_order_fails() {
+    assert_eq!(
+        mint(
+            &[collection_utxo(0)],
+            &[
+                collection_utxo(2),
+                (token(1), seller()).into(),
+                (token(0), seller()).into(),
+            ]
+        ),
+        Err(NftError::WrongTokenId)
+    );
+}
+
+#[test]
+fn mint_into_other_collection_fails() {
+    let stray = Nft::new(H256::zero(), 0, H256::zero());
+    assert_eq!(
+        mint(
+            &[collection_utxo(0)],
+            &[collection_utxo(1), (stray, seller()).into()]
+        ),
+        Err(NftError::WrongCollection)
+    );
+}
+
+#[test]
+fn transfer_works() {
+    assert_eq!(
+        SimpleConstraintChecker::check(
+            &TransferNfts,
+            &[token(0).into(), token(1).into()],
+            &[],
+            &[],
+            &[token(0).into(), token(1).into()]
+        ),
+        Ok(0)
+    );
+}
+
+#[test]
+fn transfer_changing_metadata_fails() {
+    let altered = Nft {
+        metadata: H256::zero(),
+        ..token(0)
+    };
+    assert_eq!(
+        SimpleConstraintChecker::check(
+            &TransferNfts,
+            &[token(0).into()],
+            &[],
+            &[],
+            &[altered.into()]
+        ),
+        Err(NftError::TokensNotPreserved)
+    );
+}
+
+#[test]
+fn transfer_dropping_token_fails() {
+    assert_eq!(
+        SimpleConstraintChecker::check(
+            &TransferNfts,
+            &[token(0).into(), token(1).into()],
+            &[],
+            &[],
+            &[token(0).into()]
+        ),
+        Err(NftError::TokensNotPreserved)
+    );
+}
+
+#[test]
+fn transfer_nothing_fails() {
+    assert_eq!(
+        SimpleConstraintChecker::check(&TransferNfts, &[], &[], &[], &[]),
+        Err(NftError::NoTokens)
+    );
+}
+
+#[test]
+fn transfer_with_payment_fails() {
+    assert_eq!(
+        SimpleConstraintChecker::check(
+            &TransferNfts,
+            &[token(0).into(), Gold(100).into()],
+            &[],
+            &[],
+            &[token(0).into(), Gold(100).into()]
+        ),
+        Err(NftError::BadlyTypedInput)
+    );
+}
+
+#[test]
+fn transfer_is_not_compoundable() {
+    assert!(!ConstraintChecker::is_compoundable(&TransferNfts));
+}
+
+#[test]
+fn list_works() {
+    assert_eq!(
+        SimpleConstraintChecker::check(
+            &ListNfts,
+            &[token(0).into(), token(1).into()],
+            &[],
+            &[],
+            &[
+                ListedNft::new(token(0), 100).into(),
+                ListedNft::new(token(1), 50).into()
+            ]
+        ),
+        Ok(0)
+    );
+}
+
+#[test]
+fn list_for_free_fails() {
+    assert_eq!(
+        SimpleConstraintChecker::check(
+            &ListNfts,
+            &[token(0).into()],
+            &[],
+            &[],
+            &[ListedNft::new(token(0), 0).into()]
+        ),
+        Err(NftError::ZeroPrice)
+    );
+}
+
+#[test]
+fn list_different_token_fails() {
+    assert_eq!(
+        SimpleConstraintChecker::check(
+            &ListNfts,
+            &[token(0).into()],
+            &[],
+            &[],
+            &[ListedNft::new(token(1), 100).into()]
+        ),
+        Err(NftError::TokensNotPreserved)
+    );
+}
+
+#[test]
+fn delist_works() {
+    assert_eq!(
+        SimpleConstraintChecker::check(
+            &DelistNfts,
+            &[ListedNft::new(token(0), 100).into()],
+            &[],
+            &[],
+            &[token(0).into()]
+        ),
+        Ok(0)
+    );
+}
+
+#[test]
+fn delist_different_token_fails() {
+    assert_eq!(
+        SimpleConstraintChecker::check(
+            &DelistNfts,
+            &[ListedNft::new(token(0), 100).into()],
+            &[],
+            &[],
+            &[token(1).into()]
+        ),
+        Err(NftError::TokensNotPreserved)
+    );
+}
+
+#[test]
+fn buy_with_royalty_works() {
+    assert_eq!(
+        buy(
+            &[(Gold(120), buyer()).into()],
+            &[listing()],
+            &[collection_utxo(1)],
+            &[
+                (token(0), buyer()).into(),
+                (Gold(95), seller()).into(),
+                (Gold(5), creator()).into(),
+                (Gold(20), buyer()).into(),
+            ]
+        ),
+        Ok(0)
+    );
+}
+
+#[test]
+fn buy_without_royalty_fails() {
+    assert_eq!(
+        buy(
+            &[(Gold(120), buyer()).into()],
+            &[listing()],
+            &[collection_utxo(1)],
+            &[
+                (token(0), buyer()).into(),
+                (Gold(100), seller()).into(),
+                (Gold(20), buyer()).into(),
+            ]
+        ),
+        Err(NftError::RoyaltyNotPaid)
+    );
+}
+
+#[test]
+fn buy_with_short_royalty_fails() {
+    assert_eq!(
+        buy(
+            &[(Gold(100), buyer()).into()],
+            &[listing()],
+            &[collection_utxo(1)],
+            &[
+                (token(0), buyer()).into(),
+                (Gold(96), seller()).into(),
+                (Gold(4), creator()).into(),
+            ]
+        ),
+        Err(NftError::RoyaltyNotPaid)
+    );
+}
+
+#[test]
+fn buy_underpaying_seller_fails() {
+    assert_eq!(
+        buy(
+            &[(Gold(100), buyer()).into()],
+            &[listing()],
+            &[collection_utxo(1)],
+            &[
+                (token(0), buyer()).into(),
+                (Gold(94), seller()).into(),
+                (Gold(5), creator()).into(),
+            ]
+        ),
+        Err(NftError::SellerNotPaid)
+    );
+}
+
+#[test]
+fn buy_without_peeking_collection_fails() {
+    assert_eq!(
+        buy(
+            &[(Gold(100), buyer()).into()],
+            &[listing()],
+            &[],
+            &[
+                (token(0), buyer()).into(),
+                (Gold(95), seller()).into(),
+                (Gold(5), creator()).into(),
+            ]
+        ),
+        Err(NftError::MissingCollection)
+    );
+}
+
+#[test]
+fn buy_peeking_impostor_collection_fails() {
+    // A copy of the collection protected by someone else has a different id.
+    let impostor = (collection(), seller()).into();
+    assert_eq!(
+        buy(
+            &[(Gold(100), buyer()).into()],
+            &[listing()],
+            &[impostor],
+            &[(token(0), buyer()).into(), (Gold(100), seller()).into()]
+        ),
+        Err(NftError::MissingCollection)
+    );
+}
+
+#[test]
+fn buy_different_token_fails() {
+    assert_eq!(
+        buy(
+            &[(Gold(100), buyer()).into()],
+            &[listing()],
+            &[collection_utxo(2)],
+            &[
+                (token(1), buyer()).into(),
+                (Gold(95), seller()).into(),
+                (Gold(5), creator()).into(),
+            ]
+        ),
+        Err(NftError::TokenNotDelivered)
+    );
+}
+
+#[test]
+fn buy_without_evicting_listing_fails() {
+    assert_eq!(
+        buy(
+            &[listing(), (Gold(100), buyer()).into()],
+            &[],
+            &[collection_utxo(1)],
+            &[
+                (token(0), buyer()).into(),
+                (Gold(95), seller()).into(),
+                (Gold(5), creator()).into(),
+            ]
+        ),
+        Err(NftError::WrongNumberOfListings)
+    );
+}
+
+#[test]
+fn buy_paying_more_than_brought_fails() {
+    assert_eq!(
+        buy(
+            &[(Gold(99), buyer()).into()],
+            &[listing()],
+            &[collection_utxo(1)],
+            &[
+                (token(0), buyer()).into(),
+                (Gold(95), seller()).into(),
+                (Gold(5), creator()).into(),
+            ]
+        ),
+        Err(NftError::OutputsExceedInputs)
+    );
+}
+
+#[test]
+fn buy_zero_value_coin_fails() {
+    assert_eq!(
+        buy(
+            &[(Gold(100), buyer()).into()],
+            &[listing()],
+            &[collection_utxo(1)],
+            &[
+                (token(0), buyer()).into(),
+                (Gold(95), seller()).into(),
+                (Gold(5), creator()).into(),
+                (Gold(0), buyer()).into(),
+            ]
+        ),
+        Err(NftError::ZeroValueCoin)
+    );
+}
+
+#[test]
+fn buy_from_creator_keeps_whole_price() {
+    let listing = (ListedNft::new(token(0), 100), creator()).into();
+    assert_eq!(
+        buy(
+            &[(Gold(100), buyer()).into()],
+            &[listing],
+            &[collection_utxo(1)],
+            &[(token(0), buyer()).into(), (Gold(100), creator()).into()]
+        ),
+        Ok(0)
+    );
+}
+
+#[test]
+fn buy_is_not_compoundable() {
+    assert!(!ConstraintChecker::is_compoundable(
+        &BuyNft::<Gold>::default()
+    ));
+}
+
+#[test]
+fn burn_works() {
+    let input_data = vec![token(0).into(), token(1).into()];
+
+    assert_eq!(
+        SimpleConstraintChecker::check(&BurnNfts, &input_data, &[], &[], &[]),
+        Ok(0)
+    );
+}
+
+#[test]
+fn burn_with_outputs_fails() {
+    let input_data = vec![token(0).into()];
+    let output_data = vec![token(0).into()];
+
+    assert_eq!(
+        SimpleConstraintChecker::check(&BurnNfts, &input_data, &[], &[], &output_data),
+        Err(NftError::BurnWithOutputs)
+    );
+}
+
+#[test]
+fn burn_nothing_fails() {
+    assert_eq!(
+        SimpleConstraintChecker::check(&BurnNfts, &[], &[], &[], &[]),
+        Err(NftError::NoTokens)
+    );
+}
+
+#[test]
+fn burn_wrong_input_type_fails() {
+    let input_data = vec![collection().into()];
+
+    assert_eq!(
+        SimpleConstraintChecker::check(&BurnNfts, &input_data, &[], &[], &[]),
+        Err(NftError::BadlyTypedInput)
+    );
+}
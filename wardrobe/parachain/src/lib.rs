@@ -30,14 +30,14 @@ use sp_core::H256;
 use sp_inherents::{CheckInherentsResult, InherentData};
 use sp_runtime::transaction_validity::TransactionPriority;
 use sp_std::{vec, vec::Vec};
-use tuxedo_parachain_core::tuxedo_core::dynamic_typing::DynamicallyTypedData;
+use tuxedo_parachain_core::tuxedo_core::dynamic_typing::{DynamicallyTypedData, UtxoType};
 use tuxedo_parachain_core::tuxedo_core::SimpleConstraintChecker;
 // We get all the Tuxedo core stuff through the re-export so we don't risk crossed versions.
 use tuxedo_parachain_core::ParachainInherentDataUtxo;
 use tuxedo_parachain_core::{
     tuxedo_core::{
         ensure,
-        inherents::InherentHooks,
+        inherents::{InherentError, InherentHooks},
         support_macros::{CloneNoBound, DebugNoBound, DefaultNoBound},
         types::{Input, Output, OutputRef, RedemptionStrategy, Transaction},
         Verifier,
@@ -111,6 +111,10 @@ pub struct SetParachainInfo<T>(PhantomData<T>);
 impl<T: ParachainPieceConfig + 'static> SimpleConstraintChecker for SetParachainInfo<T> {
     type Error = ParachainError;
 
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![UtxoType::of::<ParachainInherentDataUtxo>()]
+    }
+
     fn check(
         &self,
         input_data: &[DynamicallyTypedData],
@@ -166,6 +170,9 @@ impl<T: ParachainPieceConfig + 'static> SimpleConstraintChecker for SetParachain
 
         Ok(0)
     }
+
+    // Setting the parachain info is an inherent that stores the relay parent number while checking.
+    const COMPOUNDABLE: bool = false;
 }
 
 impl<T: ParachainPieceConfig + 'static> InherentHooks for SetParachainInfo<T> {
@@ -176,11 +183,12 @@ impl<T: ParachainPieceConfig + 'static> InherentHooks for SetParachainInfo<T> {
     fn create_inherent<V: Verifier>(
         authoring_inherent_data: &InherentData,
         (_previous_inherent, previous_id): (Transaction<V, Self>, H256),
-    ) -> Transaction<V, Self> {
+    ) -> Result<Transaction<V, Self>, InherentError> {
         let current_info: ParachainInherentData = authoring_inherent_data
             .get_data(&INHERENT_IDENTIFIER)
-            .expect("Inherent data should decode properly")
-            .expect("Parachain inherent data should be present.");
+            .ok()
+            .flatten()
+            .ok_or(InherentError::MissingInherentData)?;
 
         log::debug!(
             target: LOG_TARGET,
@@ -203,8 +211,7 @@ impl<T: ParachainPieceConfig + 'static> InherentHooks for SetParachainInfo<T> {
 
         let new_output = Output {
             payload: ParachainInherentDataUtxo::from(current_info).into(),
-            verifier: V::new_unspendable()
-                .expect("Must be able to create unspendable verifier to use parachain inherent."),
+            verifier: V::new_unspendable().ok_or(InherentError::NoUnspendableVerifier)?,
         };
 
         let t = Transaction {
@@ -219,10 +226,10 @@ impl<T: ParachainPieceConfig + 'static> InherentHooks for SetParachainInfo<T> {
             "created inherent transaction {:?}.", t
         );
 
-        t
+        Ok(t)
     }
 
-    fn check_inherent<V>(
+    fn check_inherent<V: Verifier>(
         _importing_inherent_data: &InherentData,
         _inherent: Transaction<V, Self>,
         _result: &mut CheckInherentsResult,
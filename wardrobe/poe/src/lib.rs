@@ -14,6 +14,9 @@
 //! to boot subsequent redundant claims when they are discovered. This difference is analogous to
 //! the difference between recorded and registered land
 //! https://cannerlaw.com/blog/the-difference-of-recorded-and-registered-land/
+//!
+//! Claims may be transferred to a new owner without changing their effective height. A claim may also
+//! be created with an expiry height, as [`ExpiringClaimData`], after which anyone may evict it from the state.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
@@ -24,9 +27,9 @@ use scale_info::TypeInfo;
 use serde::{Deserialize, Serialize};
 use sp_core::H256;
 use sp_runtime::transaction_validity::TransactionPriority;
-use sp_std::fmt::Debug;
+use sp_std::{fmt::Debug, vec, vec::Vec};
 use tuxedo_core::{
-    dynamic_typing::{DynamicallyTypedData, UtxoData},
+    dynamic_typing::{DynamicallyTypedData, UtxoData, UtxoType},
     ensure,
     support_macros::{CloneNoBound, DebugNoBound, DefaultNoBound},
     SimpleConstraintChecker,
@@ -35,19 +38,45 @@ use tuxedo_core::{
 #[cfg(test)]
 mod tests;
 
-// Notice this type doesn't have to be public. Cool.
+/// A claim that some data existed as of a particular block height.
+///
+/// This type is public so that nodes and wallets can recognize claims, for example to find
+/// redundant claims that should be disputed.
 #[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone)]
-struct ClaimData {
+pub struct ClaimData {
     /// The hash of the data whose existence is being proven.
-    claim: H256,
+    pub claim: H256,
     /// The time (in block height) at which the claim becomes valid.
-    effective_height: u32,
+    pub effective_height: u32,
 }
 
 impl UtxoData for ClaimData {
     const TYPE_ID: [u8; 4] = *b"poe_";
 }
 
+/// A claim that expires at a particular block height, after which anyone may evict it.
+///
+/// This is a separate type rather than a field on [`ClaimData`], so that claims stored before
+/// expiry was introduced still decode. Claims without an expiry last until they are revoked.
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, UtxoData)]
+pub struct ExpiringClaimData {
+    /// The claim itself.
+    pub claim: ClaimData,
+    /// The time (in block height) at which the claim expires and may be evicted by anyone.
+    pub expiry: u32,
+}
+
+/// Extract a claim of either type, along with its expiry height if it has one.
+fn extract_claim(data: &DynamicallyTypedData) -> Option<(ClaimData, Option<u32>)> {
+    match data.extract::<ClaimData>() {
+        Ok(claim) => Some((claim, None)),
+        Err(_) => data
+            .extract::<ExpiringClaimData>()
+            .ok()
+            .map(|expiring| (expiring.claim, Some(expiring.expiry))),
+    }
+}
+
 /// Errors that can occur when checking PoE Transactions
 #[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone)]
 pub enum ConstraintCheckerError {
@@ -70,6 +99,18 @@ pub enum ConstraintCheckerError {
     DisputingMismatchedClaims,
     /// The winner of a dispute must be the oldest claim (the lowest block number)
     IncorrectDisputeWinner,
+
+    /// A claim must not expire before it becomes effective.
+    ExpiryBeforeEffectiveHeight,
+
+    /// A transfer must output exactly the same claims it consumes, in the same order.
+    TransferModifiesClaim,
+
+    /// The claim being evicted does not have an expiry height, or has not reached it yet.
+    ClaimNotExpired,
+    /// An expiry transaction must evict at least one claim. Otherwise it does nothing,
+    /// and could be replayed forever.
+    NothingToExpire,
 }
 
 /// Configuration items for the Proof of Existence piece when it is
@@ -102,6 +143,13 @@ pub struct PoeClaim<T>(PhantomData<T>);
 impl<T: PoeConfig> SimpleConstraintChecker for PoeClaim<T> {
     type Error = ConstraintCheckerError;
 
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![
+            UtxoType::of::<ClaimData>(),
+            UtxoType::of::<ExpiringClaimData>(),
+        ]
+    }
+
     fn check(
         &self,
         input_data: &[DynamicallyTypedData],
@@ -125,13 +173,16 @@ impl<T: PoeConfig> SimpleConstraintChecker for PoeClaim<T> {
         // requirement allows the caller to make a somewhat weaker claim with the advantage that they have a longer
         // period of time during which their transaction is valid.
         for untyped_output in output_data {
-            let output = untyped_output
-                .extract::<ClaimData>()
-                .map_err(|_| ConstraintCheckerError::BadlyTypedOutput)?;
+            let (output, expiry) =
+                extract_claim(untyped_output).ok_or(ConstraintCheckerError::BadlyTypedOutput)?;
             ensure!(
                 output.effective_height >= T::block_height(),
                 ConstraintCheckerError::EffectiveHeightInPast
             );
+            ensure!(
+                expiry.map_or(true, |expiry| expiry > output.effective_height),
+                ConstraintCheckerError::ExpiryBeforeEffectiveHeight
+            );
         }
 
         Ok(0)
@@ -147,6 +198,13 @@ pub struct PoeRevoke;
 impl SimpleConstraintChecker for PoeRevoke {
     type Error = ConstraintCheckerError;
 
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![
+            UtxoType::of::<ClaimData>(),
+            UtxoType::of::<ExpiringClaimData>(),
+        ]
+    }
+
     fn check(
         &self,
         input_data: &[DynamicallyTypedData],
@@ -168,9 +226,7 @@ impl SimpleConstraintChecker for PoeRevoke {
 
         // Make sure the inputs are properly typed. We don't need to check anything else about them.
         for untyped_input in input_data {
-            let _ = untyped_input
-                .extract::<ClaimData>()
-                .map_err(|_| ConstraintCheckerError::BadlyTypedInput)?;
+            extract_claim(untyped_input).ok_or(ConstraintCheckerError::BadlyTypedInput)?;
         }
 
         Ok(0)
@@ -186,6 +242,13 @@ pub struct PoeDispute;
 impl SimpleConstraintChecker for PoeDispute {
     type Error = ConstraintCheckerError;
 
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![
+            UtxoType::of::<ClaimData>(),
+            UtxoType::of::<ExpiringClaimData>(),
+        ]
+    }
+
     fn check(
         &self,
         input_data: &[DynamicallyTypedData],
@@ -204,18 +267,18 @@ impl SimpleConstraintChecker for PoeDispute {
         );
 
         // Make sure there is exactly one peek (the oldest, winning claim)
-        let winner = peek_data
-            .first()
-            .ok_or(ConstraintCheckerError::WrongNumberInputs)?
-            .extract::<ClaimData>()
-            .map_err(|_| ConstraintCheckerError::BadlyTypedInput)?;
+        let (winner, _) = extract_claim(
+            peek_data
+                .first()
+                .ok_or(ConstraintCheckerError::WrongNumberInputs)?,
+        )
+        .ok_or(ConstraintCheckerError::BadlyTypedInput)?;
 
         // Make sure that all evicted inputs, claim the same hash as the winner
         // and have block heights strictly greater than the winner.
         for untyped_loser in evicted_input_data {
-            let loser = untyped_loser
-                .extract::<ClaimData>()
-                .map_err(|_| ConstraintCheckerError::BadlyTypedInput)?;
+            let (loser, _) =
+                extract_claim(untyped_loser).ok_or(ConstraintCheckerError::BadlyTypedInput)?;
             ensure!(
                 winner.claim == loser.claim,
                 Self::Error::DisputingMismatchedClaims
@@ -229,3 +292,118 @@ impl SimpleConstraintChecker for PoeDispute {
         Ok(0)
     }
 }
+
+/// A constraint checker to transfer claims to new owners.
+///
+/// Each input claim is recreated unchanged at the same position in the outputs, protected by
+/// whatever verifier the new owner chooses. Like revocation, this allows batch transfers.
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, TypeInfo)]
+pub struct PoeTransfer;
+
+impl SimpleConstraintChecker for PoeTransfer {
+    type Error = ConstraintCheckerError;
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![
+            UtxoType::of::<ClaimData>(),
+            UtxoType::of::<ExpiringClaimData>(),
+        ]
+    }
+
+    fn check(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        evicted_input_data: &[DynamicallyTypedData],
+        _peeks: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> Result<TransactionPriority, Self::Error> {
+        // Can't evict anything
+        ensure!(
+            evicted_input_data.is_empty(),
+            ConstraintCheckerError::WrongNumberInputs
+        );
+
+        // Every claim that goes in must come back out
+        ensure!(
+            input_data.len() == output_data.len(),
+            ConstraintCheckerError::WrongNumberOutputs
+        );
+
+        for (untyped_input, untyped_output) in input_data.iter().zip(output_data) {
+            // The expiry is part of the claim, so it must not change either.
+            let input =
+                extract_claim(untyped_input).ok_or(ConstraintCheckerError::BadlyTypedInput)?;
+            let output =
+                extract_claim(untyped_output).ok_or(ConstraintCheckerError::BadlyTypedOutput)?;
+            ensure!(
+                input == output,
+                ConstraintCheckerError::TransferModifiesClaim
+            );
+        }
+
+        Ok(0)
+    }
+}
+
+/// A constraint checker that cleans up expired claims.
+///
+/// Any user may evict expiring claims whose expiry height has been reached. There must be at least one,
+/// and there are no other inputs or outputs.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DebugNoBound,
+    DefaultNoBound,
+    CloneNoBound,
+    PartialEq,
+    Eq,
+    TypeInfo,
+)]
+pub struct PoeExpire<T>(PhantomData<T>);
+
+impl<T: PoeConfig> SimpleConstraintChecker for PoeExpire<T> {
+    type Error = ConstraintCheckerError;
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![
+            UtxoType::of::<ClaimData>(),
+            UtxoType::of::<ExpiringClaimData>(),
+        ]
+    }
+
+    fn check(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        evicted_input_data: &[DynamicallyTypedData],
+        _peeks: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> Result<TransactionPriority, Self::Error> {
+        // Make sure there are no normal inputs or outputs
+        ensure!(
+            input_data.is_empty(),
+            ConstraintCheckerError::WrongNumberInputs
+        );
+        ensure!(
+            output_data.is_empty(),
+            ConstraintCheckerError::WrongNumberOutputs
+        );
+
+        ensure!(
+            !evicted_input_data.is_empty(),
+            ConstraintCheckerError::NothingToExpire
+        );
+
+        for untyped_claim in evicted_input_data {
+            let (_, expiry) =
+                extract_claim(untyped_claim).ok_or(ConstraintCheckerError::BadlyTypedInput)?;
+            ensure!(
+                expiry.is_some_and(|expiry| expiry <= T::block_height()),
+                ConstraintCheckerError::ClaimNotExpired
+            );
+        }
+
+        Ok(0)
+    }
+}
@@ -372,3 +372,236 @@ fn dispute_with_output_fails() {
         Err(ConstraintCheckerError::WrongNumberOutputs)
     )
 }
+
+// Expiry on claims
+
+#[test]
+fn dispute_with_expiring_loser_works() {
+    let win_claim = ClaimData {
+        claim: H256::repeat_byte(1),
+        effective_height: 10,
+    };
+    let lose_claim = ExpiringClaimData {
+        claim: ClaimData {
+            claim: H256::repeat_byte(1),
+            effective_height: 11,
+        },
+        expiry: 20,
+    };
+
+    assert_eq!(
+        PoeDispute.check(&[], &[lose_claim.into()], &[win_claim.into()], &[]),
+        Ok(0)
+    )
+}
+
+#[test]
+fn claim_with_expiry_works() {
+    let claim = ExpiringClaimData {
+        claim: ClaimData {
+            claim: H256::repeat_byte(1),
+            effective_height: 10,
+        },
+        expiry: 20,
+    };
+
+    assert_eq!(
+        PoeClaim::<AlwaysBlockTwo>::default().check(&[], &[], &[], &[claim.into()]),
+        Ok(0)
+    )
+}
+
+#[test]
+fn claim_expiring_before_effective_height_fails() {
+    let claim = ExpiringClaimData {
+        claim: ClaimData {
+            claim: H256::repeat_byte(1),
+            effective_height: 10,
+        },
+        expiry: 10,
+    };
+
+    assert_eq!(
+        PoeClaim::<AlwaysBlockTwo>::default().check(&[], &[], &[], &[claim.into()]),
+        Err(ConstraintCheckerError::ExpiryBeforeEffectiveHeight)
+    )
+}
+
+// Transfer
+
+#[test]
+fn transfer_works_with_two_claims() {
+    let claim = ClaimData {
+        claim: H256::repeat_byte(1),
+        effective_height: 10,
+    };
+    let claim2 = ExpiringClaimData {
+        claim: ClaimData {
+            claim: H256::repeat_byte(2),
+            effective_height: 10,
+        },
+        expiry: 20,
+    };
+
+    assert_eq!(
+        PoeTransfer.check(
+            &[claim.clone().into(), claim2.clone().into()],
+            &[],
+            &[],
+            &[claim.into(), claim2.into()]
+        ),
+        Ok(0)
+    )
+}
+
+#[test]
+fn transfer_changing_effective_height_fails() {
+    let claim = ClaimData {
+        claim: H256::repeat_byte(1),
+        effective_height: 10,
+    };
+    let backdated = ClaimData {
+        claim: H256::repeat_byte(1),
+        effective_height: 5,
+    };
+
+    assert_eq!(
+        PoeTransfer.check(&[claim.into()], &[], &[], &[backdated.into()]),
+        Err(ConstraintCheckerError::TransferModifiesClaim)
+    )
+}
+
+#[test]
+fn transfer_dropping_expiry_fails() {
+    let claim = ClaimData {
+        claim: H256::repeat_byte(1),
+        effective_height: 10,
+    };
+    let expiring = ExpiringClaimData {
+        claim: claim.clone(),
+        expiry: 20,
+    };
+
+    assert_eq!(
+        PoeTransfer.check(&[expiring.into()], &[], &[], &[claim.into()]),
+        Err(ConstraintCheckerError::TransferModifiesClaim)
+    )
+}
+
+#[test]
+fn transfer_dropping_claim_fails() {
+    let claim = ClaimData {
+        claim: H256::repeat_byte(1),
+        effective_height: 10,
+    };
+
+    assert_eq!(
+        PoeTransfer.check(&[claim.into()], &[], &[], &[]),
+        Err(ConstraintCheckerError::WrongNumberOutputs)
+    )
+}
+
+#[test]
+fn transfer_with_bogus_output_fails() {
+    let claim = ClaimData {
+        claim: H256::repeat_byte(1),
+        effective_height: 10,
+    };
+
+    assert_eq!(
+        PoeTransfer.check(&[claim.into()], &[], &[], &[Bogus.into()]),
+        Err(ConstraintCheckerError::BadlyTypedOutput)
+    )
+}
+
+#[test]
+fn transfer_with_eviction_fails() {
+    let claim = ClaimData {
+        claim: H256::repeat_byte(1),
+        effective_height: 10,
+    };
+
+    assert_eq!(
+        PoeTransfer.check(&[], &[claim.clone().into()], &[], &[claim.into()]),
+        Err(ConstraintCheckerError::WrongNumberInputs)
+    )
+}
+
+// Expire
+
+#[test]
+fn expire_works_at_expiry_height() {
+    let claim = ExpiringClaimData {
+        claim: ClaimData {
+            claim: H256::repeat_byte(1),
+            effective_height: 1,
+        },
+        expiry: 2,
+    };
+
+    assert_eq!(
+        PoeExpire::<AlwaysBlockTwo>::default().check(&[], &[claim.into()], &[], &[]),
+        Ok(0)
+    )
+}
+
+#[test]
+fn expire_before_expiry_height_fails() {
+    let claim = ExpiringClaimData {
+        claim: ClaimData {
+            claim: H256::repeat_byte(1),
+            effective_height: 1,
+        },
+        expiry: 3,
+    };
+
+    assert_eq!(
+        PoeExpire::<AlwaysBlockTwo>::default().check(&[], &[claim.into()], &[], &[]),
+        Err(ConstraintCheckerError::ClaimNotExpired)
+    )
+}
+
+#[test]
+fn expire_claim_without_expiry_fails() {
+    let claim = ClaimData {
+        claim: H256::repeat_byte(1),
+        effective_height: 1,
+    };
+
+    assert_eq!(
+        PoeExpire::<AlwaysBlockTwo>::default().check(&[], &[claim.into()], &[], &[]),
+        Err(ConstraintCheckerError::ClaimNotExpired)
+    )
+}
+
+#[test]
+fn expire_nothing_fails() {
+    assert_eq!(
+        PoeExpire::<AlwaysBlockTwo>::default().check(&[], &[], &[], &[]),
+        Err(ConstraintCheckerError::NothingToExpire)
+    )
+}
+
+#[test]
+fn expire_bogus_claim_fails() {
+    assert_eq!(
+        PoeExpire::<AlwaysBlockTwo>::default().check(&[], &[Bogus.into()], &[], &[]),
+        Err(ConstraintCheckerError::BadlyTypedInput)
+    )
+}
+
+#[test]
+fn expire_with_input_fails() {
+    let claim = ExpiringClaimData {
+        claim: ClaimData {
+            claim: H256::repeat_byte(1),
+            effective_height: 1,
+        },
+        expiry: 2,
+    };
+
+    assert_eq!(
+        PoeExpire::<AlwaysBlockTwo>::default().check(&[claim.into()], &[], &[], &[]),
+        Err(ConstraintCheckerError::WrongNumberInputs)
+    )
+}
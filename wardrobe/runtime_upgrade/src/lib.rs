@@ -1,44 +1,229 @@
 //! This is a small pallet that handles runtime upgrades in chains that want
 //! to support them.
 //!
-//! Right now this method is entirely unprotected (except by the verifier) which
-//! may not be realistic enough for public production chains. It should be composed
-//! with some governance mechanism when one is available.
+//! Upgrades are governed. Anyone may propose new runtime code by creating an
+//! [`UpgradeProposal`] that carries the hash of the wasm. The proposal passes in one of two ways:
+//!
+//! * The council approves it. The council is whoever controls the [`UpgradeCouncil`] UTXO,
+//!   which is typically protected by a multisig verifier configured at genesis.
+//! * Coin holders vote on it with the voting piece. The proposal names a vote whose subject is
+//!   the wasm hash and whose options are [`AYE`] and [`NAY`]. Once that vote has been tallied,
+//!   anyone may pass the proposal by peeking at the [`VoteResult`] if the ayes won and the vote's
+//!   quorum was at least the configured minimum.
+//!
+//! Either way, the result is a [`PassedUpgrade`] that may only be enacted after a mandatory delay,
+//! which gives users time to react to the upcoming change.
+//!
+//! Runtime wasm is usually too large to fit in a single transaction, so it is uploaded in
+//! [`WasmChunk`]s across several transactions, up to a configured number and size of chunks.
+//! Once all the chunks are uploaded, anyone may schedule the upgrade, which assembles the chunks
+//! and checks them against the passed hash. Finally, the [`EnactUpgrade`] inherent enacts the
+//! scheduled upgrade at the height the upgrade passed for, or in the next block if it was
+//! scheduled later than that.
+//!
+//! A passed upgrade that is not scheduled within the scheduling period expires. Anyone may then
+//! clean it up along with its chunks, as well as any leftover chunks of scheduled upgrades.
 //!
 //! It is not possible to adhere perfectly to the UTXO model here, because the
 //! wasm code must be stored in the well-known `:code` key. We stick as closely
-//! as possible to the UTXO model by having a UTXO that holds a hash of the current
-//! wasm code. Then we pass the full wasm code as part of the constraint checker and write
-//! it to the well-known key as a side effect.
+//! as possible to the UTXO model by keeping the chunks in UTXOs until the upgrade is scheduled.
+//! Then the assembled wasm is written to a dedicated storage key as a side effect, and moved to
+//! the well-known key as a side effect of the inherent.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+use core::marker::PhantomData;
+
 use parity_scale_codec::{Decode, Encode};
 use scale_info::TypeInfo;
 use serde::{Deserialize, Serialize};
+use sp_core::H256;
+use sp_inherents::{CheckInherentsResult, InherentData, MakeFatalError};
 use sp_runtime::transaction_validity::TransactionPriority;
-use sp_std::vec::Vec;
+use sp_std::{vec, vec::Vec};
 use sp_storage::well_known_keys::CODE;
 use tuxedo_core::{
-    dynamic_typing::{DynamicallyTypedData, UtxoData},
-    ensure, SimpleConstraintChecker,
+    dynamic_typing::{DynamicallyTypedData, UtxoData, UtxoType},
+    ensure,
+    inherents::{InherentError, InherentHooks},
+    support_macros::{CloneNoBound, DebugNoBound, DefaultNoBound},
+    types::Transaction,
+    SimpleConstraintChecker, Verifier,
 };
+use voting::VoteResult;
 
 #[cfg(test)]
 mod tests;
 
-/// A reference to a runtime wasm blob. It is just a hash.
+/// The storage key where a scheduled upgrade waits to be enacted.
+/// It holds the encoded `(enact_at: u32, wasm: Vec<u8>)`.
+const PENDING_UPGRADE_KEY: &[u8] = b"pending_upgrade";
+
+/// The label of the first option of an upgrade vote, which is in favor of the upgrade.
+pub const AYE: &[u8] = b"aye";
+
+/// The label of the second option of an upgrade vote, which is against the upgrade.
+pub const NAY: &[u8] = b"nay";
+
+/// Options to configure runtime upgrade governance in your runtime.
+pub trait RuntimeUpgradeConfig {
+    /// A means of getting the current block height.
+    /// Probably this will be the Tuxedo Executive
+    fn block_height() -> u32;
+
+    /// The smallest quorum that a vote must have had for an upgrade to pass by it.
+    ///
+    /// Default is 0.
+    const MIN_QUORUM: u128 = 0;
+
+    /// The number of blocks after a vote ends during which the upgrade may pass by it.
+    ///
+    /// Default is 1 thousand.
+    const PASSING_PERIOD: u32 = 1_000;
+
+    /// The number of blocks that must pass between an upgrade passing and it being enacted.
+    ///
+    /// Default is 100.
+    const ENACTMENT_DELAY: u32 = 100;
+
+    /// The number of blocks after the enactment delay during which a passed upgrade may still be
+    /// scheduled. Afterwards, it expires.
+    ///
+    /// Default is 1 thousand.
+    const SCHEDULING_PERIOD: u32 = 1_000;
+
+    /// The most chunks that the wasm code of an upgrade may be uploaded in.
+    ///
+    /// Default is 128.
+    const MAX_CHUNKS: u32 = 128;
+
+    /// The most bytes of wasm code that a single chunk may hold.
+    ///
+    /// Default is 64 KiB.
+    const MAX_CHUNK_SIZE: u32 = 64 * 1024;
+}
+
+/// A proposal to upgrade the runtime to the wasm with the given hash.
+///
+/// Proposals are never consumed normally. Passing them evicts them, so
+/// their verifier does not matter.
 #[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone)]
-struct RuntimeRef {
-    hash: [u8; 32],
+pub struct UpgradeProposal {
+    /// The blake2 256 hash of the proposed wasm code.
+    pub wasm_hash: [u8; 32],
+    /// The id of the voting proposal on which coin holders vote on this upgrade.
+    pub vote: u64,
+}
+
+impl UtxoData for UpgradeProposal {
+    const TYPE_ID: [u8; 4] = *b"uprp";
 }
 
-impl UtxoData for RuntimeRef {
-    const TYPE_ID: [u8; 4] = *b"upgd";
+/// The right to approve upgrade proposals without a vote.
+///
+/// Because this is consumed and recreated whenever the council approves a proposal,
+/// its verifier decides who the council is.
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone)]
+pub struct UpgradeCouncil;
+
+impl UtxoData for UpgradeCouncil {
+    const TYPE_ID: [u8; 4] = *b"upcl";
+}
+
+impl UpgradeCouncil {
+    /// Create a genesis transaction that stores the upgrade council protected by
+    /// the given verifier, such as a multisig.
+    ///
+    /// This transaction would be rejected after genesis, but genesis transactions are not checked.
+    pub fn genesis<T, V, OV, OC>(v: V) -> Transaction<OV, OC>
+    where
+        T: RuntimeUpgradeConfig,
+        V: Verifier,
+        OV: Verifier + From<V>,
+        OC: tuxedo_core::ConstraintChecker + From<CouncilApproveUpgrade<T>>,
+    {
+        Transaction {
+            inputs: vec![],
+            peeks: vec![],
+            outputs: vec![(Self, v).into()],
+            checker: CouncilApproveUpgrade::<T>::default().into(),
+        }
+    }
+}
+
+/// This piece's section of a runtime's genesis configuration.
+#[derive(Serialize, Deserialize, DefaultNoBound, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields, default)]
+pub struct GenesisConfig<V> {
+    /// The verifier that protects the [`UpgradeCouncil`], such as a multisig.
+    /// Without a council, upgrades may only pass by coin vote.
+    pub council: Option<V>,
+}
+
+impl<V: Verifier> GenesisConfig<V> {
+    /// Compile this section into genesis transactions.
+    pub fn genesis_transactions<T, OV, OC>(self) -> Vec<Transaction<OV, OC>>
+    where
+        T: RuntimeUpgradeConfig,
+        OV: Verifier + From<V>,
+        OC: tuxedo_core::ConstraintChecker + From<CouncilApproveUpgrade<T>>,
+    {
+        self.council
+            .into_iter()
+            .map(UpgradeCouncil::genesis::<T, _, _, _>)
+            .collect()
+    }
 }
 
-/// Reasons that the RuntimeUpgrade constraint checker may fail
-#[derive(Debug)]
+/// An upgrade that has passed governance and may be enacted once the delay has elapsed.
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone)]
+pub struct PassedUpgrade {
+    /// The blake2 256 hash of the wasm code to upgrade to.
+    pub wasm_hash: [u8; 32],
+    /// The block height from which the upgrade may be enacted.
+    pub enact_after: u32,
+    /// The block height from which the upgrade may no longer be scheduled, and may be cleaned up.
+    pub expires_at: u32,
+}
+
+impl UtxoData for PassedUpgrade {
+    const TYPE_ID: [u8; 4] = *b"upps";
+}
+
+/// One piece of the wasm code for a passed upgrade.
+///
+/// Chunks are never consumed normally. Scheduling the upgrade or cleaning it up evicts them, so
+/// their verifier does not matter.
+#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone)]
+pub struct WasmChunk {
+    /// The blake2 256 hash of the complete wasm code this chunk is part of.
+    pub wasm_hash: [u8; 32],
+    /// The block height at which the passed upgrade expires, from which this chunk may be cleaned up.
+    pub expires_at: u32,
+    /// The position of this chunk in the complete wasm code, starting from zero.
+    pub index: u32,
+    /// The bytes of wasm code in this chunk.
+    pub data: Vec<u8>,
+}
+
+impl UtxoData for WasmChunk {
+    const TYPE_ID: [u8; 4] = *b"upwc";
+}
+
+impl PassedUpgrade {
+    /// The upgrade that results from a proposal passing in the current block.
+    fn from_proposal<T: RuntimeUpgradeConfig>(proposal: &UpgradeProposal) -> Self {
+        let enact_after = T::block_height().saturating_add(T::ENACTMENT_DELAY);
+        Self {
+            wasm_hash: proposal.wasm_hash,
+            enact_after,
+            expires_at: enact_after.saturating_add(T::SCHEDULING_PERIOD),
+        }
+    }
+}
+
+/// Reasons that the runtime upgrade constraint checkers may fail
+#[derive(Debug, PartialEq, Eq)]
 pub enum ConstraintCheckerError {
     // Again we're duplicating these common errors. Probably going to want a
     // better way to handle these.
@@ -50,32 +235,85 @@ pub enum ConstraintCheckerError {
     BadlyTypedInput,
     /// An output data has the wrong type.
     BadlyTypedOutput,
-    /// The Runtime Upgrade piece does not allow any evictions at all.
+    /// This transaction does not allow any evictions.
     NoEvictionsAllowed,
 
-    // Now we get on to the actual upgrade-specific errors
-    /// The consumed input does not match the current wasm. This should never happen
-    /// and is indicative of inconsistent state. Perhaps another piece interfered?
-    InputMismatch,
-    /// The created output does not match the provided new runtime wasm.
-    OutputMismatch,
+    // Governance errors
+    /// Exactly one proposal must be evicted when approving or passing it.
+    MissingProposal,
+    /// Council approval must consume the council as its first input and recreate it as its first output.
+    CouncilNotPreserved,
+    /// The passed upgrade does not match the proposal, or is not enacted after exactly the enactment delay.
+    PassedUpgradeMismatch,
+    /// Passing by vote requires peeking at exactly one vote result.
+    MissingVoteResult,
+    /// The vote result must be for the proposal's vote and the proposal's wasm, with the options
+    /// aye and nay, and a quorum of at least the minimum.
+    WrongVote,
+    /// The ayes did not win the vote.
+    VoteFailed,
+    /// Upgrades must pass by vote within the passing period after the vote ends.
+    VoteExpired,
+
+    // Upload and enactment errors
+    /// Uploading or scheduling wasm requires the passed upgrade that it belongs to.
+    UpgradeNotPassed,
+    /// A wasm chunk must be for the passed upgrade and expire with it, must not be empty or too
+    /// large, and its index must be less than the maximum number of chunks.
+    MalformedChunk,
+    /// The chunks must be evicted in order, starting from index zero, without gaps.
+    ChunksOutOfOrder,
+    /// The assembled wasm does not match the hash of the passed upgrade.
+    WasmHashMismatch,
+    /// The passed upgrade has expired, so its wasm may no longer be uploaded or scheduled.
+    UpgradeExpired,
+    /// Only passed upgrades and chunks that have expired may be cleaned up.
+    UpgradeNotExpired,
+    /// Another upgrade is already scheduled. It must be enacted first.
+    UpgradeAlreadyScheduled,
+    /// The enactment inherent does not consume, peek at, or create anything.
+    EnactmentTouchesState,
 }
 
-/// The sole constraint checker for the runtime upgrade. It confirms that the UTXO
-/// being consumed points to the correct current wasm and creates a new
-/// UTXO for the new wasm.
-///
-/// This constraint checker is somewhat non-standard in that it has a side-effect that
-/// writes the full wasm code to the well-known `:code` storage key. This is
-/// necessary to satisfy Substrate's assumptions that this will happen.
-#[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, Clone, TypeInfo)]
-pub struct RuntimeUpgrade {
-    full_wasm: Vec<u8>,
+/// Extract the single evicted proposal.
+fn evicted_proposal(
+    evicted_input_data: &[DynamicallyTypedData],
+) -> Result<UpgradeProposal, ConstraintCheckerError> {
+    ensure!(
+        evicted_input_data.len() == 1,
+        ConstraintCheckerError::MissingProposal
+    );
+    evicted_input_data[0]
+        .extract::<UpgradeProposal>()
+        .map_err(|_| ConstraintCheckerError::MissingProposal)
 }
 
-impl SimpleConstraintChecker for RuntimeUpgrade {
+/// Propose a runtime upgrade.
+///
+/// There are no inputs, and the only output is a new proposal. Coin holders may then vote on it
+/// by creating a voting proposal with the id that it names.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DebugNoBound,
+    DefaultNoBound,
+    CloneNoBound,
+    PartialEq,
+    Eq,
+    TypeInfo,
+)]
+#[scale_info(skip_type_params(T))]
+pub struct ProposeUpgrade<T>(PhantomData<T>);
+
+impl<T: RuntimeUpgradeConfig> SimpleConstraintChecker for ProposeUpgrade<T> {
     type Error = ConstraintCheckerError;
 
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![UtxoType::of::<UpgradeProposal>()]
+    }
+
     fn check(
         &self,
         input_data: &[DynamicallyTypedData],
@@ -83,46 +321,492 @@ impl SimpleConstraintChecker for RuntimeUpgrade {
         _peeks: &[DynamicallyTypedData],
         output_data: &[DynamicallyTypedData],
     ) -> Result<TransactionPriority, Self::Error> {
-        // Can't evict anything
         ensure!(
             evicted_input_data.is_empty(),
             ConstraintCheckerError::NoEvictionsAllowed
         );
+        ensure!(
+            input_data.is_empty(),
+            ConstraintCheckerError::WrongNumberInputs
+        );
+        ensure!(
+            output_data.len() == 1,
+            ConstraintCheckerError::WrongNumberOutputs
+        );
+
+        output_data[0]
+            .extract::<UpgradeProposal>()
+            .map_err(|_| ConstraintCheckerError::BadlyTypedOutput)?;
+
+        Ok(0)
+    }
+}
 
-        // Make sure there is a single input that matches the hash of the previous runtime logic
+/// Pass an upgrade proposal on the council's authority, without waiting for a vote.
+///
+/// The council is the only normal input and is recreated as the first output. The single evicted
+/// input is the proposal, and the second output is the passed upgrade.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DebugNoBound,
+    DefaultNoBound,
+    CloneNoBound,
+    PartialEq,
+    Eq,
+    TypeInfo,
+)]
+#[scale_info(skip_type_params(T))]
+pub struct CouncilApproveUpgrade<T>(PhantomData<T>);
+
+impl<T: RuntimeUpgradeConfig> SimpleConstraintChecker for CouncilApproveUpgrade<T> {
+    type Error = ConstraintCheckerError;
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![
+            UtxoType::of::<UpgradeCouncil>(),
+            UtxoType::of::<UpgradeProposal>(),
+            UtxoType::of::<PassedUpgrade>(),
+        ]
+    }
+
+    fn check(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        evicted_input_data: &[DynamicallyTypedData],
+        _peeks: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> Result<TransactionPriority, Self::Error> {
+        let proposal = evicted_proposal(evicted_input_data)?;
+
+        // The council's verifier is checked because it is consumed, so it must be recreated.
         ensure!(
             input_data.len() == 1,
             ConstraintCheckerError::WrongNumberInputs
         );
-        let consumed = input_data[0]
-            .extract::<RuntimeRef>()
-            .map_err(|_| ConstraintCheckerError::BadlyTypedInput)?;
-        let outgoing_runtime =
-            sp_io::storage::get(CODE).expect("Some runtime code should always be stored");
-        let outgoing_hash = sp_io::hashing::blake2_256(&outgoing_runtime);
         ensure!(
-            consumed.hash == outgoing_hash,
-            ConstraintCheckerError::InputMismatch
+            output_data.len() == 2,
+            ConstraintCheckerError::WrongNumberOutputs
+        );
+        input_data[0]
+            .extract::<UpgradeCouncil>()
+            .map_err(|_| ConstraintCheckerError::CouncilNotPreserved)?;
+        output_data[0]
+            .extract::<UpgradeCouncil>()
+            .map_err(|_| ConstraintCheckerError::CouncilNotPreserved)?;
+
+        let passed = output_data[1]
+            .extract::<PassedUpgrade>()
+            .map_err(|_| ConstraintCheckerError::BadlyTypedOutput)?;
+        ensure!(
+            passed == PassedUpgrade::from_proposal::<T>(&proposal),
+            ConstraintCheckerError::PassedUpgradeMismatch
+        );
+
+        Ok(0)
+    }
+}
+
+/// Pass an upgrade proposal by the result of the coin vote on it.
+///
+/// The single evicted input is the proposal, and the single peek is the result of its vote.
+/// The only output is the passed upgrade.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DebugNoBound,
+    DefaultNoBound,
+    CloneNoBound,
+    PartialEq,
+    Eq,
+    TypeInfo,
+)]
+#[scale_info(skip_type_params(T))]
+pub struct PassUpgradeByVote<T>(PhantomData<T>);
+
+impl<T: RuntimeUpgradeConfig> SimpleConstraintChecker for PassUpgradeByVote<T> {
+    type Error = ConstraintCheckerError;
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![
+            UtxoType::of::<UpgradeProposal>(),
+            UtxoType::of::<VoteResult>(),
+            UtxoType::of::<PassedUpgrade>(),
+        ]
+    }
+
+    fn check(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        evicted_input_data: &[DynamicallyTypedData],
+        peek_data: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> Result<TransactionPriority, Self::Error> {
+        ensure!(
+            input_data.is_empty(),
+            ConstraintCheckerError::WrongNumberInputs
+        );
+        let proposal = evicted_proposal(evicted_input_data)?;
+
+        ensure!(
+            peek_data.len() == 1,
+            ConstraintCheckerError::MissingVoteResult
+        );
+        let result = peek_data[0]
+            .extract::<VoteResult>()
+            .map_err(|_| ConstraintCheckerError::MissingVoteResult)?;
+        ensure!(
+            result.proposal_id == proposal.vote
+                && result.subject == H256(proposal.wasm_hash)
+                && result.options == [AYE, NAY]
+                && result.quorum >= T::MIN_QUORUM,
+            ConstraintCheckerError::WrongVote
+        );
+        ensure!(result.winner == Some(0), ConstraintCheckerError::VoteFailed);
+        ensure!(
+            T::block_height() < result.voting_ends.saturating_add(T::PASSING_PERIOD),
+            ConstraintCheckerError::VoteExpired
         );
 
-        // Make sure there is a single output that matches the has of the incoming runtime logic
         ensure!(
             output_data.len() == 1,
             ConstraintCheckerError::WrongNumberOutputs
         );
-        let created = output_data[0]
-            .extract::<RuntimeRef>()
+        let passed = output_data[0]
+            .extract::<PassedUpgrade>()
             .map_err(|_| ConstraintCheckerError::BadlyTypedOutput)?;
-        let incoming_hash = sp_io::hashing::blake2_256(&self.full_wasm);
         ensure!(
-            created.hash == incoming_hash,
-            ConstraintCheckerError::OutputMismatch
+            passed == PassedUpgrade::from_proposal::<T>(&proposal),
+            ConstraintCheckerError::PassedUpgradeMismatch
+        );
+
+        Ok(0)
+    }
+}
+
+/// Upload some chunks of the wasm code for a passed upgrade that has not expired.
+///
+/// The first peek is the passed upgrade, and the outputs are chunks of its wasm code.
+/// Chunks may be uploaded in any order and across any number of transactions.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DebugNoBound,
+    DefaultNoBound,
+    CloneNoBound,
+    PartialEq,
+    Eq,
+    TypeInfo,
+)]
+#[scale_info(skip_type_params(T))]
+pub struct UploadWasmChunk<T>(PhantomData<T>);
+
+impl<T: RuntimeUpgradeConfig> SimpleConstraintChecker for UploadWasmChunk<T> {
+    type Error = ConstraintCheckerError;
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![UtxoType::of::<PassedUpgrade>(), UtxoType::of::<WasmChunk>()]
+    }
+
+    fn check(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        evicted_input_data: &[DynamicallyTypedData],
+        peek_data: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> Result<TransactionPriority, Self::Error> {
+        ensure!(
+            evicted_input_data.is_empty(),
+            ConstraintCheckerError::NoEvictionsAllowed
+        );
+        ensure!(
+            input_data.is_empty(),
+            ConstraintCheckerError::WrongNumberInputs
+        );
+        ensure!(
+            !output_data.is_empty(),
+            ConstraintCheckerError::WrongNumberOutputs
         );
 
-        // SIDE EFFECT: Write the new wasm to storage
-        sp_io::storage::set(CODE, &self.full_wasm);
+        let passed = peek_data
+            .first()
+            .ok_or(ConstraintCheckerError::UpgradeNotPassed)?
+            .extract::<PassedUpgrade>()
+            .map_err(|_| ConstraintCheckerError::UpgradeNotPassed)?;
+        ensure!(
+            T::block_height() < passed.expires_at,
+            ConstraintCheckerError::UpgradeExpired
+        );
+
+        for output in output_data {
+            let chunk = output
+                .extract::<WasmChunk>()
+                .map_err(|_| ConstraintCheckerError::BadlyTypedOutput)?;
+            ensure!(
+                chunk.wasm_hash == passed.wasm_hash
+                    && chunk.expires_at == passed.expires_at
+                    && chunk.index < T::MAX_CHUNKS
+                    && !chunk.data.is_empty()
+                    && chunk.data.len() <= T::MAX_CHUNK_SIZE as usize,
+                ConstraintCheckerError::MalformedChunk
+            );
+        }
 
-        //TODO Figure out a better priority
         Ok(0)
     }
 }
+
+/// Schedule a passed upgrade whose wasm code has been uploaded.
+///
+/// The first evicted input is the passed upgrade, and the rest are all of its chunks in order.
+/// There are no normal inputs or outputs. The chunks are assembled and checked against the passed
+/// hash, and the complete wasm is stored until the [`EnactUpgrade`] inherent enacts it. An upgrade
+/// that is scheduled after the height it passed for is enacted in the next block instead, as long
+/// as it has not expired.
+///
+/// This constraint checker is somewhat non-standard in that it has a side-effect that
+/// writes the assembled wasm code to storage. Only one upgrade may be scheduled at a time.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DebugNoBound,
+    DefaultNoBound,
+    CloneNoBound,
+    PartialEq,
+    Eq,
+    TypeInfo,
+)]
+#[scale_info(skip_type_params(T))]
+pub struct ScheduleUpgrade<T>(PhantomData<T>);
+
+impl<T: RuntimeUpgradeConfig> SimpleConstraintChecker for ScheduleUpgrade<T> {
+    type Error = ConstraintCheckerError;
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![UtxoType::of::<PassedUpgrade>(), UtxoType::of::<WasmChunk>()]
+    }
+
+    fn check(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        evicted_input_data: &[DynamicallyTypedData],
+        _peeks: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> Result<TransactionPriority, Self::Error> {
+        ensure!(
+            input_data.is_empty(),
+            ConstraintCheckerError::WrongNumberInputs
+        );
+        ensure!(
+            output_data.is_empty(),
+            ConstraintCheckerError::WrongNumberOutputs
+        );
+
+        let (passed, chunks) = evicted_input_data
+            .split_first()
+            .ok_or(ConstraintCheckerError::UpgradeNotPassed)?;
+        let passed = passed
+            .extract::<PassedUpgrade>()
+            .map_err(|_| ConstraintCheckerError::UpgradeNotPassed)?;
+        ensure!(
+            T::block_height() < passed.expires_at,
+            ConstraintCheckerError::UpgradeExpired
+        );
+        ensure!(
+            !sp_io::storage::exists(PENDING_UPGRADE_KEY),
+            ConstraintCheckerError::UpgradeAlreadyScheduled
+        );
+
+        // Assemble the wasm from the chunks
+        let mut wasm = Vec::new();
+        for (index, chunk) in chunks.iter().enumerate() {
+            let chunk = chunk
+                .extract::<WasmChunk>()
+                .map_err(|_| ConstraintCheckerError::BadlyTypedInput)?;
+            ensure!(
+                chunk.wasm_hash == passed.wasm_hash,
+                ConstraintCheckerError::MalformedChunk
+            );
+            ensure!(
+                chunk.index as usize == index,
+                ConstraintCheckerError::ChunksOutOfOrder
+            );
+            wasm.extend(chunk.data);
+        }
+        ensure!(
+            sp_io::hashing::blake2_256(&wasm) == passed.wasm_hash,
+            ConstraintCheckerError::WasmHashMismatch
+        );
+
+        // SIDE EFFECT: Write the new wasm to storage until it is enacted
+        let enact_at = passed.enact_after.max(T::block_height().saturating_add(1));
+        sp_io::storage::set(PENDING_UPGRADE_KEY, &(enact_at, wasm).encode());
+
+        Ok(0)
+    }
+
+    // Scheduling stores the assembled wasm while checking, so it must check a whole transaction.
+    const COMPOUNDABLE: bool = false;
+}
+
+/// Clean up passed upgrades and wasm chunks that have expired.
+///
+/// The evicted inputs are the expired passed upgrades and chunks, in any order. There are no normal
+/// inputs, peeks, or outputs. This cleans up both upgrades that were never scheduled and chunks
+/// that were left over when an upgrade was scheduled.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DebugNoBound,
+    DefaultNoBound,
+    CloneNoBound,
+    PartialEq,
+    Eq,
+    TypeInfo,
+)]
+#[scale_info(skip_type_params(T))]
+pub struct CleanUpExpiredUpgrade<T>(PhantomData<T>);
+
+impl<T: RuntimeUpgradeConfig> SimpleConstraintChecker for CleanUpExpiredUpgrade<T> {
+    type Error = ConstraintCheckerError;
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![UtxoType::of::<PassedUpgrade>(), UtxoType::of::<WasmChunk>()]
+    }
+
+    fn check(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        evicted_input_data: &[DynamicallyTypedData],
+        _peeks: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> Result<TransactionPriority, Self::Error> {
+        ensure!(
+            input_data.is_empty(),
+            ConstraintCheckerError::WrongNumberInputs
+        );
+        ensure!(
+            output_data.is_empty(),
+            ConstraintCheckerError::WrongNumberOutputs
+        );
+
+        for evicted in evicted_input_data {
+            let expires_at = if let Ok(passed) = evicted.extract::<PassedUpgrade>() {
+                passed.expires_at
+            } else {
+                evicted
+                    .extract::<WasmChunk>()
+                    .map_err(|_| ConstraintCheckerError::BadlyTypedInput)?
+                    .expires_at
+            };
+            ensure!(
+                T::block_height() >= expires_at,
+                ConstraintCheckerError::UpgradeNotExpired
+            );
+        }
+
+        Ok(0)
+    }
+}
+
+/// The inherent that enacts a scheduled upgrade once its enactment height is reached.
+///
+/// It is included in every block, and does not consume, peek at, or create anything.
+///
+/// This constraint checker is somewhat non-standard in that it has a side-effect that
+/// writes the full wasm code to the well-known `:code` storage key. This is
+/// necessary to satisfy Substrate's assumptions that this will happen.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DebugNoBound,
+    DefaultNoBound,
+    CloneNoBound,
+    PartialEq,
+    Eq,
+    TypeInfo,
+)]
+#[scale_info(skip_type_params(T))]
+pub struct EnactUpgrade<T>(PhantomData<T>);
+
+impl<T: RuntimeUpgradeConfig> SimpleConstraintChecker for EnactUpgrade<T> {
+    type Error = ConstraintCheckerError;
+
+    fn check(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        evicted_input_data: &[DynamicallyTypedData],
+        peek_data: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> Result<TransactionPriority, Self::Error> {
+        ensure!(
+            input_data.is_empty()
+                && evicted_input_data.is_empty()
+                && peek_data.is_empty()
+                && output_data.is_empty(),
+            ConstraintCheckerError::EnactmentTouchesState
+        );
+
+        let pending = sp_io::storage::get(PENDING_UPGRADE_KEY)
+            .and_then(|d| <(u32, Vec<u8>)>::decode(&mut &*d).ok());
+        if let Some((enact_at, wasm)) = pending {
+            if enact_at <= T::block_height() {
+                // SIDE EFFECT: Write the new wasm to the well-known key
+                sp_io::storage::set(CODE, &wasm);
+                sp_io::storage::clear(PENDING_UPGRADE_KEY);
+            }
+        }
+
+        Ok(0)
+    }
+
+    // Enacting an upgrade is an inherent that writes the new code while checking.
+    const COMPOUNDABLE: bool = false;
+}
+
+impl<T: RuntimeUpgradeConfig + 'static> InherentHooks for EnactUpgrade<T> {
+    type Error = MakeFatalError<()>;
+    const INHERENT_IDENTIFIER: sp_inherents::InherentIdentifier = *b"upgrade0";
+
+    fn create_inherent<V: Verifier>(
+        _authoring_inherent_data: &InherentData,
+        _previous_inherent: (Transaction<V, Self>, H256),
+    ) -> Result<Transaction<V, Self>, InherentError> {
+        Ok(Transaction {
+            inputs: Vec::new(),
+            peeks: Vec::new(),
+            outputs: Vec::new(),
+            checker: Self::default(),
+        })
+    }
+
+    fn check_inherent<V: Verifier>(
+        _importing_inherent_data: &InherentData,
+        _inherent: Transaction<V, Self>,
+        _result: &mut CheckInherentsResult,
+    ) {
+        // There is nothing to check off-chain. Whether an upgrade is due depends only on the chain state.
+    }
+
+    fn genesis_transactions<V: Verifier>() -> Vec<Transaction<V, Self>> {
+        // Each block's inherent is created from the previous one, so there must be one in genesis too.
+        vec![Transaction {
+            inputs: Vec::new(),
+            peeks: Vec::new(),
+            outputs: Vec::new(),
+            checker: Self::default(),
+        }]
+    }
+}
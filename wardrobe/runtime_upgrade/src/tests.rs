@@ -1 +1,563 @@
-//! Tests for the runtime upgrade piece are TODO
+//! Unit tests for the runtime upgrade piece
+
+use super::*;
+use sp_io::TestExternalities;
+use tuxedo_core::dynamic_typing::testing::Bogus;
+use ConstraintCheckerError::*;
+
+/// The mock config always says the block number is one hundred.
+pub struct AlwaysBlockHundred;
+
+impl RuntimeUpgradeConfig for AlwaysBlockHundred {
+    fn block_height() -> u32 {
+        100
+    }
+
+    const MIN_QUORUM: u128 = 10;
+    const PASSING_PERIOD: u32 = 10;
+    const ENACTMENT_DELAY: u32 = 5;
+    const SCHEDULING_PERIOD: u32 = 10;
+    const MAX_CHUNKS: u32 = 2;
+    const MAX_CHUNK_SIZE: u32 = 4;
+}
+
+const OLD_WASM: &[u8] = b"old wasm";
+const NEW_WASM: &[u8] = b"new wasm";
+
+fn new_hash() -> [u8; 32] {
+    sp_io::hashing::blake2_256(NEW_WASM)
+}
+
+fn proposal() -> UpgradeProposal {
+    UpgradeProposal {
+        wasm_hash: new_hash(),
+        vote: 3,
+    }
+}
+
+/// The result of the vote on the proposal, which ended five blocks ago and which the ayes won.
+fn result() -> VoteResult {
+    VoteResult {
+        proposal_id: 3,
+        subject: H256(new_hash()),
+        options: vec![AYE.to_vec(), NAY.to_vec()],
+        voting_ends: 95,
+        quorum: 10,
+        tallies: vec![6, 4],
+        winner: Some(0),
+    }
+}
+
+fn pass_by(result: VoteResult) -> Result<TransactionPriority, ConstraintCheckerError> {
+    PassUpgradeByVote::<AlwaysBlockHundred>::default().check(
+        &[],
+        &[proposal().into()],
+        &[result.into()],
+        &[passed(105).into()],
+    )
+}
+
+fn passed(enact_after: u32) -> PassedUpgrade {
+    PassedUpgrade {
+        wasm_hash: new_hash(),
+        enact_after,
+        expires_at: enact_after + 10,
+    }
+}
+
+/// A passed upgrade whose scheduling period ended at block one hundred.
+fn expired() -> PassedUpgrade {
+    passed(90)
+}
+
+fn chunk(index: u32, data: &[u8]) -> DynamicallyTypedData {
+    WasmChunk {
+        wasm_hash: new_hash(),
+        expires_at: 115,
+        index,
+        data: data.to_vec(),
+    }
+    .into()
+}
+
+fn expired_chunk(index: u32, data: &[u8]) -> DynamicallyTypedData {
+    WasmChunk {
+        wasm_hash: new_hash(),
+        expires_at: 100,
+        index,
+        data: data.to_vec(),
+    }
+    .into()
+}
+
+type Upload = UploadWasmChunk<AlwaysBlockHundred>;
+type CleanUp = CleanUpExpiredUpgrade<AlwaysBlockHundred>;
+
+/// Schedules the new wasm, uploaded in two chunks, for enactment at block 105.
+fn schedule_new_wasm() -> Result<TransactionPriority, ConstraintCheckerError> {
+    ScheduleUpgrade::<AlwaysBlockHundred>::default().check(
+        &[],
+        &[passed(105).into(), chunk(0, b"new "), chunk(1, b"wasm")],
+        &[],
+        &[],
+    )
+}
+
+/// The mock config always says the block number is one hundred and five.
+pub struct AlwaysBlockHundredFive;
+
+impl RuntimeUpgradeConfig for AlwaysBlockHundredFive {
+    fn block_height() -> u32 {
+        105
+    }
+}
+
+#[test]
+fn propose_works() {
+    let output = proposal().into();
+    assert_eq!(
+        ProposeUpgrade::<AlwaysBlockHundred>::default().check(&[], &[], &[], &[output]),
+        Ok(0)
+    );
+}
+
+#[test]
+fn propose_bogus_fails() {
+    assert_eq!(
+        ProposeUpgrade::<AlwaysBlockHundred>::default().check(&[], &[], &[], &[Bogus.into()]),
+        Err(BadlyTypedOutput)
+    );
+}
+
+#[test]
+fn council_approval_works() {
+    let checker = CouncilApproveUpgrade::<AlwaysBlockHundred>::default();
+    assert_eq!(
+        checker.check(
+            &[UpgradeCouncil.into()],
+            &[proposal().into()],
+            &[],
+            &[UpgradeCouncil.into(), passed(105).into()],
+        ),
+        Ok(0)
+    );
+}
+
+#[test]
+fn council_approval_without_council_fails() {
+    let checker = CouncilApproveUpgrade::<AlwaysBlockHundred>::default();
+    assert_eq!(
+        checker.check(
+            &[Bogus.into()],
+            &[proposal().into()],
+            &[],
+            &[UpgradeCouncil.into(), passed(105).into()],
+        ),
+        Err(CouncilNotPreserved)
+    );
+}
+
+#[test]
+fn council_approval_skipping_delay_fails() {
+    let checker = CouncilApproveUpgrade::<AlwaysBlockHundred>::default();
+    assert_eq!(
+        checker.check(
+            &[UpgradeCouncil.into()],
+            &[proposal().into()],
+            &[],
+            &[UpgradeCouncil.into(), passed(100).into()],
+        ),
+        Err(PassedUpgradeMismatch)
+    );
+}
+
+#[test]
+fn pass_by_vote_works() {
+    assert_eq!(pass_by(result()), Ok(0));
+}
+
+#[test]
+fn pass_by_lost_vote_fails() {
+    assert_eq!(
+        pass_by(VoteResult {
+            tallies: vec![4, 6],
+            winner: Some(1),
+            ..result()
+        }),
+        Err(VoteFailed)
+    );
+}
+
+#[test]
+fn pass_by_vote_without_winner_fails() {
+    assert_eq!(
+        pass_by(VoteResult {
+            tallies: vec![4, 4],
+            winner: None,
+            ..result()
+        }),
+        Err(VoteFailed)
+    );
+}
+
+#[test]
+fn pass_by_vote_on_another_proposal_fails() {
+    assert_eq!(
+        pass_by(VoteResult {
+            proposal_id: 4,
+            ..result()
+        }),
+        Err(WrongVote)
+    );
+}
+
+#[test]
+fn pass_by_vote_on_other_wasm_fails() {
+    assert_eq!(
+        pass_by(VoteResult {
+            subject: H256(sp_io::hashing::blake2_256(OLD_WASM)),
+            ..result()
+        }),
+        Err(WrongVote)
+    );
+}
+
+#[test]
+fn pass_by_vote_with_other_options_fails() {
+    assert_eq!(
+        pass_by(VoteResult {
+            options: vec![b"yes".to_vec(), b"no".to_vec()],
+            ..result()
+        }),
+        Err(WrongVote)
+    );
+}
+
+#[test]
+fn pass_by_vote_below_minimum_quorum_fails() {
+    assert_eq!(
+        pass_by(VoteResult {
+            quorum: 9,
+            ..result()
+        }),
+        Err(WrongVote)
+    );
+}
+
+#[test]
+fn pass_by_expired_vote_fails() {
+    assert_eq!(
+        pass_by(VoteResult {
+            voting_ends: 90,
+            ..result()
+        }),
+        Err(VoteExpired)
+    );
+}
+
+#[test]
+fn pass_by_vote_without_result_fails() {
+    let checker = PassUpgradeByVote::<AlwaysBlockHundred>::default();
+    assert_eq!(
+        checker.check(&[], &[proposal().into()], &[], &[passed(105).into()]),
+        Err(MissingVoteResult)
+    );
+}
+
+#[test]
+fn pass_by_vote_skipping_delay_fails() {
+    let checker = PassUpgradeByVote::<AlwaysBlockHundred>::default();
+    assert_eq!(
+        checker.check(
+            &[],
+            &[proposal().into()],
+            &[result().into()],
+            &[passed(100).into()]
+        ),
+        Err(PassedUpgradeMismatch)
+    );
+}
+
+#[test]
+fn upload_chunks_works() {
+    assert_eq!(
+        Upload::default().check(
+            &[],
+            &[],
+            &[passed(105).into()],
+            &[chunk(1, b"wasm"), chunk(0, b"new ")],
+        ),
+        Ok(0)
+    );
+}
+
+#[test]
+fn upload_without_passed_upgrade_fails() {
+    assert_eq!(
+        Upload::default().check(&[], &[], &[], &[chunk(0, b"new ")]),
+        Err(UpgradeNotPassed)
+    );
+}
+
+#[test]
+fn upload_chunk_for_other_wasm_fails() {
+    let other_chunk = WasmChunk {
+        wasm_hash: [0; 32],
+        expires_at: 115,
+        index: 0,
+        data: b"other".to_vec(),
+    };
+    assert_eq!(
+        Upload::default().check(&[], &[], &[passed(105).into()], &[other_chunk.into()]),
+        Err(MalformedChunk)
+    );
+}
+
+#[test]
+fn upload_empty_chunk_fails() {
+    assert_eq!(
+        Upload::default().check(&[], &[], &[passed(105).into()], &[chunk(0, b"")]),
+        Err(MalformedChunk)
+    );
+}
+
+#[test]
+fn upload_too_many_chunks_fails() {
+    assert_eq!(
+        Upload::default().check(&[], &[], &[passed(105).into()], &[chunk(2, b"more")]),
+        Err(MalformedChunk)
+    );
+}
+
+#[test]
+fn upload_oversized_chunk_fails() {
+    assert_eq!(
+        Upload::default().check(&[], &[], &[passed(105).into()], &[chunk(0, NEW_WASM)]),
+        Err(MalformedChunk)
+    );
+}
+
+#[test]
+fn upload_chunk_expiring_later_fails() {
+    let lasting_chunk = WasmChunk {
+        wasm_hash: new_hash(),
+        expires_at: 1_000,
+        index: 0,
+        data: b"new ".to_vec(),
+    };
+    assert_eq!(
+        Upload::default().check(&[], &[], &[passed(105).into()], &[lasting_chunk.into()]),
+        Err(MalformedChunk)
+    );
+}
+
+#[test]
+fn upload_for_expired_upgrade_fails() {
+    assert_eq!(
+        Upload::default().check(&[], &[], &[expired().into()], &[expired_chunk(0, b"new ")]),
+        Err(UpgradeExpired)
+    );
+}
+
+#[test]
+fn schedule_works() {
+    TestExternalities::default().execute_with(|| {
+        assert_eq!(schedule_new_wasm(), Ok(0));
+        assert_eq!(
+            sp_io::storage::get(PENDING_UPGRADE_KEY).map(|d| d.to_vec()),
+            Some((105u32, NEW_WASM.to_vec()).encode())
+        );
+    });
+}
+
+#[test]
+fn schedule_chunks_out_of_order_fails() {
+    TestExternalities::default().execute_with(|| {
+        let checker = ScheduleUpgrade::<AlwaysBlockHundred>::default();
+        assert_eq!(
+            checker.check(
+                &[],
+                &[passed(105).into(), chunk(1, b"wasm"), chunk(0, b"new ")],
+                &[],
+                &[],
+            ),
+            Err(ChunksOutOfOrder)
+        );
+        assert!(!sp_io::storage::exists(PENDING_UPGRADE_KEY));
+    });
+}
+
+#[test]
+fn schedule_missing_chunk_fails() {
+    TestExternalities::default().execute_with(|| {
+        let checker = ScheduleUpgrade::<AlwaysBlockHundred>::default();
+        assert_eq!(
+            checker.check(&[], &[passed(105).into(), chunk(0, b"new ")], &[], &[]),
+            Err(WasmHashMismatch)
+        );
+    });
+}
+
+#[test]
+fn schedule_after_enactment_height_enacts_next_block() {
+    TestExternalities::default().execute_with(|| {
+        let checker = ScheduleUpgrade::<AlwaysBlockHundredFive>::default();
+        assert_eq!(
+            checker.check(
+                &[],
+                &[passed(105).into(), chunk(0, b"new "), chunk(1, b"wasm")],
+                &[],
+                &[],
+            ),
+            Ok(0)
+        );
+        assert_eq!(
+            sp_io::storage::get(PENDING_UPGRADE_KEY).map(|d| d.to_vec()),
+            Some((106u32, NEW_WASM.to_vec()).encode())
+        );
+    });
+}
+
+#[test]
+fn schedule_expired_upgrade_fails() {
+    TestExternalities::default().execute_with(|| {
+        let checker = ScheduleUpgrade::<AlwaysBlockHundred>::default();
+        assert_eq!(
+            checker.check(
+                &[],
+                &[
+                    expired().into(),
+                    expired_chunk(0, b"new "),
+                    expired_chunk(1, b"wasm")
+                ],
+                &[],
+                &[],
+            ),
+            Err(UpgradeExpired)
+        );
+        assert!(!sp_io::storage::exists(PENDING_UPGRADE_KEY));
+    });
+}
+
+#[test]
+fn schedule_second_upgrade_fails() {
+    TestExternalities::default().execute_with(|| {
+        assert_eq!(schedule_new_wasm(), Ok(0));
+        assert_eq!(schedule_new_wasm(), Err(UpgradeAlreadyScheduled));
+    });
+}
+
+#[test]
+fn clean_up_expired_upgrade_and_chunks_works() {
+    assert_eq!(
+        CleanUp::default().check(
+            &[],
+            &[
+                expired_chunk(1, b"wasm"),
+                expired().into(),
+                expired_chunk(0, b"new ")
+            ],
+            &[],
+            &[],
+        ),
+        Ok(0)
+    );
+}
+
+#[test]
+fn clean_up_unexpired_upgrade_fails() {
+    assert_eq!(
+        CleanUp::default().check(&[], &[passed(105).into()], &[], &[]),
+        Err(UpgradeNotExpired)
+    );
+}
+
+#[test]
+fn clean_up_unexpired_chunk_fails() {
+    assert_eq!(
+        CleanUp::default().check(&[], &[expired().into(), chunk(0, b"new ")], &[], &[]),
+        Err(UpgradeNotExpired)
+    );
+}
+
+#[test]
+fn clean_up_something_else_fails() {
+    assert_eq!(
+        CleanUp::default().check(&[], &[Bogus.into()], &[], &[]),
+        Err(BadlyTypedInput)
+    );
+}
+
+#[test]
+fn clean_up_creating_state_fails() {
+    assert_eq!(
+        CleanUp::default().check(&[], &[expired().into()], &[], &[expired().into()]),
+        Err(WrongNumberOutputs)
+    );
+}
+
+#[test]
+fn enact_before_enactment_height_does_nothing() {
+    TestExternalities::default().execute_with(|| {
+        sp_io::storage::set(CODE, OLD_WASM);
+        assert_eq!(schedule_new_wasm(), Ok(0));
+
+        let checker = EnactUpgrade::<AlwaysBlockHundred>::default();
+        assert_eq!(checker.check(&[], &[], &[], &[]), Ok(0));
+        assert_eq!(sp_io::storage::get(CODE).as_deref(), Some(OLD_WASM));
+        assert!(sp_io::storage::exists(PENDING_UPGRADE_KEY));
+    });
+}
+
+#[test]
+fn enact_at_enactment_height_works() {
+    TestExternalities::default().execute_with(|| {
+        sp_io::storage::set(CODE, OLD_WASM);
+        assert_eq!(schedule_new_wasm(), Ok(0));
+
+        let checker = EnactUpgrade::<AlwaysBlockHundredFive>::default();
+        assert_eq!(checker.check(&[], &[], &[], &[]), Ok(0));
+        assert_eq!(sp_io::storage::get(CODE).as_deref(), Some(NEW_WASM));
+        assert!(!sp_io::storage::exists(PENDING_UPGRADE_KEY));
+    });
+}
+
+#[test]
+fn enact_touching_state_fails() {
+    TestExternalities::default().execute_with(|| {
+        let checker = EnactUpgrade::<AlwaysBlockHundredFive>::default();
+        assert_eq!(
+            checker.check(&[], &[], &[passed(105).into()], &[]),
+            Err(EnactmentTouchesState)
+        );
+    });
+}
+
+#[test]
+fn genesis_config_creates_council() {
+    use tuxedo_core::verifier::UpForGrabs;
+
+    let config = GenesisConfig {
+        council: Some(UpForGrabs),
+    };
+
+    let transactions: Vec<Transaction<UpForGrabs, CouncilApproveUpgrade<AlwaysBlockHundred>>> =
+        config.genesis_transactions::<AlwaysBlockHundred, _, _>();
+
+    assert_eq!(transactions.len(), 1);
+    assert!(transactions[0].inputs.is_empty());
+    assert_eq!(
+        transactions[0].outputs,
+        vec![(UpgradeCouncil, UpForGrabs).into()]
+    );
+}
+
+#[test]
+fn genesis_config_without_council_creates_nothing() {
+    use tuxedo_core::verifier::UpForGrabs;
+
+    let transactions: Vec<Transaction<UpForGrabs, CouncilApproveUpgrade<AlwaysBlockHundred>>> =
+        GenesisConfig::<UpForGrabs>::default().genesis_transactions::<AlwaysBlockHundred, _, _>();
+
+    assert!(transactions.is_empty());
+}
@@ -5,6 +5,14 @@
 //!
 //! In each block, the block author must include a single `SetTimestamp` transaction that peeks at the
 //! Timestamp UTXO that was created in the previous block, and creates a new one with an updated timestamp.
+//!
+//! The noted timestamps can also be peeked at by other transactions as a proof of the current time.
+//! This piece uses that to lock arbitrary data, such as coins, until a wall-clock time.
+//!
+//! Optionally, the piece enforces Bitcoin's median-time-past rule instead of a minimum interval between
+//! consecutive timestamps. Each new timestamp must then be later than the median of the last several noted
+//! timestamps, which are kept in storage. Clients can query the current median time past through the
+//! [`TimestampApi`].
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
@@ -19,9 +27,9 @@ use sp_runtime::transaction_validity::TransactionPriority;
 use sp_std::{vec, vec::Vec};
 use sp_timestamp::InherentError::TooFarInFuture;
 use tuxedo_core::{
-    dynamic_typing::{DynamicallyTypedData, UtxoData},
+    dynamic_typing::{DynamicallyTypedData, UtxoData, UtxoType},
     ensure,
-    inherents::InherentHooks,
+    inherents::{InherentError, InherentHooks},
     support_macros::{CloneNoBound, DebugNoBound, DefaultNoBound},
     types::{Output, OutputRef, Transaction},
     SimpleConstraintChecker, Verifier,
@@ -30,11 +38,19 @@ use tuxedo_core::{
 #[cfg(test)]
 mod cleanup_tests;
 #[cfg(test)]
+mod lock_tests;
+#[cfg(test)]
+mod median_time_past_tests;
+#[cfg(test)]
 mod update_timestamp_tests;
 
 /// A piece-wide target for logging
 const LOG_TARGET: &str = "timestamp-piece";
 
+/// The storage key where the most recent timestamps are kept for the median-time-past rule.
+/// It holds a `Vec<Timestamp>`, newest first.
+const RECENT_TIMESTAMPS_KEY: &[u8] = b"recent_timestamps";
+
 /// A timestamp, since the unix epoch, noted at some point in the history of the chain.
 /// It also records the block height in which it was included.
 #[derive(Debug, Encode, Decode, PartialEq, Eq, Clone, Copy, Default, PartialOrd, Ord)]
@@ -63,6 +79,7 @@ pub trait TimestampConfig {
     fn block_height() -> u32;
 
     /// The minimum amount of time by which the timestamp may be updated.
+    /// It does not apply when the median-time-past rule is enabled.
     ///
     /// The default is 2 seconds which should be slightly lower than most chains' block times.
     const MINIMUM_TIME_INTERVAL: u64 = 2_000;
@@ -85,6 +102,47 @@ pub trait TimestampConfig {
     /// Default is 15 thousand which is roughly equivalent to 1 day with 6 second
     /// block times which is a common default in Substrate chains because of Polkadot.
     const MIN_BLOCKS_BEFORE_CLEANUP: u32 = 15_000;
+
+    /// The number of recent noted timestamps whose median the new timestamp must exceed.
+    /// Bitcoin uses 11.
+    ///
+    /// Default is 0 which disables the median-time-past rule, so that each timestamp must instead be
+    /// at least `MINIMUM_TIME_INTERVAL` after the previous one. The rule should be enabled from genesis.
+    const MEDIAN_TIME_PAST_WINDOW: u32 = 0;
+}
+
+/// The median time of some noted timestamps, or `None` if there are no timestamps.
+///
+/// When there is an even number of timestamps, the later of the two middle ones is used.
+pub fn median_time(timestamps: &[Timestamp]) -> Option<u64> {
+    let mut times: Vec<u64> = timestamps.iter().map(|t| t.time).collect();
+    times.sort_unstable();
+    times.get(times.len() / 2).copied()
+}
+
+/// The most recent noted timestamps, newest first. It is empty if the median-time-past rule is disabled.
+pub fn recent_timestamps() -> Vec<Timestamp> {
+    sp_io::storage::get(RECENT_TIMESTAMPS_KEY)
+        .and_then(|d| Vec::<Timestamp>::decode(&mut &*d).ok())
+        .unwrap_or_default()
+}
+
+/// The median of the most recent noted timestamps, or `None` if the median-time-past rule is disabled
+/// or no timestamps have been noted yet.
+pub fn median_time_past() -> Option<u64> {
+    median_time(&recent_timestamps())
+}
+
+sp_api::decl_runtime_apis! {
+    /// Gives clients access to the chain's notion of time.
+    pub trait TimestampApi {
+        /// The median time past as of the block at which the API is called.
+        /// This is the time that the next block's timestamp must exceed, and the safest time to compare
+        /// time locks against.
+        ///
+        /// Returns `None` if the median-time-past rule is disabled or no timestamps have been noted yet.
+        fn median_time_past() -> Option<u64>;
+    }
 }
 
 /// Reasons that setting or cleaning up the timestamp may go wrong.
@@ -105,6 +163,7 @@ pub enum TimestampError {
     /// Inputs were specified while setting the timestamp, but none are allowed.
     InputsWhileSettingTimestamp,
     /// The new timestamp is not sufficiently far after the previous (or may even be before it).
+    /// This only applies when the median-time-past rule is disabled.
     TimestampTooOld,
     /// When cleaning up old timestamps, you must supply exactly one peek input which is the "new time reference"
     /// All the timestamps that will be cleaned up must be at least the CLEANUP_AGE older than this reference.
@@ -117,6 +176,8 @@ pub enum TimestampError {
     DontBeSoHasty,
     /// When cleaning up old timestamps, you must evict them. You may not use normal inputs.
     CleanupEvictionsOnly,
+    /// The new timestamp is not later than the median of the recent timestamps.
+    TimestampNotAfterMedian,
 }
 
 /// A constraint checker for the simple act of setting a new best timetamp.
@@ -130,6 +191,10 @@ pub enum TimestampError {
 /// On the other hand, the noted timestamps stick around in storage for a while so that other
 /// transactions that need to peek at them are not immediately invalidated. Noted timestamps
 /// can be voluntarily cleand up later by another transaction.
+///
+/// When the median-time-past rule is enabled, it replaces the minimum interval. This constraint
+/// checker is then somewhat non-standard in that it has a side-effect that records the new
+/// timestamp among the recent timestamps in storage.
 #[derive(
     Serialize,
     Deserialize,
@@ -148,6 +213,10 @@ pub struct SetTimestamp<T>(PhantomData<T>);
 impl<T: TimestampConfig + 'static> SimpleConstraintChecker for SetTimestamp<T> {
     type Error = TimestampError;
 
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![UtxoType::of::<Timestamp>()]
+    }
+
     fn check(
         &self,
         input_data: &[DynamicallyTypedData],
@@ -193,9 +262,10 @@ impl<T: TimestampConfig + 'static> SimpleConstraintChecker for SetTimestamp<T> {
             .extract::<Timestamp>()
             .map_err(|_| Self::Error::BadlyTyped)?;
 
-        // Compare the new timestamp to the previous timestamp
+        // Compare the new timestamp to the previous timestamp, unless the median-time-past rule replaces that
         ensure!(
-            old_timestamp.block == 0 // first block hack
+            T::MEDIAN_TIME_PAST_WINDOW > 0
+                || old_timestamp.block == 0 // first block hack
                 || new_timestamp.time >= old_timestamp.time + T::MINIMUM_TIME_INTERVAL,
             Self::Error::TimestampTooOld
         );
@@ -206,8 +276,26 @@ impl<T: TimestampConfig + 'static> SimpleConstraintChecker for SetTimestamp<T> {
             Self::Error::PreviousTimestampWrongHeight,
         );
 
+        // Like in Bitcoin, the median-time-past rule requires the new timestamp to be later than the
+        // median of the recent timestamps, rather than later than the previous one.
+        if T::MEDIAN_TIME_PAST_WINDOW > 0 {
+            let mut recent = recent_timestamps();
+            ensure!(
+                median_time(&recent).map_or(true, |median| new_timestamp.time > median),
+                Self::Error::TimestampNotAfterMedian
+            );
+
+            // SIDE EFFECT: Record the new timestamp, forgetting the oldest one once the window is full.
+            recent.insert(0, new_timestamp);
+            recent.truncate(T::MEDIAN_TIME_PAST_WINDOW as usize);
+            sp_io::storage::set(RECENT_TIMESTAMPS_KEY, &recent.encode());
+        }
+
         Ok(0)
     }
+
+    // Setting the timestamp is an inherent, and it records recent timestamps while checking.
+    const COMPOUNDABLE: bool = false;
 }
 
 impl<T: TimestampConfig + 'static> InherentHooks for SetTimestamp<T> {
@@ -217,11 +305,12 @@ impl<T: TimestampConfig + 'static> InherentHooks for SetTimestamp<T> {
     fn create_inherent<V: Verifier>(
         authoring_inherent_data: &InherentData,
         previous_inherent: (Transaction<V, Self>, H256),
-    ) -> tuxedo_core::types::Transaction<V, Self> {
+    ) -> Result<Transaction<V, Self>, InherentError> {
         let current_timestamp: u64 = authoring_inherent_data
             .get_data(&sp_timestamp::INHERENT_IDENTIFIER)
-            .expect("Inherent data should decode properly")
-            .expect("Timestamp inherent data should be present.");
+            .ok()
+            .flatten()
+            .ok_or(InherentError::MissingInherentData)?;
         let new_timestamp = Timestamp {
             time: current_timestamp,
             block: T::block_height(),
@@ -242,36 +331,52 @@ impl<T: TimestampConfig + 'static> InherentHooks for SetTimestamp<T> {
 
         let new_output = Output {
             payload: new_timestamp.into(),
-            verifier: V::new_unspendable()
-                .expect("Must be able to create unspendable verifier to use timestamp inherent."),
+            verifier: V::new_unspendable().ok_or(InherentError::NoUnspendableVerifier)?,
         };
 
-        Transaction {
+        Ok(Transaction {
             inputs: Vec::new(),
             peeks: vec![old_output],
             outputs: vec![new_output],
             checker: Self::default(),
-        }
+        })
     }
 
-    fn check_inherent<V>(
+    fn check_inherent<V: Verifier>(
         importing_inherent_data: &InherentData,
         inherent: Transaction<V, Self>,
         result: &mut CheckInherentsResult,
     ) {
-        let local_time: u64 = importing_inherent_data
-            .get_data(&sp_timestamp::INHERENT_IDENTIFIER)
-            .expect("Inherent data should decode properly")
-            .expect("Timestamp inherent data should be present.");
+        let Some(local_time) = importing_inherent_data
+            .get_data::<u64>(&sp_timestamp::INHERENT_IDENTIFIER)
+            .ok()
+            .flatten()
+        else {
+            result
+                .put_error(
+                    Self::INHERENT_IDENTIFIER,
+                    &InherentError::MissingInherentData,
+                )
+                .expect("Should be able to push some error");
+            return;
+        };
 
         log::debug!(
             target: LOG_TARGET,
             "🕰️🖴 Local timestamp while checking inherent is: {:#?}", local_time
         );
 
-        let on_chain_timestamp = inherent.outputs[0].payload.extract::<Timestamp>().expect(
-            "SetTimestamp extrinsic should have an output that decodes as a StorableTimestamp.",
-        );
+        // The block has not been executed yet, so the inherent may not even contain a timestamp.
+        let Some(on_chain_timestamp) = inherent
+            .outputs
+            .first()
+            .and_then(|output| output.payload.extract::<Timestamp>().ok())
+        else {
+            result
+                .put_error(Self::INHERENT_IDENTIFIER, &InherentError::MalformedInherent)
+                .expect("Should be able to push some error");
+            return;
+        };
 
         log::debug!(
             target: LOG_TARGET,
@@ -334,6 +439,10 @@ pub struct CleanUpTimestamp<T>(PhantomData<T>);
 impl<T: TimestampConfig> SimpleConstraintChecker for CleanUpTimestamp<T> {
     type Error = TimestampError;
 
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![UtxoType::of::<Timestamp>()]
+    }
+
     fn check(
         &self,
         input_data: &[DynamicallyTypedData],
@@ -381,3 +490,162 @@ impl<T: TimestampConfig> SimpleConstraintChecker for CleanUpTimestamp<T> {
         Ok(0)
     }
 }
+
+/// Some data that is locked until a wall-clock time.
+///
+/// Verifiers only see the transaction and the block height, so they cannot check the time themselves.
+/// Instead, the locked data is wrapped in this type, and may only be unwrapped by a transaction that
+/// peeks at a noted [`Timestamp`] that is at least as late as the unlock time. The verifier on the
+/// locked output still decides who may unlock it.
+#[derive(Debug, Encode, Decode, PartialEq, Eq, Clone)]
+pub struct TimestampLocked {
+    /// The time, in milliseconds since the unix epoch, after which the data may be unlocked.
+    pub unlock_time: u64,
+    /// The data that is locked.
+    pub payload: DynamicallyTypedData,
+}
+
+impl UtxoData for TimestampLocked {
+    const TYPE_ID: [u8; 4] = *b"tlok";
+}
+
+impl TimestampLocked {
+    pub fn new(unlock_time: u64, payload: DynamicallyTypedData) -> Self {
+        Self {
+            unlock_time,
+            payload,
+        }
+    }
+}
+
+/// Reasons that locking or unlocking data with a timestamp may go wrong.
+#[derive(Debug, Eq, PartialEq)]
+pub enum TimestampLockError {
+    /// UTXO data has an unexpected type
+    BadlyTyped,
+    /// Locking and unlocking consume their inputs normally. Evictions are not allowed.
+    NoEvictions,
+    /// The transaction does not lock or unlock anything.
+    NothingToLock,
+    /// The outputs must be exactly the inputs, wrapped or unwrapped, in the same order.
+    DataNotPreserved,
+    /// No noted timestamp was peeked at in this transaction, but one is required to unlock.
+    MissingTimestamp,
+    /// The peeked timestamp is earlier than the unlock time of some locked input.
+    StillLocked,
+}
+
+/// Lock some data until a wall-clock time.
+///
+/// Each input is wrapped in a [`TimestampLocked`] at the same position in the outputs. The outputs
+/// may be protected by any verifier, so funds can be locked on behalf of somebody else.
+#[derive(
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Encode,
+    Decode,
+    Hash,
+    Debug,
+    TypeInfo,
+)]
+pub struct LockUntilTimestamp;
+
+impl SimpleConstraintChecker for LockUntilTimestamp {
+    type Error = TimestampLockError;
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![UtxoType::of::<TimestampLocked>()]
+    }
+
+    fn check(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        evicted_input_data: &[DynamicallyTypedData],
+        _peeks: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> Result<TransactionPriority, Self::Error> {
+        ensure!(evicted_input_data.is_empty(), Self::Error::NoEvictions);
+        ensure!(!input_data.is_empty(), Self::Error::NothingToLock);
+        ensure!(
+            input_data.len() == output_data.len(),
+            Self::Error::DataNotPreserved
+        );
+
+        for (input, output) in input_data.iter().zip(output_data) {
+            let locked = output
+                .extract::<TimestampLocked>()
+                .map_err(|_| Self::Error::BadlyTyped)?;
+            ensure!(&locked.payload == input, Self::Error::DataNotPreserved);
+        }
+
+        Ok(0)
+    }
+}
+
+/// Unlock data whose unlock time has passed.
+///
+/// The first peek must be a noted [`Timestamp`] at least as late as the unlock time of every input.
+/// Each input is unwrapped to its original data at the same position in the outputs.
+#[derive(
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Encode,
+    Decode,
+    Hash,
+    Debug,
+    TypeInfo,
+)]
+pub struct UnlockWithTimestamp;
+
+impl SimpleConstraintChecker for UnlockWithTimestamp {
+    type Error = TimestampLockError;
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![
+            UtxoType::of::<TimestampLocked>(),
+            UtxoType::of::<Timestamp>(),
+        ]
+    }
+
+    fn check(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        evicted_input_data: &[DynamicallyTypedData],
+        peek_data: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> Result<TransactionPriority, Self::Error> {
+        ensure!(evicted_input_data.is_empty(), Self::Error::NoEvictions);
+        ensure!(!input_data.is_empty(), Self::Error::NothingToLock);
+        ensure!(
+            input_data.len() == output_data.len(),
+            Self::Error::DataNotPreserved
+        );
+
+        // Timestamps can only be created by the inherent, so this proves what time it is (or was).
+        let now = peek_data
+            .first()
+            .ok_or(Self::Error::MissingTimestamp)?
+            .extract::<Timestamp>()
+            .map_err(|_| Self::Error::BadlyTyped)?;
+
+        for (input, output) in input_data.iter().zip(output_data) {
+            let locked = input
+                .extract::<TimestampLocked>()
+                .map_err(|_| Self::Error::BadlyTyped)?;
+            ensure!(locked.unlock_time <= now.time, Self::Error::StillLocked);
+            ensure!(&locked.payload == output, Self::Error::DataNotPreserved);
+        }
+
+        Ok(0)
+    }
+}
@@ -0,0 +1,142 @@
+//! Unit tests for the Timestamp piece.
+//! This module tests locking data until a wall-clock time and unlocking it with a noted timestamp.
+
+use super::*;
+use tuxedo_core::dynamic_typing::testing::Bogus;
+use TimestampLockError::*;
+
+/// Some data to lock, which is a timestamp simply because it is a convenient type that is available.
+fn data() -> DynamicallyTypedData {
+    Timestamp::new(42, 42).into()
+}
+
+fn locked(unlock_time: u64) -> DynamicallyTypedData {
+    TimestampLocked::new(unlock_time, data()).into()
+}
+
+#[test]
+fn lock_happy_path() {
+    assert_eq!(
+        LockUntilTimestamp.check(
+            &[data(), Bogus.into()],
+            &[],
+            &[],
+            &[
+                locked(1_000),
+                TimestampLocked::new(2_000, Bogus.into()).into(),
+            ]
+        ),
+        Ok(0)
+    );
+}
+
+#[test]
+fn lock_nothing_fails() {
+    assert_eq!(
+        LockUntilTimestamp.check(&[], &[], &[], &[]),
+        Err(NothingToLock)
+    );
+}
+
+#[test]
+fn lock_with_eviction_fails() {
+    assert_eq!(
+        LockUntilTimestamp.check(&[data()], &[Bogus.into()], &[], &[locked(1_000)]),
+        Err(NoEvictions)
+    );
+}
+
+#[test]
+fn lock_different_data_fails() {
+    assert_eq!(
+        LockUntilTimestamp.check(&[Bogus.into()], &[], &[], &[locked(1_000)]),
+        Err(DataNotPreserved)
+    );
+}
+
+#[test]
+fn lock_extra_output_fails() {
+    assert_eq!(
+        LockUntilTimestamp.check(&[data()], &[], &[], &[locked(1_000), locked(1_000)]),
+        Err(DataNotPreserved)
+    );
+}
+
+#[test]
+fn lock_without_wrapping_fails() {
+    assert_eq!(
+        LockUntilTimestamp.check(&[data()], &[], &[], &[data()]),
+        Err(BadlyTyped)
+    );
+}
+
+#[test]
+fn unlock_happy_path() {
+    let now: DynamicallyTypedData = Timestamp::new(1_000, 10).into();
+
+    assert_eq!(
+        UnlockWithTimestamp.check(
+            &[locked(1_000), locked(500)],
+            &[],
+            &[now],
+            &[data(), data()]
+        ),
+        Ok(0)
+    );
+}
+
+#[test]
+fn unlock_too_early_fails() {
+    let now: DynamicallyTypedData = Timestamp::new(999, 10).into();
+
+    assert_eq!(
+        UnlockWithTimestamp.check(&[locked(1_000)], &[], &[now], &[data()]),
+        Err(StillLocked)
+    );
+}
+
+#[test]
+fn unlock_without_timestamp_fails() {
+    assert_eq!(
+        UnlockWithTimestamp.check(&[locked(1_000)], &[], &[], &[data()]),
+        Err(MissingTimestamp)
+    );
+}
+
+#[test]
+fn unlock_with_bogus_peek_fails() {
+    assert_eq!(
+        UnlockWithTimestamp.check(&[locked(1_000)], &[], &[Bogus.into()], &[data()]),
+        Err(BadlyTyped)
+    );
+}
+
+#[test]
+fn unlock_different_data_fails() {
+    let now: DynamicallyTypedData = Timestamp::new(1_000, 10).into();
+
+    assert_eq!(
+        UnlockWithTimestamp.check(&[locked(1_000)], &[], &[now], &[Bogus.into()]),
+        Err(DataNotPreserved)
+    );
+}
+
+#[test]
+fn unlock_unlocked_data_fails() {
+    let now: DynamicallyTypedData = Timestamp::new(1_000, 10).into();
+
+    assert_eq!(
+        UnlockWithTimestamp.check(&[data()], &[], &[now], &[data()]),
+        Err(BadlyTyped)
+    );
+}
+
+#[test]
+fn unlock_by_eviction_fails() {
+    let now: DynamicallyTypedData = Timestamp::new(1_000, 10).into();
+
+    assert_eq!(
+        UnlockWithTimestamp.check(&[], &[locked(1_000)], &[now], &[data()]),
+        Err(NoEvictions)
+    );
+}
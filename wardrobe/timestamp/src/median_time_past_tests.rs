@@ -0,0 +1,125 @@
+//! Unit tests for the Timestamp piece.
+//! This module tests the optional median-time-past rule when updating the timestamp.
+
+use super::*;
+use sp_io::TestExternalities;
+use TimestampError::*;
+
+/// The mock config always says the block number is five, and uses a median-time-past window of three.
+pub struct AlwaysBlockFive;
+
+impl TimestampConfig for AlwaysBlockFive {
+    fn block_height() -> u32 {
+        5
+    }
+
+    const MEDIAN_TIME_PAST_WINDOW: u32 = 3;
+}
+
+/// Externalities in which the given times were noted in the blocks before block five, newest first.
+fn ext_with_recent(times: &[u64]) -> TestExternalities {
+    let recent: Vec<Timestamp> = times
+        .iter()
+        .enumerate()
+        .map(|(age, time)| Timestamp::new(*time, 4 - age as u32))
+        .collect();
+
+    let mut ext = TestExternalities::default();
+    ext.execute_with(|| sp_io::storage::set(RECENT_TIMESTAMPS_KEY, &recent.encode()));
+    ext
+}
+
+#[test]
+fn median_of_no_timestamps() {
+    assert_eq!(median_time(&[]), None);
+}
+
+#[test]
+fn median_of_odd_number_of_timestamps() {
+    let timestamps = [
+        Timestamp::new(30, 3),
+        Timestamp::new(10, 1),
+        Timestamp::new(20, 2),
+    ];
+    assert_eq!(median_time(&timestamps), Some(20));
+}
+
+#[test]
+fn median_of_even_number_of_timestamps_is_later_middle() {
+    let timestamps = [Timestamp::new(10, 1), Timestamp::new(20, 2)];
+    assert_eq!(median_time(&timestamps), Some(20));
+}
+
+#[test]
+fn update_timestamp_after_median_works() {
+    ext_with_recent(&[10_000, 9_000, 20_000]).execute_with(|| {
+        let checker = SetTimestamp::<AlwaysBlockFive>(Default::default());
+        let old: DynamicallyTypedData = Timestamp::new(10_000, 4).into();
+        let new: DynamicallyTypedData = Timestamp::new(12_000, 5).into();
+
+        assert_eq!(median_time_past(), Some(10_000));
+        assert_eq!(checker.check(&[], &[], &[old], &[new]), Ok(0));
+        assert_eq!(
+            recent_timestamps(),
+            vec![
+                Timestamp::new(12_000, 5),
+                Timestamp::new(10_000, 4),
+                Timestamp::new(9_000, 3),
+            ]
+        );
+        assert_eq!(median_time_past(), Some(10_000));
+    });
+}
+
+#[test]
+fn update_timestamp_before_previous_but_after_median_works() {
+    ext_with_recent(&[20_000, 30_000, 10_000]).execute_with(|| {
+        let checker = SetTimestamp::<AlwaysBlockFive>(Default::default());
+        let old: DynamicallyTypedData = Timestamp::new(20_000, 4).into();
+        let new: DynamicallyTypedData = Timestamp::new(20_500, 5).into();
+
+        assert_eq!(checker.check(&[], &[], &[old], &[new]), Ok(0));
+    });
+}
+
+#[test]
+fn update_timestamp_not_after_median_fails() {
+    ext_with_recent(&[25_000, 30_000, 20_000]).execute_with(|| {
+        let checker = SetTimestamp::<AlwaysBlockFive>(Default::default());
+        let old: DynamicallyTypedData = Timestamp::new(25_000, 4).into();
+        let new: DynamicallyTypedData = Timestamp::new(25_000, 5).into();
+
+        assert_eq!(
+            checker.check(&[], &[], &[old], &[new]),
+            Err(TimestampNotAfterMedian)
+        );
+        assert_eq!(recent_timestamps().len(), 3);
+    });
+}
+
+#[test]
+fn update_timestamp_without_recent_timestamps_works() {
+    TestExternalities::default().execute_with(|| {
+        let checker = SetTimestamp::<AlwaysBlockFive>(Default::default());
+        let old: DynamicallyTypedData = Timestamp::new(0, 4).into();
+        let new: DynamicallyTypedData = Timestamp::new(1, 5).into();
+
+        assert_eq!(median_time_past(), None);
+        assert_eq!(checker.check(&[], &[], &[old], &[new]), Ok(0));
+        assert_eq!(recent_timestamps(), vec![Timestamp::new(1, 5)]);
+    });
+}
+
+#[test]
+fn update_timestamp_previous_wrong_height_fails() {
+    ext_with_recent(&[10_000, 9_000, 20_000]).execute_with(|| {
+        let checker = SetTimestamp::<AlwaysBlockFive>(Default::default());
+        let old: DynamicallyTypedData = Timestamp::new(10_000, 3).into();
+        let new: DynamicallyTypedData = Timestamp::new(12_000, 5).into();
+
+        assert_eq!(
+            checker.check(&[], &[], &[old], &[new]),
+            Err(PreviousTimestampWrongHeight)
+        );
+    });
+}
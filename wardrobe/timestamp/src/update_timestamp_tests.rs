@@ -1,8 +1,12 @@
 //! Unit tests for the Timestamp piece.
-//! This module tests the primary flow of updating the timestamp via an inherent after it has been initialized.
+//! This module tests the primary flow of updating the timestamp via an inherent after it has been initialized,
+//! as well as creating and checking that inherent.
 
 use super::*;
-use tuxedo_core::dynamic_typing::testing::Bogus;
+use tuxedo_core::{
+    dynamic_typing::testing::Bogus, inherents::InherentAdapter, verifier::Unspendable,
+    ConstraintChecker,
+};
 use TimestampError::*;
 
 /// The mock config always says the block number is two.
@@ -151,3 +155,114 @@ fn update_timestamp_previous_timestamp_wrong_height() {
         Err(PreviousTimestampWrongHeight)
     );
 }
+
+type Adapter = InherentAdapter<SetTimestamp<AlwaysBlockTwo>>;
+
+/// Inherent data in which the local time is the given time.
+fn inherent_data_at(time: u64) -> InherentData {
+    let mut data = InherentData::new();
+    data.put_data(sp_timestamp::INHERENT_IDENTIFIER, &time)
+        .unwrap();
+    data
+}
+
+#[test]
+fn create_inherent_without_previous_inherent_fails() {
+    assert_eq!(
+        Adapter::create_inherents::<Unspendable>(&inherent_data_at(3_000), Vec::new()).err(),
+        Some(InherentError::MissingPreviousInherent)
+    );
+}
+
+#[test]
+fn create_inherent_with_multiple_previous_inherents_fails() {
+    let previous = Adapter::genesis_transactions::<Unspendable>()
+        .pop()
+        .unwrap();
+    let previous_inherents = vec![
+        (previous.clone(), H256::repeat_byte(1)),
+        (previous, H256::repeat_byte(2)),
+    ];
+
+    assert_eq!(
+        Adapter::create_inherents(&inherent_data_at(3_000), previous_inherents).err(),
+        Some(InherentError::MultiplePreviousInherents)
+    );
+}
+
+#[test]
+fn create_inherent_without_local_time_fails() {
+    let previous =
+        <SetTimestamp<AlwaysBlockTwo> as InherentHooks>::genesis_transactions::<Unspendable>()
+            .pop()
+            .unwrap();
+
+    assert_eq!(
+        SetTimestamp::<AlwaysBlockTwo>::create_inherent(
+            &InherentData::new(),
+            (previous, H256::zero()),
+        )
+        .err(),
+        Some(InherentError::MissingInherentData)
+    );
+}
+
+#[test]
+fn check_inherent_without_timestamp_fails() {
+    let inherent = Transaction::<Unspendable, SetTimestamp<AlwaysBlockTwo>> {
+        inputs: Vec::new(),
+        peeks: Vec::new(),
+        outputs: Vec::new(),
+        checker: Default::default(),
+    };
+    let mut result = CheckInherentsResult::new();
+
+    SetTimestamp::<AlwaysBlockTwo>::check_inherent(&inherent_data_at(3_000), inherent, &mut result);
+
+    assert!(result.fatal_error());
+}
+
+#[test]
+fn check_inherent_with_badly_typed_output_fails() {
+    let inherent = Transaction::<Unspendable, SetTimestamp<AlwaysBlockTwo>> {
+        inputs: Vec::new(),
+        peeks: Vec::new(),
+        outputs: vec![(Bogus, Unspendable).into()],
+        checker: Default::default(),
+    };
+    let mut result = CheckInherentsResult::new();
+
+    SetTimestamp::<AlwaysBlockTwo>::check_inherent(&inherent_data_at(3_000), inherent, &mut result);
+
+    assert!(result.fatal_error());
+}
+
+#[test]
+fn check_inherent_without_local_time_fails() {
+    let inherent = Transaction::<Unspendable, SetTimestamp<AlwaysBlockTwo>> {
+        inputs: Vec::new(),
+        peeks: Vec::new(),
+        outputs: vec![(Timestamp::new(3_000, 2), Unspendable).into()],
+        checker: Default::default(),
+    };
+    let mut result = CheckInherentsResult::new();
+
+    SetTimestamp::<AlwaysBlockTwo>::check_inherent(&InherentData::new(), inherent, &mut result);
+
+    assert!(result.fatal_error());
+}
+
+#[test]
+fn check_inherent_with_current_timestamp_works() {
+    let inherent = Transaction::<Unspendable, SetTimestamp<AlwaysBlockTwo>> {
+        inputs: Vec::new(),
+        peeks: Vec::new(),
+        outputs: vec![(Timestamp::new(3_000, 2), Unspendable).into()],
+        checker: Default::default(),
+    };
+    let mut result = CheckInherentsResult::new();
+
+    SetTimestamp::<AlwaysBlockTwo>::check_inherent(&inherent_data_at(3_000), inherent, &mut result);
+
+    assert!(result.ok());
+}
@@ -0,0 +1,331 @@
+//! A token factory that lets users issue their own fungible assets without a runtime upgrade.
+//!
+//! The money piece distinguishes its coins with a const generic, so every asset it supports must
+//! be compiled into the runtime. Here, an asset is created at runtime by an issue transaction that
+//! stores an [`AssetDefinition`] in a UTXO. The verifier protecting that UTXO is the asset's mint
+//! authority. Minting consumes the definition and recreates it unchanged, so only someone who can
+//! satisfy the mint authority may create new coins. The authority may be any verifier the runtime
+//! supports, such as a multisig.
+//!
+//! Every [`AssetCoin`] carries the id of its asset, which is the hash of the definition together
+//! with its mint authority. A single spend transaction may move coins of several assets, and value
+//! is conserved separately for each one.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use parity_scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use serde::{Deserialize, Serialize};
+use sp_core::H256;
+use sp_runtime::{
+    traits::{BlakeTwo256, Hash},
+    transaction_validity::TransactionPriority,
+};
+use sp_std::{collections::btree_map::BTreeMap, prelude::*};
+use tuxedo_core::{
+    dynamic_typing::{UtxoData, UtxoType},
+    ensure,
+    types::{Output, Transaction},
+    ConstraintChecker, Verifier, VerifierAwareConstraintChecker,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// The longest name an asset may have, in bytes.
+pub const MAX_NAME_LENGTH: usize = 32;
+
+/// The most decimal places an asset may have. A `u128` holds 38 full decimal digits.
+pub const MAX_DECIMALS: u8 = 38;
+
+/// The description of an asset. It is stored in a UTXO whose verifier is the asset's mint authority.
+#[derive(
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Encode,
+    Decode,
+    Hash,
+    Debug,
+    TypeInfo,
+    UtxoData,
+)]
+pub struct AssetDefinition {
+    /// A human readable name such as a ticker symbol.
+    pub name: Vec<u8>,
+    /// How many decimal places wallets should use when displaying amounts of this asset.
+    pub decimals: u8,
+}
+
+impl AssetDefinition {
+    pub fn new(name: &[u8], decimals: u8) -> Self {
+        Self {
+            name: name.to_vec(),
+            decimals,
+        }
+    }
+
+    /// The id of the asset that this definition describes when it is protected by the given
+    /// mint authority.
+    ///
+    /// The authority must be encoded exactly as it is stored on chain, which usually means as the
+    /// runtime's outer verifier. It is part of the id so that nobody can issue another definition
+    /// with the same name and use it to mint coins of an existing asset.
+    pub fn asset_id<V: Encode>(&self, mint_authority: &V) -> H256 {
+        BlakeTwo256::hash_of(&(self, mint_authority))
+    }
+
+    /// Create an issue transaction for this asset.
+    pub fn issue<V, OV, OC>(self, mint_authority: V) -> Transaction<OV, OC>
+    where
+        V: Verifier,
+        OV: Verifier + From<V>,
+        OC: ConstraintChecker + From<TokenFactoryConstraintChecker>,
+    {
+        Transaction {
+            inputs: vec![],
+            peeks: vec![],
+            outputs: vec![(self, mint_authority).into()],
+            checker: TokenFactoryConstraintChecker::Issue.into(),
+        }
+    }
+}
+
+/// A single coin of some asset created by the token factory.
+#[derive(
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Encode,
+    Decode,
+    Hash,
+    Debug,
+    TypeInfo,
+    UtxoData,
+)]
+pub struct AssetCoin {
+    /// The asset to which this coin belongs. See [`AssetDefinition::asset_id`].
+    pub asset_id: H256,
+    /// The value of this coin in the asset's smallest unit.
+    pub amount: u128,
+}
+
+impl AssetCoin {
+    pub fn new(asset_id: H256, amount: u128) -> Self {
+        Self { asset_id, amount }
+    }
+}
+
+/// Errors that can occur when checking token factory transactions.
+#[derive(
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Encode,
+    Decode,
+    Hash,
+    Debug,
+    TypeInfo,
+)]
+pub enum TokenFactoryError {
+    /// An input data has the wrong type.
+    BadlyTypedInput,
+    /// An output data has the wrong type.
+    BadlyTypedOutput,
+    /// The token factory does not allow any evictions at all.
+    NoEvictionsAllowed,
+    /// An issue transaction attempts to consume inputs.
+    IssuingWithInputs,
+    /// An issue transaction must create exactly one asset definition and nothing else.
+    WrongNumberOfDefinitions,
+    /// The asset's name is empty.
+    EmptyName,
+    /// The asset's name is longer than `MAX_NAME_LENGTH`.
+    NameTooLong,
+    /// The asset has more than `MAX_DECIMALS` decimal places.
+    TooManyDecimals,
+    /// A mint transaction must consume exactly one input, which is the asset definition.
+    WrongNumberOfMintInputs,
+    /// A mint transaction must recreate the asset definition it consumed, with the same
+    /// data and the same mint authority, as its first output.
+    DefinitionNotPreserved,
+    /// A mint transaction does not create any coins.
+    MintingNothing,
+    /// A coin created by a mint transaction belongs to a different asset than the one
+    /// whose definition was consumed.
+    WrongAsset,
+    /// The transaction attempts to spend without consuming any inputs.
+    SpendingNothing,
+    /// For at least one asset, the value of the output coins exceeds the value of the input coins.
+    OutputsExceedInputs,
+    /// The value consumed or created by this transaction overflows the value type.
+    ValueOverflow,
+    /// The transaction attempted to create a coin with zero value. This is not allowed
+    /// because it wastes state space.
+    ZeroValueCoin,
+}
+
+/// The constraint checker for the token factory piece.
+#[derive(
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Encode,
+    Decode,
+    Hash,
+    Debug,
+    TypeInfo,
+)]
+pub enum TokenFactoryConstraintChecker {
+    /// Create a new asset by storing its definition in a UTXO. The verifier on that UTXO
+    /// becomes the asset's mint authority. No coins are created.
+    Issue,
+    /// Create new coins of an asset. The single input is the asset's definition, which
+    /// must be recreated unchanged as the first output. The remaining outputs are the new coins.
+    Mint,
+    /// Consume and create coins of any number of assets. For each asset, the value of the
+    /// outputs may not exceed the value of the inputs. The difference is burned.
+    ///
+    /// Values of different assets cannot be compared, so spends always have zero priority.
+    Spend,
+}
+
+/// Sum the coins in a set of outputs by asset, making sure none of them has zero value.
+fn sum_by_asset<V>(
+    utxos: &[Output<V>],
+    badly_typed: TokenFactoryError,
+) -> Result<BTreeMap<H256, u128>, TokenFactoryError> {
+    let mut totals = BTreeMap::new();
+    for utxo in utxos {
+        let coin = utxo
+            .payload
+            .extract::<AssetCoin>()
+            .map_err(|_| badly_typed.clone())?;
+        ensure!(coin.amount > 0, TokenFactoryError::ZeroValueCoin);
+        let total: &mut u128 = totals.entry(coin.asset_id).or_default();
+        *total = total
+            .checked_add(coin.amount)
+            .ok_or(TokenFactoryError::ValueOverflow)?;
+    }
+    Ok(totals)
+}
+
+impl VerifierAwareConstraintChecker for TokenFactoryConstraintChecker {
+    type Error = TokenFactoryError;
+
+    fn check<V: Verifier>(
+        &self,
+        inputs: &[Output<V>],
+        evicted_inputs: &[Output<V>],
+        _peeks: &[Output<V>],
+        outputs: &[Output<V>],
+    ) -> Result<TransactionPriority, Self::Error> {
+        // Can't evict anything
+        ensure!(
+            evicted_inputs.is_empty(),
+            TokenFactoryError::NoEvictionsAllowed
+        );
+
+        match self {
+            Self::Issue => {
+                ensure!(inputs.is_empty(), TokenFactoryError::IssuingWithInputs);
+                ensure!(
+                    outputs.len() == 1,
+                    TokenFactoryError::WrongNumberOfDefinitions
+                );
+
+                let definition = outputs[0]
+                    .payload
+                    .extract::<AssetDefinition>()
+                    .map_err(|_| TokenFactoryError::BadlyTypedOutput)?;
+                ensure!(!definition.name.is_empty(), TokenFactoryError::EmptyName);
+                ensure!(
+                    definition.name.len() <= MAX_NAME_LENGTH,
+                    TokenFactoryError::NameTooLong
+                );
+                ensure!(
+                    definition.decimals <= MAX_DECIMALS,
+                    TokenFactoryError::TooManyDecimals
+                );
+
+                Ok(0)
+            }
+            Self::Mint => {
+                ensure!(
+                    inputs.len() == 1,
+                    TokenFactoryError::WrongNumberOfMintInputs
+                );
+                let definition_utxo = &inputs[0];
+                let definition = definition_utxo
+                    .payload
+                    .extract::<AssetDefinition>()
+                    .map_err(|_| TokenFactoryError::BadlyTypedInput)?;
+
+                // The definition must come back out exactly as it went in. Comparing the encoded
+                // verifiers ensures the mint authority can't be swapped out along the way.
+                let (recreated_utxo, new_coins) = outputs
+                    .split_first()
+                    .ok_or(TokenFactoryError::DefinitionNotPreserved)?;
+                let recreated = recreated_utxo
+                    .payload
+                    .extract::<AssetDefinition>()
+                    .map_err(|_| TokenFactoryError::DefinitionNotPreserved)?;
+                ensure!(
+                    recreated == definition
+                        && recreated_utxo.verifier.encode() == definition_utxo.verifier.encode(),
+                    TokenFactoryError::DefinitionNotPreserved
+                );
+
+                ensure!(!new_coins.is_empty(), TokenFactoryError::MintingNothing);
+                let asset_id = definition.asset_id(&definition_utxo.verifier);
+                let minted = sum_by_asset(new_coins, TokenFactoryError::BadlyTypedOutput)?;
+                ensure!(
+                    minted.keys().all(|id| *id == asset_id),
+                    TokenFactoryError::WrongAsset
+                );
+
+                // No priority for minting
+                Ok(0)
+            }
+            Self::Spend => {
+                ensure!(!inputs.is_empty(), TokenFactoryError::SpendingNothing);
+
+                let input_totals = sum_by_asset(inputs, TokenFactoryError::BadlyTypedInput)?;
+                let output_totals = sum_by_asset(outputs, TokenFactoryError::BadlyTypedOutput)?;
+
+                for (asset_id, output_total) in output_totals {
+                    let input_total = input_totals.get(&asset_id).copied().unwrap_or_default();
+                    ensure!(
+                        output_total <= input_total,
+                        TokenFactoryError::OutputsExceedInputs
+                    );
+                }
+
+                Ok(0)
+            }
+        }
+    }
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![
+            UtxoType::of::<AssetDefinition>(),
+            UtxoType::of::<AssetCoin>(),
+        ]
+    }
+}
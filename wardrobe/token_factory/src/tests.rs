@@ -0,0 +1,391 @@
+//! Unit tests for the Token Factory piece
+
+use super::*;
+use tuxedo_core::{dynamic_typing::testing::Bogus, verifier::TestVerifier};
+
+/// The mint authority used throughout these tests.
+const AUTHORITY: TestVerifier = TestVerifier { verifies: true };
+
+fn definition() -> AssetDefinition {
+    AssetDefinition::new(b"TUX", 12)
+}
+
+fn asset_id() -> H256 {
+    definition().asset_id(&AUTHORITY)
+}
+
+fn other_asset_id() -> H256 {
+    AssetDefinition::new(b"DOGE", 8).asset_id(&AUTHORITY)
+}
+
+fn coin(asset_id: H256, amount: u128) -> Output<TestVerifier> {
+    (AssetCoin::new(asset_id, amount), AUTHORITY).into()
+}
+
+fn definition_owned_by(verifies: bool) -> Output<TestVerifier> {
+    (definition(), TestVerifier { verifies }).into()
+}
+
+fn check(
+    checker: TokenFactoryConstraintChecker,
+    inputs: &[Output<TestVerifier>],
+    evicted_inputs: &[Output<TestVerifier>],
+    outputs: &[Output<TestVerifier>],
+) -> Result<TransactionPriority, TokenFactoryError> {
+    VerifierAwareConstraintChecker::check(&checker, inputs, evicted_inputs, &[], outputs)
+}
+
+#[test]
+fn asset_id_depends_on_mint_authority() {
+    assert_ne!(
+        definition().asset_id(&TestVerifier { verifies: true }),
+        definition().asset_id(&TestVerifier { verifies: false }),
+    );
+}
+
+#[test]
+fn issue_valid_transaction_works() {
+    assert_eq!(
+        check(
+            TokenFactoryConstraintChecker::Issue,
+            &[],
+            &[],
+            &[definition_owned_by(true)]
+        ),
+        Ok(0)
+    );
+}
+
+#[test]
+fn issue_with_inputs_fails() {
+    assert_eq!(
+        check(
+            TokenFactoryConstraintChecker::Issue,
+            &[coin(asset_id(), 1)],
+            &[],
+            &[definition_owned_by(true)]
+        ),
+        Err(TokenFactoryError::IssuingWithInputs)
+    );
+}
+
+#[test]
+fn issue_with_extra_outputs_fails() {
+    assert_eq!(
+        check(
+            TokenFactoryConstraintChecker::Issue,
+            &[],
+            &[],
+            &[definition_owned_by(true), coin(asset_id(), 100)]
+        ),
+        Err(TokenFactoryError::WrongNumberOfDefinitions)
+    );
+}
+
+#[test]
+fn issue_wrong_output_type_fails() {
+    assert_eq!(
+        check(
+            TokenFactoryConstraintChecker::Issue,
+            &[],
+            &[],
+            &[(Bogus, AUTHORITY).into()]
+        ),
+        Err(TokenFactoryError::BadlyTypedOutput)
+    );
+}
+
+#[test]
+fn issue_with_empty_name_fails() {
+    assert_eq!(
+        check(
+            TokenFactoryConstraintChecker::Issue,
+            &[],
+            &[],
+            &[(AssetDefinition::new(b"", 12), AUTHORITY).into()]
+        ),
+        Err(TokenFactoryError::EmptyName)
+    );
+}
+
+#[test]
+fn issue_with_long_name_fails() {
+    let name = [b'a'; MAX_NAME_LENGTH + 1];
+    assert_eq!(
+        check(
+            TokenFactoryConstraintChecker::Issue,
+            &[],
+            &[],
+            &[(AssetDefinition::new(&name, 12), AUTHORITY).into()]
+        ),
+        Err(TokenFactoryError::NameTooLong)
+    );
+}
+
+#[test]
+fn issue_with_too_many_decimals_fails() {
+    assert_eq!(
+        check(
+            TokenFactoryConstraintChecker::Issue,
+            &[],
+            &[],
+            &[(AssetDefinition::new(b"TUX", MAX_DECIMALS + 1), AUTHORITY).into()]
+        ),
+        Err(TokenFactoryError::TooManyDecimals)
+    );
+}
+
+#[test]
+fn mint_valid_transaction_works() {
+    assert_eq!(
+        check(
+            TokenFactoryConstraintChecker::Mint,
+            &[definition_owned_by(true)],
+            &[],
+            &[
+                definition_owned_by(true),
+                coin(asset_id(), 100),
+                coin(asset_id(), 5)
+            ]
+        ),
+        Ok(0)
+    );
+}
+
+#[test]
+fn mint_without_definition_fails() {
+    assert_eq!(
+        check(
+            TokenFactoryConstraintChecker::Mint,
+            &[coin(asset_id(), 1)],
+            &[],
+            &[definition_owned_by(true), coin(asset_id(), 100)]
+        ),
+        Err(TokenFactoryError::BadlyTypedInput)
+    );
+}
+
+#[test]
+fn mint_with_extra_inputs_fails() {
+    assert_eq!(
+        check(
+            TokenFactoryConstraintChecker::Mint,
+            &[definition_owned_by(true), coin(asset_id(), 1)],
+            &[],
+            &[definition_owned_by(true), coin(asset_id(), 100)]
+        ),
+        Err(TokenFactoryError::WrongNumberOfMintInputs)
+    );
+}
+
+#[test]
+fn mint_without_recreating_definition_fails() {
+    assert_eq!(
+        check(
+            TokenFactoryConstraintChecker::Mint,
+            &[definition_owned_by(true)],
+            &[],
+            &[coin(asset_id(), 100)]
+        ),
+        Err(TokenFactoryError::DefinitionNotPreserved)
+    );
+}
+
+#[test]
+fn mint_changing_definition_fails() {
+    assert_eq!(
+        check(
+            TokenFactoryConstraintChecker::Mint,
+            &[definition_owned_by(true)],
+            &[],
+            &[
+                (AssetDefinition::new(b"TUX", 2), AUTHORITY).into(),
+                coin(asset_id(), 100)
+            ]
+        ),
+        Err(TokenFactoryError::DefinitionNotPreserved)
+    );
+}
+
+#[test]
+fn mint_changing_authority_fails() {
+    assert_eq!(
+        check(
+            TokenFactoryConstraintChecker::Mint,
+            &[definition_owned_by(true)],
+            &[],
+            &[definition_owned_by(false), coin(asset_id(), 100)]
+        ),
+        Err(TokenFactoryError::DefinitionNotPreserved)
+    );
+}
+
+#[test]
+fn mint_nothing_fails() {
+    assert_eq!(
+        check(
+            TokenFactoryConstraintChecker::Mint,
+            &[definition_owned_by(true)],
+            &[],
+            &[definition_owned_by(true)]
+        ),
+        Err(TokenFactoryError::MintingNothing)
+    );
+}
+
+#[test]
+fn mint_other_asset_fails() {
+    assert_eq!(
+        check(
+            TokenFactoryConstraintChecker::Mint,
+            &[definition_owned_by(true)],
+            &[],
+            &[definition_owned_by(true), coin(other_asset_id(), 100)]
+        ),
+        Err(TokenFactoryError::WrongAsset)
+    );
+}
+
+#[test]
+fn mint_zero_value_coin_fails() {
+    assert_eq!(
+        check(
+            TokenFactoryConstraintChecker::Mint,
+            &[definition_owned_by(true)],
+            &[],
+            &[definition_owned_by(true), coin(asset_id(), 0)]
+        ),
+        Err(TokenFactoryError::ZeroValueCoin)
+    );
+}
+
+#[test]
+fn spend_valid_transaction_works() {
+    assert_eq!(
+        check(
+            TokenFactoryConstraintChecker::Spend,
+            &[coin(asset_id(), 5), coin(asset_id(), 7)],
+            &[],
+            &[coin(asset_id(), 10), coin(asset_id(), 1)]
+        ),
+        Ok(0)
+    );
+}
+
+#[test]
+fn spend_several_assets_works() {
+    assert_eq!(
+        check(
+            TokenFactoryConstraintChecker::Spend,
+            &[coin(asset_id(), 5), coin(other_asset_id(), 7)],
+            &[],
+            &[coin(other_asset_id(), 7), coin(asset_id(), 5)]
+        ),
+        Ok(0)
+    );
+}
+
+#[test]
+fn spend_no_outputs_is_a_burn() {
+    assert_eq!(
+        check(
+            TokenFactoryConstraintChecker::Spend,
+            &[coin(asset_id(), 5)],
+            &[],
+            &[]
+        ),
+        Ok(0)
+    );
+}
+
+#[test]
+fn spend_no_inputs_fails() {
+    assert_eq!(
+        check(
+            TokenFactoryConstraintChecker::Spend,
+            &[],
+            &[],
+            &[coin(asset_id(), 5)]
+        ),
+        Err(TokenFactoryError::SpendingNothing)
+    );
+}
+
+#[test]
+fn spend_value_is_conserved_per_asset() {
+    // The total value is conserved, but value moves from one asset to another.
+    assert_eq!(
+        check(
+            TokenFactoryConstraintChecker::Spend,
+            &[coin(asset_id(), 5), coin(other_asset_id(), 7)],
+            &[],
+            &[coin(asset_id(), 7), coin(other_asset_id(), 5)]
+        ),
+        Err(TokenFactoryError::OutputsExceedInputs)
+    );
+}
+
+#[test]
+fn spend_creating_unspent_asset_fails() {
+    assert_eq!(
+        check(
+            TokenFactoryConstraintChecker::Spend,
+            &[coin(asset_id(), 5)],
+            &[],
+            &[coin(other_asset_id(), 1)]
+        ),
+        Err(TokenFactoryError::OutputsExceedInputs)
+    );
+}
+
+#[test]
+fn spend_zero_value_output_fails() {
+    assert_eq!(
+        check(
+            TokenFactoryConstraintChecker::Spend,
+            &[coin(asset_id(), 5)],
+            &[],
+            &[coin(asset_id(), 5), coin(asset_id(), 0)]
+        ),
+        Err(TokenFactoryError::ZeroValueCoin)
+    );
+}
+
+#[test]
+fn spend_wrong_input_type_fails() {
+    assert_eq!(
+        check(
+            TokenFactoryConstraintChecker::Spend,
+            &[definition_owned_by(true)],
+            &[],
+            &[]
+        ),
+        Err(TokenFactoryError::BadlyTypedInput)
+    );
+}
+
+#[test]
+fn spend_with_evictions_fails() {
+    assert_eq!(
+        check(
+            TokenFactoryConstraintChecker::Spend,
+            &[coin(asset_id(), 5)],
+            &[coin(asset_id(), 5)],
+            &[]
+        ),
+        Err(TokenFactoryError::NoEvictionsAllowed)
+    );
+}
+
+#[test]
+fn spend_overflow_fails() {
+    assert_eq!(
+        check(
+            TokenFactoryConstraintChecker::Spend,
+            &[coin(asset_id(), u128::MAX), coin(asset_id(), 1)],
+            &[],
+            &[]
+        ),
+        Err(TokenFactoryError::ValueOverflow)
+    );
+}
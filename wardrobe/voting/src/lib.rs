@@ -0,0 +1,537 @@
+//! Coin-weighted voting on proposals with several options.
+//!
+//! Anyone may create a [`Proposal`] about some subject, typically the hash of whatever is being
+//! decided, listing the options to choose between, when voting ends, and the quorum. Voters lock
+//! coins into [`Vote`]s until the voting period ends, and each vote adds the value of its coins to
+//! the tally of the chosen option. Once the voting period ends, anyone may tally the proposal, which
+//! replaces it with a [`VoteResult`]. Other pieces, such as runtime upgrades or treasury spends, can
+//! peek at results to act on the outcome of a vote.
+//!
+//! Several proposals may share a subject, so each proposal gets a unique id from a counter in
+//! storage, and votes and results refer to the proposal by that id. A vote only has a winner if
+//! the total value voted reaches the proposal's quorum.
+//!
+//! Votes are counted into the proposal as they are cast, rather than by peeking at them all in the
+//! tally. A tally that peeks at votes could never prove that it saw every vote, or that it did not
+//! peek at the same vote twice. The price of this is that votes on the same proposal in the same
+//! block conflict with each other, because each one evicts and recreates the proposal.
+//!
+//! Votes may be cast with any coin type that implements the `Cash` trait. Each runtime chooses
+//! which coins to support by including the corresponding vote checkers.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use core::marker::PhantomData;
+
+use parity_scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use serde::{Deserialize, Serialize};
+use sp_core::H256;
+use sp_runtime::transaction_validity::TransactionPriority;
+use sp_std::prelude::*;
+use tuxedo_core::{
+    dynamic_typing::{DynamicallyTypedData, UtxoData, UtxoType},
+    ensure,
+    support_macros::{CloneNoBound, DebugNoBound, DefaultNoBound},
+    traits::Cash,
+    SimpleConstraintChecker,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// The storage key where the id of the next proposal is kept.
+const NEXT_PROPOSAL_ID_KEY: &[u8] = b"next_proposal_id";
+
+/// The id that the next proposal must have. Ids start at zero and count up.
+pub fn next_proposal_id() -> u64 {
+    sp_io::storage::get(NEXT_PROPOSAL_ID_KEY)
+        .and_then(|d| u64::decode(&mut &*d).ok())
+        .unwrap_or_default()
+}
+
+/// Options to configure voting in your runtime.
+pub trait VotingConfig {
+    /// A means of getting the current block height.
+    /// Probably this will be the Tuxedo Executive
+    fn block_height() -> u32;
+
+    /// The fewest blocks that a proposal's voting period may last.
+    ///
+    /// Default is 100.
+    const MIN_VOTING_PERIOD: u32 = 100;
+
+    /// The most options that a proposal may have.
+    ///
+    /// Default is 16.
+    const MAX_OPTIONS: u32 = 16;
+
+    /// The longest that an option's label may be, in bytes.
+    ///
+    /// Default is 32.
+    const MAX_OPTION_LENGTH: u32 = 32;
+
+    /// The smallest quorum that a proposal may have. A proposal only has a winner if the total
+    /// value of the coins voting on it reaches its quorum.
+    ///
+    /// Default is 0.
+    const MIN_QUORUM: u128 = 0;
+}
+
+/// A question that coin holders vote on. Proposals are never consumed normally.
+/// Votes and tallies evict them, so their verifier does not matter.
+#[derive(
+    Serialize, Deserialize, PartialEq, Eq, Clone, Encode, Decode, Hash, Debug, TypeInfo, UtxoData,
+)]
+pub struct Proposal {
+    /// The unique id of this proposal.
+    pub id: u64,
+    /// What is being decided, typically a hash of it.
+    pub subject: H256,
+    /// The labels of the options to choose between.
+    pub options: Vec<Vec<u8>>,
+    /// The block height at which voting ends.
+    pub voting_ends: u32,
+    /// The total value of the coins that must vote for the proposal to have a winner.
+    pub quorum: u128,
+    /// The total value of the coins voting for each option, in the same order as the options.
+    pub tallies: Vec<u128>,
+}
+
+impl Proposal {
+    /// A new proposal that nobody has voted on yet.
+    pub fn new(
+        id: u64,
+        subject: H256,
+        options: Vec<Vec<u8>>,
+        voting_ends: u32,
+        quorum: u128,
+    ) -> Self {
+        let tallies = vec![0; options.len()];
+        Self {
+            id,
+            subject,
+            options,
+            voting_ends,
+            quorum,
+            tallies,
+        }
+    }
+
+    /// The total value of the coins voting on this proposal.
+    pub fn turnout(&self) -> u128 {
+        self.tallies
+            .iter()
+            .fold(0, |total, tally| total.saturating_add(*tally))
+    }
+
+    /// The index of the option with the most votes, or `None` if no single option has the most
+    /// or the quorum was not reached.
+    pub fn winner(&self) -> Option<u32> {
+        if self.turnout() < self.quorum {
+            return None;
+        }
+        let (index, most) = self
+            .tallies
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, tally)| **tally)?;
+        let tied = self.tallies.iter().filter(|tally| *tally == most).count() > 1;
+        (*most > 0 && !tied).then_some(index as u32)
+    }
+}
+
+/// Some coins locked in a vote on a proposal. The vote's verifier belongs to the voter.
+#[derive(
+    Serialize, Deserialize, PartialEq, Eq, Clone, Encode, Decode, Debug, TypeInfo, UtxoData,
+)]
+pub struct Vote {
+    /// The id of the proposal that was voted on.
+    pub proposal_id: u64,
+    /// The index of the chosen option.
+    pub option: u32,
+    /// The block height from which the coins may be reclaimed.
+    pub unlocks_at: u32,
+    /// The locked coins, exactly as they were before voting.
+    pub coins: Vec<DynamicallyTypedData>,
+}
+
+/// The final outcome of a vote, for other pieces to peek at.
+#[derive(
+    Serialize, Deserialize, PartialEq, Eq, Clone, Encode, Decode, Hash, Debug, TypeInfo, UtxoData,
+)]
+pub struct VoteResult {
+    /// The id of the proposal that was voted on.
+    pub proposal_id: u64,
+    /// What was decided, typically a hash of it.
+    pub subject: H256,
+    /// The labels of the options that were voted on.
+    pub options: Vec<Vec<u8>>,
+    /// The block height at which voting ended.
+    pub voting_ends: u32,
+    /// The total value of the coins that had to vote for there to be a winner.
+    pub quorum: u128,
+    /// The total value of the coins that voted for each option, in the same order as the options.
+    pub tallies: Vec<u128>,
+    /// The index of the option with the most votes, or `None` if no single option has the most
+    /// or the quorum was not reached.
+    pub winner: Option<u32>,
+}
+
+impl From<Proposal> for VoteResult {
+    fn from(proposal: Proposal) -> Self {
+        let winner = proposal.winner();
+        Self {
+            proposal_id: proposal.id,
+            subject: proposal.subject,
+            options: proposal.options,
+            voting_ends: proposal.voting_ends,
+            quorum: proposal.quorum,
+            tallies: proposal.tallies,
+            winner,
+        }
+    }
+}
+
+/// Errors that can occur when checking voting transactions.
+#[derive(
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Encode,
+    Decode,
+    Hash,
+    Debug,
+    TypeInfo,
+)]
+pub enum VotingError {
+    /// An input data has the wrong type.
+    BadlyTypedInput,
+    /// An output data has the wrong type.
+    BadlyTypedOutput,
+    /// Wrong number of inputs were provided to the constraint checker.
+    WrongNumberInputs,
+    /// Wrong number of outputs were provided to the constraint checker.
+    WrongNumberOutputs,
+    /// Only votes and tallies may evict anything, and they must evict exactly one proposal.
+    WrongNumberOfProposals,
+    /// A proposal must have between two and the maximum number of options,
+    /// each with a label that is not empty and not too long.
+    InvalidOptions,
+    /// A new proposal must start with empty tallies, last at least the minimum voting period,
+    /// and have at least the minimum quorum.
+    MalformedProposal,
+    /// A new proposal must have the next proposal id.
+    WrongProposalId,
+    /// A vote must lock at least one coin.
+    VotingNothing,
+    /// The voting period for this proposal has ended.
+    VotingClosed,
+    /// The voting period for this proposal has not ended yet.
+    VotingStillOpen,
+    /// The chosen option does not exist.
+    NoSuchOption,
+    /// A vote must record the voted coins exactly, and unlock them when the voting period ends.
+    VoteNotRecorded,
+    /// A vote must recreate the proposal as its first output, with only the chosen option's tally
+    /// increased by the value of the voted coins.
+    ProposalNotPreserved,
+    /// The locked coins may not be reclaimed until the voting period ends.
+    VoteStillLocked,
+    /// Reclaiming votes must return exactly the coins that were locked, in order.
+    CoinsNotReturned,
+    /// A tally must output exactly the result of the proposal.
+    ResultMismatch,
+    /// The transaction attempted to vote with a coin of zero value.
+    ZeroValueCoin,
+    /// The value of the voted coins overflows the value type.
+    ValueOverflow,
+}
+
+/// Extract the single evicted proposal.
+fn evicted_proposal(evicted_input_data: &[DynamicallyTypedData]) -> Result<Proposal, VotingError> {
+    ensure!(
+        evicted_input_data.len() == 1,
+        VotingError::WrongNumberOfProposals
+    );
+    evicted_input_data[0]
+        .extract::<Proposal>()
+        .map_err(|_| VotingError::BadlyTypedInput)
+}
+
+/// Create a new proposal.
+///
+/// There are no inputs, and the only output is a proposal that nobody has voted on yet.
+///
+/// This constraint checker is somewhat non-standard in that it has a side-effect that
+/// increments the proposal id counter in storage, so that no two proposals share an id.
+/// Proposals created in the same block must therefore have consecutive ids.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DebugNoBound,
+    DefaultNoBound,
+    CloneNoBound,
+    PartialEq,
+    Eq,
+    TypeInfo,
+)]
+#[scale_info(skip_type_params(T))]
+pub struct CreateProposal<T>(PhantomData<T>);
+
+impl<T: VotingConfig> SimpleConstraintChecker for CreateProposal<T> {
+    type Error = VotingError;
+
+    fn check(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        evicted_input_data: &[DynamicallyTypedData],
+        _peeks: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> Result<TransactionPriority, Self::Error> {
+        ensure!(
+            evicted_input_data.is_empty(),
+            VotingError::WrongNumberOfProposals
+        );
+        ensure!(input_data.is_empty(), VotingError::WrongNumberInputs);
+        ensure!(output_data.len() == 1, VotingError::WrongNumberOutputs);
+
+        let proposal = output_data[0]
+            .extract::<Proposal>()
+            .map_err(|_| VotingError::BadlyTypedOutput)?;
+        ensure!(
+            proposal.options.len() >= 2
+                && proposal.options.len() <= T::MAX_OPTIONS as usize
+                && proposal.options.iter().all(|option| {
+                    !option.is_empty() && option.len() <= T::MAX_OPTION_LENGTH as usize
+                }),
+            VotingError::InvalidOptions
+        );
+        ensure!(
+            proposal.tallies == vec![0; proposal.options.len()]
+                && proposal.voting_ends >= T::block_height().saturating_add(T::MIN_VOTING_PERIOD)
+                && proposal.quorum >= T::MIN_QUORUM,
+            VotingError::MalformedProposal
+        );
+        ensure!(
+            proposal.id == next_proposal_id(),
+            VotingError::WrongProposalId
+        );
+
+        // SIDE EFFECT: Claim the id so that no other proposal can have it.
+        sp_io::storage::set(
+            NEXT_PROPOSAL_ID_KEY,
+            &proposal.id.saturating_add(1).encode(),
+        );
+
+        Ok(0)
+    }
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![UtxoType::of::<Proposal>()]
+    }
+
+    // Creating a proposal claims its id while checking, so it must check a whole transaction.
+    const COMPOUNDABLE: bool = false;
+}
+
+/// Vote on a proposal by locking coins of type `C`.
+///
+/// The single evicted input is the proposal and the normal inputs are the voter's coins.
+/// The first output is the proposal with the chosen option's tally updated, and the second is
+/// the vote that locks the coins until the voting period ends.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DebugNoBound,
+    DefaultNoBound,
+    CloneNoBound,
+    PartialEq,
+    Eq,
+    TypeInfo,
+)]
+#[scale_info(skip_type_params(C, T))]
+pub struct CastVote<C, T>(PhantomData<(C, T)>);
+
+impl<C: Cash + UtxoData, T: VotingConfig> SimpleConstraintChecker for CastVote<C, T> {
+    type Error = VotingError;
+
+    fn check(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        evicted_input_data: &[DynamicallyTypedData],
+        _peeks: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> Result<TransactionPriority, Self::Error> {
+        let proposal = evicted_proposal(evicted_input_data)?;
+        ensure!(
+            T::block_height() < proposal.voting_ends,
+            VotingError::VotingClosed
+        );
+
+        // Add up the voted coins
+        ensure!(!input_data.is_empty(), VotingError::VotingNothing);
+        let mut total: u128 = 0;
+        for input in input_data {
+            let value = input
+                .extract::<C>()
+                .map_err(|_| VotingError::BadlyTypedInput)?
+                .value();
+            ensure!(value > 0, VotingError::ZeroValueCoin);
+            total = total.checked_add(value).ok_or(VotingError::ValueOverflow)?;
+        }
+
+        // The outputs are the updated proposal and the vote
+        ensure!(output_data.len() == 2, VotingError::WrongNumberOutputs);
+        let vote = output_data[1]
+            .extract::<Vote>()
+            .map_err(|_| VotingError::BadlyTypedOutput)?;
+        ensure!(
+            vote.proposal_id == proposal.id
+                && vote.unlocks_at == proposal.voting_ends
+                && vote.coins == input_data,
+            VotingError::VoteNotRecorded
+        );
+
+        let mut expected_proposal = proposal;
+        let tally = expected_proposal
+            .tallies
+            .get_mut(vote.option as usize)
+            .ok_or(VotingError::NoSuchOption)?;
+        *tally = tally.checked_add(total).ok_or(VotingError::ValueOverflow)?;
+        let updated_proposal = output_data[0]
+            .extract::<Proposal>()
+            .map_err(|_| VotingError::ProposalNotPreserved)?;
+        ensure!(
+            updated_proposal == expected_proposal,
+            VotingError::ProposalNotPreserved
+        );
+
+        Ok(0)
+    }
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![
+            UtxoType::of::<Proposal>(),
+            UtxoType::of::<Vote>(),
+            UtxoType::of::<C>(),
+        ]
+    }
+}
+
+/// Reclaim the coins locked in votes once their voting periods have ended.
+///
+/// The inputs are votes, which are consumed normally so the voter's verifier must be satisfied.
+/// The outputs are all of their locked coins, in order.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DebugNoBound,
+    DefaultNoBound,
+    CloneNoBound,
+    PartialEq,
+    Eq,
+    TypeInfo,
+)]
+#[scale_info(skip_type_params(T))]
+pub struct ReclaimVotes<T>(PhantomData<T>);
+
+impl<T: VotingConfig> SimpleConstraintChecker for ReclaimVotes<T> {
+    type Error = VotingError;
+
+    fn check(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        evicted_input_data: &[DynamicallyTypedData],
+        _peeks: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> Result<TransactionPriority, Self::Error> {
+        ensure!(
+            evicted_input_data.is_empty(),
+            VotingError::WrongNumberOfProposals
+        );
+        ensure!(!input_data.is_empty(), VotingError::WrongNumberInputs);
+
+        let mut locked_coins = Vec::new();
+        for input in input_data {
+            let vote = input
+                .extract::<Vote>()
+                .map_err(|_| VotingError::BadlyTypedInput)?;
+            ensure!(
+                T::block_height() >= vote.unlocks_at,
+                VotingError::VoteStillLocked
+            );
+            locked_coins.extend(vote.coins);
+        }
+
+        ensure!(locked_coins == output_data, VotingError::CoinsNotReturned);
+
+        Ok(0)
+    }
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![UtxoType::of::<Vote>()]
+    }
+}
+
+/// Tally a proposal once its voting period has ended.
+///
+/// The single evicted input is the proposal, and the only output is its result.
+#[derive(
+    Serialize,
+    Deserialize,
+    Encode,
+    Decode,
+    DebugNoBound,
+    DefaultNoBound,
+    CloneNoBound,
+    PartialEq,
+    Eq,
+    TypeInfo,
+)]
+#[scale_info(skip_type_params(T))]
+pub struct TallyVotes<T>(PhantomData<T>);
+
+impl<T: VotingConfig> SimpleConstraintChecker for TallyVotes<T> {
+    type Error = VotingError;
+
+    fn check(
+        &self,
+        input_data: &[DynamicallyTypedData],
+        evicted_input_data: &[DynamicallyTypedData],
+        _peeks: &[DynamicallyTypedData],
+        output_data: &[DynamicallyTypedData],
+    ) -> Result<TransactionPriority, Self::Error> {
+        ensure!(input_data.is_empty(), VotingError::WrongNumberInputs);
+        let proposal = evicted_proposal(evicted_input_data)?;
+        ensure!(
+            T::block_height() >= proposal.voting_ends,
+            VotingError::VotingStillOpen
+        );
+
+        ensure!(output_data.len() == 1, VotingError::WrongNumberOutputs);
+        let result = output_data[0]
+            .extract::<VoteResult>()
+            .map_err(|_| VotingError::BadlyTypedOutput)?;
+        ensure!(
+            result == VoteResult::from(proposal),
+            VotingError::ResultMismatch
+        );
+
+        Ok(0)
+    }
+
+    fn utxo_types() -> Vec<UtxoType> {
+        vec![UtxoType::of::<Proposal>(), UtxoType::of::<VoteResult>()]
+    }
+}
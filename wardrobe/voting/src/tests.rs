@@ -0,0 +1,559 @@
+//! Unit tests for the voting piece
+
+use super::*;
+use sp_io::TestExternalities;
+use tuxedo_core::dynamic_typing::testing::{Bogus, Gold};
+use VotingError::*;
+
+/// The mock config always says the block number is one hundred.
+pub struct AlwaysBlockHundred;
+
+impl VotingConfig for AlwaysBlockHundred {
+    fn block_height() -> u32 {
+        100
+    }
+
+    const MIN_VOTING_PERIOD: u32 = 10;
+    const MAX_OPTIONS: u32 = 3;
+    const MAX_OPTION_LENGTH: u32 = 8;
+    const MIN_QUORUM: u128 = 10;
+}
+
+type Create = CreateProposal<AlwaysBlockHundred>;
+type Cast = CastVote<Gold, AlwaysBlockHundred>;
+type Reclaim = ReclaimVotes<AlwaysBlockHundred>;
+type Tally = TallyVotes<AlwaysBlockHundred>;
+
+fn subject() -> H256 {
+    H256::repeat_byte(7)
+}
+
+fn options() -> Vec<Vec<u8>> {
+    vec![b"red".to_vec(), b"green".to_vec(), b"blue".to_vec()]
+}
+
+fn proposal(voting_ends: u32, tallies: Vec<u128>) -> Proposal {
+    Proposal {
+        id: 0,
+        proposal_id: 0,
+        options: options(),
+        voting_ends,
+        quorum: 10,
+        tallies,
+    }
+}
+
+fn new_proposal(options: Vec<Vec<u8>>, voting_ends: u32) -> Proposal {
+    Proposal::new(0, subject(), options, voting_ends, 10)
+}
+
+fn vote(option: u32, coins: Vec<DynamicallyTypedData>) -> Vote {
+    Vote {
+        proposal_id: 0,
+        option,
+        unlocks_at: 110,
+        coins,
+    }
+}
+
+#[test]
+fn create_proposal_happy_path() {
+    TestExternalities::default().execute_with(|| {
+        let created = new_proposal(options(), 110);
+
+        assert_eq!(created.tallies, vec![0, 0, 0]);
+        assert_eq!(
+            Create::default().check(&[], &[], &[], &[created.into()]),
+            Ok(0)
+        );
+        assert_eq!(next_proposal_id(), 1);
+    });
+}
+
+#[test]
+fn create_proposal_with_wrong_id_fails() {
+    TestExternalities::default().execute_with(|| {
+        let created = Proposal::new(1, subject(), options(), 110, 10);
+
+        assert_eq!(
+            Create::default().check(&[], &[], &[], &[created.into()]),
+            Err(WrongProposalId)
+        );
+        assert_eq!(next_proposal_id(), 0);
+    });
+}
+
+#[test]
+fn create_proposal_reusing_id_fails() {
+    TestExternalities::default().execute_with(|| {
+        let first = new_proposal(options(), 110);
+        let second = new_proposal(vec![b"yes".to_vec(), b"no".to_vec()], 120);
+
+        assert_eq!(
+            Create::default().check(&[], &[], &[], &[first.into()]),
+            Ok(0)
+        );
+        assert_eq!(
+            Create::default().check(&[], &[], &[], &[second.into()]),
+            Err(WrongProposalId)
+        );
+    });
+}
+
+#[test]
+fn create_proposal_below_minimum_quorum_fails() {
+    let created = Proposal::new(0, subject(), options(), 110, 9);
+
+    assert_eq!(
+        Create::default().check(&[], &[], &[], &[created.into()]),
+        Err(MalformedProposal)
+    );
+}
+
+#[test]
+fn create_proposal_with_tallies_fails() {
+    assert_eq!(
+        Create::default().check(&[], &[], &[], &[proposal(110, vec![0, 5, 0]).into()]),
+        Err(MalformedProposal)
+    );
+}
+
+#[test]
+fn create_proposal_with_short_voting_period_fails() {
+    let created = new_proposal(options(), 109);
+
+    assert_eq!(
+        Create::default().check(&[], &[], &[], &[created.into()]),
+        Err(MalformedProposal)
+    );
+}
+
+#[test]
+fn create_proposal_with_one_option_fails() {
+    let created = new_proposal(vec![b"yes".to_vec()], 110);
+
+    assert_eq!(
+        Create::default().check(&[], &[], &[], &[created.into()]),
+        Err(InvalidOptions)
+    );
+}
+
+#[test]
+fn create_proposal_with_too_many_options_fails() {
+    let mut too_many = options();
+    too_many.push(b"yellow".to_vec());
+    let created = new_proposal(too_many, 110);
+
+    assert_eq!(
+        Create::default().check(&[], &[], &[], &[created.into()]),
+        Err(InvalidOptions)
+    );
+}
+
+#[test]
+fn create_proposal_with_bad_labels_fails() {
+    let empty = new_proposal(vec![b"yes".to_vec(), vec![]], 110);
+    let long = new_proposal(vec![b"yes".to_vec(), b"absolutely".to_vec()], 110);
+
+    assert_eq!(
+        Create::default().check(&[], &[], &[], &[empty.into()]),
+        Err(InvalidOptions)
+    );
+    assert_eq!(
+        Create::default().check(&[], &[], &[], &[long.into()]),
+        Err(InvalidOptions)
+    );
+}
+
+#[test]
+fn create_proposal_with_inputs_fails() {
+    assert_eq!(
+        Create::default().check(
+            &[Gold(5).into()],
+            &[],
+            &[],
+            &[new_proposal(options(), 110).into()]
+        ),
+        Err(WrongNumberInputs)
+    );
+}
+
+#[test]
+fn create_bogus_proposal_fails() {
+    assert_eq!(
+        Create::default().check(&[], &[], &[], &[Bogus.into()]),
+        Err(BadlyTypedOutput)
+    );
+}
+
+#[test]
+fn cast_vote_happy_path() {
+    let coins: Vec<DynamicallyTypedData> = vec![Gold(5).into(), Gold(7).into()];
+
+    assert_eq!(
+        Cast::default().check(
+            &coins,
+            &[proposal(110, vec![1, 2, 3]).into()],
+            &[],
+            &[
+                proposal(110, vec![1, 14, 3]).into(),
+                vote(1, coins.clone()).into()
+            ]
+        ),
+        Ok(0)
+    );
+}
+
+#[test]
+fn cast_vote_after_voting_ends_fails() {
+    let coins: Vec<DynamicallyTypedData> = vec![Gold(5).into()];
+
+    assert_eq!(
+        Cast::default().check(
+            &coins,
+            &[proposal(100, vec![0, 0, 0]).into()],
+            &[],
+            &[
+                proposal(100, vec![5, 0, 0]).into(),
+                Vote {
+                    unlocks_at: 100,
+                    ..vote(0, coins.clone())
+                }
+                .into()
+            ]
+        ),
+        Err(VotingClosed)
+    );
+}
+
+#[test]
+fn cast_vote_without_proposal_fails() {
+    let coins: Vec<DynamicallyTypedData> = vec![Gold(5).into()];
+
+    assert_eq!(
+        Cast::default().check(
+            &coins,
+            &[],
+            &[],
+            &[
+                proposal(110, vec![5, 0, 0]).into(),
+                vote(0, coins.clone()).into()
+            ]
+        ),
+        Err(WrongNumberOfProposals)
+    );
+}
+
+#[test]
+fn cast_vote_without_coins_fails() {
+    assert_eq!(
+        Cast::default().check(
+            &[],
+            &[proposal(110, vec![0, 0, 0]).into()],
+            &[],
+            &[proposal(110, vec![0, 0, 0]).into(), vote(0, vec![]).into()]
+        ),
+        Err(VotingNothing)
+    );
+}
+
+#[test]
+fn cast_vote_with_zero_value_coin_fails() {
+    let coins: Vec<DynamicallyTypedData> = vec![Gold(0).into()];
+
+    assert_eq!(
+        Cast::default().check(
+            &coins,
+            &[proposal(110, vec![0, 0, 0]).into()],
+            &[],
+            &[
+                proposal(110, vec![0, 0, 0]).into(),
+                vote(0, coins.clone()).into()
+            ]
+        ),
+        Err(ZeroValueCoin)
+    );
+}
+
+#[test]
+fn cast_vote_with_bogus_coin_fails() {
+    let coins: Vec<DynamicallyTypedData> = vec![Bogus.into()];
+
+    assert_eq!(
+        Cast::default().check(
+            &coins,
+            &[proposal(110, vec![0, 0, 0]).into()],
+            &[],
+            &[
+                proposal(110, vec![1, 0, 0]).into(),
+                vote(0, coins.clone()).into()
+            ]
+        ),
+        Err(BadlyTypedInput)
+    );
+}
+
+#[test]
+fn cast_vote_for_missing_option_fails() {
+    let coins: Vec<DynamicallyTypedData> = vec![Gold(5).into()];
+
+    assert_eq!(
+        Cast::default().check(
+            &coins,
+            &[proposal(110, vec![0, 0, 0]).into()],
+            &[],
+            &[
+                proposal(110, vec![0, 0, 0]).into(),
+                vote(3, coins.clone()).into()
+            ]
+        ),
+        Err(NoSuchOption)
+    );
+}
+
+#[test]
+fn cast_vote_miscounted_fails() {
+    let coins: Vec<DynamicallyTypedData> = vec![Gold(5).into()];
+
+    assert_eq!(
+        Cast::default().check(
+            &coins,
+            &[proposal(110, vec![0, 0, 0]).into()],
+            &[],
+            &[
+                proposal(110, vec![0, 50, 0]).into(),
+                vote(1, coins.clone()).into()
+            ]
+        ),
+        Err(ProposalNotPreserved)
+    );
+}
+
+#[test]
+fn cast_vote_changing_proposal_fails() {
+    let coins: Vec<DynamicallyTypedData> = vec![Gold(5).into()];
+    let extended = Proposal {
+        voting_ends: 1_000,
+        ..proposal(110, vec![5, 0, 0])
+    };
+
+    assert_eq!(
+        Cast::default().check(
+            &coins,
+            &[proposal(110, vec![0, 0, 0]).into()],
+            &[],
+            &[extended.into(), vote(0, coins.clone()).into()]
+        ),
+        Err(ProposalNotPreserved)
+    );
+}
+
+#[test]
+fn cast_vote_with_early_unlock_fails() {
+    let coins: Vec<DynamicallyTypedData> = vec![Gold(5).into()];
+
+    assert_eq!(
+        Cast::default().check(
+            &coins,
+            &[proposal(110, vec![0, 0, 0]).into()],
+            &[],
+            &[
+                proposal(110, vec![5, 0, 0]).into(),
+                Vote {
+                    unlocks_at: 101,
+                    ..vote(0, coins.clone())
+                }
+                .into()
+            ]
+        ),
+        Err(VoteNotRecorded)
+    );
+}
+
+#[test]
+fn cast_vote_for_another_proposal_fails() {
+    let coins: Vec<DynamicallyTypedData> = vec![Gold(5).into()];
+
+    assert_eq!(
+        Cast::default().check(
+            &coins,
+            &[proposal(110, vec![0, 0, 0]).into()],
+            &[],
+            &[
+                proposal(110, vec![5, 0, 0]).into(),
+                Vote {
+                    proposal_id: 1,
+                    ..vote(0, coins.clone())
+                }
+                .into()
+            ]
+        ),
+        Err(VoteNotRecorded)
+    );
+}
+
+#[test]
+fn cast_vote_keeping_coins_fails() {
+    let coins: Vec<DynamicallyTypedData> = vec![Gold(5).into(), Gold(7).into()];
+
+    assert_eq!(
+        Cast::default().check(
+            &coins,
+            &[proposal(110, vec![0, 0, 0]).into()],
+            &[],
+            &[
+                proposal(110, vec![12, 0, 0]).into(),
+                vote(0, vec![Gold(5).into()]).into()
+            ]
+        ),
+        Err(VoteNotRecorded)
+    );
+}
+
+#[test]
+fn reclaim_votes_happy_path() {
+    let first = Vote {
+        unlocks_at: 90,
+        ..vote(0, vec![Gold(5).into()])
+    };
+    let second = Vote {
+        unlocks_at: 100,
+        ..vote(2, vec![Gold(7).into(), Gold(1).into()])
+    };
+
+    assert_eq!(
+        Reclaim::default().check(
+            &[first.into(), second.into()],
+            &[],
+            &[],
+            &[Gold(5).into(), Gold(7).into(), Gold(1).into()]
+        ),
+        Ok(0)
+    );
+}
+
+#[test]
+fn reclaim_vote_too_early_fails() {
+    assert_eq!(
+        Reclaim::default().check(
+            &[vote(0, vec![Gold(5).into()]).into()],
+            &[],
+            &[],
+            &[Gold(5).into()]
+        ),
+        Err(VoteStillLocked)
+    );
+}
+
+#[test]
+fn reclaim_vote_for_more_fails() {
+    let unlocked = Vote {
+        unlocks_at: 100,
+        ..vote(0, vec![Gold(5).into()])
+    };
+
+    assert_eq!(
+        Reclaim::default().check(&[unlocked.into()], &[], &[], &[Gold(6).into()]),
+        Err(CoinsNotReturned)
+    );
+}
+
+#[test]
+fn reclaim_nothing_fails() {
+    assert_eq!(
+        Reclaim::default().check(&[], &[], &[], &[]),
+        Err(WrongNumberInputs)
+    );
+}
+
+#[test]
+fn tally_votes_happy_path() {
+    let ended = proposal(100, vec![5, 12, 3]);
+    let result = VoteResult {
+        proposal_id: 0,
+        options: options(),
+        tallies: vec![5, 12, 3],
+        winner: Some(1),
+    };
+
+    assert_eq!(
+        Tally::default().check(&[], &[ended.into()], &[], &[result.into()]),
+        Ok(0)
+    );
+}
+
+#[test]
+fn tally_votes_with_tie_has_no_winner() {
+    let ended = proposal(100, vec![12, 12, 3]);
+    let result = VoteResult {
+        proposal_id: 0,
+        options: options(),
+        tallies: vec![12, 12, 3],
+        winner: None,
+    };
+
+    assert_eq!(
+        Tally::default().check(&[], &[ended.into()], &[], &[result.into()]),
+        Ok(0)
+    );
+}
+
+#[test]
+fn tally_votes_below_quorum_has_no_winner() {
+    let ended = proposal(100, vec![2, 6, 1]);
+    let result = VoteResult {
+        proposal_id: 0,
+        subject: subject(),
+        options: options(),
+        voting_ends: 100,
+        quorum: 10,
+        tallies: vec![2, 6, 1],
+        winner: None,
+    };
+
+    assert_eq!(ended.turnout(), 9);
+    assert_eq!(
+        Tally::default().check(&[], &[ended.into()], &[], &[result.into()]),
+        Ok(0)
+    );
+}
+
+#[test]
+fn tally_votes_without_votes_has_no_winner() {
+    assert_eq!(proposal(100, vec![0, 0, 0]).winner(), None);
+}
+
+#[test]
+fn tally_votes_before_voting_ends_fails() {
+    let open = proposal(101, vec![5, 12, 3]);
+    let result = VoteResult::from(open.clone());
+
+    assert_eq!(
+        Tally::default().check(&[], &[open.into()], &[], &[result.into()]),
+        Err(VotingStillOpen)
+    );
+}
+
+#[test]
+fn tally_votes_with_wrong_winner_fails() {
+    let ended = proposal(100, vec![5, 12, 3]);
+    let result = VoteResult {
+        winner: Some(0),
+        ..VoteResult::from(ended.clone())
+    };
+
+    assert_eq!(
+        Tally::default().check(&[], &[ended.into()], &[], &[result.into()]),
+        Err(ResultMismatch)
+    );
+}
+
+#[test]
+fn tally_votes_without_proposal_fails() {
+    let result = VoteResult::from(proposal(100, vec![5, 12, 3]));
+
+    assert_eq!(
+        Tally::default().check(&[], &[], &[], &[result.into()]),
+        Err(WrongNumberOfProposals)
+    );
+}